@@ -0,0 +1,162 @@
+//! BLAKE2s functions (RFC 7693), for 32-byte digests without key.
+
+use core::simd::Simd;
+use std::simd::u32x16;
+
+pub const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Initial chaining value: the IV xored with the parameter block, i.e. a 32-byte digest without
+/// key, fanout and depth 1.
+pub const H: [u32; 8] = [
+    IV[0] ^ 0x01010020,
+    IV[1],
+    IV[2],
+    IV[3],
+    IV[4],
+    IV[5],
+    IV[6],
+    IV[7],
+];
+
+pub const SIGMA: [[usize; 16]; N_ROUNDS] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Words `[a, b, c, d]` of the working state mixed by each G of a round: the columns, then the
+/// diagonals.
+pub const G_INDICES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+pub const N_ROUNDS: usize = 10;
+
+/// Number of G evaluations of a compression.
+pub const N_G: usize = N_ROUNDS * G_INDICES.len();
+
+pub const BLOCK_SIZE: usize = 64;
+
+pub const CHUNK_SIZE: usize = 32; // 32 u16 = 16 u32
+
+/// Words 12 and 13 of the initial working state, which hold the byte counter.
+pub const fn counter_words(counter: u64) -> [u32; 2] {
+    [IV[4] ^ counter as u32, IV[5] ^ (counter >> 32) as u32]
+}
+
+/// Word 14 of the initial working state, inverted for the last block of a message.
+pub const fn last_block_word(is_last: bool) -> u32 {
+    if is_last {
+        !IV[6]
+    } else {
+        IV[6]
+    }
+}
+
+#[inline(always)]
+pub fn rotr_u32x16(x: u32x16, n: u32) -> u32x16 {
+    (x >> Simd::splat(n)) | (x << Simd::splat(32 - n))
+}
+
+/// The mixing function, on the words `indices` of the working state.
+fn g(v: &mut [u32; 16], [a, b, c, d]: [usize; 4], x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
+
+/// Compresses the message words `m` into the chaining value `h`. `counter` is the number of
+/// bytes hashed through this block, i.e. the message length for the last block.
+pub fn compress(h: [u32; 8], m: [u32; 16], counter: u64, is_last: bool) -> [u32; 8] {
+    let [v12, v13] = counter_words(counter);
+    let mut v = [
+        h[0],
+        h[1],
+        h[2],
+        h[3],
+        h[4],
+        h[5],
+        h[6],
+        h[7],
+        IV[0],
+        IV[1],
+        IV[2],
+        IV[3],
+        v12,
+        v13,
+        last_block_word(is_last),
+        IV[7],
+    ];
+    for s in SIGMA {
+        for (i, indices) in G_INDICES.into_iter().enumerate() {
+            g(&mut v, indices, m[s[2 * i]], m[s[2 * i + 1]]);
+        }
+    }
+    std::array::from_fn(|i| h[i] ^ v[i] ^ v[i + 8])
+}
+
+/// BLAKE2s-256 digest of `message`.
+pub fn blake2s(message: &[u8]) -> [u8; 32] {
+    let blocks = crate::message::pad_message(message);
+    let n_blocks = blocks.len();
+    let h = blocks.into_iter().enumerate().fold(H, |h, (index, words)| {
+        let counter = crate::message::block_counter(message.len(), index, n_blocks);
+        compress(h, words, counter, index + 1 == n_blocks)
+    });
+    crate::message::to_bytes(h)
+}
+
+#[cfg(test)]
+mod tests {
+    use blake2::{Blake2s256, Digest};
+
+    use super::*;
+
+    #[test]
+    fn test_reference_implementation() {
+        for len in [0, 1, 3, 63, 64, 65, 128, 1000] {
+            let message = (0..len).map(|i| i as u8).collect::<Vec<_>>();
+            assert_eq!(blake2s(&message)[..], Blake2s256::digest(&message)[..]);
+        }
+    }
+
+    #[test]
+    fn test_rfc_7693() {
+        assert_eq!(
+            hex::encode(blake2s(b"abc")),
+            "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"
+        );
+    }
+
+    #[test]
+    fn test_rotr_u32x16() {
+        let base: [u32; 16] = std::array::from_fn(|i| 0x12345678_u32.wrapping_mul(i as u32));
+        for n in [16, 12, 8, 7] {
+            assert_eq!(
+                base.map(|x| x.rotate_right(n)),
+                rotr_u32x16(u32x16::from_array(base), n).to_array()
+            );
+        }
+    }
+}
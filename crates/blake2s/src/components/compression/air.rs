@@ -0,0 +1,240 @@
+use itertools::izip;
+use num_traits::One;
+use stwo::core::fields::m31::M31;
+use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval, RelationEntry};
+
+use crate::{
+    blake2s::{CHUNK_SIZE, G_INDICES, H, IV, SIGMA},
+    components::{
+        compression::columns::{ChainColumnsOwned, FeedForwardColumnsOwned},
+        g::air::{constant, eq_constraints, eval_g, rotr_limbs, Word},
+        STATE_SIZE,
+    },
+    partitions::BYTES,
+    relations::Relations,
+};
+
+pub type Component = FrameworkComponent<Eval>;
+
+fn eval_compression_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relations) {
+    let ChainColumnsOwned {
+        message_id,
+        block_index,
+        is_first,
+        is_last,
+        is_active,
+        v12_low,
+        v12_high,
+        v13_low,
+        v13_high,
+    } = ChainColumnsOwned::<<E as EvalAtRow>::F>::from_eval(eval);
+    let h_in: [E::F; STATE_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
+    let m: [E::F; CHUNK_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
+
+    eval.add_constraint(is_first.clone() * (is_first.clone() - E::F::one()));
+    eval.add_constraint(is_last.clone() * (is_last.clone() - E::F::one()));
+    eval.add_constraint(is_active.clone() * (is_active.clone() - E::F::one()));
+    // Padding blocks are standalone, so that they emit no output claim
+    eval.add_constraint((E::F::one() - is_active.clone()) * (E::F::one() - is_last.clone()));
+    // The first block of a message has index 0 and starts from the initial chaining value
+    eval.add_constraint(is_first.clone() * block_index.clone());
+    for (i, h) in h_in.iter().enumerate() {
+        let h_limb = (H[i / 2] >> (16 * (i % 2))) & 0xffff;
+        eval.add_constraint(is_first.clone() * (h.clone() - E::F::from(M31::from(h_limb))));
+    }
+
+    // Initial working state: the chaining value, the IV, the counter words claimed with the
+    // message and the finalization word, inverted on the last block
+    let mut v: [Word<E::F>; 16] = std::array::from_fn(|i| match i {
+        0..8 => [h_in[2 * i].clone(), h_in[2 * i + 1].clone()],
+        12 => [v12_low.clone(), v12_high.clone()],
+        13 => [v13_low.clone(), v13_high.clone()],
+        14 => [IV[6] & 0xffff, IV[6] >> 16].map(|limb| {
+            let limb = E::F::from(M31::from(limb));
+            let inverted = E::F::from(M31::from(0xffff)) - limb.clone();
+            limb.clone() + is_last.clone() * (inverted - limb)
+        }),
+        _ => constant::<E>(IV[i - 8]),
+    });
+
+    for sigma in SIGMA {
+        for (i, [a, b, c, d]) in G_INDICES.into_iter().enumerate() {
+            let word = |j: usize| [m[2 * j].clone(), m[2 * j + 1].clone()];
+            let x = word(sigma[2 * i]);
+            let y = word(sigma[2 * i + 1]);
+            [v[a], v[b], v[c], v[d]] = eval_g(
+                eval,
+                &relations.tables,
+                [a, b, c, d].map(|j| v[j].clone()),
+                &x,
+                &y,
+            );
+        }
+    }
+
+    // Feed-forward: h_out = h_in ^ v[i] ^ v[i + 8], byte by byte
+    let minus_one = -E::EF::one();
+    let mut h_out = Vec::with_capacity(STATE_SIZE);
+    for (i, (v_left, v_right)) in v[..8].iter().zip(&v[8..]).enumerate() {
+        let FeedForwardColumnsOwned {
+            left_0,
+            left_1,
+            left_2,
+            left_3,
+            right_0,
+            right_1,
+            right_2,
+            right_3,
+            xor_0,
+            xor_1,
+            xor_2,
+            xor_3,
+            h_0,
+            h_1,
+            h_2,
+            h_3,
+            h_out_0,
+            h_out_1,
+            h_out_2,
+            h_out_3,
+        } = FeedForwardColumnsOwned::<<E as EvalAtRow>::F>::from_eval(eval);
+        let left = [left_0, left_1, left_2, left_3];
+        let right = [right_0, right_1, right_2, right_3];
+        let xor = [xor_0, xor_1, xor_2, xor_3];
+        let h = [h_0, h_1, h_2, h_3];
+        let h_out_8 = [h_out_0, h_out_1, h_out_2, h_out_3];
+
+        eq_constraints(eval, v_left, &rotr_limbs::<E, 4>(&left, BYTES, 0));
+        eq_constraints(eval, v_right, &rotr_limbs::<E, 4>(&right, BYTES, 0));
+        eq_constraints(
+            eval,
+            &[h_in[2 * i].clone(), h_in[2 * i + 1].clone()],
+            &rotr_limbs::<E, 4>(&h, BYTES, 0),
+        );
+        h_out.extend(rotr_limbs::<E, 4>(&h_out_8, BYTES, 0));
+
+        let xor_lookups = izip!(left, right, xor.clone()).chain(izip!(h, xor, h_out_8));
+        for (lhs, rhs, xor) in xor_lookups {
+            relations
+                .tables
+                .xor
+                .add_to_eval(eval, 8, minus_one.clone(), &[lhs, rhs, xor]);
+        }
+    }
+
+    // Consume the input chaining value, unless this is the first block of the message
+    let chain_in = [message_id.clone(), block_index.clone()]
+        .into_iter()
+        .chain(h_in)
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.chain,
+        E::EF::from(is_first - E::F::one()),
+        &chain_in,
+    ));
+
+    // Emit the output chaining value, unless this is the last block of the message
+    let chain_out = [message_id.clone(), block_index.clone() + E::F::one()]
+        .into_iter()
+        .chain(h_out.clone())
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.chain,
+        E::EF::from(E::F::one() - is_last.clone()),
+        &chain_out,
+    ));
+
+    // Emit the public claims of the block, consumed by the verifier: its counter and message
+    // words, and the digest of its message if it is the last block
+    let input_claim = [
+        message_id.clone(),
+        block_index,
+        v12_low,
+        v12_high,
+        v13_low,
+        v13_high,
+    ]
+    .into_iter()
+    .chain(m)
+    .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.input_claim,
+        E::EF::from(is_active.clone()),
+        &input_claim,
+    ));
+    let output_claim = [message_id].into_iter().chain(h_out).collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.output_claim,
+        E::EF::from(is_last + is_active - E::F::one()),
+        &output_claim,
+    ));
+
+    eval.finalize_logup_in_pairs();
+}
+
+#[derive(Clone)]
+pub struct Eval {
+    pub log_size: u32,
+    pub relations: Relations,
+}
+impl FrameworkEval for Eval {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        self.log_size() + 1
+    }
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        eval_compression_constraints(&mut eval, &self.relations);
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use stwo::core::{pcs::TreeVec, poly::circle::CanonicCoset};
+    use stwo_constraint_framework::assert_constraints_on_polys;
+
+    use super::*;
+    use crate::{
+        components::compression::witness::{gen_interaction_trace, gen_trace},
+        message::{gen_blocks, generate_blocks, Block},
+    };
+
+    fn assert_compression_constraints(log_size: u32, blocks: &[Block]) {
+        let (trace, lookup_data) = gen_trace(log_size, blocks);
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) = gen_interaction_trace(&lookup_data, &relations);
+
+        let traces = TreeVec::new(vec![vec![], trace, interaction_trace]);
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(log_size),
+            |mut eval| {
+                eval_compression_constraints(&mut eval, &relations);
+            },
+            claimed_sum,
+        );
+    }
+
+    #[test]
+    fn test_compression_constraints() {
+        const LOG_N_ROWS: u32 = 4;
+
+        assert_compression_constraints(LOG_N_ROWS, &generate_blocks(1 << LOG_N_ROWS));
+    }
+
+    #[test]
+    fn test_compression_constraints_chained_blocks() {
+        const LOG_N_ROWS: u32 = 4;
+
+        // Multi-block messages, a last block with a partial counter and padding rows.
+        let messages = [vec![0x42; 200], b"abc".to_vec(), vec![]];
+        assert_compression_constraints(LOG_N_ROWS, &gen_blocks(&messages));
+    }
+}
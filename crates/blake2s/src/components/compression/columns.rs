@@ -0,0 +1,69 @@
+use utils::trace_columns;
+
+trace_columns!(
+    ChainColumns,
+    message_id,
+    block_index,
+    is_first,
+    is_last,
+    is_active,
+    v12_low,
+    v12_high,
+    v13_low,
+    v13_high
+);
+
+trace_columns!(
+    ChainInteractionColumns,
+    message_id,
+    block_index,
+    next_block_index,
+    is_first,
+    is_last,
+    is_active,
+    v12_low,
+    v12_high,
+    v13_low,
+    v13_high
+);
+
+trace_columns!(
+    FeedForwardColumns,
+    left_0,
+    left_1,
+    left_2,
+    left_3,
+    right_0,
+    right_1,
+    right_2,
+    right_3,
+    xor_0,
+    xor_1,
+    xor_2,
+    xor_3,
+    h_0,
+    h_1,
+    h_2,
+    h_3,
+    h_out_0,
+    h_out_1,
+    h_out_2,
+    h_out_3
+);
+
+impl<'a, T> FeedForwardColumns<'a, T> {
+    /// `[a, b, a ^ b]` of the byte XOR lookups, in the order of the AIR: `v[i] ^ v[i + 8]`, then
+    /// the XOR with the input chaining value.
+    pub fn xor_lookups(&self) -> [[&'a T; 3]; 8] {
+        [
+            [self.left_0, self.right_0, self.xor_0],
+            [self.left_1, self.right_1, self.xor_1],
+            [self.left_2, self.right_2, self.xor_2],
+            [self.left_3, self.right_3, self.xor_3],
+            [self.h_0, self.xor_0, self.h_out_0],
+            [self.h_1, self.xor_1, self.h_out_1],
+            [self.h_2, self.xor_2, self.h_out_2],
+            [self.h_3, self.xor_3, self.h_out_3],
+        ]
+    }
+}
@@ -0,0 +1,3 @@
+pub mod air;
+pub mod columns;
+pub mod witness;
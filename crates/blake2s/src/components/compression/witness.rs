@@ -0,0 +1,458 @@
+//! The compression component is responsible for proving the BLAKE2s compression function.
+//!
+//! This is, 10 rounds of 8 evaluations of G mixing the message words into the working state,
+//! followed by the feed-forward of the input chaining value.
+
+use std::simd::u32x16;
+
+use itertools::{chain, izip, Itertools};
+use num_traits::One;
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        poly::circle::CanonicCoset,
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            column::BaseColumn,
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, consume_pair, simd::for_each_row_chunk, write_pair};
+
+use crate::{
+    blake2s::{counter_words, CHUNK_SIZE, G_INDICES, IV, N_G, SIGMA},
+    components::{
+        compression::columns::{ChainColumns, ChainInteractionColumns, FeedForwardColumns},
+        g::{
+            columns::{GColumns, GInteractionColumns},
+            witness::{g_trace_columns, g_u32x16, gen_g_interaction_trace},
+        },
+        STATE_SIZE,
+    },
+    message::{block_column, Block},
+    partitions::{split_u32x16, BYTES},
+    relations::Relations,
+};
+
+// Trace layout: chain columns, input chaining value, message words, G evaluations and
+// feed-forward.
+const H_IN_OFFSET: usize = ChainColumns::SIZE;
+const M_OFFSET: usize = H_IN_OFFSET + STATE_SIZE;
+const G_OFFSET: usize = M_OFFSET + CHUNK_SIZE;
+const FEED_FORWARD_OFFSET: usize = G_OFFSET + GColumns::SIZE * N_G;
+const N_COLUMNS: usize = FEED_FORWARD_OFFSET + FeedForwardColumns::SIZE * 8;
+
+// Chain columns read back when filling the rows, see [`ChainColumns`].
+const IS_LAST: usize = 3;
+const V12_OFFSET: usize = 5;
+
+// Lookup data layout: chain columns, input chaining value, message words, G evaluations,
+// feed-forward and output chaining value.
+const H_IN_INTERACTION_OFFSET: usize = ChainInteractionColumns::SIZE;
+const M_INTERACTION_OFFSET: usize = H_IN_INTERACTION_OFFSET + STATE_SIZE;
+const G_INTERACTION_OFFSET: usize = M_INTERACTION_OFFSET + CHUNK_SIZE;
+const FEED_FORWARD_INTERACTION_OFFSET: usize =
+    G_INTERACTION_OFFSET + GInteractionColumns::SIZE * N_G;
+const H_OUT_INTERACTION_OFFSET: usize =
+    FEED_FORWARD_INTERACTION_OFFSET + FeedForwardColumns::SIZE * 8;
+const N_INTERACTION_COLUMNS: usize = H_OUT_INTERACTION_OFFSET + STATE_SIZE;
+
+/// Lookup data of the feed-forward of the word `h`, following [`FeedForwardColumns`], together
+/// with the output word.
+#[inline(always)]
+fn feed_forward_u32x16(
+    h: u32x16,
+    left: u32x16,
+    right: u32x16,
+) -> (u32x16, [u32x16; FeedForwardColumns::SIZE]) {
+    let xor = left ^ right;
+    let h_out = h ^ xor;
+
+    let mut values = [u32x16::splat(0); FeedForwardColumns::SIZE];
+    let lookup_data = chain!(
+        split_u32x16(left, BYTES),
+        split_u32x16(right, BYTES),
+        split_u32x16(xor, BYTES),
+        split_u32x16(h, BYTES),
+        split_u32x16(h_out, BYTES),
+    );
+    for (value, column) in lookup_data.zip_eq(values.iter_mut()) {
+        *column = value;
+    }
+
+    (h_out, values)
+}
+
+/// Lookup data of the G evaluations, following [`GInteractionColumns`].
+pub fn g_lookup_data(lookup_data: &[Vec<u32x16>]) -> &[Vec<u32x16>] {
+    &lookup_data[G_INTERACTION_OFFSET..FEED_FORWARD_INTERACTION_OFFSET]
+}
+
+/// `[a, b, a ^ b]` of the byte XOR lookups of the feed-forward, see
+/// [`FeedForwardColumns::xor_lookups`].
+pub fn feed_forward_xor_lookups(lookup_data: &[Vec<u32x16>]) -> Vec<[&Vec<u32x16>; 3]> {
+    lookup_data[FEED_FORWARD_INTERACTION_OFFSET..H_OUT_INTERACTION_OFFSET]
+        .array_chunks::<{ FeedForwardColumns::SIZE }>()
+        .flat_map(|word| FeedForwardColumns::from_slice(word).xor_lookups())
+        .collect()
+}
+
+#[allow(clippy::type_complexity)]
+pub fn gen_trace(
+    log_size: u32,
+    blocks: &[Block],
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    Vec<Vec<u32x16>>,
+) {
+    assert!(log_size >= LOG_N_LANES);
+    assert!(blocks.len() <= 1 << log_size);
+    let simd_size = 1 << (log_size - LOG_N_LANES);
+
+    // Initialize vec for all groups of columns
+    let mut evals: Vec<Vec<u32x16>> = (0..N_COLUMNS)
+        .map(|_| vec![u32x16::splat(0); simd_size])
+        .collect::<Vec<_>>();
+    let mut lookup_data: Vec<Vec<u32x16>> = (0..N_INTERACTION_COLUMNS)
+        .map(|_| vec![u32x16::splat(0); simd_size])
+        .collect::<Vec<_>>();
+
+    // Chain columns
+    let limb = |word: u32, i: usize| (word >> (16 * (i % 2))) & 0xffff;
+    let message_id = block_column(blocks, simd_size, |block| block.message_id);
+    let block_index = block_column(blocks, simd_size, |block| block.index);
+    let next_block_index = block_column(blocks, simd_size, |block| block.index + 1);
+    let is_first = block_column(blocks, simd_size, |block| block.is_first() as u32);
+    let is_last = block_column(blocks, simd_size, |block| block.is_last as u32);
+    let is_active = (0..simd_size)
+        .map(|simd_row| {
+            u32x16::from_array(std::array::from_fn(|lane| {
+                (((simd_row << LOG_N_LANES) + lane) < blocks.len()) as u32
+            }))
+        })
+        .collect::<Vec<_>>();
+    let [v12_low, v12_high, v13_low, v13_high] = std::array::from_fn(|i| {
+        block_column(blocks, simd_size, |block| {
+            limb(counter_words(block.counter)[i / 2], i)
+        })
+    });
+    let chain_values: ChainColumns<Vec<u32x16>> = ChainColumns {
+        message_id: &message_id,
+        block_index: &block_index,
+        is_first: &is_first,
+        is_last: &is_last,
+        is_active: &is_active,
+        v12_low: &v12_low,
+        v12_high: &v12_high,
+        v13_low: &v13_low,
+        v13_high: &v13_high,
+    };
+    for (i, value) in chain_values.iter().enumerate() {
+        evals[i] = value.clone();
+    }
+    let chain_interaction_values: ChainInteractionColumns<Vec<u32x16>> = ChainInteractionColumns {
+        message_id: &message_id,
+        block_index: &block_index,
+        next_block_index: &next_block_index,
+        is_first: &is_first,
+        is_last: &is_last,
+        is_active: &is_active,
+        v12_low: &v12_low,
+        v12_high: &v12_high,
+        v13_low: &v13_low,
+        v13_high: &v13_high,
+    };
+    for (i, value) in chain_interaction_values.iter().enumerate() {
+        lookup_data[i] = value.clone();
+    }
+
+    // Input chaining value and message words
+    for i in 0..STATE_SIZE {
+        let h_in = block_column(blocks, simd_size, |block| limb(block.h_in[i / 2], i));
+        lookup_data[H_IN_INTERACTION_OFFSET + i] = h_in.clone();
+        evals[H_IN_OFFSET + i] = h_in;
+    }
+    for i in 0..CHUNK_SIZE {
+        let m = block_column(blocks, simd_size, |block| limb(block.words[i / 2], i));
+        lookup_data[M_INTERACTION_OFFSET + i] = m.clone();
+        evals[M_OFFSET + i] = m;
+    }
+
+    let g_columns = g_trace_columns();
+
+    for_each_row_chunk(&mut evals, &mut lookup_data, |evals, lookup_data| {
+        for row in 0..evals[0].len() {
+            let word = |offset: usize, i: usize| {
+                evals[offset + 2 * i][row] + (evals[offset + 2 * i + 1][row] << 16)
+            };
+            let h_in: [u32x16; 8] = std::array::from_fn(|i| word(H_IN_OFFSET, i));
+            let m: [u32x16; 16] = std::array::from_fn(|i| word(M_OFFSET, i));
+            let [v12, v13] = [0, 1].map(|i| word(V12_OFFSET, i));
+            let is_last = evals[IS_LAST][row];
+
+            // The working state is kept in registers across the rounds
+            let mut v: [u32x16; 16] = std::array::from_fn(|i| match i {
+                0..8 => h_in[i],
+                12 => v12,
+                13 => v13,
+                14 => u32x16::splat(IV[6]) ^ (u32x16::splat(0) - is_last),
+                _ => u32x16::splat(IV[i - 8]),
+            });
+
+            for (round, sigma) in SIGMA.iter().enumerate() {
+                for (i, [a, b, c, d]) in G_INDICES.into_iter().enumerate() {
+                    let g = round * G_INDICES.len() + i;
+                    let (output, values) = g_u32x16(
+                        [v[a], v[b], v[c], v[d]],
+                        m[sigma[2 * i]],
+                        m[sigma[2 * i + 1]],
+                    );
+                    [v[a], v[b], v[c], v[d]] = output;
+
+                    let index = G_OFFSET + g * GColumns::SIZE;
+                    for (j, column) in g_columns.iter().enumerate() {
+                        evals[index + j][row] = values[*column];
+                    }
+                    let interaction_index = G_INTERACTION_OFFSET + g * GInteractionColumns::SIZE;
+                    for (j, value) in values.into_iter().enumerate() {
+                        lookup_data[interaction_index + j][row] = value;
+                    }
+                }
+            }
+
+            for (i, (h, left, right)) in izip!(h_in, &v[..8], &v[8..]).enumerate() {
+                let (h_out, values) = feed_forward_u32x16(h, *left, *right);
+
+                let index = FEED_FORWARD_OFFSET + i * FeedForwardColumns::SIZE;
+                let interaction_index =
+                    FEED_FORWARD_INTERACTION_OFFSET + i * FeedForwardColumns::SIZE;
+                for (j, value) in values.into_iter().enumerate() {
+                    evals[index + j][row] = value;
+                    lookup_data[interaction_index + j][row] = value;
+                }
+                lookup_data[H_OUT_INTERACTION_OFFSET + 2 * i][row] = h_out & u32x16::splat(0xffff);
+                lookup_data[H_OUT_INTERACTION_OFFSET + 2 * i + 1][row] = h_out >> 16;
+            }
+        }
+    });
+
+    let domain = CanonicCoset::new(log_size).circle_domain();
+    let trace = evals
+        .into_iter()
+        .map(|eval| {
+            let column = BaseColumn::from_simd(
+                eval.into_iter()
+                    .map(|x| unsafe { PackedM31::from_simd_unchecked(x) })
+                    .collect(),
+            );
+            CircleEvaluation::<SimdBackend, BaseField, BitReversedOrder>::new(domain, column)
+        })
+        .collect::<Vec<_>>();
+
+    (trace, lookup_data)
+}
+
+pub fn gen_interaction_trace(
+    lookup_data: &[Vec<u32x16>],
+    relations: &Relations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let simd_size = lookup_data[0].len();
+    let mut interaction_trace = LogupTraceGenerator::new(simd_size.ilog2() + LOG_N_LANES);
+
+    // Range check the additions and look up the XORs of each G evaluation
+    gen_g_interaction_trace(
+        g_lookup_data(lookup_data),
+        &relations.tables,
+        &mut interaction_trace,
+    );
+
+    // Look up the XORs of the feed-forward
+    let denoms = feed_forward_xor_lookups(lookup_data)
+        .into_iter()
+        .map(|columns| relations.tables.xor.combine(8, columns))
+        .collect::<Vec<_>>();
+    for [d_0, d_1] in denoms.array_chunks::<2>() {
+        consume_pair!(d_0.iter().copied(), d_1.iter().copied(), interaction_trace);
+    }
+
+    let ChainInteractionColumns {
+        message_id,
+        block_index,
+        next_block_index,
+        is_first,
+        is_last,
+        is_active,
+        v12_low,
+        v12_high,
+        v13_low,
+        v13_high,
+    } = ChainInteractionColumns::from_slice(&lookup_data[..H_IN_INTERACTION_OFFSET]);
+    let h_in = &lookup_data[H_IN_INTERACTION_OFFSET..M_INTERACTION_OFFSET];
+    let m = &lookup_data[M_INTERACTION_OFFSET..G_INTERACTION_OFFSET];
+    let h_out = &lookup_data[H_OUT_INTERACTION_OFFSET..N_INTERACTION_COLUMNS];
+
+    // Consume the input chaining value and emit the output one
+    let chain_in = combine!(
+        relations.chain,
+        [message_id, block_index]
+            .into_iter()
+            .chain(h_in)
+            .collect::<Vec<_>>()
+    );
+    let chain_out = combine!(
+        relations.chain,
+        [message_id, next_block_index]
+            .into_iter()
+            .chain(h_out)
+            .collect::<Vec<_>>()
+    );
+    let one = PackedQM31::one();
+    write_pair!(
+        is_first.iter().map(|v| to_packed_qm31(*v) - one),
+        chain_in,
+        is_last.iter().map(|v| one - to_packed_qm31(*v)),
+        chain_out,
+        interaction_trace
+    );
+
+    // Emit the public claims
+    let input_claim = combine!(
+        relations.input_claim,
+        [
+            message_id,
+            block_index,
+            v12_low,
+            v12_high,
+            v13_low,
+            v13_high
+        ]
+        .into_iter()
+        .chain(m)
+        .collect::<Vec<_>>()
+    );
+    let output_claim = combine!(
+        relations.output_claim,
+        [message_id].into_iter().chain(h_out).collect::<Vec<_>>()
+    );
+    write_pair!(
+        is_active.iter().map(|v| to_packed_qm31(*v)),
+        input_claim,
+        is_last
+            .iter()
+            .zip(is_active)
+            .map(|(l, a)| to_packed_qm31(*l) + to_packed_qm31(*a) - one),
+        output_claim,
+        interaction_trace
+    );
+
+    interaction_trace.finalize_last()
+}
+
+#[inline(always)]
+fn to_packed_qm31(value: u32x16) -> PackedQM31 {
+    PackedQM31::from(unsafe { PackedM31::from_simd_unchecked(value) })
+}
+
+#[cfg(test)]
+mod tests {
+    use blake2::{Blake2s256, Digest};
+
+    use super::*;
+    use crate::{
+        blake2s::compress,
+        message::{digests, gen_blocks, generate_blocks, to_bytes},
+    };
+
+    fn to_u32x16(
+        trace: &[CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>],
+    ) -> Vec<Vec<u32x16>> {
+        trace
+            .iter()
+            .map(|eval| {
+                eval.data
+                    .clone()
+                    .into_iter()
+                    .map(|x| x.into_simd())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Output chaining value of `row`, read from the lookup data.
+    fn h_out(lookup_data: &[Vec<u32x16>], row: usize) -> [u32; 8] {
+        std::array::from_fn(|i| {
+            let limb =
+                |j: usize| lookup_data[H_OUT_INTERACTION_OFFSET + 2 * i + j][row / 16][row % 16];
+            limb(0) + (limb(1) << 16)
+        })
+    }
+
+    #[test]
+    fn test_gen_trace_columns_count() {
+        let (trace, lookup_data) = gen_trace(4, &generate_blocks(16));
+        assert_eq!(trace.len(), N_COLUMNS);
+        assert_eq!(lookup_data.len(), N_INTERACTION_COLUMNS);
+    }
+
+    #[test]
+    fn test_gen_trace_values() {
+        let blocks = generate_blocks(16);
+        let (trace, lookup_data) = gen_trace(4, &blocks);
+        let trace = to_u32x16(&trace);
+
+        for (row, block) in blocks.iter().enumerate() {
+            assert_eq!(
+                h_out(&lookup_data, row),
+                compress(block.h_in, block.words, block.counter, block.is_last)
+            );
+            // The message words are committed as limbs
+            for i in 0..CHUNK_SIZE {
+                assert_eq!(
+                    trace[M_OFFSET + i][row / 16][row % 16],
+                    (block.words[i / 2] >> (16 * (i % 2))) & 0xffff
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_gen_trace_chained_blocks() {
+        let messages = [vec![0x42; 200], b"abc".to_vec()];
+        let blocks = gen_blocks(&messages);
+        let (_, lookup_data) = gen_trace(4, &blocks);
+
+        for (row, (block, next)) in blocks.iter().zip(&blocks[1..]).enumerate() {
+            if !block.is_last {
+                assert_eq!(h_out(&lookup_data, row), next.h_in);
+            }
+        }
+
+        let last_rows = blocks.iter().positions(|block| block.is_last);
+        for ((row, digest), message) in last_rows.zip(digests(&blocks)).zip(&messages) {
+            assert_eq!(to_bytes(h_out(&lookup_data, row)), digest);
+            assert_eq!(digest[..], Blake2s256::digest(message)[..]);
+        }
+    }
+
+    #[test]
+    fn test_gen_trace_padding_rows() {
+        // Rows past the blocks compress the padding block, i.e. hash the empty message.
+        let (trace, lookup_data) = gen_trace(4, &generate_blocks(3));
+        let trace = to_u32x16(&trace);
+        assert_eq!(trace[4][0][3], 0);
+        assert_eq!(
+            to_bytes(h_out(&lookup_data, 3)),
+            Blake2s256::digest(b"")[..]
+        );
+    }
+}
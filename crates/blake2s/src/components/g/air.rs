@@ -0,0 +1,207 @@
+use itertools::izip;
+use num_traits::{One, Zero};
+use stwo::core::fields::m31::M31;
+use stwo_constraint_framework::EvalAtRow;
+use utils::add_to_relation;
+
+use crate::{
+    components::g::columns::GColumnsOwned,
+    partitions::{rotr_positions, BYTES, R12, R7},
+    relations::TableRelations,
+};
+
+/// A 32-bit word, as 16-bit limbs.
+pub type Word<F> = [F; 2];
+
+pub fn constant<E: EvalAtRow>(value: u32) -> Word<E::F> {
+    [
+        E::F::from(M31::from(value & 0xffff)),
+        E::F::from(M31::from(value >> 16)),
+    ]
+}
+
+/// Limbs of the word made of `pieces` of the given widths, rotated right by `r`.
+pub fn rotr_limbs<E: EvalAtRow, const N: usize>(
+    pieces: &[E::F; N],
+    widths: [u32; N],
+    r: u32,
+) -> Word<E::F> {
+    let mut limbs = [E::F::zero(), E::F::zero()];
+    for (piece, (limb, shift)) in pieces.iter().zip(rotr_positions(widths, r)) {
+        limbs[limb] = limbs[limb].clone() + piece.clone() * E::F::from(M31::from(1 << shift));
+    }
+    limbs
+}
+
+pub fn eq_constraints<E: EvalAtRow>(eval: &mut E, lhs: &Word<E::F>, rhs: &Word<E::F>) {
+    eval.add_constraint(lhs[0].clone() - rhs[0].clone());
+    eval.add_constraint(lhs[1].clone() - rhs[1].clone());
+}
+
+/// Constrains `sum` to be the sum of `terms` modulo 2^32, the carries being range checked
+/// together with the limbs of `sum`.
+pub fn add_constraints<E: EvalAtRow>(
+    eval: &mut E,
+    terms: &[&Word<E::F>],
+    sum: &Word<E::F>,
+    carry_low: &E::F,
+    carry_high: &E::F,
+) {
+    let two_16 = E::F::from(M31::from(1 << 16));
+    let low = terms
+        .iter()
+        .fold(E::F::zero(), |acc, term| acc + term[0].clone());
+    eval.add_constraint(low - sum[0].clone() - carry_low.clone() * two_16.clone());
+    let high = terms
+        .iter()
+        .fold(carry_low.clone(), |acc, term| acc + term[1].clone());
+    eval.add_constraint(high - sum[1].clone() - carry_high.clone() * two_16);
+}
+
+/// Constrains one evaluation of G and returns the new values of `[a, b, c, d]`.
+///
+/// Each XOR is looked up piece by piece, the pieces being cut so that the following rotation only
+/// moves them around, see [`crate::partitions`]. The sums are only committed through their
+/// pieces, which range checks their limbs.
+pub fn eval_g<E: EvalAtRow>(
+    eval: &mut E,
+    relations: &TableRelations,
+    [a, b, c, d]: [Word<E::F>; 4],
+    x: &Word<E::F>,
+    y: &Word<E::F>,
+) -> [Word<E::F>; 4] {
+    let GColumnsOwned {
+        a1_carry_low,
+        a1_carry_high,
+        d_16_0,
+        d_16_1,
+        d_16_2,
+        d_16_3,
+        a1_16_0,
+        a1_16_1,
+        a1_16_2,
+        a1_16_3,
+        xor_16_0,
+        xor_16_1,
+        xor_16_2,
+        xor_16_3,
+        c1_carry_low,
+        c1_carry_high,
+        b_12_0,
+        b_12_1,
+        b_12_2,
+        b_12_3,
+        b_12_4,
+        b_12_5,
+        c1_12_0,
+        c1_12_1,
+        c1_12_2,
+        c1_12_3,
+        c1_12_4,
+        c1_12_5,
+        xor_12_0,
+        xor_12_1,
+        xor_12_2,
+        xor_12_3,
+        xor_12_4,
+        xor_12_5,
+        a2_carry_low,
+        a2_carry_high,
+        a2_8_0,
+        a2_8_1,
+        a2_8_2,
+        a2_8_3,
+        xor_8_0,
+        xor_8_1,
+        xor_8_2,
+        xor_8_3,
+        c2_carry_low,
+        c2_carry_high,
+        b1_7_0,
+        b1_7_1,
+        b1_7_2,
+        b1_7_3,
+        c2_7_0,
+        c2_7_1,
+        c2_7_2,
+        c2_7_3,
+        xor_7_0,
+        xor_7_1,
+        xor_7_2,
+        xor_7_3,
+    } = GColumnsOwned::<<E as EvalAtRow>::F>::from_eval(eval);
+    let d_16 = [d_16_0, d_16_1, d_16_2, d_16_3];
+    let a1_16 = [a1_16_0, a1_16_1, a1_16_2, a1_16_3];
+    let xor_16 = [xor_16_0, xor_16_1, xor_16_2, xor_16_3];
+    let b_12 = [b_12_0, b_12_1, b_12_2, b_12_3, b_12_4, b_12_5];
+    let c1_12 = [c1_12_0, c1_12_1, c1_12_2, c1_12_3, c1_12_4, c1_12_5];
+    let xor_12 = [xor_12_0, xor_12_1, xor_12_2, xor_12_3, xor_12_4, xor_12_5];
+    let a2_8 = [a2_8_0, a2_8_1, a2_8_2, a2_8_3];
+    let xor_8 = [xor_8_0, xor_8_1, xor_8_2, xor_8_3];
+    let b1_7 = [b1_7_0, b1_7_1, b1_7_2, b1_7_3];
+    let c2_7 = [c2_7_0, c2_7_1, c2_7_2, c2_7_3];
+    let xor_7 = [xor_7_0, xor_7_1, xor_7_2, xor_7_3];
+
+    // a1 = a + b + x
+    let a1 = rotr_limbs::<E, 4>(&a1_16, BYTES, 0);
+    add_constraints(eval, &[&a, &b, x], &a1, &a1_carry_low, &a1_carry_high);
+
+    // d1 = (d ^ a1) >>> 16
+    eq_constraints(eval, &d, &rotr_limbs::<E, 4>(&d_16, BYTES, 0));
+    let d1 = rotr_limbs::<E, 4>(&xor_16, BYTES, 16);
+
+    // c1 = c + d1
+    let c1 = rotr_limbs::<E, 6>(&c1_12, R12, 0);
+    add_constraints(eval, &[&c, &d1], &c1, &c1_carry_low, &c1_carry_high);
+
+    // b1 = (b ^ c1) >>> 12
+    eq_constraints(eval, &b, &rotr_limbs::<E, 6>(&b_12, R12, 0));
+    let b1 = rotr_limbs::<E, 6>(&xor_12, R12, 12);
+
+    // a2 = a1 + b1 + y
+    let a2 = rotr_limbs::<E, 4>(&a2_8, BYTES, 0);
+    add_constraints(eval, &[&a1, &b1, y], &a2, &a2_carry_low, &a2_carry_high);
+
+    // d2 = (d1 ^ a2) >>> 8, the bytes of d1 being the bytes of the first XOR in another order
+    let d1_8 = [2, 3, 0, 1].map(|i| xor_16[i].clone());
+    let d2 = rotr_limbs::<E, 4>(&xor_8, BYTES, 8);
+
+    // c2 = c1 + d2
+    let c2 = rotr_limbs::<E, 4>(&c2_7, R7, 0);
+    add_constraints(eval, &[&c1, &d2], &c2, &c2_carry_low, &c2_carry_high);
+
+    // b2 = (b1 ^ c2) >>> 7
+    eq_constraints(eval, &b1, &rotr_limbs::<E, 4>(&b1_7, R7, 0));
+    let b2 = rotr_limbs::<E, 4>(&xor_7, R7, 7);
+
+    let minus_one = -E::EF::one();
+    for (value, carry) in [
+        (&a1[0], &a1_carry_low),
+        (&a1[1], &a1_carry_high),
+        (&c1[0], &c1_carry_low),
+        (&c1[1], &c1_carry_high),
+        (&a2[0], &a2_carry_low),
+        (&a2[1], &a2_carry_high),
+        (&c2[0], &c2_carry_low),
+        (&c2[1], &c2_carry_high),
+    ] {
+        add_to_relation!(
+            eval,
+            relations.range_check_add.add_4,
+            minus_one,
+            value,
+            carry
+        );
+    }
+    let xor_lookups = izip!(BYTES, a1_16, d_16, xor_16)
+        .chain(izip!(R12, b_12, c1_12, xor_12))
+        .chain(izip!(BYTES, a2_8, d1_8, xor_8))
+        .chain(izip!(R7, b1_7, c2_7, xor_7));
+    for (n_bits, lhs, rhs, xor) in xor_lookups {
+        relations
+            .xor
+            .add_to_eval(eval, n_bits, minus_one.clone(), &[lhs, rhs, xor]);
+    }
+
+    [a2, b2, c2, d2]
+}
@@ -0,0 +1,182 @@
+use utils::trace_columns;
+
+use crate::partitions::{R12, R7};
+
+trace_columns!(
+    GColumns,
+    a1_carry_low,
+    a1_carry_high,
+    d_16_0,
+    d_16_1,
+    d_16_2,
+    d_16_3,
+    a1_16_0,
+    a1_16_1,
+    a1_16_2,
+    a1_16_3,
+    xor_16_0,
+    xor_16_1,
+    xor_16_2,
+    xor_16_3,
+    c1_carry_low,
+    c1_carry_high,
+    b_12_0,
+    b_12_1,
+    b_12_2,
+    b_12_3,
+    b_12_4,
+    b_12_5,
+    c1_12_0,
+    c1_12_1,
+    c1_12_2,
+    c1_12_3,
+    c1_12_4,
+    c1_12_5,
+    xor_12_0,
+    xor_12_1,
+    xor_12_2,
+    xor_12_3,
+    xor_12_4,
+    xor_12_5,
+    a2_carry_low,
+    a2_carry_high,
+    a2_8_0,
+    a2_8_1,
+    a2_8_2,
+    a2_8_3,
+    xor_8_0,
+    xor_8_1,
+    xor_8_2,
+    xor_8_3,
+    c2_carry_low,
+    c2_carry_high,
+    b1_7_0,
+    b1_7_1,
+    b1_7_2,
+    b1_7_3,
+    c2_7_0,
+    c2_7_1,
+    c2_7_2,
+    c2_7_3,
+    xor_7_0,
+    xor_7_1,
+    xor_7_2,
+    xor_7_3
+);
+
+trace_columns!(
+    GInteractionColumns,
+    a1_low,
+    a1_high,
+    a1_carry_low,
+    a1_carry_high,
+    d_16_0,
+    d_16_1,
+    d_16_2,
+    d_16_3,
+    a1_16_0,
+    a1_16_1,
+    a1_16_2,
+    a1_16_3,
+    xor_16_0,
+    xor_16_1,
+    xor_16_2,
+    xor_16_3,
+    c1_low,
+    c1_high,
+    c1_carry_low,
+    c1_carry_high,
+    b_12_0,
+    b_12_1,
+    b_12_2,
+    b_12_3,
+    b_12_4,
+    b_12_5,
+    c1_12_0,
+    c1_12_1,
+    c1_12_2,
+    c1_12_3,
+    c1_12_4,
+    c1_12_5,
+    xor_12_0,
+    xor_12_1,
+    xor_12_2,
+    xor_12_3,
+    xor_12_4,
+    xor_12_5,
+    a2_low,
+    a2_high,
+    a2_carry_low,
+    a2_carry_high,
+    a2_8_0,
+    a2_8_1,
+    a2_8_2,
+    a2_8_3,
+    xor_8_0,
+    xor_8_1,
+    xor_8_2,
+    xor_8_3,
+    c2_low,
+    c2_high,
+    c2_carry_low,
+    c2_carry_high,
+    b1_7_0,
+    b1_7_1,
+    b1_7_2,
+    b1_7_3,
+    c2_7_0,
+    c2_7_1,
+    c2_7_2,
+    c2_7_3,
+    xor_7_0,
+    xor_7_1,
+    xor_7_2,
+    xor_7_3
+);
+
+/// Number of lookups of a G evaluation: the range checks of the 8 limb additions, then the XOR
+/// lookups.
+pub const N_G_LOOKUPS: usize = 8 + 18;
+
+impl<'a, T> GInteractionColumns<'a, T> {
+    /// `[value, carry]` of the range checks of the additions, in the order of the AIR.
+    pub fn add_lookups(&self) -> [[&'a T; 2]; 8] {
+        [
+            [self.a1_low, self.a1_carry_low],
+            [self.a1_high, self.a1_carry_high],
+            [self.c1_low, self.c1_carry_low],
+            [self.c1_high, self.c1_carry_high],
+            [self.a2_low, self.a2_carry_low],
+            [self.a2_high, self.a2_carry_high],
+            [self.c2_low, self.c2_carry_low],
+            [self.c2_high, self.c2_carry_high],
+        ]
+    }
+
+    /// Width and `[a, b, a ^ b]` of the XOR lookups, in the order of the AIR.
+    ///
+    /// The pieces of `d1` are not committed: `d1` is the rotation of the first XOR by a multiple
+    /// of 8 bits, so that its bytes are the bytes of the XOR in another order.
+    pub fn xor_lookups(&self) -> [(u32, [&'a T; 3]); 18] {
+        [
+            (8, [self.a1_16_0, self.d_16_0, self.xor_16_0]),
+            (8, [self.a1_16_1, self.d_16_1, self.xor_16_1]),
+            (8, [self.a1_16_2, self.d_16_2, self.xor_16_2]),
+            (8, [self.a1_16_3, self.d_16_3, self.xor_16_3]),
+            (R12[0], [self.b_12_0, self.c1_12_0, self.xor_12_0]),
+            (R12[1], [self.b_12_1, self.c1_12_1, self.xor_12_1]),
+            (R12[2], [self.b_12_2, self.c1_12_2, self.xor_12_2]),
+            (R12[3], [self.b_12_3, self.c1_12_3, self.xor_12_3]),
+            (R12[4], [self.b_12_4, self.c1_12_4, self.xor_12_4]),
+            (R12[5], [self.b_12_5, self.c1_12_5, self.xor_12_5]),
+            (8, [self.a2_8_0, self.xor_16_2, self.xor_8_0]),
+            (8, [self.a2_8_1, self.xor_16_3, self.xor_8_1]),
+            (8, [self.a2_8_2, self.xor_16_0, self.xor_8_2]),
+            (8, [self.a2_8_3, self.xor_16_1, self.xor_8_3]),
+            (R7[0], [self.b1_7_0, self.c2_7_0, self.xor_7_0]),
+            (R7[1], [self.b1_7_1, self.c2_7_1, self.xor_7_1]),
+            (R7[2], [self.b1_7_2, self.c2_7_2, self.xor_7_2]),
+            (R7[3], [self.b1_7_3, self.c2_7_3, self.xor_7_3]),
+        ]
+    }
+}
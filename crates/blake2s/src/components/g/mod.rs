@@ -0,0 +1,9 @@
+//! Constraints and witness of the G mixing function, shared by the compression components of the
+//! AIRs built on the BLAKE2s tables.
+//!
+//! Each evaluation of G range checks its 8 limb additions and looks up its XORs piece by piece,
+//! see [`crate::partitions`].
+
+pub mod air;
+pub mod columns;
+pub mod witness;
@@ -0,0 +1,101 @@
+use std::simd::u32x16;
+
+use itertools::{chain, Itertools};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, consume_pair};
+
+use crate::{
+    blake2s::rotr_u32x16,
+    components::g::columns::{GColumns, GInteractionColumns, N_G_LOOKUPS},
+    partitions::{split_u32x16, BYTES, R12, R7},
+    relations::TableRelations,
+};
+
+/// Sum of `terms` modulo 2^32, together with the carries of its low and high limbs.
+#[inline(always)]
+fn add_u32x16(terms: &[u32x16]) -> (u32x16, u32x16, u32x16) {
+    let mask = u32x16::splat(0xffff);
+    let low = terms
+        .iter()
+        .fold(u32x16::splat(0), |acc, term| acc + (*term & mask));
+    let high = terms
+        .iter()
+        .fold(low >> 16, |acc, term| acc + (*term >> 16));
+    ((low & mask) + ((high & mask) << 16), low >> 16, high >> 16)
+}
+
+/// Evaluates G on `[a, b, c, d]`, returning their new values and the lookup data of the
+/// evaluation, following [`GInteractionColumns`].
+#[inline(always)]
+pub fn g_u32x16(
+    [a, b, c, d]: [u32x16; 4],
+    x: u32x16,
+    y: u32x16,
+) -> ([u32x16; 4], [u32x16; GInteractionColumns::SIZE]) {
+    let mask = u32x16::splat(0xffff);
+    let (a1, a1_carry_low, a1_carry_high) = add_u32x16(&[a, b, x]);
+    let d1 = rotr_u32x16(d ^ a1, 16);
+    let (c1, c1_carry_low, c1_carry_high) = add_u32x16(&[c, d1]);
+    let b1 = rotr_u32x16(b ^ c1, 12);
+    let (a2, a2_carry_low, a2_carry_high) = add_u32x16(&[a1, b1, y]);
+    let d2 = rotr_u32x16(d1 ^ a2, 8);
+    let (c2, c2_carry_low, c2_carry_high) = add_u32x16(&[c1, d2]);
+    let b2 = rotr_u32x16(b1 ^ c2, 7);
+
+    let mut values = [u32x16::splat(0); GInteractionColumns::SIZE];
+    let lookup_data = chain!(
+        [a1 & mask, a1 >> 16, a1_carry_low, a1_carry_high],
+        split_u32x16(d, BYTES),
+        split_u32x16(a1, BYTES),
+        split_u32x16(d ^ a1, BYTES),
+        [c1 & mask, c1 >> 16, c1_carry_low, c1_carry_high],
+        split_u32x16(b, R12),
+        split_u32x16(c1, R12),
+        split_u32x16(b ^ c1, R12),
+        [a2 & mask, a2 >> 16, a2_carry_low, a2_carry_high],
+        split_u32x16(a2, BYTES),
+        split_u32x16(d1 ^ a2, BYTES),
+        [c2 & mask, c2 >> 16, c2_carry_low, c2_carry_high],
+        split_u32x16(b1, R7),
+        split_u32x16(c2, R7),
+        split_u32x16(b1 ^ c2, R7),
+    );
+    for (value, column) in lookup_data.zip_eq(values.iter_mut()) {
+        *column = value;
+    }
+
+    ([a2, b2, c2, d2], values)
+}
+
+/// Position of the trace columns of a G evaluation in its lookup data.
+pub fn g_trace_columns() -> [usize; GColumns::SIZE] {
+    std::array::from_fn(|i| {
+        GInteractionColumns::NAMES
+            .iter()
+            .position(|name| *name == GColumns::NAMES[i])
+            .unwrap()
+    })
+}
+
+/// Range checks the additions and looks up the XORs of each G evaluation of `g_lookup_data`, laid
+/// out one after the other following [`GInteractionColumns`].
+pub fn gen_g_interaction_trace(
+    g_lookup_data: &[Vec<u32x16>],
+    relations: &TableRelations,
+    interaction_trace: &mut LogupTraceGenerator,
+) {
+    for g in g_lookup_data.array_chunks::<{ GInteractionColumns::SIZE }>() {
+        let cols = GInteractionColumns::from_slice(g);
+        let adds = cols
+            .add_lookups()
+            .map(|columns| combine!(relations.range_check_add.add_4, columns));
+        let xors = cols
+            .xor_lookups()
+            .map(|(n_bits, columns)| relations.xor.combine(n_bits, columns));
+        let denoms = adds.into_iter().chain(xors).collect::<Vec<_>>();
+        debug_assert_eq!(denoms.len(), N_G_LOOKUPS);
+        for [d_0, d_1] in denoms.array_chunks::<2>() {
+            consume_pair!(d_0.iter().copied(), d_1.iter().copied(), interaction_trace);
+        }
+    }
+}
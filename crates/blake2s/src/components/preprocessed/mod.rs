@@ -0,0 +1,22 @@
+//! Components of the XOR and addition range check tables.
+//!
+//! Their multiplicities only depend on the lookup data of the G evaluations and of the byte XORs
+//! around them, so that they can be shared by the AIRs built on the G function of BLAKE2s.
+
+mod range_check_add;
+mod xor;
+
+use utils::components;
+
+components!(
+    relations: crate::relations::TableRelations,
+    lookup_data: (
+        g_lookup_data: &[Vec<u32x16>],
+        byte_xor_lookups: &[[&Vec<u32x16>; 3]],
+    ),
+    xor::xor_4,
+    xor::xor_7,
+    xor::xor_8,
+    xor::xor_9,
+    range_check_add::range_check_add,
+);
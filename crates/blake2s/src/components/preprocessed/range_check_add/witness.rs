@@ -0,0 +1,101 @@
+use std::simd::u32x16;
+
+use itertools::{izip, Itertools};
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, histogram::par_histograms, write_col, write_pair};
+
+use crate::{
+    components::g::columns::GInteractionColumns,
+    preprocessed::range_check_add::{self, RangeCheckAddColumns, LOG_SIZE},
+    relations::TableRelations,
+};
+
+/// Multiplicities of the addition range check table, looked up by the G evaluations of
+/// `g_lookup_data`, the byte XORs outside of G doing no addition.
+pub fn gen_trace(
+    log_size: u32,
+    g_lookup_data: &[Vec<u32x16>],
+    _byte_xor_lookups: &[[&Vec<u32x16>; 3]],
+) -> Vec<Vec<u32x16>> {
+    // One task per G evaluation
+    let n_g = g_lookup_data.len() / GInteractionColumns::SIZE;
+    let [add_4_mult] = par_histograms([LOG_SIZE], n_g, |g, [add_4_mult]| {
+        let start = g * GInteractionColumns::SIZE;
+        let end = start + GInteractionColumns::SIZE;
+
+        let cols = GInteractionColumns::from_slice(&g_lookup_data[start..end]);
+        for [value, carry] in cols.add_lookups() {
+            izip!(value, carry).for_each(|(value, carry)| {
+                add_4_mult.add((*value << 2) + *carry);
+            });
+        }
+    });
+
+    add_4_mult.into_columns(log_size)
+}
+
+pub fn gen_interaction_trace(
+    trace: &[Vec<u32x16>],
+    relations: &TableRelations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let preprocessed_columns = range_check_add::gen_column_simd();
+    let RangeCheckAddColumns { value, carry } =
+        RangeCheckAddColumns::from_slice(&preprocessed_columns[..]);
+
+    let simd_size = trace[0].len();
+    let log_size = simd_size.ilog2() + LOG_N_LANES;
+    let mut interaction_trace = LogupTraceGenerator::new(log_size);
+
+    let add_4_den = combine!(relations.range_check_add.add_4, [&value, &carry]);
+
+    for ([add_4_mult_0, add_4_mult_1], (add_4_den_0, add_4_den_1)) in trace
+        .array_chunks::<2>()
+        .zip(add_4_den.chunks(simd_size).tuples())
+    {
+        write_pair!(
+            add_4_mult_0
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            add_4_den_0.to_vec(),
+            add_4_mult_1
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            add_4_den_1.to_vec(),
+            interaction_trace
+        );
+    }
+
+    if trace.len() % 2 == 1 {
+        let add_4_mult = trace.last().unwrap();
+        let add_4_den_chunk = add_4_den.chunks(simd_size).last().unwrap();
+        write_col!(
+            add_4_mult
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            add_4_den_chunk.to_vec(),
+            interaction_trace
+        );
+    }
+
+    interaction_trace.finalize_last()
+}
@@ -0,0 +1,128 @@
+use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval};
+
+use crate::{
+    components::preprocessed::xor::columns::ComponentColumnsOwned as ComponentColumns,
+    preprocessed::xor::column_ids, relations::TableRelations,
+};
+
+pub type Component<const N_BITS: u32> = FrameworkComponent<Eval<N_BITS>>;
+
+fn eval_constraints<E: EvalAtRow, const N_BITS: u32>(
+    eval: &mut E,
+    relations: &TableRelations,
+    log_size: u32,
+) {
+    let chunk_count = 1 << (2 * N_BITS).saturating_sub(log_size);
+    for chunk in 0..chunk_count {
+        let ComponentColumns { mult } = ComponentColumns::<<E as EvalAtRow>::F>::from_eval(eval);
+        let [a, b, c] = column_ids(N_BITS, chunk).map(|id| eval.get_preprocessed_column(id));
+        relations
+            .xor
+            .add_to_eval(eval, N_BITS, E::EF::from(mult), &[a, b, c]);
+    }
+    eval.finalize_logup_in_pairs();
+}
+
+/// The table of `N_BITS`-bit XORs has `2^(2 * N_BITS)` rows, see [`crate::preprocessed::xor`].
+#[derive(Clone)]
+pub struct Eval<const N_BITS: u32> {
+    pub log_size: u32,
+    pub relations: TableRelations,
+}
+impl<const N_BITS: u32> FrameworkEval for Eval<N_BITS> {
+    fn log_size(&self) -> u32 {
+        (2 * N_BITS).min(self.log_size)
+    }
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        (2 * N_BITS).min(self.log_size) + 1
+    }
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        eval_constraints::<E, N_BITS>(&mut eval, &self.relations, self.log_size);
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::simd::u32x16;
+
+    use itertools::Itertools;
+    use stwo::{
+        core::{pcs::TreeVec, poly::circle::CanonicCoset},
+        prover::backend::simd::m31::LOG_N_LANES,
+    };
+    use stwo_constraint_framework::assert_constraints_on_polys;
+    use utils::circle_evaluation_u32x16;
+
+    use super::*;
+    use crate::{
+        components::{
+            compression::witness::{
+                feed_forward_xor_lookups, g_lookup_data, gen_trace as gen_compression_trace,
+            },
+            preprocessed::xor::witness::{gen_interaction_trace, gen_trace},
+        },
+        message::generate_blocks,
+        preprocessed::xor::gen_table_columns,
+    };
+
+    fn assert_xor_constraints<const N_BITS: u32>(
+        g_lookup_data: &[Vec<u32x16>],
+        byte_xor_lookups: &[[&Vec<u32x16>; 3]],
+    ) {
+        let max_log_size = 10;
+        let trace = gen_trace::<N_BITS>(max_log_size, g_lookup_data, byte_xor_lookups);
+
+        let simd_size = trace[0].len();
+        let log_size = simd_size.ilog2() + LOG_N_LANES;
+
+        let relations = TableRelations::dummy();
+        let (interaction_trace, claimed_sum) = gen_interaction_trace::<N_BITS>(&trace, &relations);
+
+        // Chunks of the table, column by column inside each chunk
+        let table = gen_table_columns(N_BITS);
+        let preprocessed_trace = (0..trace.len())
+            .flat_map(|chunk| {
+                table.iter().map(move |column| {
+                    circle_evaluation_u32x16!(column[chunk * simd_size..(chunk + 1) * simd_size])
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let traces = TreeVec::new(vec![
+            preprocessed_trace,
+            trace
+                .into_iter()
+                .map(|c| circle_evaluation_u32x16!(c))
+                .collect::<Vec<_>>(),
+            interaction_trace,
+        ]);
+
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(log_size),
+            |mut eval| {
+                eval_constraints::<_, N_BITS>(&mut eval, &relations, log_size);
+            },
+            claimed_sum,
+        );
+    }
+
+    #[test_log::test]
+    fn test_constraints() {
+        const LOG_N_ROWS: u32 = 8;
+
+        let blocks = generate_blocks(1 << LOG_N_ROWS);
+        let (_, compression_lookup_data) = gen_compression_trace(LOG_N_ROWS, &blocks);
+        let g_lookup_data = g_lookup_data(&compression_lookup_data);
+        let byte_xor_lookups = feed_forward_xor_lookups(&compression_lookup_data);
+
+        assert_xor_constraints::<4>(g_lookup_data, &byte_xor_lookups);
+        assert_xor_constraints::<7>(g_lookup_data, &byte_xor_lookups);
+        assert_xor_constraints::<8>(g_lookup_data, &byte_xor_lookups);
+        assert_xor_constraints::<9>(g_lookup_data, &byte_xor_lookups);
+    }
+}
@@ -0,0 +1,51 @@
+//! Multiplicities of the XOR tables, one component per table width.
+//!
+//! The components only differ by the width of their table, so they share a generic AIR and
+//! witness, instantiated below inside their own namespaces to comply with the components! macro.
+
+pub mod air;
+pub mod columns;
+pub mod witness;
+
+macro_rules! xor_components {
+    ($($name:ident: $n_bits:literal),+ $(,)?) => {
+        $(
+            pub mod $name {
+                pub mod air {
+                    pub type Eval = super::super::air::Eval<$n_bits>;
+                    pub type Component = super::super::air::Component<$n_bits>;
+                }
+
+                pub mod witness {
+                    use std::simd::u32x16;
+
+                    use crate::{
+                        components::preprocessed::xor::witness::InteractionTrace,
+                        relations::TableRelations,
+                    };
+
+                    pub fn gen_trace(
+                        log_size: u32,
+                        g_lookup_data: &[Vec<u32x16>],
+                        byte_xor_lookups: &[[&Vec<u32x16>; 3]],
+                    ) -> Vec<Vec<u32x16>> {
+                        super::super::witness::gen_trace::<$n_bits>(
+                            log_size,
+                            g_lookup_data,
+                            byte_xor_lookups,
+                        )
+                    }
+
+                    pub fn gen_interaction_trace(
+                        trace: &[Vec<u32x16>],
+                        relations: &TableRelations,
+                    ) -> InteractionTrace {
+                        super::super::witness::gen_interaction_trace::<$n_bits>(trace, relations)
+                    }
+                }
+            }
+        )+
+    };
+}
+
+xor_components!(xor_4: 4, xor_7: 7, xor_8: 8, xor_9: 9);
@@ -0,0 +1,108 @@
+use std::simd::u32x16;
+
+use itertools::{izip, Itertools};
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::LogupTraceGenerator;
+use utils::{histogram::par_histograms, write_col, write_pair};
+
+use crate::{
+    components::g::columns::GInteractionColumns, preprocessed::xor::gen_table_columns,
+    relations::TableRelations,
+};
+
+pub type InteractionTrace = (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+);
+
+/// Multiplicities of the `N_BITS`-bit XOR table, looked up by the G evaluations of
+/// `g_lookup_data` and by the byte XORs of `byte_xor_lookups`.
+pub fn gen_trace<const N_BITS: u32>(
+    log_size: u32,
+    g_lookup_data: &[Vec<u32x16>],
+    byte_xor_lookups: &[[&Vec<u32x16>; 3]],
+) -> Vec<Vec<u32x16>> {
+    // One task per G evaluation and one for the other XORs, which are all on bytes
+    let n_g = g_lookup_data.len() / GInteractionColumns::SIZE;
+    let [mult] = par_histograms([2 * N_BITS], n_g + 1, |task, [mult]| {
+        if task < n_g {
+            let start = task * GInteractionColumns::SIZE;
+            let end = start + GInteractionColumns::SIZE;
+
+            let cols = GInteractionColumns::from_slice(&g_lookup_data[start..end]);
+            for (n_bits, [a, b, _]) in cols.xor_lookups() {
+                if n_bits == N_BITS {
+                    izip!(a, b).for_each(|(a, b)| mult.add((*a << N_BITS) + *b));
+                }
+            }
+        } else if N_BITS == 8 {
+            for [a, b, _] in byte_xor_lookups {
+                izip!(*a, *b).for_each(|(a, b)| mult.add((*a << N_BITS) + *b));
+            }
+        }
+    });
+
+    mult.into_columns(log_size)
+}
+
+pub fn gen_interaction_trace<const N_BITS: u32>(
+    trace: &[Vec<u32x16>],
+    relations: &TableRelations,
+) -> InteractionTrace {
+    let preprocessed_columns = gen_table_columns(N_BITS);
+    let [a, b, c] = &preprocessed_columns[..] else {
+        unreachable!()
+    };
+
+    let simd_size = trace[0].len();
+    let log_size = simd_size.ilog2() + LOG_N_LANES;
+    let mut interaction_trace = LogupTraceGenerator::new(log_size);
+
+    let den = relations.xor.combine(N_BITS, [a, b, c]);
+
+    for ([mult_0, mult_1], (den_0, den_1)) in trace
+        .array_chunks::<2>()
+        .zip(den.chunks(simd_size).tuples())
+    {
+        write_pair!(
+            mult_0
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            den_0.to_vec(),
+            mult_1
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            den_1.to_vec(),
+            interaction_trace
+        );
+    }
+
+    if trace.len() % 2 == 1 {
+        let mult = trace.last().unwrap();
+        let den_chunk = den.chunks(simd_size).last().unwrap();
+        write_col!(
+            mult.iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            den_chunk.to_vec(),
+            interaction_trace
+        );
+    }
+
+    interaction_trace.finalize_last()
+}
@@ -0,0 +1,194 @@
+#![allow(non_camel_case_types)]
+#![feature(portable_simd, array_chunks, iter_array_chunks)]
+pub mod blake2s;
+pub mod components;
+pub mod message;
+pub mod partitions;
+pub mod preprocessed;
+pub mod prover;
+pub mod public;
+pub mod relations;
+pub mod verifier;
+
+use stwo::{
+    core::{channel::MerkleChannel, pcs::PcsConfig, proof::StarkProof},
+    prover::backend::{simd::SimdBackend, BackendForChannel},
+};
+use utils::stwo::air::ProveError;
+
+use crate::{components::ClaimedSum, message::Block};
+pub use crate::{
+    prover::Blake2sProver,
+    public::HashClaim,
+    verifier::{preprocessed_root, verify_blake2s, Blake2sVerifierError},
+};
+
+/// Largest supported trace log size: each row does 864 lookups into the 8-bit XOR table, and the
+/// multiplicities must fit in M31.
+pub const MAX_LOG_SIZE: u32 = 21;
+
+/// Errors of the BLAKE2s prover.
+pub type Blake2sProverError = ProveError<ClaimedSum>;
+
+/// Proves the BLAKE2s compression of each message block, see [`message::gen_blocks`] to split
+/// messages into chained blocks.
+///
+/// Blocks of the same message are linked through the chain relation, so they must appear in
+/// order and with consistent chaining values. The batch is padded with the single block of the
+/// empty message, which emits no public claim: each block claims its counter and message words
+/// and each message its digest, see [`public`]. Verify with [`verify_blake2s`].
+pub fn prove_blake2s<MC: MerkleChannel>(
+    blocks: &[Block],
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), Blake2sProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    Blake2sProver::<MC>::new(log_size, config)?.prove(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use blake2::{Blake2s256, Digest};
+    use num_traits::Zero;
+    use stwo::{
+        core::{fields::qm31::SecureField, vcs::blake2_merkle::Blake2sMerkleChannel},
+        prover::backend::simd::m31::LOG_N_LANES,
+    };
+
+    use super::*;
+    use crate::message::{gen_blocks, generate_blocks};
+
+    fn prove_messages(
+        messages: &[Vec<u8>],
+        log_size: u32,
+        config: PcsConfig,
+    ) -> (
+        StarkProof<<Blake2sMerkleChannel as MerkleChannel>::H>,
+        ClaimedSum,
+        Vec<HashClaim>,
+    ) {
+        let (proof, claimed_sum) =
+            prove_blake2s::<Blake2sMerkleChannel>(&gen_blocks(messages), log_size, config).unwrap();
+        let claims = messages
+            .iter()
+            .map(|message| HashClaim::new(message.clone()))
+            .collect();
+        (proof, claimed_sum, claims)
+    }
+
+    #[test_log::test]
+    fn test_verify_blake2s() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![vec![0xab; 200], b"abc".to_vec(), vec![], vec![0x42; 64]];
+        let (proof, claimed_sum, claims) = prove_messages(&messages, log_size, config);
+        for (message, claim) in messages.iter().zip(&claims) {
+            assert_eq!(claim.digest[..], Blake2s256::digest(message)[..]);
+        }
+        verify_blake2s::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_blake2s_wrong_digest() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![vec![0xab; 200], b"abc".to_vec()];
+        let (proof, claimed_sum, mut claims) = prove_messages(&messages, log_size, config);
+        claims[1].digest[31] ^= 1;
+        assert!(verify_blake2s::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            &claims
+        )
+        .is_err());
+    }
+
+    #[test_log::test]
+    fn test_verify_blake2s_wrong_message() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec()];
+        let (proof, claimed_sum, _) = prove_messages(&messages, log_size, config);
+        let claims = [HashClaim {
+            message: b"abd".to_vec(),
+            digest: HashClaim::new(b"abc".to_vec()).digest,
+        }];
+        assert!(verify_blake2s::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            &claims
+        )
+        .is_err());
+    }
+
+    #[test_log::test]
+    fn test_verify_blake2s_missing_claim() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec(), b"def".to_vec()];
+        let (proof, claimed_sum, claims) = prove_messages(&messages, log_size, config);
+        assert!(verify_blake2s::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            &claims[..1]
+        )
+        .is_err());
+    }
+
+    #[test_log::test]
+    fn test_prove_blake2s_broken_chain() {
+        let log_size = 8;
+        let mut blocks = gen_blocks(&[vec![0xab; 200]]);
+        blocks[2].h_in[0] ^= 1;
+        let err = prove_blake2s::<Blake2sMerkleChannel>(&blocks, log_size, PcsConfig::default())
+            .unwrap_err();
+        let Blake2sProverError::UnbalancedLogup { sum, claimed_sum } = err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_ne!(sum, SecureField::zero());
+        assert_ne!(claimed_sum.compression, SecureField::zero());
+    }
+
+    #[test]
+    fn test_prove_blake2s_invalid_log_size() {
+        let blocks = generate_blocks(1);
+        for log_size in [0, LOG_N_LANES - 1, MAX_LOG_SIZE + 1] {
+            assert!(matches!(
+                prove_blake2s::<Blake2sMerkleChannel>(&blocks, log_size, PcsConfig::default()),
+                Err(Blake2sProverError::InvalidLogSize { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_prove_blake2s_too_many_blocks() {
+        let log_size = LOG_N_LANES;
+        let blocks = generate_blocks((1 << log_size) + 1);
+        assert!(matches!(
+            prove_blake2s::<Blake2sMerkleChannel>(&blocks, log_size, PcsConfig::default()),
+            Err(Blake2sProverError::TooManyBlocks { .. })
+        ));
+    }
+
+    #[test_log::test]
+    fn test_verify_blake2s_wrong_preprocessed_root() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec()];
+        let (mut proof, claimed_sum, claims) = prove_messages(&messages, log_size, config);
+        proof.commitments[0] = proof.commitments[1];
+        assert!(matches!(
+            verify_blake2s::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims),
+            Err(Blake2sVerifierError::InvalidPreprocessedRoot)
+        ));
+    }
+}
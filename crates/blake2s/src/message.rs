@@ -0,0 +1,201 @@
+//! Message handling: BLAKE2s zero padding and splitting of messages into chained blocks.
+//!
+//! Each trace row compresses one [`Block`]. Blocks of the same message are linked through the
+//! chain relation: a block's output chaining value is the next block's input chaining value.
+
+use std::simd::u32x16;
+
+use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+use crate::blake2s::{compress, BLOCK_SIZE, H};
+
+/// A message block, together with its position in the message and its input chaining value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    /// Index of the message this block belongs to.
+    pub message_id: u32,
+    /// Index of the block in its message.
+    pub index: u32,
+    /// Whether this is the last block of its message.
+    pub is_last: bool,
+    /// Number of message bytes hashed through this block, see [`block_counter`].
+    pub counter: u64,
+    /// Chaining value the compression starts from.
+    pub h_in: [u32; 8],
+    /// Message words, little-endian.
+    pub words: [u32; 16],
+}
+
+impl Block {
+    pub const fn is_first(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Output chaining value, i.e. the compression of `words` into `h_in`.
+    pub fn h_out(&self) -> [u32; 8] {
+        compress(self.h_in, self.words, self.counter, self.is_last)
+    }
+
+    /// A standalone zero block used to pad the trace, i.e. the only block of the empty message.
+    pub const fn padding() -> Self {
+        Self {
+            message_id: 0,
+            index: 0,
+            is_last: true,
+            counter: 0,
+            h_in: H,
+            words: [0; 16],
+        }
+    }
+}
+
+impl Default for Block {
+    /// See [`Block::padding`].
+    fn default() -> Self {
+        Self::padding()
+    }
+}
+
+/// Pads a message with zeros to a multiple of the block size and splits it into 512-bit blocks.
+/// The empty message is a single zero block.
+pub fn pad_message(message: &[u8]) -> Vec<[u32; 16]> {
+    let mut padded = Vec::from(message);
+    padded.resize(message.len().div_ceil(BLOCK_SIZE).max(1) * BLOCK_SIZE, 0);
+
+    padded
+        .chunks_exact(BLOCK_SIZE)
+        .map(|chunk| {
+            std::array::from_fn(|i| u32::from_le_bytes(chunk[4 * i..4 * i + 4].try_into().unwrap()))
+        })
+        .collect()
+}
+
+/// Byte counter of the block `index` out of `n_blocks`: the end of the block, except for the last
+/// block which counts the message length.
+pub fn block_counter(message_len: usize, index: usize, n_blocks: usize) -> u64 {
+    if index + 1 == n_blocks {
+        message_len as u64
+    } else {
+        ((index + 1) * BLOCK_SIZE) as u64
+    }
+}
+
+/// Pads each message and chains its blocks, starting from the initial chaining value.
+pub fn gen_blocks<M: AsRef<[u8]>>(messages: &[M]) -> Vec<Block> {
+    let mut blocks = vec![];
+    for (message_id, message) in messages.iter().enumerate() {
+        let message = message.as_ref();
+        let padded = pad_message(message);
+        let n_blocks = padded.len();
+        let mut h = H;
+        for (index, words) in padded.into_iter().enumerate() {
+            let block = Block {
+                message_id: message_id as u32,
+                index: index as u32,
+                is_last: index + 1 == n_blocks,
+                counter: block_counter(message.len(), index, n_blocks),
+                h_in: h,
+                words,
+            };
+            h = block.h_out();
+            blocks.push(block);
+        }
+    }
+    blocks
+}
+
+/// Little-endian bytes of a chaining value.
+pub fn to_bytes(hash: [u32; 8]) -> [u8; 32] {
+    std::array::from_fn(|i| hash[i / 4].to_le_bytes()[i % 4])
+}
+
+/// Digest of each message, i.e. the output chaining value of its last block.
+pub fn digests(blocks: &[Block]) -> Vec<[u8; 32]> {
+    blocks
+        .iter()
+        .filter(|block| block.is_last)
+        .map(|block| to_bytes(block.h_out()))
+        .collect()
+}
+
+/// Deterministic single-block messages, used for tests and benchmarks.
+///
+/// The 16-bit limb `i` of block `row` is `(i + row) & 0xffff`.
+pub fn generate_blocks(n_blocks: usize) -> Vec<Block> {
+    (0..n_blocks)
+        .map(|row| Block {
+            message_id: row as u32,
+            counter: BLOCK_SIZE as u64,
+            words: std::array::from_fn(|i| {
+                let low = (2 * i + row) as u32 & 0xffff;
+                let high = (2 * i + 1 + row) as u32 & 0xffff;
+                low + (high << 16)
+            }),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Builds a trace column from a value of each block, padding with [`Block::padding`] blocks up to
+/// `simd_size` SIMD rows.
+pub fn block_column(blocks: &[Block], simd_size: usize, f: impl Fn(&Block) -> u32) -> Vec<u32x16> {
+    let padding = Block::padding();
+    (0..simd_size)
+        .map(|simd_row| {
+            u32x16::from_array(std::array::from_fn(|lane| {
+                f(blocks
+                    .get((simd_row << LOG_N_LANES) + lane)
+                    .unwrap_or(&padding))
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use blake2::{Blake2s256, Digest};
+
+    use super::*;
+
+    #[test]
+    fn test_pad_message() {
+        assert_eq!(pad_message(b""), [[0; 16]]);
+        assert_eq!(pad_message(&[0; 64]).len(), 1);
+        assert_eq!(pad_message(&[0; 65]).len(), 2);
+
+        let chunks = pad_message(b"abc");
+        assert_eq!(chunks[0][0], 0x00636261);
+        assert_eq!(chunks[0][1..], [0; 15]);
+    }
+
+    #[test]
+    fn test_gen_blocks() {
+        let messages = [vec![0xab; 200], b"abc".to_vec(), vec![], vec![0x42; 128]];
+        let blocks = gen_blocks(&messages);
+        assert_eq!(blocks.len(), 4 + 1 + 1 + 2);
+        assert_eq!(
+            blocks[..4]
+                .iter()
+                .map(|block| block.counter)
+                .collect::<Vec<_>>(),
+            [64, 128, 192, 200]
+        );
+        // A full last block is not followed by an empty one.
+        assert_eq!(blocks[7].counter, 128);
+        assert!(blocks[7].is_last);
+        for (block, next) in blocks.iter().zip(&blocks[1..]) {
+            if !block.is_last {
+                assert_eq!(next.h_in, block.h_out());
+            }
+        }
+
+        for (digest, message) in digests(&blocks).iter().zip(&messages) {
+            assert_eq!(digest[..], Blake2s256::digest(message)[..]);
+        }
+    }
+
+    #[test]
+    fn test_padding_block() {
+        assert_eq!(digests(&[Block::padding()]), [crate::blake2s::blake2s(b"")]);
+    }
+}
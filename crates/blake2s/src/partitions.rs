@@ -0,0 +1,84 @@
+//! Splits of 32-bit words into pieces for the XOR lookups of the G function.
+//!
+//! Each XOR of G is followed by a rotation. Both operands and the result are split into the same
+//! pieces, whose widths are listed low bits first. The cuts fall on the limb boundary and on the
+//! bits which the rotation moves to a limb boundary, so that each piece lies inside a single
+//! 16-bit limb before and after the rotation: limbs are linear combinations of pieces in both
+//! cases. Pieces are at most 9 bits wide, so that their XOR tables stay small.
+
+use std::simd::u32x16;
+
+/// Bytes, for the rotations by 16 and 8 and for the feed-forward.
+pub const BYTES: [u32; 4] = [8; 4];
+
+/// Pieces for the rotation by 12, cut at bits 12 and 28.
+pub const R12: [u32; 6] = [8, 4, 4, 8, 4, 4];
+
+/// Pieces for the rotation by 7, cut at bits 7 and 23.
+pub const R7: [u32; 4] = [7, 9, 7, 9];
+
+/// Widths of the XOR tables, see [`crate::preprocessed::xor`].
+pub const XOR_WIDTHS: [u32; 4] = [4, 7, 8, 9];
+
+/// Splits each lane of `x` into pieces of the given widths, low bits first.
+#[inline(always)]
+pub fn split_u32x16<const N: usize>(x: u32x16, widths: [u32; N]) -> [u32x16; N] {
+    let mut shift = 0;
+    widths.map(|width| {
+        let piece = (x >> shift) & u32x16::splat((1 << width) - 1);
+        shift += width;
+        piece
+    })
+}
+
+/// Limb and shift of each piece in the limbs of the word rotated right by `r`, e.g. `r = 0` for
+/// the limbs of the word itself.
+pub const fn rotr_positions<const N: usize>(widths: [u32; N], r: u32) -> [(usize, u32); N] {
+    let mut positions = [(0, 0); N];
+    let mut start = 0;
+    let mut i = 0;
+    while i < N {
+        let bit = (start + 32 - r) % 32;
+        positions[i] = ((bit / 16) as usize, bit % 16);
+        start += widths[i];
+        i += 1;
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_partition<const N: usize>(widths: [u32; N], r: u32) {
+        assert_eq!(widths.iter().sum::<u32>(), 32);
+        for (i, width) in widths.into_iter().enumerate() {
+            assert!(XOR_WIDTHS.contains(&width));
+            // Each piece lies inside a limb, before and after the rotation
+            for (limb, shift) in [rotr_positions(widths, 0)[i], rotr_positions(widths, r)[i]] {
+                assert!(limb < 2);
+                assert!(shift + width <= 16);
+            }
+        }
+
+        let x = u32x16::from_array(std::array::from_fn(|i| {
+            0x9e3779b9_u32.wrapping_mul(i as u32)
+        }));
+        let pieces = split_u32x16(x, widths);
+        for (x, r) in [(x, 0), (crate::blake2s::rotr_u32x16(x, r), r)] {
+            let mut limbs = [u32x16::splat(0); 2];
+            for (piece, (limb, shift)) in pieces.into_iter().zip(rotr_positions(widths, r)) {
+                limbs[limb] += piece << shift;
+            }
+            assert_eq!(limbs[0] + (limbs[1] << 16), x);
+        }
+    }
+
+    #[test]
+    fn test_partitions() {
+        check_partition(BYTES, 16);
+        check_partition(R12, 12);
+        check_partition(BYTES, 8);
+        check_partition(R7, 7);
+    }
+}
@@ -0,0 +1,69 @@
+use std::simd::u32x16;
+
+use stwo::core::channel::Channel;
+use stwo_constraint_framework::relation;
+use utils::trace_columns;
+
+// [value, carry]
+const N_COLUMNS: usize = 2;
+
+/// Log size of the table: 16-bit values and carries up to 3.
+pub const LOG_SIZE: u32 = 18;
+
+relation!(Add4, N_COLUMNS);
+
+trace_columns!(RangeCheckAddColumns, value, carry);
+
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub add_4: Add4,
+}
+
+impl Relation {
+    pub fn dummy() -> Self {
+        Self {
+            add_4: Add4::dummy(),
+        }
+    }
+
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            add_4: Add4::draw(channel),
+        }
+    }
+}
+
+/// Row `(value << 2) + carry` is `[value, carry]`.
+pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
+    const N: usize = 1 << (LOG_SIZE - 4);
+    let mut all_columns = vec![Vec::with_capacity(N), Vec::with_capacity(N)];
+
+    let carry = u32x16::from_array(std::array::from_fn(|j| j as u32 & 3));
+
+    for i in 0..N as u32 {
+        let value = u32x16::from_array(std::array::from_fn(|j| i * 4 + (j as u32 >> 2)));
+        all_columns[0].push(value);
+        all_columns[1].push(carry);
+    }
+
+    all_columns
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+    use super::*;
+
+    #[test]
+    fn test_gen_column_simd() {
+        let columns = gen_column_simd();
+        assert_eq!(columns.len(), RangeCheckAddColumns::SIZE);
+        assert_eq!(columns[0].len().ilog2(), LOG_SIZE - LOG_N_LANES);
+        assert_eq!(columns[1].len().ilog2(), LOG_SIZE - LOG_N_LANES);
+
+        let row = (0xabcd << 2) + 3;
+        assert_eq!(columns[0][row / 16][row % 16], 0xabcd);
+        assert_eq!(columns[1][row / 16][row % 16], 3);
+    }
+}
@@ -0,0 +1,152 @@
+//! XOR tables of the G function and of the feed-forward, one for each piece width, see
+//! [`crate::partitions`].
+//!
+//! The table of width `n` has a row `[a, b, a ^ b]` for each pair of `n`-bit values, i.e. `2^2n`
+//! rows, the row index being `(a << n) + b`.
+
+use std::simd::u32x16;
+
+use stwo::{core::channel::Channel, prover::backend::simd::qm31::PackedQM31};
+use stwo_constraint_framework::{
+    preprocessed_columns::PreProcessedColumnId, relation, EvalAtRow, Relation as _, RelationEntry,
+};
+use utils::{combine, trace_columns};
+
+// [a, b, a ^ b]
+const N_COLUMNS: usize = 3;
+
+relation!(Xor4, N_COLUMNS);
+relation!(Xor7, N_COLUMNS);
+relation!(Xor8, N_COLUMNS);
+relation!(Xor9, N_COLUMNS);
+
+trace_columns!(Xor4Columns, a, b, c);
+trace_columns!(Xor7Columns, a, b, c);
+trace_columns!(Xor8Columns, a, b, c);
+trace_columns!(Xor9Columns, a, b, c);
+
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub xor_4: Xor4,
+    pub xor_7: Xor7,
+    pub xor_8: Xor8,
+    pub xor_9: Xor9,
+}
+
+impl Relation {
+    pub fn dummy() -> Self {
+        Self {
+            xor_4: Xor4::dummy(),
+            xor_7: Xor7::dummy(),
+            xor_8: Xor8::dummy(),
+            xor_9: Xor9::dummy(),
+        }
+    }
+
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            xor_4: Xor4::draw(channel),
+            xor_7: Xor7::draw(channel),
+            xor_8: Xor8::draw(channel),
+            xor_9: Xor9::draw(channel),
+        }
+    }
+
+    /// Adds `[a, b, a ^ b]` to the relation of the `n_bits` table.
+    pub fn add_to_eval<E: EvalAtRow>(
+        &self,
+        eval: &mut E,
+        n_bits: u32,
+        multiplicity: E::EF,
+        values: &[E::F; N_COLUMNS],
+    ) {
+        match n_bits {
+            4 => eval.add_to_relation(RelationEntry::new(&self.xor_4, multiplicity, values)),
+            7 => eval.add_to_relation(RelationEntry::new(&self.xor_7, multiplicity, values)),
+            8 => eval.add_to_relation(RelationEntry::new(&self.xor_8, multiplicity, values)),
+            9 => eval.add_to_relation(RelationEntry::new(&self.xor_9, multiplicity, values)),
+            _ => panic!("No XOR table of width {n_bits}"),
+        }
+    }
+
+    /// Combines the columns `[a, b, a ^ b]` with the relation of the `n_bits` table.
+    pub fn combine(&self, n_bits: u32, columns: [&Vec<u32x16>; N_COLUMNS]) -> Vec<PackedQM31> {
+        match n_bits {
+            4 => combine!(self.xor_4, columns),
+            7 => combine!(self.xor_7, columns),
+            8 => combine!(self.xor_8, columns),
+            9 => combine!(self.xor_9, columns),
+            _ => panic!("No XOR table of width {n_bits}"),
+        }
+    }
+}
+
+/// Ids of the columns of chunk `chunk` of the `n_bits` table.
+pub fn column_ids(n_bits: u32, chunk: u32) -> [PreProcessedColumnId; N_COLUMNS] {
+    let ids = match n_bits {
+        4 => Xor4Columns::to_ids(Some(chunk)),
+        7 => Xor7Columns::to_ids(Some(chunk)),
+        8 => Xor8Columns::to_ids(Some(chunk)),
+        9 => Xor9Columns::to_ids(Some(chunk)),
+        _ => panic!("No XOR table of width {n_bits}"),
+    };
+    ids.try_into().unwrap()
+}
+
+/// Columns `[a, b, a ^ b]` of the `n_bits` table.
+pub fn gen_table_columns(n_bits: u32) -> Vec<Vec<u32x16>> {
+    let mask = (1 << n_bits) - 1;
+    let mut all_columns = vec![vec![], vec![], vec![]];
+    for i in (0..1 << (2 * n_bits)).step_by(u32x16::LEN) {
+        let index = u32x16::from_array(std::array::from_fn(|j| i + j as u32));
+        let a = index >> n_bits;
+        let b = index & u32x16::splat(mask);
+        all_columns[0].push(a);
+        all_columns[1].push(b);
+        all_columns[2].push(a ^ b);
+    }
+    all_columns
+}
+
+/// Columns of all the tables, by increasing width.
+pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
+    crate::partitions::XOR_WIDTHS
+        .into_iter()
+        .flat_map(gen_table_columns)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+    use super::*;
+
+    #[test]
+    fn test_ids() {
+        assert_eq!(
+            column_ids(7, 3).map(|id| id.id),
+            ["Xor7Columns_a_3", "Xor7Columns_b_3", "Xor7Columns_c_3"]
+        );
+    }
+
+    #[test]
+    fn test_gen_column_simd() {
+        let columns = gen_column_simd();
+        assert_eq!(columns.len(), 4 * Xor4Columns::SIZE);
+        for (table, n_bits) in columns.chunks(N_COLUMNS).zip([4, 7, 8, 9]) {
+            assert!(table
+                .iter()
+                .all(|column| column.len().ilog2() == 2 * n_bits - LOG_N_LANES));
+        }
+
+        let [a, b, c] = &gen_table_columns(8)[..] else {
+            unreachable!()
+        };
+        let row = (0x12 << 8) + 0x34;
+        let lane = row % 16;
+        assert_eq!(a[row / 16][lane], 0x12);
+        assert_eq!(b[row / 16][lane], 0x34);
+        assert_eq!(c[row / 16][lane], 0x12 ^ 0x34);
+    }
+}
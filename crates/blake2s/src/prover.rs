@@ -0,0 +1,99 @@
+//! Prover side of the BLAKE2s AIR.
+//!
+//! The preprocessed trace, the twiddles and the commitment of the preprocessed tree only depend
+//! on the log size and the commitment scheme config. A [`Blake2sProver`] computes them once and
+//! shares them across all the proofs it generates.
+
+use stwo::{
+    core::{channel::MerkleChannel, pcs::PcsConfig, proof::StarkProof, vcs::MerkleHasher},
+    prover::backend::{
+        simd::{m31::LOG_N_LANES, SimdBackend},
+        BackendForChannel,
+    },
+};
+use utils::stwo::air::Prover;
+
+use crate::{
+    components::{Blake2sAir, ClaimedSum},
+    message::Block,
+    preprocessed::PreProcessedTrace,
+    Blake2sProverError, MAX_LOG_SIZE,
+};
+
+/// Proves batches of BLAKE2s blocks for a fixed log size and config.
+pub struct Blake2sProver<MC: MerkleChannel>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    prover: Prover<MC>,
+}
+
+impl<MC: MerkleChannel> Blake2sProver<MC>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    pub fn new(log_size: u32, config: PcsConfig) -> Result<Self, Blake2sProverError> {
+        Blake2sProverError::check_params(log_size, LOG_N_LANES, MAX_LOG_SIZE, &config)?;
+        let preprocessed_trace = PreProcessedTrace::new(log_size);
+        Ok(Self {
+            prover: Prover::new(
+                log_size,
+                config,
+                preprocessed_trace.trace,
+                preprocessed_trace.ids,
+            ),
+        })
+    }
+
+    pub const fn log_size(&self) -> u32 {
+        self.prover.log_size()
+    }
+
+    pub const fn config(&self) -> PcsConfig {
+        self.prover.config()
+    }
+
+    /// Merkle root of the preprocessed trace, i.e. the first commitment of every proof.
+    pub fn preprocessed_root(&self) -> <MC::H as MerkleHasher>::Hash {
+        self.prover.preprocessed_root()
+    }
+
+    /// See [`crate::prove_blake2s`].
+    pub fn prove(
+        &self,
+        blocks: &[Block],
+    ) -> Result<(StarkProof<MC::H>, ClaimedSum), Blake2sProverError> {
+        Blake2sProverError::check_n_blocks(blocks.len(), self.log_size())?;
+        self.prover.prove(&Blake2sAir, blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::core::vcs::blake2_merkle::Blake2sMerkleChannel;
+
+    use super::*;
+    use crate::{message::gen_blocks, preprocessed_root, public::HashClaim, verify_blake2s};
+
+    #[test_log::test]
+    fn test_prover_reuse() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let prover = Blake2sProver::<Blake2sMerkleChannel>::new(log_size, config).unwrap();
+        assert_eq!(
+            prover.preprocessed_root(),
+            preprocessed_root::<Blake2sMerkleChannel>(log_size, config)
+        );
+
+        for messages in [
+            vec![b"abc".to_vec()],
+            vec![vec![0xab; 200], b"def".to_vec()],
+        ] {
+            let (proof, claimed_sum) = prover.prove(&gen_blocks(&messages)).unwrap();
+            assert_eq!(proof.commitments[0], prover.preprocessed_root());
+            let claims = messages.into_iter().map(HashClaim::new).collect::<Vec<_>>();
+            verify_blake2s::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Public inputs and outputs of the proof.
+//!
+//! The compression component emits two kinds of claims, consumed by the verifier:
+//! - an input claim for each block, made of its counter and message words,
+//! - an output claim for each message, made of its digest.
+//!
+//! The verifier rebuilds the same claims from the public messages and digests, so the logup sum
+//! only balances if the proof covers exactly these messages and digests. Intermediate chaining
+//! values are linked inside the AIR and never claimed.
+
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use stwo::core::{
+    channel::Channel,
+    fields::{m31::M31, qm31::SecureField, FieldExpOps},
+};
+use stwo_constraint_framework::Relation;
+
+use crate::{
+    blake2s::{blake2s, counter_words, CHUNK_SIZE},
+    components::STATE_SIZE,
+    message::{block_counter, pad_message, Block},
+    relations::Relations,
+};
+
+/// [message_id, block_index, counter words, message words], as 16-bit limbs. The counter words
+/// are the words 12 and 13 of the initial working state, see [`counter_words`].
+pub const INPUT_CLAIM_SIZE: usize = 2 + 4 + CHUNK_SIZE;
+
+/// [message_id, digest], as 16-bit limbs.
+pub const OUTPUT_CLAIM_SIZE: usize = 1 + STATE_SIZE;
+
+/// Claims of a proof, see the module documentation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Claims {
+    pub inputs: Vec<[u32; INPUT_CLAIM_SIZE]>,
+    pub outputs: Vec<[u32; OUTPUT_CLAIM_SIZE]>,
+}
+
+/// A message together with its claimed digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashClaim {
+    pub message: Vec<u8>,
+    pub digest: [u8; 32],
+}
+
+impl HashClaim {
+    /// Claims the actual BLAKE2s-256 digest of `message`.
+    pub fn new(message: impl Into<Vec<u8>>) -> Self {
+        let message = message.into();
+        let digest = blake2s(&message);
+        Self { message, digest }
+    }
+}
+
+/// Splits 32-bit words into 16-bit limbs, low limb first.
+fn to_limbs(words: &[u32]) -> impl Iterator<Item = u32> + '_ {
+    words.iter().flat_map(|word| [word & 0xffff, word >> 16])
+}
+
+fn input_claim(
+    message_id: u32,
+    index: u32,
+    counter: u64,
+    words: &[u32; 16],
+) -> [u32; INPUT_CLAIM_SIZE] {
+    [message_id, index]
+        .into_iter()
+        .chain(to_limbs(&counter_words(counter)))
+        .chain(to_limbs(words))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+fn output_claim(message_id: u32, digest: &[u32; 8]) -> [u32; OUTPUT_CLAIM_SIZE] {
+    [message_id]
+        .into_iter()
+        .chain(to_limbs(digest))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+/// Claims emitted by the compression component for the given blocks.
+pub fn block_claims(blocks: &[Block]) -> Claims {
+    let mut claims = Claims::default();
+    for block in blocks {
+        claims.inputs.push(input_claim(
+            block.message_id,
+            block.index,
+            block.counter,
+            &block.words,
+        ));
+        if block.is_last {
+            claims
+                .outputs
+                .push(output_claim(block.message_id, &block.h_out()));
+        }
+    }
+    claims
+}
+
+/// Claims expected by the verifier, which only pads the messages. Message ids follow the order of
+/// the claims, see [`crate::message::gen_blocks`].
+pub fn hash_claims(claims: &[HashClaim]) -> Claims {
+    let mut result = Claims::default();
+    for (message_id, claim) in claims.iter().enumerate() {
+        let padded = pad_message(&claim.message);
+        let n_blocks = padded.len();
+        for (index, words) in padded.iter().enumerate() {
+            let counter = block_counter(claim.message.len(), index, n_blocks);
+            result
+                .inputs
+                .push(input_claim(message_id as u32, index as u32, counter, words));
+        }
+        let digest = std::array::from_fn(|i| {
+            u32::from_le_bytes(claim.digest[4 * i..4 * i + 4].try_into().unwrap())
+        });
+        result
+            .outputs
+            .push(output_claim(message_id as u32, &digest));
+    }
+    result
+}
+
+pub fn mix_into(claims: &Claims, channel: &mut impl Channel) {
+    channel.mix_u32s(&[claims.inputs.len() as u32]);
+    for claim in &claims.inputs {
+        channel.mix_u32s(claim);
+    }
+    channel.mix_u32s(&[claims.outputs.len() as u32]);
+    for claim in &claims.outputs {
+        channel.mix_u32s(claim);
+    }
+}
+
+/// Logup sum of the verifier consuming the claims, to be added to the components claimed sum.
+pub fn logup_sum(claims: &Claims, relations: &Relations) -> SecureField {
+    let inputs = claims.inputs.iter().map(|claim| {
+        let denom: SecureField = relations.input_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    let outputs = claims.outputs.iter().map(|claim| {
+        let denom: SecureField = relations.output_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    inputs
+        .chain(outputs)
+        .fold(SecureField::zero(), |acc, frac| acc - frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::gen_blocks;
+
+    #[test]
+    fn test_hash_claims_match_block_claims() {
+        let messages = [b"abc".to_vec(), vec![0x42; 100], vec![], vec![0x43; 128]];
+        let claims = messages
+            .iter()
+            .map(|message| HashClaim::new(message.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(hash_claims(&claims), block_claims(&gen_blocks(&messages)));
+    }
+
+    #[test]
+    fn test_hash_claims_wrong_digest() {
+        let mut claim = HashClaim::new(b"abc".to_vec());
+        claim.digest[0] ^= 1;
+        assert_ne!(
+            hash_claims(&[claim]),
+            block_claims(&gen_blocks(&[b"abc".to_vec()]))
+        );
+    }
+
+    #[test]
+    fn test_hash_claims_counter() {
+        // Messages which pad to the same block only differ by their counter.
+        let claims = hash_claims(&[HashClaim::new(b"abc".to_vec()), HashClaim::new(b"abc\0")]);
+        assert_eq!(claims.inputs[0][6..], claims.inputs[1][6..]);
+        assert_ne!(claims.inputs[0][2..6], claims.inputs[1][2..6]);
+    }
+}
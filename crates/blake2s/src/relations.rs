@@ -0,0 +1,79 @@
+use stwo::core::channel::Channel;
+
+use crate::preprocessed::{range_check_add, xor};
+
+mod chain {
+    use stwo_constraint_framework::relation;
+
+    use crate::components::STATE_SIZE;
+    // [message_id, block_index, state]
+    relation!(Relation, 2 + STATE_SIZE);
+}
+
+mod input_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::INPUT_CLAIM_SIZE;
+    // [message_id, block_index, counter words, message words]
+    relation!(Relation, INPUT_CLAIM_SIZE);
+}
+
+mod output_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::OUTPUT_CLAIM_SIZE;
+    // [message_id, digest]
+    relation!(Relation, OUTPUT_CLAIM_SIZE);
+}
+
+/// Relations of the XOR and addition range check tables, looked up by the G evaluations and shared
+/// with the AIRs built on them.
+#[derive(Clone)]
+pub struct TableRelations {
+    pub xor: xor::Relation,
+    pub range_check_add: range_check_add::Relation,
+}
+
+impl TableRelations {
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            xor: xor::Relation::draw(channel),
+            range_check_add: range_check_add::Relation::draw(channel),
+        }
+    }
+
+    pub fn dummy() -> Self {
+        Self {
+            xor: xor::Relation::dummy(),
+            range_check_add: range_check_add::Relation::dummy(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Relations {
+    pub tables: TableRelations,
+    pub chain: chain::Relation,
+    pub input_claim: input_claim::Relation,
+    pub output_claim: output_claim::Relation,
+}
+
+impl Relations {
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            tables: TableRelations::draw(channel),
+            chain: chain::Relation::draw(channel),
+            input_claim: input_claim::Relation::draw(channel),
+            output_claim: output_claim::Relation::draw(channel),
+        }
+    }
+
+    pub fn dummy() -> Self {
+        Self {
+            tables: TableRelations::dummy(),
+            chain: chain::Relation::dummy(),
+            input_claim: input_claim::Relation::dummy(),
+            output_claim: output_claim::Relation::dummy(),
+        }
+    }
+}
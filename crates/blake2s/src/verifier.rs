@@ -0,0 +1,115 @@
+//! Verifier side of the BLAKE2s AIR.
+//!
+//! Replays the prover's transcript: commitments are mixed into the channel in the same order,
+//! the relations are redrawn and the components are rebuilt before calling stwo's `verify`.
+//!
+//! The preprocessed trace is not sent by the prover: its commitment must match the canonical
+//! root of the XOR and range check tables, see [`preprocessed_root`].
+
+use stwo::{
+    core::{
+        channel::MerkleChannel, fields::qm31::SecureField, pcs::PcsConfig, proof::StarkProof,
+        vcs::MerkleHasher, verifier::VerificationError,
+    },
+    prover::backend::{
+        simd::{m31::LOG_N_LANES, SimdBackend},
+        BackendForChannel,
+    },
+};
+use thiserror::Error;
+use utils::stwo::air::{self, is_supported, Verifier, VerifyError};
+
+use crate::{
+    components::{Blake2sAir, ClaimedSum},
+    preprocessed::PreProcessedTrace,
+    public::{self, Claims, HashClaim},
+    MAX_LOG_SIZE,
+};
+
+#[derive(Debug, Error)]
+pub enum Blake2sVerifierError {
+    #[error("Invalid log size: {0}")]
+    InvalidLogSize(u32),
+    #[error("Unsupported config for log size {log_size}: {config:?}")]
+    UnsupportedConfig { log_size: u32, config: PcsConfig },
+    #[error("Invalid proof: expected 3 commitments, got {0}")]
+    InvalidCommitmentsCount(usize),
+    #[error("Invalid proof: the preprocessed commitment does not match the BLAKE2s tables")]
+    InvalidPreprocessedRoot,
+    #[error("Too many hash claims: {0} blocks do not fit in the trace")]
+    TooManyClaims(usize),
+    #[error("Relation summary is not zero: {0}")]
+    InvalidLogupSum(SecureField),
+    #[error(transparent)]
+    Stwo(#[from] VerificationError),
+}
+
+impl From<VerifyError> for Blake2sVerifierError {
+    fn from(err: VerifyError) -> Self {
+        match err {
+            VerifyError::InvalidCommitmentsCount(n) => Self::InvalidCommitmentsCount(n),
+            VerifyError::InvalidPreprocessedRoot => Self::InvalidPreprocessedRoot,
+            VerifyError::InvalidLogupSum(sum) => Self::InvalidLogupSum(sum),
+            VerifyError::Stwo(err) => Self::Stwo(err),
+        }
+    }
+}
+
+/// Merkle root of the BLAKE2s tables for `log_size` and `config`, see [`air::preprocessed_root`].
+pub fn preprocessed_root<MC: MerkleChannel>(
+    log_size: u32,
+    config: PcsConfig,
+) -> <MC::H as MerkleHasher>::Hash
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    air::preprocessed_root::<MC>(PreProcessedTrace::new(log_size).trace, log_size, config)
+}
+
+/// Verifies that `proof` attests the BLAKE2s-256 digest of each claimed message.
+pub fn verify_blake2s<MC: MerkleChannel>(
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    hash_claims: &[HashClaim],
+) -> Result<(), Blake2sVerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    let claims = public::hash_claims(hash_claims);
+    verify_claims::<MC>(proof, log_size, config, claimed_sum, &claims)
+}
+
+/// Verifies that `proof` balances the given public claims, see [`public`].
+fn verify_claims<MC: MerkleChannel>(
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    claims: &Claims,
+) -> Result<(), Blake2sVerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    if !(LOG_N_LANES..=MAX_LOG_SIZE).contains(&log_size) {
+        return Err(Blake2sVerifierError::InvalidLogSize(log_size));
+    }
+    if !is_supported(log_size, &config) {
+        return Err(Blake2sVerifierError::UnsupportedConfig { log_size, config });
+    }
+    // Each row claims at most one block
+    if claims.inputs.len() > 1 << log_size {
+        return Err(Blake2sVerifierError::TooManyClaims(claims.inputs.len()));
+    }
+
+    let preprocessed_trace = PreProcessedTrace::new(log_size);
+    let verifier = Verifier::<MC>::new(
+        log_size,
+        config,
+        preprocessed_trace.ids.clone(),
+        preprocessed_trace.log_sizes(),
+        air::preprocessed_root::<MC>(preprocessed_trace.trace, log_size, config),
+    );
+    Ok(verifier.verify(&Blake2sAir, proof, claimed_sum, claims)?)
+}
@@ -0,0 +1,136 @@
+//! BLAKE3 functions, for 32-byte digests in the default hash mode, i.e. without key nor key
+//! derivation.
+
+/// Initial chaining value of each chunk and of each parent node.
+pub const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+pub const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+pub const N_ROUNDS: usize = 7;
+
+/// Message words used by each round: the message is permuted between rounds.
+pub const SCHEDULE: [[usize; 16]; N_ROUNDS] = schedule();
+
+/// Words `[a, b, c, d]` of the working state mixed by each G of a round: the columns, then the
+/// diagonals.
+pub const G_INDICES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// Number of G evaluations of a compression.
+pub const N_G: usize = N_ROUNDS * G_INDICES.len();
+
+pub const BLOCK_SIZE: usize = 64;
+
+/// Number of blocks of a chunk, the leaves of the hash tree.
+pub const CHUNK_BLOCKS: usize = 16;
+
+pub const CHUNK_SIZE: usize = 32; // 32 u16 = 16 u32
+
+// Domain separation flags, word 15 of the initial working state.
+pub const CHUNK_START: u32 = 1 << 0;
+pub const CHUNK_END: u32 = 1 << 1;
+pub const PARENT: u32 = 1 << 2;
+pub const ROOT: u32 = 1 << 3;
+
+const fn schedule() -> [[usize; 16]; N_ROUNDS] {
+    let mut schedule = [[0; 16]; N_ROUNDS];
+    let mut i = 0;
+    while i < 16 {
+        schedule[0][i] = i;
+        i += 1;
+    }
+    let mut round = 1;
+    while round < N_ROUNDS {
+        let mut i = 0;
+        while i < 16 {
+            schedule[round][i] = schedule[round - 1][MSG_PERMUTATION[i]];
+            i += 1;
+        }
+        round += 1;
+    }
+    schedule
+}
+
+/// Words 12 and 13 of the initial working state, which hold the chunk counter.
+pub const fn counter_words(counter: u64) -> [u32; 2] {
+    [counter as u32, (counter >> 32) as u32]
+}
+
+/// The mixing function, on the words `indices` of the working state.
+fn g(v: &mut [u32; 16], [a, b, c, d]: [usize; 4], x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
+
+/// Compresses the message words `m` into the chaining value `h`, returning the output chaining
+/// value, i.e. the first half of the compression output. `block_len` is the number of message
+/// bytes in the block.
+pub fn compress(h: [u32; 8], m: [u32; 16], counter: u64, block_len: u32, flags: u32) -> [u32; 8] {
+    let [v12, v13] = counter_words(counter);
+    let mut v = [
+        h[0], h[1], h[2], h[3], h[4], h[5], h[6], h[7], IV[0], IV[1], IV[2], IV[3], v12, v13,
+        block_len, flags,
+    ];
+    for s in SCHEDULE {
+        for (i, indices) in G_INDICES.into_iter().enumerate() {
+            g(&mut v, indices, m[s[2 * i]], m[s[2 * i + 1]]);
+        }
+    }
+    std::array::from_fn(|i| v[i] ^ v[i + 8])
+}
+
+/// BLAKE3 digest of `message`.
+pub fn blake3(message: &[u8]) -> [u8; 32] {
+    let blocks = crate::message::gen_blocks(&[message]);
+    crate::message::digests(&blocks)[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_implementation() {
+        // Single block, single chunk, and trees of 2, 3 and 5 chunks
+        for len in [
+            0, 1, 3, 63, 64, 65, 1023, 1024, 1025, 2048, 2049, 4097, 5000,
+        ] {
+            let message = (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+            assert_eq!(blake3(&message), *::blake3::hash(&message).as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_abc() {
+        assert_eq!(
+            hex::encode(blake3(b"abc")),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[test]
+    fn test_schedule() {
+        assert_eq!(SCHEDULE[0], std::array::from_fn(|i| i));
+        assert_eq!(SCHEDULE[1], MSG_PERMUTATION);
+        assert_eq!(
+            SCHEDULE[6],
+            [11, 15, 5, 0, 1, 9, 8, 6, 14, 10, 2, 12, 3, 4, 7, 13]
+        );
+    }
+}
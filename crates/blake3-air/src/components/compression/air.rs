@@ -0,0 +1,286 @@
+use blake2s::{
+    components::g::air::{constant, eq_constraints, eval_g, rotr_limbs, Word},
+    partitions::BYTES,
+};
+use itertools::izip;
+use num_traits::{One, Zero};
+use stwo::core::fields::m31::M31;
+use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval, RelationEntry};
+
+use crate::{
+    blake3::{CHUNK_END, CHUNK_SIZE, CHUNK_START, G_INDICES, IV, PARENT, ROOT, SCHEDULE},
+    components::{
+        compression::columns::{ChainColumnsOwned, FeedForwardColumnsOwned},
+        STATE_SIZE,
+    },
+    relations::Relations,
+};
+
+pub type Component = FrameworkComponent<Eval>;
+
+fn eval_compression_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relations) {
+    let ChainColumnsOwned {
+        message_id,
+        node_id,
+        block_index,
+        is_first,
+        is_last,
+        is_parent,
+        is_root,
+        is_active,
+        counter_0,
+        counter_1,
+        counter_2,
+        counter_3,
+        block_len,
+        flags,
+        left_id,
+        right_id,
+    } = ChainColumnsOwned::<<E as EvalAtRow>::F>::from_eval(eval);
+    let h_in: [E::F; STATE_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
+    let m: [E::F; CHUNK_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
+
+    let one = E::F::one();
+    let flag = |value: u32| E::F::from(M31::from(value));
+    for selector in [&is_first, &is_last, &is_parent, &is_root, &is_active] {
+        eval.add_constraint(selector.clone() * (selector.clone() - one.clone()));
+    }
+    // The flags are claimed, and set the selectors of the row
+    let chunk_flags = is_first.clone() * flag(CHUNK_START) + is_last.clone() * flag(CHUNK_END);
+    eval.add_constraint(
+        flags.clone()
+            - chunk_flags * (one.clone() - is_parent.clone())
+            - is_parent.clone() * flag(PARENT)
+            - is_root.clone() * flag(ROOT),
+    );
+    // Parent nodes are single compressions
+    eval.add_constraint(is_parent.clone() * (one.clone() - is_first.clone()));
+    eval.add_constraint(is_parent.clone() * (one.clone() - is_last.clone()));
+    // Padding blocks are standalone roots, so that they emit no claim
+    eval.add_constraint(
+        (one.clone() - is_active.clone()) * (flags.clone() - flag(CHUNK_START | CHUNK_END | ROOT)),
+    );
+    // The first block of a node has index 0 and starts from the initial chaining value
+    eval.add_constraint(is_first.clone() * block_index.clone());
+    for (i, h) in h_in.iter().enumerate() {
+        let h_limb = (IV[i / 2] >> (16 * (i % 2))) & 0xffff;
+        eval.add_constraint(is_first.clone() * (h.clone() - E::F::from(M31::from(h_limb))));
+    }
+
+    // Initial working state: the chaining value, the IV, and the counter, block length and flags
+    // claimed with the block
+    let mut v: [Word<E::F>; 16] = std::array::from_fn(|i| match i {
+        0..8 => [h_in[2 * i].clone(), h_in[2 * i + 1].clone()],
+        12 => [counter_0.clone(), counter_1.clone()],
+        13 => [counter_2.clone(), counter_3.clone()],
+        14 => [block_len.clone(), E::F::zero()],
+        15 => [flags.clone(), E::F::zero()],
+        _ => constant::<E>(IV[i - 8]),
+    });
+
+    for schedule in SCHEDULE {
+        for (i, [a, b, c, d]) in G_INDICES.into_iter().enumerate() {
+            let word = |j: usize| [m[2 * j].clone(), m[2 * j + 1].clone()];
+            let x = word(schedule[2 * i]);
+            let y = word(schedule[2 * i + 1]);
+            [v[a], v[b], v[c], v[d]] = eval_g(
+                eval,
+                &relations.tables,
+                [a, b, c, d].map(|j| v[j].clone()),
+                &x,
+                &y,
+            );
+        }
+    }
+
+    // Output chaining value: h_out = v[i] ^ v[i + 8], byte by byte
+    let minus_one = -E::EF::one();
+    let mut h_out = Vec::with_capacity(STATE_SIZE);
+    for (v_left, v_right) in v[..8].iter().zip(&v[8..]) {
+        let FeedForwardColumnsOwned {
+            left_0,
+            left_1,
+            left_2,
+            left_3,
+            right_0,
+            right_1,
+            right_2,
+            right_3,
+            xor_0,
+            xor_1,
+            xor_2,
+            xor_3,
+        } = FeedForwardColumnsOwned::<<E as EvalAtRow>::F>::from_eval(eval);
+        let left = [left_0, left_1, left_2, left_3];
+        let right = [right_0, right_1, right_2, right_3];
+        let xor = [xor_0, xor_1, xor_2, xor_3];
+
+        eq_constraints(eval, v_left, &rotr_limbs::<E, 4>(&left, BYTES, 0));
+        eq_constraints(eval, v_right, &rotr_limbs::<E, 4>(&right, BYTES, 0));
+        h_out.extend(rotr_limbs::<E, 4>(&xor, BYTES, 0));
+
+        for (lhs, rhs, xor) in izip!(left, right, xor) {
+            relations
+                .tables
+                .xor
+                .add_to_eval(eval, 8, minus_one.clone(), &[lhs, rhs, xor]);
+        }
+    }
+
+    // Consume the input chaining value, unless this is the first block of the node
+    let chain_in = [message_id.clone(), node_id.clone(), block_index.clone()]
+        .into_iter()
+        .chain(h_in)
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.chain,
+        E::EF::from(is_first - one.clone()),
+        &chain_in,
+    ));
+
+    // Emit the output chaining value, unless this is the last block of the node
+    let chain_out = [
+        message_id.clone(),
+        node_id.clone(),
+        block_index.clone() + one.clone(),
+    ]
+    .into_iter()
+    .chain(h_out.clone())
+    .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.chain,
+        E::EF::from(one.clone() - is_last.clone()),
+        &chain_out,
+    ));
+
+    // Parent nodes consume the output chaining values of their children as message words
+    let (m_left, m_right) = m.split_at(STATE_SIZE);
+    for (child_id, words) in [(left_id.clone(), m_left), (right_id.clone(), m_right)] {
+        let child = [message_id.clone(), child_id]
+            .into_iter()
+            .chain(words.iter().cloned())
+            .collect::<Vec<_>>();
+        eval.add_to_relation(RelationEntry::new(
+            &relations.node,
+            E::EF::from(-is_parent.clone()),
+            &child,
+        ));
+    }
+
+    // Emit the output chaining value of the node to its parent, unless this is the root
+    let node_out = [message_id.clone(), node_id.clone()]
+        .into_iter()
+        .chain(h_out.clone())
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.node,
+        E::EF::from(is_last - is_root.clone()),
+        &node_out,
+    ));
+
+    // Emit the public claims of the block, consumed by the verifier: its position in the tree
+    // and parameters, its message words unless this is a parent node, and the digest of its
+    // message if this is the root
+    let input_claim = [
+        message_id.clone(),
+        node_id.clone(),
+        block_index.clone(),
+        counter_0,
+        counter_1,
+        counter_2,
+        counter_3,
+        block_len,
+        flags,
+        left_id,
+        right_id,
+    ];
+    eval.add_to_relation(RelationEntry::new(
+        &relations.input_claim,
+        E::EF::from(is_active.clone()),
+        &input_claim,
+    ));
+    let message_claim = [message_id.clone(), node_id, block_index]
+        .into_iter()
+        .chain(m)
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.message_claim,
+        E::EF::from(is_active.clone() - is_parent),
+        &message_claim,
+    ));
+    let output_claim = [message_id].into_iter().chain(h_out).collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.output_claim,
+        E::EF::from(is_root + is_active - one),
+        &output_claim,
+    ));
+
+    eval.finalize_logup_in_pairs();
+}
+
+#[derive(Clone)]
+pub struct Eval {
+    pub log_size: u32,
+    pub relations: Relations,
+}
+impl FrameworkEval for Eval {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        self.log_size() + 1
+    }
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        eval_compression_constraints(&mut eval, &self.relations);
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use stwo::core::{pcs::TreeVec, poly::circle::CanonicCoset};
+    use stwo_constraint_framework::assert_constraints_on_polys;
+
+    use super::*;
+    use crate::{
+        components::compression::witness::{gen_interaction_trace, gen_trace},
+        message::{gen_blocks, generate_blocks, Block},
+    };
+
+    fn assert_compression_constraints(log_size: u32, blocks: &[Block]) {
+        let (trace, lookup_data) = gen_trace(log_size, blocks);
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) = gen_interaction_trace(&lookup_data, &relations);
+
+        let traces = TreeVec::new(vec![vec![], trace, interaction_trace]);
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(log_size),
+            |mut eval| {
+                eval_compression_constraints(&mut eval, &relations);
+            },
+            claimed_sum,
+        );
+    }
+
+    #[test]
+    fn test_compression_constraints() {
+        const LOG_N_ROWS: u32 = 4;
+
+        assert_compression_constraints(LOG_N_ROWS, &generate_blocks(1 << LOG_N_ROWS));
+    }
+
+    #[test]
+    fn test_compression_constraints_hash_trees() {
+        const LOG_N_ROWS: u32 = 6;
+
+        // Trees of 3 and 2 chunks, single-block messages, partial blocks and padding rows.
+        let messages = [vec![0x42; 2100], b"abc".to_vec(), vec![], vec![0x43; 1500]];
+        assert_compression_constraints(LOG_N_ROWS, &gen_blocks(&messages));
+    }
+}
@@ -0,0 +1,70 @@
+use utils::trace_columns;
+
+trace_columns!(
+    ChainColumns,
+    message_id,
+    node_id,
+    block_index,
+    is_first,
+    is_last,
+    is_parent,
+    is_root,
+    is_active,
+    counter_0,
+    counter_1,
+    counter_2,
+    counter_3,
+    block_len,
+    flags,
+    left_id,
+    right_id
+);
+
+trace_columns!(
+    ChainInteractionColumns,
+    message_id,
+    node_id,
+    block_index,
+    next_block_index,
+    is_first,
+    is_last,
+    is_parent,
+    is_root,
+    is_active,
+    counter_0,
+    counter_1,
+    counter_2,
+    counter_3,
+    block_len,
+    flags,
+    left_id,
+    right_id
+);
+
+trace_columns!(
+    FeedForwardColumns,
+    left_0,
+    left_1,
+    left_2,
+    left_3,
+    right_0,
+    right_1,
+    right_2,
+    right_3,
+    xor_0,
+    xor_1,
+    xor_2,
+    xor_3
+);
+
+impl<'a, T> FeedForwardColumns<'a, T> {
+    /// `[a, b, a ^ b]` of the byte XOR lookups of `v[i] ^ v[i + 8]`, in the order of the AIR.
+    pub fn xor_lookups(&self) -> [[&'a T; 3]; 4] {
+        [
+            [self.left_0, self.right_0, self.xor_0],
+            [self.left_1, self.right_1, self.xor_1],
+            [self.left_2, self.right_2, self.xor_2],
+            [self.left_3, self.right_3, self.xor_3],
+        ]
+    }
+}
@@ -0,0 +1,340 @@
+//! Message handling: splitting of messages into chunks of chained blocks, and the hash tree above
+//! the chunks.
+//!
+//! Each trace row compresses one [`Block`], either a block of a chunk or a parent node. Blocks of
+//! the same chunk are linked through the chain relation: a block's output chaining value is the
+//! next block's input chaining value. The output chaining value of each node, chunk or parent, is
+//! linked to the message words of its parent through the node relation. The root node outputs the
+//! digest.
+
+use std::simd::u32x16;
+
+use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+use crate::blake3::{compress, BLOCK_SIZE, CHUNK_BLOCKS, CHUNK_END, CHUNK_START, IV, PARENT, ROOT};
+
+/// A compression, together with its position in the hash tree and its input chaining value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    /// Index of the message this block belongs to.
+    pub message_id: u32,
+    /// Node of the hash tree this block belongs to, see [`parent_nodes`].
+    pub node_id: u32,
+    /// Index of the block in its chunk, 0 for parent nodes.
+    pub index: u32,
+    /// Index of the chunk, 0 for parent nodes.
+    pub counter: u64,
+    /// Number of message bytes in the block.
+    pub block_len: u32,
+    /// Domain separation flags, see [`crate::blake3`].
+    pub flags: u32,
+    /// Children of a parent node, whose output chaining values are the message words.
+    pub left_id: u32,
+    pub right_id: u32,
+    /// Chaining value the compression starts from.
+    pub h_in: [u32; 8],
+    /// Message words, little-endian.
+    pub words: [u32; 16],
+}
+
+impl Block {
+    /// Whether the compression starts from the initial chaining value.
+    pub const fn is_first(&self) -> bool {
+        self.flags & (CHUNK_START | PARENT) != 0
+    }
+
+    /// Whether the compression outputs the chaining value of its node.
+    pub const fn is_last(&self) -> bool {
+        self.flags & (CHUNK_END | PARENT) != 0
+    }
+
+    pub const fn is_parent(&self) -> bool {
+        self.flags & PARENT != 0
+    }
+
+    pub const fn is_root(&self) -> bool {
+        self.flags & ROOT != 0
+    }
+
+    /// Output chaining value, i.e. the compression of `words` into `h_in`.
+    pub fn h_out(&self) -> [u32; 8] {
+        compress(
+            self.h_in,
+            self.words,
+            self.counter,
+            self.block_len,
+            self.flags,
+        )
+    }
+
+    /// A standalone zero block used to pad the trace, i.e. the only block of the empty message.
+    pub const fn padding() -> Self {
+        Self {
+            message_id: 0,
+            node_id: 0,
+            index: 0,
+            counter: 0,
+            block_len: 0,
+            flags: CHUNK_START | CHUNK_END | ROOT,
+            left_id: 0,
+            right_id: 0,
+            h_in: IV,
+            words: [0; 16],
+        }
+    }
+}
+
+impl Default for Block {
+    /// See [`Block::padding`].
+    fn default() -> Self {
+        Self::padding()
+    }
+}
+
+/// A parent node of the hash tree, whose children are chunks or other parent nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParentNode {
+    pub node_id: u32,
+    pub left_id: u32,
+    pub right_id: u32,
+    pub is_root: bool,
+}
+
+/// Splits a message into chunks of blocks, each zero padded and paired with its length in bytes.
+/// The empty message is a single chunk made of an empty block.
+pub fn pad_message(message: &[u8]) -> Vec<Vec<([u32; 16], u32)>> {
+    let chunk_size = CHUNK_BLOCKS * BLOCK_SIZE;
+    let n_chunks = message.len().div_ceil(chunk_size).max(1);
+    (0..n_chunks)
+        .map(|i| {
+            let chunk = &message[i * chunk_size..message.len().min((i + 1) * chunk_size)];
+            let n_blocks = chunk.len().div_ceil(BLOCK_SIZE).max(1);
+            (0..n_blocks)
+                .map(|j| {
+                    let block = &chunk[j * BLOCK_SIZE..chunk.len().min((j + 1) * BLOCK_SIZE)];
+                    let mut padded = [0; BLOCK_SIZE];
+                    padded[..block.len()].copy_from_slice(block);
+                    let words = std::array::from_fn(|k| {
+                        u32::from_le_bytes(padded[4 * k..4 * k + 4].try_into().unwrap())
+                    });
+                    (words, block.len() as u32)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Flags of the block `index` out of `n_blocks` of a chunk. The chunk is the root when it is the
+/// only one of its message.
+pub const fn chunk_flags(index: usize, n_blocks: usize, is_root: bool) -> u32 {
+    let mut flags = 0;
+    if index == 0 {
+        flags |= CHUNK_START;
+    }
+    if index + 1 == n_blocks {
+        flags |= CHUNK_END;
+        if is_root {
+            flags |= ROOT;
+        }
+    }
+    flags
+}
+
+/// Parent nodes above `n_chunks` chunks, children first, so that the last one is the root.
+///
+/// The chunks are the nodes `0..n_chunks`, and parent nodes are numbered from `n_chunks` in this
+/// order. The left subtree of a node holds the largest power of 2 of its chunks which leaves at
+/// least one chunk to the right subtree.
+pub fn parent_nodes(n_chunks: usize) -> Vec<ParentNode> {
+    fn merge(chunks: std::ops::Range<u32>, nodes: &mut Vec<ParentNode>, n_chunks: u32) -> u32 {
+        if chunks.len() == 1 {
+            return chunks.start;
+        }
+        let split = chunks.start + (1 << (chunks.len() as u32 - 1).ilog2());
+        let left_id = merge(chunks.start..split, nodes, n_chunks);
+        let right_id = merge(split..chunks.end, nodes, n_chunks);
+        let node_id = n_chunks + nodes.len() as u32;
+        nodes.push(ParentNode {
+            node_id,
+            left_id,
+            right_id,
+            is_root: false,
+        });
+        node_id
+    }
+
+    let mut nodes = vec![];
+    merge(0..n_chunks as u32, &mut nodes, n_chunks as u32);
+    if let Some(root) = nodes.last_mut() {
+        root.is_root = true;
+    }
+    nodes
+}
+
+/// Splits each message into chunks of chained blocks, followed by the parent nodes of its hash
+/// tree, see [`parent_nodes`].
+pub fn gen_blocks<M: AsRef<[u8]>>(messages: &[M]) -> Vec<Block> {
+    let mut blocks = vec![];
+    for (message_id, message) in messages.iter().enumerate() {
+        let chunks = pad_message(message.as_ref());
+        let n_chunks = chunks.len();
+
+        // Output chaining value of each node
+        let mut nodes = Vec::with_capacity(2 * n_chunks - 1);
+        for (counter, chunk) in chunks.into_iter().enumerate() {
+            let n_blocks = chunk.len();
+            let mut h = IV;
+            for (index, (words, block_len)) in chunk.into_iter().enumerate() {
+                let block = Block {
+                    message_id: message_id as u32,
+                    node_id: counter as u32,
+                    index: index as u32,
+                    counter: counter as u64,
+                    block_len,
+                    flags: chunk_flags(index, n_blocks, n_chunks == 1),
+                    left_id: 0,
+                    right_id: 0,
+                    h_in: h,
+                    words,
+                };
+                h = block.h_out();
+                blocks.push(block);
+            }
+            nodes.push(h);
+        }
+
+        for node in parent_nodes(n_chunks) {
+            let [left, right] = [node.left_id, node.right_id].map(|id| nodes[id as usize]);
+            let block = Block {
+                message_id: message_id as u32,
+                node_id: node.node_id,
+                index: 0,
+                counter: 0,
+                block_len: BLOCK_SIZE as u32,
+                flags: PARENT | if node.is_root { ROOT } else { 0 },
+                left_id: node.left_id,
+                right_id: node.right_id,
+                h_in: IV,
+                words: std::array::from_fn(|i| if i < 8 { left[i] } else { right[i - 8] }),
+            };
+            nodes.push(block.h_out());
+            blocks.push(block);
+        }
+    }
+    blocks
+}
+
+/// Little-endian bytes of a chaining value.
+pub fn to_bytes(hash: [u32; 8]) -> [u8; 32] {
+    std::array::from_fn(|i| hash[i / 4].to_le_bytes()[i % 4])
+}
+
+/// Digest of each message, i.e. the output chaining value of its root node.
+pub fn digests(blocks: &[Block]) -> Vec<[u8; 32]> {
+    blocks
+        .iter()
+        .filter(|block| block.is_root())
+        .map(|block| to_bytes(block.h_out()))
+        .collect()
+}
+
+/// Deterministic single-block messages, used for tests and benchmarks.
+///
+/// The 16-bit limb `i` of block `row` is `(i + row) & 0xffff`.
+pub fn generate_blocks(n_blocks: usize) -> Vec<Block> {
+    (0..n_blocks)
+        .map(|row| Block {
+            message_id: row as u32,
+            block_len: BLOCK_SIZE as u32,
+            words: std::array::from_fn(|i| {
+                let low = (2 * i + row) as u32 & 0xffff;
+                let high = (2 * i + 1 + row) as u32 & 0xffff;
+                low + (high << 16)
+            }),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Builds a trace column from a value of each block, padding with [`Block::padding`] blocks up to
+/// `simd_size` SIMD rows.
+pub fn block_column(blocks: &[Block], simd_size: usize, f: impl Fn(&Block) -> u32) -> Vec<u32x16> {
+    let padding = Block::padding();
+    (0..simd_size)
+        .map(|simd_row| {
+            u32x16::from_array(std::array::from_fn(|lane| {
+                f(blocks
+                    .get((simd_row << LOG_N_LANES) + lane)
+                    .unwrap_or(&padding))
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad_message() {
+        assert_eq!(pad_message(b""), [[([0; 16], 0)]]);
+        assert_eq!(pad_message(&[0; 1024]).len(), 1);
+        assert_eq!(pad_message(&[0; 1024])[0].len(), 16);
+        assert_eq!(pad_message(&[0; 1025]).len(), 2);
+        assert_eq!(pad_message(&[0; 1025])[1], [([0; 16], 1)]);
+
+        let chunks = pad_message(b"abc");
+        assert_eq!(chunks[0][0].0[0], 0x00636261);
+        assert_eq!(chunks[0][0].0[1..], [0; 15]);
+        assert_eq!(chunks[0][0].1, 3);
+    }
+
+    #[test]
+    fn test_parent_nodes() {
+        assert_eq!(parent_nodes(1), []);
+        let node = |node_id, left_id, right_id, is_root| ParentNode {
+            node_id,
+            left_id,
+            right_id,
+            is_root,
+        };
+        // ((0, 1), 2) and (((0, 1), (2, 3)), 4)
+        assert_eq!(parent_nodes(3), [node(3, 0, 1, false), node(4, 3, 2, true)]);
+        assert_eq!(
+            parent_nodes(5),
+            [
+                node(5, 0, 1, false),
+                node(6, 2, 3, false),
+                node(7, 5, 6, false),
+                node(8, 7, 4, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gen_blocks() {
+        let messages = [vec![0xab; 3000], b"abc".to_vec(), vec![], vec![0x42; 1024]];
+        let blocks = gen_blocks(&messages);
+        // 3 chunks of 16, 16 and 15 blocks, and 2 parent nodes
+        assert_eq!(blocks.len(), 47 + 2 + 1 + 1 + 16);
+        assert_eq!(blocks[15].flags, CHUNK_END);
+        assert_eq!(blocks[46].block_len, 3000 % 64);
+        assert_eq!(blocks[48].flags, PARENT | ROOT);
+        // A full single chunk is its own root.
+        assert_eq!(blocks[66].flags, CHUNK_END | ROOT);
+        for (block, next) in blocks.iter().zip(&blocks[1..]) {
+            if !block.is_last() {
+                assert_eq!(next.h_in, block.h_out());
+            }
+        }
+
+        for (digest, message) in digests(&blocks).iter().zip(&messages) {
+            assert_eq!(digest, ::blake3::hash(message).as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_padding_block() {
+        assert_eq!(digests(&[Block::padding()]), [crate::blake3::blake3(b"")]);
+    }
+}
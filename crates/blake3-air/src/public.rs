@@ -0,0 +1,233 @@
+//! Public inputs and outputs of the proof.
+//!
+//! The compression component emits three kinds of claims, consumed by the verifier:
+//! - an input claim for each compression, made of its position in the hash tree, its counter,
+//!   block length and flags, and the children of parent nodes,
+//! - a message claim for each block of a chunk, made of its message words,
+//! - an output claim for each message, made of its digest.
+//!
+//! The verifier rebuilds the same claims from the public messages and digests, so the logup sum
+//! only balances if the proof covers exactly these messages, hash trees and digests. Chaining
+//! values of chunk blocks and of non-root nodes are linked inside the AIR and never claimed.
+
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use stwo::core::{
+    channel::Channel,
+    fields::{m31::M31, qm31::SecureField, FieldExpOps},
+};
+use stwo_constraint_framework::Relation;
+
+use crate::{
+    blake3::{blake3, counter_words, BLOCK_SIZE, CHUNK_SIZE, PARENT, ROOT},
+    components::STATE_SIZE,
+    message::{chunk_flags, pad_message, parent_nodes, Block},
+    relations::Relations,
+};
+
+/// [message_id, node_id, block_index, counter words, block_len, flags, left_id, right_id], the
+/// counter words as 16-bit limbs. The counter words are the words 12 and 13 of the initial working
+/// state, see [`counter_words`].
+pub const INPUT_CLAIM_SIZE: usize = 3 + 4 + 2 + 2;
+
+/// [message_id, node_id, block_index, message words], as 16-bit limbs.
+pub const MESSAGE_CLAIM_SIZE: usize = 3 + CHUNK_SIZE;
+
+/// [message_id, digest], as 16-bit limbs.
+pub const OUTPUT_CLAIM_SIZE: usize = 1 + STATE_SIZE;
+
+/// Claims of a proof, see the module documentation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Claims {
+    pub inputs: Vec<[u32; INPUT_CLAIM_SIZE]>,
+    pub messages: Vec<[u32; MESSAGE_CLAIM_SIZE]>,
+    pub outputs: Vec<[u32; OUTPUT_CLAIM_SIZE]>,
+}
+
+/// A message together with its claimed digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashClaim {
+    pub message: Vec<u8>,
+    pub digest: [u8; 32],
+}
+
+impl HashClaim {
+    /// Claims the actual BLAKE3 digest of `message`.
+    pub fn new(message: impl Into<Vec<u8>>) -> Self {
+        let message = message.into();
+        let digest = blake3(&message);
+        Self { message, digest }
+    }
+}
+
+/// Splits 32-bit words into 16-bit limbs, low limb first.
+fn to_limbs(words: &[u32]) -> impl Iterator<Item = u32> + '_ {
+    words.iter().flat_map(|word| [word & 0xffff, word >> 16])
+}
+
+fn input_claim(block: &Block) -> [u32; INPUT_CLAIM_SIZE] {
+    [block.message_id, block.node_id, block.index]
+        .into_iter()
+        .chain(to_limbs(&counter_words(block.counter)))
+        .chain([block.block_len, block.flags, block.left_id, block.right_id])
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+fn message_claim(block: &Block) -> [u32; MESSAGE_CLAIM_SIZE] {
+    [block.message_id, block.node_id, block.index]
+        .into_iter()
+        .chain(to_limbs(&block.words))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+fn output_claim(message_id: u32, digest: &[u32; 8]) -> [u32; OUTPUT_CLAIM_SIZE] {
+    [message_id]
+        .into_iter()
+        .chain(to_limbs(digest))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+/// Claims emitted by the compression component for the given blocks.
+pub fn block_claims(blocks: &[Block]) -> Claims {
+    let mut claims = Claims::default();
+    for block in blocks {
+        claims.inputs.push(input_claim(block));
+        if !block.is_parent() {
+            claims.messages.push(message_claim(block));
+        }
+        if block.is_root() {
+            claims
+                .outputs
+                .push(output_claim(block.message_id, &block.h_out()));
+        }
+    }
+    claims
+}
+
+/// Claims expected by the verifier, which only splits the messages into chunks and builds their
+/// hash trees. Message ids follow the order of the claims, see [`crate::message::gen_blocks`].
+pub fn hash_claims(claims: &[HashClaim]) -> Claims {
+    let mut result = Claims::default();
+    for (message_id, claim) in claims.iter().enumerate() {
+        let message_id = message_id as u32;
+        let chunks = pad_message(&claim.message);
+        let n_chunks = chunks.len();
+        for (counter, chunk) in chunks.into_iter().enumerate() {
+            let n_blocks = chunk.len();
+            for (index, (words, block_len)) in chunk.into_iter().enumerate() {
+                // The input chaining value is not claimed
+                let block = Block {
+                    message_id,
+                    node_id: counter as u32,
+                    index: index as u32,
+                    counter: counter as u64,
+                    block_len,
+                    flags: chunk_flags(index, n_blocks, n_chunks == 1),
+                    words,
+                    ..Default::default()
+                };
+                result.inputs.push(input_claim(&block));
+                result.messages.push(message_claim(&block));
+            }
+        }
+        for node in parent_nodes(n_chunks) {
+            // The message words, i.e. the chaining values of the children, are not claimed
+            let block = Block {
+                message_id,
+                node_id: node.node_id,
+                block_len: BLOCK_SIZE as u32,
+                flags: PARENT | if node.is_root { ROOT } else { 0 },
+                left_id: node.left_id,
+                right_id: node.right_id,
+                ..Default::default()
+            };
+            result.inputs.push(input_claim(&block));
+        }
+        let digest = std::array::from_fn(|i| {
+            u32::from_le_bytes(claim.digest[4 * i..4 * i + 4].try_into().unwrap())
+        });
+        result.outputs.push(output_claim(message_id, &digest));
+    }
+    result
+}
+
+pub fn mix_into(claims: &Claims, channel: &mut impl Channel) {
+    channel.mix_u32s(&[claims.inputs.len() as u32]);
+    for claim in &claims.inputs {
+        channel.mix_u32s(claim);
+    }
+    channel.mix_u32s(&[claims.messages.len() as u32]);
+    for claim in &claims.messages {
+        channel.mix_u32s(claim);
+    }
+    channel.mix_u32s(&[claims.outputs.len() as u32]);
+    for claim in &claims.outputs {
+        channel.mix_u32s(claim);
+    }
+}
+
+/// Logup sum of the verifier consuming the claims, to be added to the components claimed sum.
+pub fn logup_sum(claims: &Claims, relations: &Relations) -> SecureField {
+    let inputs = claims.inputs.iter().map(|claim| {
+        let denom: SecureField = relations.input_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    let messages = claims.messages.iter().map(|claim| {
+        let denom: SecureField = relations.message_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    let outputs = claims.outputs.iter().map(|claim| {
+        let denom: SecureField = relations.output_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    inputs
+        .chain(messages)
+        .chain(outputs)
+        .fold(SecureField::zero(), |acc, frac| acc - frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::gen_blocks;
+
+    #[test]
+    fn test_hash_claims_match_block_claims() {
+        let messages = [
+            b"abc".to_vec(),
+            vec![0x42; 100],
+            vec![],
+            vec![0x43; 1024],
+            vec![0x44; 5000],
+        ];
+        let claims = messages
+            .iter()
+            .map(|message| HashClaim::new(message.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(hash_claims(&claims), block_claims(&gen_blocks(&messages)));
+    }
+
+    #[test]
+    fn test_hash_claims_wrong_digest() {
+        let mut claim = HashClaim::new(b"abc".to_vec());
+        claim.digest[0] ^= 1;
+        assert_ne!(
+            hash_claims(&[claim]),
+            block_claims(&gen_blocks(&[b"abc".to_vec()]))
+        );
+    }
+
+    #[test]
+    fn test_hash_claims_block_len() {
+        // Messages which pad to the same block only differ by their block length.
+        let claims = hash_claims(&[HashClaim::new(b"abc".to_vec()), HashClaim::new(b"abc\0")]);
+        assert_eq!(claims.messages[0][1..], claims.messages[1][1..]);
+        assert_ne!(claims.inputs[0][1..], claims.inputs[1][1..]);
+    }
+}
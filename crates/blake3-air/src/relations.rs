@@ -0,0 +1,76 @@
+use blake2s::relations::TableRelations;
+use stwo::core::channel::Channel;
+
+mod chain {
+    use stwo_constraint_framework::relation;
+
+    use crate::components::STATE_SIZE;
+    // [message_id, node_id, block_index, state]
+    relation!(Relation, 3 + STATE_SIZE);
+}
+
+mod node {
+    use stwo_constraint_framework::relation;
+
+    use crate::components::STATE_SIZE;
+    // [message_id, node_id, state]
+    relation!(Relation, 2 + STATE_SIZE);
+}
+
+mod input_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::INPUT_CLAIM_SIZE;
+    // [message_id, node_id, block_index, counter words, block_len, flags, left_id, right_id]
+    relation!(Relation, INPUT_CLAIM_SIZE);
+}
+
+mod message_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::MESSAGE_CLAIM_SIZE;
+    // [message_id, node_id, block_index, message words]
+    relation!(Relation, MESSAGE_CLAIM_SIZE);
+}
+
+mod output_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::OUTPUT_CLAIM_SIZE;
+    // [message_id, digest]
+    relation!(Relation, OUTPUT_CLAIM_SIZE);
+}
+
+#[derive(Clone)]
+pub struct Relations {
+    pub tables: TableRelations,
+    pub chain: chain::Relation,
+    pub node: node::Relation,
+    pub input_claim: input_claim::Relation,
+    pub message_claim: message_claim::Relation,
+    pub output_claim: output_claim::Relation,
+}
+
+impl Relations {
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            tables: TableRelations::draw(channel),
+            chain: chain::Relation::draw(channel),
+            node: node::Relation::draw(channel),
+            input_claim: input_claim::Relation::draw(channel),
+            message_claim: message_claim::Relation::draw(channel),
+            output_claim: output_claim::Relation::draw(channel),
+        }
+    }
+
+    pub fn dummy() -> Self {
+        Self {
+            tables: TableRelations::dummy(),
+            chain: chain::Relation::dummy(),
+            node: node::Relation::dummy(),
+            input_claim: input_claim::Relation::dummy(),
+            message_claim: message_claim::Relation::dummy(),
+            output_claim: output_claim::Relation::dummy(),
+        }
+    }
+}
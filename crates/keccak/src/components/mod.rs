@@ -0,0 +1,241 @@
+use std::simd::u32x16;
+
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use stwo::{
+    core::{
+        air::Component,
+        channel::Channel,
+        fields::{m31::BaseField, qm31::SecureField},
+        pcs::TreeVec,
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{m31::LOG_N_LANES, SimdBackend},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        ComponentProver,
+    },
+};
+use stwo_constraint_framework::{
+    relation_tracker::{add_to_relation_entries, RelationTrackerEntry},
+    TraceLocationAllocator,
+};
+use tracing::{span, Level};
+use utils::stwo::air::{Air, AirComponents, LogupSum, Trace};
+
+use crate::{
+    message::Block,
+    public::{self, Claims},
+    relations::Relations,
+};
+pub const STATE_SIZE: usize = 100; // 100 u16 = 25 u64
+
+pub mod permutation;
+pub mod preprocessed;
+
+pub struct LookupData {
+    pub permutation: Vec<Vec<u32x16>>,
+    pub preprocessed: preprocessed::Traces,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClaimedSum {
+    pub permutation: SecureField,
+    pub preprocessed: preprocessed::ClaimedSum,
+}
+
+impl ClaimedSum {
+    pub fn zero() -> Self {
+        Self {
+            permutation: SecureField::zero(),
+            preprocessed: preprocessed::ClaimedSum::zero(),
+        }
+    }
+}
+
+impl LogupSum for ClaimedSum {
+    fn sum(&self) -> SecureField {
+        self.permutation + self.preprocessed.sum()
+    }
+
+    fn mix_into(&self, channel: &mut impl Channel) {
+        channel.mix_felts(&[self.permutation]);
+        self.preprocessed.mix_into(channel);
+    }
+}
+
+pub fn gen_trace(
+    log_size: u32,
+    blocks: &[Block],
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    LookupData,
+) {
+    assert!(log_size >= LOG_N_LANES);
+
+    let span = span!(Level::INFO, "Permutation").entered();
+    let (permutation_trace, permutation_lookup_data) =
+        permutation::witness::gen_trace(log_size, blocks);
+    span.exit();
+
+    let span = span!(Level::INFO, "Preprocessed").entered();
+    let preprocessed_trace = preprocessed::gen_trace(log_size, &permutation_lookup_data);
+    span.exit();
+
+    let lookup_data = LookupData {
+        permutation: permutation_lookup_data,
+        preprocessed: preprocessed_trace.clone(),
+    };
+
+    let mut trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> =
+        Vec::with_capacity(permutation_trace.len() + preprocessed_trace.len());
+    trace.extend(permutation_trace);
+    trace.extend(preprocessed_trace);
+
+    (trace, lookup_data)
+}
+
+pub fn gen_interaction_trace(
+    lookup_data: LookupData,
+    relations: &Relations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    ClaimedSum,
+) {
+    let span = span!(Level::INFO, "Permutation").entered();
+    let (permutation_interaction_trace, permutation_claimed_sum) =
+        permutation::witness::gen_interaction_trace(&lookup_data.permutation, relations);
+    span.exit();
+
+    let span = span!(Level::INFO, "Preprocessed").entered();
+    let (preprocessed_interaction_trace, preprocessed_claimed_sum) =
+        preprocessed::gen_interaction_trace(&lookup_data.preprocessed, relations);
+    span.exit();
+
+    let mut interaction_trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> =
+        Vec::with_capacity(
+            permutation_interaction_trace.len() + preprocessed_interaction_trace.len(),
+        );
+    interaction_trace.extend(permutation_interaction_trace);
+    interaction_trace.extend(preprocessed_interaction_trace);
+    (
+        interaction_trace,
+        ClaimedSum {
+            permutation: permutation_claimed_sum,
+            preprocessed: preprocessed_claimed_sum,
+        },
+    )
+}
+
+pub struct Components {
+    permutation: permutation::air::Component,
+    preprocessed: preprocessed::Components,
+}
+
+impl Components {
+    pub fn new(
+        log_size: u32,
+        location_allocator: &mut TraceLocationAllocator,
+        relations: &Relations,
+        claimed_sum: &ClaimedSum,
+    ) -> Self {
+        Self {
+            permutation: permutation::air::Component::new(
+                location_allocator,
+                permutation::air::Eval {
+                    log_size,
+                    relations: relations.clone(),
+                },
+                claimed_sum.permutation,
+            ),
+            preprocessed: preprocessed::Components::new(
+                log_size,
+                location_allocator,
+                relations.clone(),
+                &claimed_sum.preprocessed,
+            ),
+        }
+    }
+}
+
+impl AirComponents for Components {
+    fn provers(&self) -> Vec<&dyn ComponentProver<SimdBackend>> {
+        let mut provers: Vec<&dyn ComponentProver<SimdBackend>> = vec![&self.permutation];
+        provers.extend(self.preprocessed.provers());
+        provers
+    }
+
+    fn components(&self) -> Vec<&dyn Component> {
+        let mut components: Vec<&dyn Component> = vec![&self.permutation];
+        components.extend(self.preprocessed.components());
+        components
+    }
+
+    fn trace_log_degree_bounds(&self) -> Vec<TreeVec<ColumnVec<u32>>> {
+        let mut log_degree_bounds: Vec<TreeVec<ColumnVec<u32>>> = Vec::new();
+        log_degree_bounds.push(self.permutation.trace_log_degree_bounds());
+        log_degree_bounds.extend(self.preprocessed.trace_log_degree_bounds());
+        log_degree_bounds
+    }
+
+    fn relation_entries(&self, trace: &TreeVec<Vec<&Vec<BaseField>>>) -> Vec<RelationTrackerEntry> {
+        add_to_relation_entries(&self.permutation, trace)
+            .into_iter()
+            .chain(self.preprocessed.relation_entries(trace))
+            .collect()
+    }
+}
+
+/// The Keccak AIR, proving batches of absorbed blocks.
+pub struct KeccakAir;
+
+impl Air for KeccakAir {
+    type Input = [Block];
+    type Claims = Claims;
+    type Relations = Relations;
+    type LookupData = LookupData;
+    type ClaimedSum = ClaimedSum;
+    type Components = Components;
+
+    fn claims(&self, blocks: &[Block]) -> Claims {
+        public::block_claims(blocks)
+    }
+
+    fn mix_claims(&self, claims: &Claims, channel: &mut impl Channel) {
+        public::mix_into(claims, channel);
+    }
+
+    fn draw_relations(&self, channel: &mut impl Channel) -> Relations {
+        Relations::draw(channel)
+    }
+
+    fn dummy_relations(&self) -> Relations {
+        Relations::dummy()
+    }
+
+    fn logup_sum(&self, claims: &Claims, relations: &Relations) -> SecureField {
+        public::logup_sum(claims, relations)
+    }
+
+    fn gen_trace(&self, log_size: u32, blocks: &[Block]) -> (Trace, LookupData) {
+        gen_trace(log_size, blocks)
+    }
+
+    fn gen_interaction_trace(
+        &self,
+        lookup_data: LookupData,
+        relations: &Relations,
+    ) -> (Trace, ClaimedSum) {
+        gen_interaction_trace(lookup_data, relations)
+    }
+
+    fn components(
+        &self,
+        log_size: u32,
+        location_allocator: &mut TraceLocationAllocator,
+        relations: &Relations,
+        claimed_sum: &ClaimedSum,
+    ) -> Components {
+        Components::new(log_size, location_allocator, relations, claimed_sum)
+    }
+}
@@ -0,0 +1,291 @@
+use num_traits::{One, Zero};
+use stwo::core::fields::m31::M31;
+use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval, RelationEntry};
+
+use crate::{
+    components::permutation::columns::{AbsorbLanes, ChainColumnsOwned, Lane, RoundLanes},
+    keccak::{N_LANES, N_ROUNDS, PI, RATE_LANES, RC, RHO},
+    partitions::{is_split, rotl_sources, THETA_ROTATION},
+    relations::Relations,
+};
+
+pub type Component = FrameworkComponent<Eval>;
+
+fn constant<E: EvalAtRow>(value: u64) -> E::F {
+    E::F::from(M31::from(value as u32))
+}
+
+fn next_lane<E: EvalAtRow>(eval: &mut E) -> Lane<E::F> {
+    std::array::from_fn(|_| eval.next_trace_mask())
+}
+
+/// Reads a lane committed for a rotation left by `r`, see [`crate::partitions`], and returns its
+/// bytes together with the bytes of its rotation. The range checks `[r % 8, low, high]` of the
+/// pieces of split lanes are added to `split_lookups`.
+fn next_rotated_lane<E: EvalAtRow>(
+    eval: &mut E,
+    r: u32,
+    split_lookups: &mut Vec<[E::F; 3]>,
+) -> (Lane<E::F>, Lane<E::F>) {
+    let sources = rotl_sources(r);
+    if !is_split(r) {
+        let lane = next_lane(eval);
+        let rotated = sources.map(|(low, _)| lane[low].clone());
+        return (lane, rotated);
+    }
+
+    let s = r % 8;
+    let [low, high] = [(); 2].map(|_| next_lane(eval));
+    let lane =
+        std::array::from_fn(|k| low[k].clone() + high[k].clone() * constant::<E>(1 << (8 - s)));
+    let rotated = sources.map(|(l, h)| low[l].clone() * constant::<E>(1 << s) + high[h].clone());
+    for (low, high) in low.into_iter().zip(high) {
+        split_lookups.push([constant::<E>(s as u64), low, high]);
+    }
+    (lane, rotated)
+}
+
+/// 16-bit limbs of `lanes`, as the combination of pairs of bytes.
+fn limbs<E: EvalAtRow>(lanes: &[Lane<E::F>]) -> impl Iterator<Item = E::F> + '_ {
+    lanes.iter().flat_map(|lane| {
+        (0..4).map(|k| lane[2 * k].clone() + lane[2 * k + 1].clone() * constant::<E>(1 << 8))
+    })
+}
+
+/// Constrains one round of the permutation on the state `a` and returns the output state.
+///
+/// Lanes are committed as bytes, and rotated lanes as the pieces of their bytes so that their
+/// rotation is a linear combination of the pieces. The XORs and AND-NOTs are looked up byte by
+/// byte, which also range checks the committed bytes.
+fn eval_round<E: EvalAtRow>(
+    eval: &mut E,
+    relations: &Relations,
+    a: [Lane<E::F>; N_LANES],
+    round: usize,
+) -> [Lane<E::F>; N_LANES] {
+    let mut split_lookups = Vec::new();
+
+    // θ: the partial parities of each column, the last one split for its rotation, then d
+    let c: [([Lane<E::F>; 4], Lane<E::F>); 5] = std::array::from_fn(|_| {
+        let [c0, c1, c2] = [(); 3].map(|_| next_lane(eval));
+        let (c3, rotated) = next_rotated_lane(eval, THETA_ROTATION, &mut split_lookups);
+        ([c0, c1, c2, c3], rotated)
+    });
+    let rotl_c = c.each_ref().map(|(_, rotated)| rotated.clone());
+    let c = c.map(|(c, _)| c);
+    let d = std::array::from_fn(|_| next_lane(eval));
+
+    // ρ and π: e = a ^ d, split for its rotation
+    let e: [(Lane<E::F>, Lane<E::F>); N_LANES] =
+        std::array::from_fn(|i| next_rotated_lane(eval, RHO[i], &mut split_lookups));
+    let mut b: [Lane<E::F>; N_LANES] =
+        std::array::from_fn(|_| std::array::from_fn(|_| E::F::zero()));
+    for (i, (_, rotated)) in e.iter().enumerate() {
+        b[PI[i]] = rotated.clone();
+    }
+    let e = e.map(|(lane, _)| lane);
+
+    // χ and ι
+    let not_and = std::array::from_fn(|_| next_lane(eval));
+    let chi = std::array::from_fn(|_| next_lane(eval));
+    let iota = next_lane(eval);
+    let rc = std::array::from_fn(|k| constant::<E>((RC[round] >> (8 * k)) & 0xff));
+
+    let lanes = RoundLanes {
+        a,
+        c,
+        rotl_c,
+        d,
+        e,
+        b,
+        not_and,
+        chi,
+        rc,
+        iota,
+    };
+    let minus_one = -E::EF::one();
+    for lookup in lanes.xor_lookups() {
+        eval.add_to_relation(RelationEntry::new(
+            &relations.xor.xor_8,
+            minus_one.clone(),
+            &lookup,
+        ));
+    }
+    for lookup in lanes.and_not_lookups() {
+        eval.add_to_relation(RelationEntry::new(
+            &relations.and_not.and_not_8,
+            minus_one.clone(),
+            &lookup,
+        ));
+    }
+    for lookup in split_lookups {
+        eval.add_to_relation(RelationEntry::new(
+            &relations.split.byte,
+            minus_one.clone(),
+            &lookup,
+        ));
+    }
+
+    lanes.output()
+}
+
+fn eval_permutation_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relations) {
+    let ChainColumnsOwned {
+        message_id,
+        block_index,
+        is_first,
+        is_last,
+        is_active,
+    } = ChainColumnsOwned::<<E as EvalAtRow>::F>::from_eval(eval);
+    let absorb = AbsorbLanes {
+        state_in: std::array::from_fn(|_| next_lane(eval)),
+        words: std::array::from_fn(|_| next_lane(eval)),
+        absorbed: std::array::from_fn(|_| next_lane(eval)),
+    };
+
+    let one = E::F::one();
+    for selector in [&is_first, &is_last, &is_active] {
+        eval.add_constraint(selector.clone() * (selector.clone() - one.clone()));
+    }
+    // Padding blocks are standalone, so that they emit no claim
+    eval.add_constraint((one.clone() - is_active.clone()) * (one.clone() - is_first.clone()));
+    eval.add_constraint((one.clone() - is_active.clone()) * (one.clone() - is_last.clone()));
+    // The first block of a message has index 0 and is absorbed into the zero state
+    eval.add_constraint(is_first.clone() * block_index.clone());
+    for byte in absorb.state_in.as_flattened() {
+        eval.add_constraint(is_first.clone() * byte.clone());
+    }
+
+    // Absorb the message words into the rate lanes
+    let minus_one = -E::EF::one();
+    for lookup in absorb.xor_lookups() {
+        eval.add_to_relation(RelationEntry::new(
+            &relations.xor.xor_8,
+            minus_one.clone(),
+            &lookup,
+        ));
+    }
+    let mut state: [Lane<E::F>; N_LANES] = std::array::from_fn(|i| {
+        if i < RATE_LANES {
+            absorb.absorbed[i].clone()
+        } else {
+            absorb.state_in[i].clone()
+        }
+    });
+
+    for round in 0..N_ROUNDS {
+        state = eval_round(eval, relations, state, round);
+    }
+
+    // Consume the input state, unless this is the first block of the message
+    let chain_in = [message_id.clone(), block_index.clone()]
+        .into_iter()
+        .chain(limbs::<E>(&absorb.state_in))
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.chain,
+        E::EF::from(is_first - one.clone()),
+        &chain_in,
+    ));
+
+    // Emit the output state, unless this is the last block of the message
+    let chain_out = [message_id.clone(), block_index.clone() + one.clone()]
+        .into_iter()
+        .chain(limbs::<E>(&state))
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.chain,
+        E::EF::from(one.clone() - is_last.clone()),
+        &chain_out,
+    ));
+
+    // Emit the public claims of the block, consumed by the verifier: its position in the message
+    // and message words, and the digest of its message if this is the last block
+    let message_claim = [message_id.clone(), block_index, is_last.clone()]
+        .into_iter()
+        .chain(limbs::<E>(&absorb.words))
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.message_claim,
+        E::EF::from(is_active.clone()),
+        &message_claim,
+    ));
+    let output_claim = [message_id]
+        .into_iter()
+        .chain(limbs::<E>(&state[..4]))
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.output_claim,
+        E::EF::from(is_last + is_active - one),
+        &output_claim,
+    ));
+
+    eval.finalize_logup_in_pairs();
+}
+
+#[derive(Clone)]
+pub struct Eval {
+    pub log_size: u32,
+    pub relations: Relations,
+}
+impl FrameworkEval for Eval {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        self.log_size() + 1
+    }
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        eval_permutation_constraints(&mut eval, &self.relations);
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use stwo::core::{pcs::TreeVec, poly::circle::CanonicCoset};
+    use stwo_constraint_framework::assert_constraints_on_polys;
+
+    use super::*;
+    use crate::{
+        components::permutation::witness::{gen_interaction_trace, gen_trace},
+        message::{gen_blocks, generate_blocks, Block},
+    };
+
+    fn assert_permutation_constraints(log_size: u32, blocks: &[Block]) {
+        let (trace, lookup_data) = gen_trace(log_size, blocks);
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) = gen_interaction_trace(&lookup_data, &relations);
+
+        let traces = TreeVec::new(vec![vec![], trace, interaction_trace]);
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(log_size),
+            |mut eval| {
+                eval_permutation_constraints(&mut eval, &relations);
+            },
+            claimed_sum,
+        );
+    }
+
+    #[test]
+    fn test_permutation_constraints() {
+        const LOG_N_ROWS: u32 = 4;
+
+        assert_permutation_constraints(LOG_N_ROWS, &generate_blocks(1 << LOG_N_ROWS));
+    }
+
+    #[test]
+    fn test_permutation_constraints_chained_blocks() {
+        const LOG_N_ROWS: u32 = 4;
+
+        // Messages of 3, 1, 1 and 2 blocks, and padding rows.
+        let messages = [vec![0x42; 300], b"abc".to_vec(), vec![], vec![0x43; 136]];
+        assert_permutation_constraints(LOG_N_ROWS, &gen_blocks(&messages));
+    }
+}
@@ -0,0 +1,265 @@
+use utils::trace_columns;
+
+use crate::{
+    keccak::{N_LANES, RATE_LANES, RHO},
+    partitions::{is_split, n_columns, THETA_ROTATION},
+};
+
+trace_columns!(
+    ChainColumns,
+    message_id,
+    block_index,
+    is_first,
+    is_last,
+    is_active
+);
+
+trace_columns!(
+    ChainInteractionColumns,
+    message_id,
+    block_index,
+    next_block_index,
+    is_first,
+    is_last,
+    is_active
+);
+
+/// A 64-bit lane, as bytes, low byte first.
+pub type Lane<T> = [T; 8];
+
+/// Number of trace columns of a round: the partial column parities of θ, the last one split for
+/// its rotation, the θ effect `d`, the lanes split for their ρ rotation, then χ and ι, see
+/// [`crate::partitions`].
+pub const N_ROUND_COLUMNS: usize =
+    5 * (3 * 8 + n_columns(THETA_ROTATION)) + 5 * 8 + n_rho_columns() + 2 * 8 * N_LANES + 8;
+
+/// Number of lookups of a round: the XORs, then the AND-NOTs of χ and the range checks of the
+/// pieces of the split lanes.
+pub const N_ROUND_LOOKUPS: usize = N_ROUND_XOR_LOOKUPS + 8 * N_LANES + N_ROUND_SPLIT_LOOKUPS;
+
+/// XORs of the column parities, of `d`, of θ, of χ and of ι.
+pub const N_ROUND_XOR_LOOKUPS: usize = 8 * (5 * 4 + 5 + 2 * N_LANES + 1);
+
+pub const N_ROUND_SPLIT_LOOKUPS: usize = 8 * (5 + n_split_lanes());
+
+const fn n_rho_columns() -> usize {
+    let mut n = 0;
+    let mut i = 0;
+    while i < N_LANES {
+        n += n_columns(RHO[i]);
+        i += 1;
+    }
+    n
+}
+
+const fn n_split_lanes() -> usize {
+    let mut n = 0;
+    let mut i = 0;
+    while i < N_LANES {
+        n += is_split(RHO[i]) as usize;
+        i += 1;
+    }
+    n
+}
+
+fn next_lanes<'a, T: 'a, const N: usize>(
+    lanes: &mut impl Iterator<Item = &'a [T; 8]>,
+) -> [Lane<&'a T>; N] {
+    std::array::from_fn(|_| lanes.next().unwrap().each_ref())
+}
+
+/// Lanes of the absorption of a block, in the lookup data.
+#[derive(Debug, Clone)]
+pub struct AbsorbLanes<T> {
+    pub state_in: [Lane<T>; N_LANES],
+    pub words: [Lane<T>; RATE_LANES],
+    pub absorbed: [Lane<T>; RATE_LANES],
+}
+
+impl AbsorbLanes<()> {
+    pub const SIZE: usize = 8 * (N_LANES + 2 * RATE_LANES);
+}
+
+impl<'a, T> AbsorbLanes<&'a T> {
+    pub fn from_slice(slice: &'a [T]) -> Self {
+        assert_eq!(slice.len(), AbsorbLanes::SIZE);
+        let lanes = &mut slice.array_chunks::<8>();
+        Self {
+            state_in: next_lanes(lanes),
+            words: next_lanes(lanes),
+            absorbed: next_lanes(lanes),
+        }
+    }
+}
+
+impl<T: Clone> AbsorbLanes<T> {
+    /// `[a, b, a ^ b]` of the byte XOR lookups of the rate lanes with the message words, in the
+    /// order of the AIR.
+    pub fn xor_lookups(&self) -> Vec<[T; 3]> {
+        let mut lookups = Vec::with_capacity(8 * RATE_LANES);
+        for (state, (word, absorbed)) in self
+            .state_in
+            .iter()
+            .zip(self.words.iter().zip(&self.absorbed))
+        {
+            for k in 0..8 {
+                lookups.push([state[k].clone(), word[k].clone(), absorbed[k].clone()]);
+            }
+        }
+        lookups
+    }
+}
+
+/// Lanes of a round, in the lookup data.
+///
+/// Only the trace columns of [`N_ROUND_COLUMNS`] are committed: the rotated lanes `rotl_c` and
+/// `b`, and the last partial parities and the lanes `e` when they are split, are linear
+/// combinations of pieces, and the round constant `rc` is known.
+#[derive(Debug, Clone)]
+pub struct RoundLanes<T> {
+    /// Input state.
+    pub a: [Lane<T>; N_LANES],
+    /// Partial parities of each column, `c[x][3]` being the parity of column `x`.
+    pub c: [[Lane<T>; 4]; 5],
+    /// Parities rotated by θ.
+    pub rotl_c: [Lane<T>; 5],
+    /// θ effect on each column.
+    pub d: [Lane<T>; 5],
+    /// Output state of θ.
+    pub e: [Lane<T>; N_LANES],
+    /// Output state of ρ and π.
+    pub b: [Lane<T>; N_LANES],
+    /// `!b[x + 1] & b[x + 2]` for each lane `x` of each row.
+    pub not_and: [Lane<T>; N_LANES],
+    /// Output state of χ.
+    pub chi: [Lane<T>; N_LANES],
+    /// Round constant.
+    pub rc: Lane<T>,
+    /// First lane of the output state, i.e. `chi[0] ^ rc`.
+    pub iota: Lane<T>,
+}
+
+impl RoundLanes<()> {
+    pub const SIZE: usize = 8 * (6 * N_LANES + 5 * 4 + 2 * 5 + 2);
+}
+
+impl<'a, T> RoundLanes<&'a T> {
+    pub fn from_slice(slice: &'a [T]) -> Self {
+        assert_eq!(slice.len(), RoundLanes::SIZE);
+        let lanes = &mut slice.array_chunks::<8>();
+        Self {
+            a: next_lanes(lanes),
+            c: std::array::from_fn(|_| next_lanes(lanes)),
+            rotl_c: next_lanes(lanes),
+            d: next_lanes(lanes),
+            e: next_lanes(lanes),
+            b: next_lanes(lanes),
+            not_and: next_lanes(lanes),
+            chi: next_lanes(lanes),
+            rc: lanes.next().unwrap().each_ref(),
+            iota: lanes.next().unwrap().each_ref(),
+        }
+    }
+}
+
+impl<T: Clone> RoundLanes<T> {
+    /// `[a, b, a ^ b]` of the byte XOR lookups, in the order of the AIR.
+    pub fn xor_lookups(&self) -> Vec<[T; 3]> {
+        let mut lookups = Vec::with_capacity(N_ROUND_XOR_LOOKUPS);
+        let mut push = |lhs: &Lane<T>, rhs: &Lane<T>, xor: &Lane<T>| {
+            for k in 0..8 {
+                lookups.push([lhs[k].clone(), rhs[k].clone(), xor[k].clone()]);
+            }
+        };
+
+        // θ: c[x][j] = c[x][j - 1] ^ a[x + 5j], then d[x] = c[x - 1] ^ (c[x + 1] <<< 1)
+        for (x, c) in self.c.iter().enumerate() {
+            push(&self.a[x], &self.a[x + 5], &c[0]);
+            for j in 1..4 {
+                push(&c[j - 1], &self.a[x + 5 * (j + 1)], &c[j]);
+            }
+        }
+        for (x, d) in self.d.iter().enumerate() {
+            push(&self.c[(x + 4) % 5][3], &self.rotl_c[(x + 1) % 5], d);
+        }
+        for (i, (a, e)) in self.a.iter().zip(&self.e).enumerate() {
+            push(a, &self.d[i % 5], e);
+        }
+
+        // χ and ι
+        for (b, (not_and, chi)) in self.b.iter().zip(self.not_and.iter().zip(&self.chi)) {
+            push(b, not_and, chi);
+        }
+        push(&self.chi[0], &self.rc, &self.iota);
+
+        debug_assert_eq!(lookups.len(), N_ROUND_XOR_LOOKUPS);
+        lookups
+    }
+
+    /// `[a, b, !a & b]` of the byte AND-NOT lookups of χ, in the order of the AIR.
+    pub fn and_not_lookups(&self) -> Vec<[T; 3]> {
+        let mut lookups = Vec::with_capacity(8 * N_LANES);
+        for (i, not_and) in self.not_and.iter().enumerate() {
+            let (x, y) = (i % 5, i - i % 5);
+            let [lhs, rhs] = [1, 2].map(|offset| &self.b[y + (x + offset) % 5]);
+            for k in 0..8 {
+                lookups.push([lhs[k].clone(), rhs[k].clone(), not_and[k].clone()]);
+            }
+        }
+        lookups
+    }
+
+    /// Rotation and byte of the range checks of the pieces of the split lanes, in the order of
+    /// the AIR: the parities rotated by θ, then the lanes rotated by ρ.
+    pub fn split_lookups(&self) -> Vec<(u32, T)> {
+        let parities = self.c.iter().map(|c| (THETA_ROTATION, &c[3]));
+        let lanes = RHO.into_iter().zip(&self.e).filter(|(r, _)| is_split(*r));
+        let lookups = parities
+            .chain(lanes)
+            .flat_map(|(r, lane)| lane.iter().map(move |byte| (r % 8, byte.clone())))
+            .collect::<Vec<_>>();
+        debug_assert_eq!(lookups.len(), N_ROUND_SPLIT_LOOKUPS);
+        lookups
+    }
+
+    /// Output state of the round, i.e. the output of χ with the first lane xored by ι.
+    pub fn output(&self) -> [Lane<T>; N_LANES] {
+        let mut output = self.chi.clone();
+        output[0] = self.iota.clone();
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_sizes() {
+        assert_eq!(N_ROUND_COLUMNS, 1024);
+        assert_eq!(RoundLanes::SIZE, 8 * 157);
+        assert_eq!(N_ROUND_XOR_LOOKUPS, 608);
+        assert_eq!(N_ROUND_SPLIT_LOOKUPS, 216);
+        // Lookups are added in pairs, round by round
+        assert_eq!(N_ROUND_LOOKUPS % 2, 0);
+        assert_eq!(AbsorbLanes::SIZE % 2, 0);
+    }
+
+    #[test]
+    fn test_lookups() {
+        let ids = (0..RoundLanes::SIZE).collect::<Vec<_>>();
+        let lanes = RoundLanes::from_slice(&ids);
+        assert_eq!(*lanes.iota[7], RoundLanes::SIZE - 1);
+
+        // The first XOR of d[0] is the byte of c[4][3]
+        let xors = lanes.xor_lookups();
+        assert_eq!(*xors[8 * 20][0], 8 * (N_LANES + 4 * 4 + 3));
+        // not_and[0] = !b[1] & b[2]
+        let and_nots = lanes.and_not_lookups();
+        assert_eq!(
+            and_nots[0].map(|id| *id),
+            [81, 82, 105].map(|lane| 8 * lane)
+        );
+        assert_eq!(lanes.split_lookups().len(), N_ROUND_SPLIT_LOOKUPS);
+    }
+}
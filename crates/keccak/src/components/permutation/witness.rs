@@ -0,0 +1,487 @@
+//! The permutation component is responsible for proving the Keccak-f[1600] permutation.
+//!
+//! Each row absorbs a block of message words into the rate lanes of the sponge state, then
+//! permutes the state with 24 rounds of θ, ρ, π, χ and ι. Blocks of the same message are chained
+//! through their sponge states.
+
+use std::simd::{u32x16, u64x16};
+
+use itertools::Itertools;
+use num_traits::One;
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        poly::circle::CanonicCoset,
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            column::BaseColumn,
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, consume_pair, simd::for_each_row_chunk, write_pair};
+
+use crate::{
+    components::permutation::columns::{
+        AbsorbLanes, ChainColumns, ChainInteractionColumns, Lane, RoundLanes, N_ROUND_COLUMNS,
+        N_ROUND_LOOKUPS,
+    },
+    keccak::{rotl_u64x16, N_LANES, N_ROUNDS, PI, RATE_LANES, RC, RHO},
+    message::{block_column, Block},
+    partitions::{bytes_u64x16, is_split, lane_u64x16, split_u64x16, THETA_ROTATION},
+    relations::Relations,
+};
+
+// Trace layout: chain columns, input state, message words, absorbed rate lanes and rounds.
+const STATE_IN_OFFSET: usize = ChainColumns::SIZE;
+const WORDS_OFFSET: usize = STATE_IN_OFFSET + 8 * N_LANES;
+const ABSORBED_OFFSET: usize = WORDS_OFFSET + 8 * RATE_LANES;
+const ROUNDS_OFFSET: usize = ABSORBED_OFFSET + 8 * RATE_LANES;
+const N_COLUMNS: usize = ROUNDS_OFFSET + N_ROUND_COLUMNS * N_ROUNDS;
+
+// Lookup data layout: chain columns, absorption and rounds, see [`AbsorbLanes`] and
+// [`RoundLanes`].
+pub const ABSORB_INTERACTION_OFFSET: usize = ChainInteractionColumns::SIZE;
+pub const ROUNDS_INTERACTION_OFFSET: usize = ABSORB_INTERACTION_OFFSET + AbsorbLanes::SIZE;
+const N_INTERACTION_COLUMNS: usize = ROUNDS_INTERACTION_OFFSET + RoundLanes::SIZE * N_ROUNDS;
+
+/// Evaluates the round `round` on the state `a` and returns the output state. The trace columns
+/// of the round are passed to `trace`, following the AIR, and its lookup data to `lookup_data`,
+/// following [`RoundLanes`].
+#[inline(always)]
+fn round_u64x16(
+    a: [u64x16; N_LANES],
+    round: usize,
+    trace: &mut impl FnMut(u32x16),
+    lookup_data: &mut impl FnMut(u32x16),
+) -> [u64x16; N_LANES] {
+    // θ
+    let c: [[u64x16; 4]; 5] = std::array::from_fn(|x| {
+        let c0 = a[x] ^ a[x + 5];
+        let c1 = c0 ^ a[x + 10];
+        let c2 = c1 ^ a[x + 15];
+        [c0, c1, c2, c2 ^ a[x + 20]]
+    });
+    let rotl_c: [u64x16; 5] = std::array::from_fn(|x| rotl_u64x16(c[x][3], THETA_ROTATION));
+    let d: [u64x16; 5] = std::array::from_fn(|x| c[(x + 4) % 5][3] ^ rotl_c[(x + 1) % 5]);
+    let e: [u64x16; N_LANES] = std::array::from_fn(|i| a[i] ^ d[i % 5]);
+
+    // ρ and π
+    let mut b = [u64x16::splat(0); N_LANES];
+    for (i, e) in e.iter().enumerate() {
+        b[PI[i]] = rotl_u64x16(*e, RHO[i]);
+    }
+
+    // χ and ι
+    let not_and: [u64x16; N_LANES] = std::array::from_fn(|i| {
+        let (x, y) = (i % 5, i - i % 5);
+        !b[y + (x + 1) % 5] & b[y + (x + 2) % 5]
+    });
+    let chi: [u64x16; N_LANES] = std::array::from_fn(|i| b[i] ^ not_and[i]);
+    let rc = u64x16::splat(RC[round]);
+    let iota = chi[0] ^ rc;
+
+    for c in &c {
+        for lane in &c[..3] {
+            bytes_u64x16(*lane).into_iter().for_each(&mut *trace);
+        }
+        split_u64x16(c[3], THETA_ROTATION)
+            .into_iter()
+            .flatten()
+            .for_each(&mut *trace);
+    }
+    for lane in &d {
+        bytes_u64x16(*lane).into_iter().for_each(&mut *trace);
+    }
+    for (lane, r) in e.iter().zip(RHO) {
+        if is_split(r) {
+            split_u64x16(*lane, r)
+                .into_iter()
+                .flatten()
+                .for_each(&mut *trace);
+        } else {
+            bytes_u64x16(*lane).into_iter().for_each(&mut *trace);
+        }
+    }
+    for lane in not_and.iter().chain(&chi).chain([&iota]) {
+        bytes_u64x16(*lane).into_iter().for_each(&mut *trace);
+    }
+
+    let lanes = a
+        .iter()
+        .chain(c.as_flattened())
+        .chain(&rotl_c)
+        .chain(&d)
+        .chain(&e)
+        .chain(&b)
+        .chain(&not_and)
+        .chain(&chi)
+        .chain([&rc, &iota]);
+    for lane in lanes {
+        bytes_u64x16(*lane).into_iter().for_each(&mut *lookup_data);
+    }
+
+    let mut output = chi;
+    output[0] = iota;
+    output
+}
+
+#[allow(clippy::type_complexity)]
+pub fn gen_trace(
+    log_size: u32,
+    blocks: &[Block],
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    Vec<Vec<u32x16>>,
+) {
+    assert!(log_size >= LOG_N_LANES);
+    assert!(blocks.len() <= 1 << log_size);
+    let simd_size = 1 << (log_size - LOG_N_LANES);
+
+    // Initialize vec for all groups of columns
+    let mut evals: Vec<Vec<u32x16>> = (0..N_COLUMNS)
+        .map(|_| vec![u32x16::splat(0); simd_size])
+        .collect::<Vec<_>>();
+    let mut lookup_data: Vec<Vec<u32x16>> = (0..N_INTERACTION_COLUMNS)
+        .map(|_| vec![u32x16::splat(0); simd_size])
+        .collect::<Vec<_>>();
+
+    // Chain columns
+    let message_id = block_column(blocks, simd_size, |block| block.message_id);
+    let block_index = block_column(blocks, simd_size, |block| block.index);
+    let next_block_index = block_column(blocks, simd_size, |block| block.index + 1);
+    let is_first = block_column(blocks, simd_size, |block| block.is_first() as u32);
+    let is_last = block_column(blocks, simd_size, |block| block.is_last as u32);
+    let is_active = (0..simd_size)
+        .map(|simd_row| {
+            u32x16::from_array(std::array::from_fn(|lane| {
+                (((simd_row << LOG_N_LANES) + lane) < blocks.len()) as u32
+            }))
+        })
+        .collect::<Vec<_>>();
+    let chain_values: ChainColumns<Vec<u32x16>> = ChainColumns {
+        message_id: &message_id,
+        block_index: &block_index,
+        is_first: &is_first,
+        is_last: &is_last,
+        is_active: &is_active,
+    };
+    for (i, value) in chain_values.iter().enumerate() {
+        evals[i] = value.clone();
+    }
+    let chain_interaction_values: ChainInteractionColumns<Vec<u32x16>> = ChainInteractionColumns {
+        message_id: &message_id,
+        block_index: &block_index,
+        next_block_index: &next_block_index,
+        is_first: &is_first,
+        is_last: &is_last,
+        is_active: &is_active,
+    };
+    for (i, value) in chain_interaction_values.iter().enumerate() {
+        lookup_data[i] = value.clone();
+    }
+
+    // Input state and message words, as bytes
+    let lane_byte = |lane: u64, k: usize| ((lane >> (8 * k)) & 0xff) as u32;
+    for i in 0..8 * N_LANES {
+        let state_in = block_column(blocks, simd_size, |block| {
+            lane_byte(block.state_in[i / 8], i % 8)
+        });
+        lookup_data[ABSORB_INTERACTION_OFFSET + i] = state_in.clone();
+        evals[STATE_IN_OFFSET + i] = state_in;
+    }
+    for i in 0..8 * RATE_LANES {
+        let words = block_column(blocks, simd_size, |block| {
+            lane_byte(block.words[i / 8], i % 8)
+        });
+        lookup_data[ABSORB_INTERACTION_OFFSET + 8 * N_LANES + i] = words.clone();
+        evals[WORDS_OFFSET + i] = words;
+    }
+
+    for_each_row_chunk(&mut evals, &mut lookup_data, |evals, lookup_data| {
+        for row in 0..evals[0].len() {
+            let read_lane = |offset: usize, i: usize| {
+                lane_u64x16(std::array::from_fn(|k| evals[offset + 8 * i + k][row]))
+            };
+            let words: [u64x16; RATE_LANES] = std::array::from_fn(|i| read_lane(WORDS_OFFSET, i));
+
+            // The state is kept in registers across the rounds
+            let mut state: [u64x16; N_LANES] =
+                std::array::from_fn(|i| read_lane(STATE_IN_OFFSET, i));
+            for (i, (lane, word)) in state.iter_mut().zip(words).enumerate() {
+                *lane ^= word;
+                for (k, byte) in bytes_u64x16(*lane).into_iter().enumerate() {
+                    evals[ABSORBED_OFFSET + 8 * i + k][row] = byte;
+                    let index = ABSORB_INTERACTION_OFFSET + 8 * (N_LANES + RATE_LANES + i) + k;
+                    lookup_data[index][row] = byte;
+                }
+            }
+
+            let mut column = ROUNDS_OFFSET;
+            let mut write_trace = |value: u32x16| {
+                evals[column][row] = value;
+                column += 1;
+            };
+            let mut interaction_column = ROUNDS_INTERACTION_OFFSET;
+            let mut write_lookup_data = |value: u32x16| {
+                lookup_data[interaction_column][row] = value;
+                interaction_column += 1;
+            };
+            for round in 0..N_ROUNDS {
+                state = round_u64x16(state, round, &mut write_trace, &mut write_lookup_data);
+            }
+            debug_assert_eq!(column, N_COLUMNS);
+            debug_assert_eq!(interaction_column, N_INTERACTION_COLUMNS);
+        }
+    });
+
+    let domain = CanonicCoset::new(log_size).circle_domain();
+    let trace = evals
+        .into_iter()
+        .map(|eval| {
+            let column = BaseColumn::from_simd(
+                eval.into_iter()
+                    .map(|x| unsafe { PackedM31::from_simd_unchecked(x) })
+                    .collect(),
+            );
+            CircleEvaluation::<SimdBackend, BaseField, BitReversedOrder>::new(domain, column)
+        })
+        .collect::<Vec<_>>();
+
+    (trace, lookup_data)
+}
+
+/// 16-bit limbs of `lanes`, as the combination of pairs of bytes.
+fn limbs(lanes: &[Lane<&Vec<u32x16>>]) -> Vec<Vec<u32x16>> {
+    lanes
+        .iter()
+        .flat_map(|lane| {
+            (0..4).map(|k| {
+                lane[2 * k]
+                    .iter()
+                    .zip(lane[2 * k + 1])
+                    .map(|(low, high)| *low + (*high << 8))
+                    .collect()
+            })
+        })
+        .collect()
+}
+
+pub fn gen_interaction_trace(
+    lookup_data: &[Vec<u32x16>],
+    relations: &Relations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let simd_size = lookup_data[0].len();
+    let mut interaction_trace = LogupTraceGenerator::new(simd_size.ilog2() + LOG_N_LANES);
+
+    // Look up the XORs of the absorption
+    let absorb =
+        AbsorbLanes::from_slice(&lookup_data[ABSORB_INTERACTION_OFFSET..ROUNDS_INTERACTION_OFFSET]);
+    let denoms = absorb
+        .xor_lookups()
+        .into_iter()
+        .map(|columns| combine!(relations.xor.xor_8, columns));
+    for (d_0, d_1) in denoms.tuples() {
+        consume_pair!(d_0.iter().copied(), d_1.iter().copied(), interaction_trace);
+    }
+
+    // Look up the XORs and AND-NOTs of each round, and range check its pieces. The denominators
+    // are combined lazily, a pair at a time.
+    let rounds = lookup_data[ROUNDS_INTERACTION_OFFSET..N_INTERACTION_COLUMNS]
+        .chunks_exact(RoundLanes::SIZE)
+        .map(RoundLanes::from_slice)
+        .collect::<Vec<_>>();
+    for lanes in &rounds {
+        let xors = lanes
+            .xor_lookups()
+            .into_iter()
+            .map(|columns| combine!(relations.xor.xor_8, columns));
+        let and_nots = lanes
+            .and_not_lookups()
+            .into_iter()
+            .map(|columns| combine!(relations.and_not.and_not_8, columns));
+        let splits = lanes.split_lookups().into_iter().map(|(shift, byte)| {
+            let width = 8 - shift;
+            let shift = vec![u32x16::splat(shift); simd_size];
+            let low = byte
+                .iter()
+                .map(|byte| *byte & u32x16::splat((1 << width) - 1))
+                .collect::<Vec<_>>();
+            let high = byte.iter().map(|byte| *byte >> width).collect::<Vec<_>>();
+            combine!(relations.split.byte, [&shift, &low, &high])
+        });
+        let denoms = xors.chain(and_nots).chain(splits);
+        let mut n_lookups = 0;
+        for (d_0, d_1) in denoms.tuples() {
+            consume_pair!(d_0.iter().copied(), d_1.iter().copied(), interaction_trace);
+            n_lookups += 2;
+        }
+        debug_assert_eq!(n_lookups, N_ROUND_LOOKUPS);
+    }
+
+    let ChainInteractionColumns {
+        message_id,
+        block_index,
+        next_block_index,
+        is_first,
+        is_last,
+        is_active,
+    } = ChainInteractionColumns::from_slice(&lookup_data[..ABSORB_INTERACTION_OFFSET]);
+    let state_in = limbs(&absorb.state_in);
+    let words = limbs(&absorb.words);
+    let state_out = limbs(&rounds[N_ROUNDS - 1].output());
+
+    // Consume the input state and emit the output one
+    let chain_in = combine!(
+        relations.chain,
+        [message_id, block_index]
+            .into_iter()
+            .chain(&state_in)
+            .collect::<Vec<_>>()
+    );
+    let chain_out = combine!(
+        relations.chain,
+        [message_id, next_block_index]
+            .into_iter()
+            .chain(&state_out)
+            .collect::<Vec<_>>()
+    );
+    let one = PackedQM31::one();
+    write_pair!(
+        is_first.iter().map(|v| to_packed_qm31(*v) - one),
+        chain_in,
+        is_last.iter().map(|v| one - to_packed_qm31(*v)),
+        chain_out,
+        interaction_trace
+    );
+
+    // Emit the message and output claims
+    let message_claim = combine!(
+        relations.message_claim,
+        [message_id, block_index, is_last]
+            .into_iter()
+            .chain(&words)
+            .collect::<Vec<_>>()
+    );
+    let output_claim = combine!(
+        relations.output_claim,
+        [message_id]
+            .into_iter()
+            .chain(&state_out[..4 * 4])
+            .collect::<Vec<_>>()
+    );
+    write_pair!(
+        is_active.iter().map(|v| to_packed_qm31(*v)),
+        message_claim,
+        is_last
+            .iter()
+            .zip(is_active)
+            .map(|(l, a)| to_packed_qm31(*l) + to_packed_qm31(*a) - one),
+        output_claim,
+        interaction_trace
+    );
+
+    interaction_trace.finalize_last()
+}
+
+#[inline(always)]
+fn to_packed_qm31(value: u32x16) -> PackedQM31 {
+    PackedQM31::from(unsafe { PackedM31::from_simd_unchecked(value) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        keccak::keccak256,
+        message::{digests, gen_blocks, generate_blocks, to_bytes},
+    };
+
+    fn to_u32x16(
+        trace: &[CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>],
+    ) -> Vec<Vec<u32x16>> {
+        trace
+            .iter()
+            .map(|eval| {
+                eval.data
+                    .clone()
+                    .into_iter()
+                    .map(|x| x.into_simd())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Output state of `row`, read from the lookup data of the last round.
+    fn state_out(lookup_data: &[Vec<u32x16>], row: usize) -> [u64; N_LANES] {
+        let lanes =
+            RoundLanes::from_slice(&lookup_data[N_INTERACTION_COLUMNS - RoundLanes::SIZE..]);
+        lanes.output().map(|lane| {
+            lane.iter().enumerate().fold(0, |acc, (k, byte)| {
+                acc | ((byte[row / 16][row % 16] as u64) << (8 * k))
+            })
+        })
+    }
+
+    #[test]
+    fn test_gen_trace_columns_count() {
+        let (trace, lookup_data) = gen_trace(4, &generate_blocks(16));
+        assert_eq!(trace.len(), N_COLUMNS);
+        assert_eq!(lookup_data.len(), N_INTERACTION_COLUMNS);
+    }
+
+    #[test]
+    fn test_gen_trace_values() {
+        let blocks = generate_blocks(16);
+        let (trace, lookup_data) = gen_trace(4, &blocks);
+        let trace = to_u32x16(&trace);
+
+        for (row, block) in blocks.iter().enumerate() {
+            assert_eq!(state_out(&lookup_data, row), block.state_out());
+            // The message words are committed as bytes
+            for i in 0..8 * RATE_LANES {
+                assert_eq!(
+                    trace[WORDS_OFFSET + i][row / 16][row % 16] as u64,
+                    (block.words[i / 8] >> (8 * (i % 8))) & 0xff
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_gen_trace_chained_blocks() {
+        let messages = [vec![0x42; 300], b"abc".to_vec()];
+        let blocks = gen_blocks(&messages);
+        let (_, lookup_data) = gen_trace(4, &blocks);
+
+        for (row, (block, next)) in blocks.iter().zip(&blocks[1..]).enumerate() {
+            if !block.is_last {
+                assert_eq!(state_out(&lookup_data, row), next.state_in);
+            }
+        }
+
+        let last_rows = blocks.iter().positions(|block| block.is_last);
+        for ((row, digest), message) in last_rows.zip(digests(&blocks)).zip(&messages) {
+            assert_eq!(to_bytes(&state_out(&lookup_data, row)), digest);
+            assert_eq!(digest, keccak256(message));
+        }
+    }
+
+    #[test]
+    fn test_gen_trace_padding_rows() {
+        // Rows past the blocks permute the padding block, i.e. hash the empty message.
+        let (trace, lookup_data) = gen_trace(4, &generate_blocks(3));
+        let trace = to_u32x16(&trace);
+        assert_eq!(trace[4][0][3], 0);
+        assert_eq!(to_bytes(&state_out(&lookup_data, 3)), keccak256(b""));
+    }
+}
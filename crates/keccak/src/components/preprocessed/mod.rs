@@ -0,0 +1,13 @@
+mod and_not;
+mod split;
+mod xor;
+
+use utils::components;
+
+components!(
+    relations: crate::relations::Relations,
+    lookup_data: (permutation_lookup_data: &[Vec<u32x16>]),
+    xor::xor,
+    and_not::and_not,
+    split::split,
+);
@@ -0,0 +1,113 @@
+use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval};
+use utils::add_to_relation;
+
+use crate::{
+    components::preprocessed::split::columns::ComponentColumnsOwned as ComponentColumns,
+    preprocessed::split::{SplitColumnsOwned as SplitColumns, LOG_SIZE},
+    relations::Relations,
+};
+
+pub type Component = FrameworkComponent<Eval>;
+
+fn eval_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relations, log_size: u32) {
+    let chunk_count = 1 << LOG_SIZE.saturating_sub(log_size);
+    for chunk in 0..chunk_count {
+        let ComponentColumns { byte_mult } =
+            ComponentColumns::<<E as EvalAtRow>::F>::from_eval(eval);
+        let SplitColumns { shift, low, high } =
+            SplitColumns::<<E as EvalAtRow>::F>::from_ids(eval, Some(chunk));
+        add_to_relation!(
+            eval,
+            relations.split.byte,
+            E::EF::from(byte_mult),
+            shift,
+            low,
+            high
+        );
+    }
+    eval.finalize_logup_in_pairs();
+}
+
+#[derive(Clone)]
+pub struct Eval {
+    pub log_size: u32,
+    pub relations: Relations,
+}
+impl FrameworkEval for Eval {
+    fn log_size(&self) -> u32 {
+        LOG_SIZE.min(self.log_size)
+    }
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        LOG_SIZE.min(self.log_size) + 1
+    }
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        eval_constraints(&mut eval, &self.relations, self.log_size);
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use stwo::{
+        core::{pcs::TreeVec, poly::circle::CanonicCoset},
+        prover::backend::simd::m31::LOG_N_LANES,
+    };
+    use stwo_constraint_framework::assert_constraints_on_polys;
+    use utils::circle_evaluation_u32x16;
+
+    use super::*;
+    use crate::{
+        components::{
+            permutation::witness::gen_trace as gen_permutation_trace,
+            preprocessed::split::witness::{gen_interaction_trace, gen_trace},
+        },
+        message::generate_blocks,
+        preprocessed::split::{self, SplitColumns as SplitColumnsBorrowed},
+    };
+
+    #[test_log::test]
+    fn test_constraints() {
+        const LOG_N_ROWS: u32 = 4;
+
+        // Trace.
+        let blocks = generate_blocks(1 << LOG_N_ROWS);
+        let (_, permutation_lookup_data) = gen_permutation_trace(LOG_N_ROWS, &blocks);
+        let max_log_size = 10;
+        let trace = gen_trace(max_log_size, &permutation_lookup_data);
+
+        let simd_size = trace[0].len().ilog2();
+        let log_size = simd_size + LOG_N_LANES;
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) = gen_interaction_trace(&trace, &relations);
+
+        let split_cols = split::gen_column_simd();
+        let preprocessed_trace = SplitColumnsBorrowed::from_slice(&split_cols)
+            .chunks((1 << simd_size) as usize)
+            .into_iter()
+            .flat_map(|c| c.iter().map(|c| circle_evaluation_u32x16!(c)))
+            .collect::<Vec<_>>();
+
+        let traces = TreeVec::new(vec![
+            preprocessed_trace,
+            trace
+                .into_iter()
+                .map(|c| circle_evaluation_u32x16!(c))
+                .collect::<Vec<_>>(),
+            interaction_trace,
+        ]);
+
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(log_size),
+            |mut eval| {
+                eval_constraints(&mut eval, &relations, log_size);
+            },
+            claimed_sum,
+        );
+    }
+}
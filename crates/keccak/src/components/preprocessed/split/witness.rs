@@ -0,0 +1,93 @@
+use std::simd::u32x16;
+
+use itertools::Itertools;
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, histogram::par_histograms, write_col, write_pair};
+
+use crate::{
+    components::permutation::{columns::RoundLanes, witness::ROUNDS_INTERACTION_OFFSET},
+    keccak::N_ROUNDS,
+    preprocessed::split::{self, SplitColumns, LOG_SIZE},
+    relations::Relations,
+};
+
+pub fn gen_trace(log_size: u32, permutation_lookup_data: &[Vec<u32x16>]) -> Vec<Vec<u32x16>> {
+    // One task per round
+    let [byte_mult] = par_histograms([LOG_SIZE], N_ROUNDS, |round, [byte_mult]| {
+        let start = ROUNDS_INTERACTION_OFFSET + round * RoundLanes::SIZE;
+        let end = start + RoundLanes::SIZE;
+
+        let lanes = RoundLanes::from_slice(&permutation_lookup_data[start..end]);
+        for (shift, byte) in lanes.split_lookups() {
+            let shift = u32x16::splat(shift << 8);
+            byte.iter().for_each(|byte| byte_mult.add(shift + *byte));
+        }
+    });
+
+    byte_mult.into_columns(log_size)
+}
+
+pub fn gen_interaction_trace(
+    trace: &[Vec<u32x16>],
+    relations: &Relations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let preprocessed_columns = split::gen_column_simd();
+    let SplitColumns { shift, low, high } = SplitColumns::from_slice(&preprocessed_columns[..]);
+
+    let simd_size = trace[0].len();
+    let log_size = simd_size.ilog2() + LOG_N_LANES;
+    let mut interaction_trace = LogupTraceGenerator::new(log_size);
+
+    let byte_den = combine!(relations.split.byte, [&shift, &low, &high]);
+
+    for ([byte_mult_0, byte_mult_1], (byte_den_0, byte_den_1)) in trace
+        .array_chunks::<2>()
+        .zip(byte_den.chunks(simd_size).tuples())
+    {
+        write_pair!(
+            byte_mult_0
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            byte_den_0.to_vec(),
+            byte_mult_1
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            byte_den_1.to_vec(),
+            interaction_trace
+        );
+    }
+
+    if trace.len() % 2 == 1 {
+        let byte_mult = trace.last().unwrap();
+        let byte_den_chunk = byte_den.chunks(simd_size).last().unwrap();
+        write_col!(
+            byte_mult
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            byte_den_chunk.to_vec(),
+            interaction_trace
+        );
+    }
+
+    interaction_trace.finalize_last()
+}
@@ -0,0 +1,3 @@
+use utils::trace_columns;
+
+trace_columns!(ComponentColumns, xor_8_mult);
@@ -0,0 +1,9 @@
+pub mod air;
+pub mod columns;
+pub mod witness;
+
+// Re-export inside a new namespace to comply with the components! macro
+#[allow(clippy::module_inception)]
+pub mod xor {
+    pub use super::{air, witness};
+}
@@ -0,0 +1,100 @@
+use std::simd::u32x16;
+
+use itertools::{izip, Itertools};
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, histogram::par_histograms, write_col, write_pair};
+
+use crate::{
+    components::permutation::{
+        columns::{AbsorbLanes, RoundLanes},
+        witness::{ABSORB_INTERACTION_OFFSET, ROUNDS_INTERACTION_OFFSET},
+    },
+    keccak::N_ROUNDS,
+    preprocessed::xor::{self, XorColumns, LOG_SIZE},
+    relations::Relations,
+};
+
+pub fn gen_trace(log_size: u32, permutation_lookup_data: &[Vec<u32x16>]) -> Vec<Vec<u32x16>> {
+    // One task per round and one for the absorption
+    let [xor_8_mult] = par_histograms([LOG_SIZE], N_ROUNDS + 1, |task, [xor_8_mult]| {
+        let lookups = if task < N_ROUNDS {
+            let start = ROUNDS_INTERACTION_OFFSET + task * RoundLanes::SIZE;
+            let end = start + RoundLanes::SIZE;
+            RoundLanes::from_slice(&permutation_lookup_data[start..end]).xor_lookups()
+        } else {
+            let absorb =
+                &permutation_lookup_data[ABSORB_INTERACTION_OFFSET..ROUNDS_INTERACTION_OFFSET];
+            AbsorbLanes::from_slice(absorb).xor_lookups()
+        };
+        for [a, b, _] in lookups {
+            izip!(a, b).for_each(|(a, b)| xor_8_mult.add((*a << 8) + *b));
+        }
+    });
+
+    xor_8_mult.into_columns(log_size)
+}
+
+pub fn gen_interaction_trace(
+    trace: &[Vec<u32x16>],
+    relations: &Relations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let preprocessed_columns = xor::gen_column_simd();
+    let XorColumns { a, b, c } = XorColumns::from_slice(&preprocessed_columns[..]);
+
+    let simd_size = trace[0].len();
+    let log_size = simd_size.ilog2() + LOG_N_LANES;
+    let mut interaction_trace = LogupTraceGenerator::new(log_size);
+
+    let xor_8_den = combine!(relations.xor.xor_8, [&a, &b, &c]);
+
+    for ([xor_8_mult_0, xor_8_mult_1], (xor_8_den_0, xor_8_den_1)) in trace
+        .array_chunks::<2>()
+        .zip(xor_8_den.chunks(simd_size).tuples())
+    {
+        write_pair!(
+            xor_8_mult_0
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            xor_8_den_0.to_vec(),
+            xor_8_mult_1
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            xor_8_den_1.to_vec(),
+            interaction_trace
+        );
+    }
+
+    if trace.len() % 2 == 1 {
+        let xor_8_mult = trace.last().unwrap();
+        let xor_8_den_chunk = xor_8_den.chunks(simd_size).last().unwrap();
+        write_col!(
+            xor_8_mult
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            xor_8_den_chunk.to_vec(),
+            interaction_trace
+        );
+    }
+
+    interaction_trace.finalize_last()
+}
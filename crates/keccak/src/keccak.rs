@@ -0,0 +1,242 @@
+//! Keccak-f[1600] permutation and the Keccak-256 and SHA3-256 sponges, Keccak-256 being the hash
+//! function of Ethereum.
+//!
+//! Keccak-256 only differs from SHA3-256 by its padding: the message is followed by the byte
+//! `0x01` instead of `0x06`, see [`HashFunction`].
+
+use core::simd::Simd;
+use std::simd::u64x16;
+
+/// Number of 64-bit lanes of the state, lane `x + 5 * y` being at column `x` and row `y`.
+pub const N_LANES: usize = 25;
+
+pub const N_ROUNDS: usize = 24;
+
+/// Bytes absorbed by each permutation, i.e. the 1088-bit rate of a 256-bit digest.
+pub const RATE: usize = 136;
+
+pub const RATE_LANES: usize = RATE / 8;
+
+pub const RATE_SIZE: usize = 68; // 68 u16 = 17 u64
+
+pub const DIGEST_SIZE: usize = 16; // 16 u16 = 4 u64
+
+/// Hash functions built on the Keccak-f[1600] sponge with a 1088-bit rate, which only differ by
+/// their first padding byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HashFunction {
+    #[default]
+    Keccak256,
+    Sha3_256,
+}
+
+impl HashFunction {
+    /// First padding byte: the SHA-3 domain separation bits `01`, if any, followed by the first
+    /// bit of pad10*1.
+    pub const fn domain(self) -> u8 {
+        match self {
+            Self::Keccak256 => 0x01,
+            Self::Sha3_256 => 0x06,
+        }
+    }
+}
+
+/// Round constants, xored into lane 0 by ι.
+pub const RC: [u64; N_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Left rotation of each lane by ρ.
+pub const RHO: [u32; N_LANES] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Destination of each lane by π: lane `(x, y)` moves to `(y, 2x + 3y)`.
+pub const PI: [usize; N_LANES] = pi();
+
+const fn pi() -> [usize; N_LANES] {
+    let mut pi = [0; N_LANES];
+    let mut i = 0;
+    while i < N_LANES {
+        let (x, y) = (i % 5, i / 5);
+        pi[i] = y + 5 * ((2 * x + 3 * y) % 5);
+        i += 1;
+    }
+    pi
+}
+
+#[inline(always)]
+pub fn rotl_u64x16(x: u64x16, n: u32) -> u64x16 {
+    if n == 0 {
+        return x;
+    }
+    (x << Simd::splat(n as u64)) | (x >> Simd::splat(64 - n as u64))
+}
+
+/// One round of the permutation: θ, ρ, π, χ and ι with the round constant `rc`.
+pub fn round(a: [u64; N_LANES], rc: u64) -> [u64; N_LANES] {
+    // θ
+    let c: [u64; 5] = std::array::from_fn(|x| (0..5).fold(0, |acc, y| acc ^ a[x + 5 * y]));
+    let d: [u64; 5] = std::array::from_fn(|x| c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1));
+
+    // ρ and π
+    let mut b = [0; N_LANES];
+    for i in 0..N_LANES {
+        b[PI[i]] = (a[i] ^ d[i % 5]).rotate_left(RHO[i]);
+    }
+
+    // χ and ι
+    let mut out: [u64; N_LANES] = std::array::from_fn(|i| {
+        let (x, y) = (i % 5, i - i % 5);
+        b[i] ^ (!b[y + (x + 1) % 5] & b[y + (x + 2) % 5])
+    });
+    out[0] ^= rc;
+    out
+}
+
+pub fn keccak_f(state: &mut [u64; N_LANES]) {
+    for rc in RC {
+        *state = round(*state, rc);
+    }
+}
+
+/// Keccak-256 digest of `message`.
+pub fn keccak256(message: &[u8]) -> [u8; 32] {
+    digest(HashFunction::Keccak256, message)
+}
+
+/// SHA3-256 digest of `message`.
+pub fn sha3_256(message: &[u8]) -> [u8; 32] {
+    digest(HashFunction::Sha3_256, message)
+}
+
+/// Digest of `message` for `hash_function`.
+pub fn digest(hash_function: HashFunction, message: &[u8]) -> [u8; 32] {
+    let blocks = crate::message::gen_blocks_for(hash_function, &[message]);
+    crate::message::digests(&blocks)[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak256() {
+        for (message, digest) in [
+            (
+                &b""[..],
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            ),
+            (
+                b"abc",
+                "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+            ),
+            (
+                b"The quick brown fox jumps over the lazy dog",
+                "4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15",
+            ),
+        ] {
+            assert_eq!(hex::encode(keccak256(message)), digest);
+        }
+    }
+
+    #[test]
+    fn test_keccak256_multi_block() {
+        // Around the rate, where the padding takes a block of its own, and several blocks
+        for (len, digest) in [
+            (
+                135,
+                "cbdfd9dee5faad3818d6b06f95a219fd290b0e1706f6a82e5a595b9ce9faca62",
+            ),
+            (
+                136,
+                "7ce759f1ab7f9ce437719970c26b0a66ff11fe3e38e17df89cf5d29c7d7f807e",
+            ),
+            (
+                137,
+                "ac73d4fae68b8453f764007c1a20ce95994187861f0c3227a3a8e99a73a3b1db",
+            ),
+            (
+                1000,
+                "af692982e84a5a9688359025660a7857cd28ee7c8d867cfa1677baf2e6d1f63b",
+            ),
+        ] {
+            let message = (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+            assert_eq!(hex::encode(keccak256(&message)), digest);
+        }
+    }
+
+    #[test]
+    fn test_sha3_256() {
+        // NIST examples, the last one spanning two blocks
+        for (message, digest) in [
+            (
+                vec![],
+                "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            ),
+            (
+                b"abc".to_vec(),
+                "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            ),
+            (
+                vec![0xa3; 200],
+                "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787",
+            ),
+        ] {
+            assert_eq!(hex::encode(sha3_256(&message)), digest);
+        }
+    }
+
+    #[test]
+    fn test_keccak_f() {
+        // First lane of the permutation of the zero state
+        let mut state = [0; N_LANES];
+        keccak_f(&mut state);
+        assert_eq!(state[0], 0xf1258f7940e1dde7);
+    }
+
+    #[test]
+    fn test_pi() {
+        assert_eq!(PI[0], 0);
+        assert_eq!(PI[1], 10);
+        assert_eq!(PI[5], 16);
+        let mut sorted = PI;
+        sorted.sort();
+        assert_eq!(sorted, std::array::from_fn(|i| i));
+    }
+
+    #[test]
+    fn test_rotl_u64x16() {
+        let base: [u64; 16] =
+            std::array::from_fn(|i| 0x9e3779b97f4a7c15_u64.wrapping_mul(i as u64));
+        for n in RHO {
+            assert_eq!(
+                base.map(|x| x.rotate_left(n)),
+                rotl_u64x16(u64x16::from_array(base), n).to_array()
+            );
+        }
+    }
+}
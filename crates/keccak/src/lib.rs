@@ -0,0 +1,267 @@
+#![allow(non_camel_case_types)]
+#![feature(portable_simd, array_chunks, iter_array_chunks)]
+pub mod components;
+pub mod keccak;
+pub mod message;
+pub mod partitions;
+pub mod preprocessed;
+pub mod prover;
+pub mod public;
+pub mod relations;
+pub mod verifier;
+
+use stwo::{
+    core::{channel::MerkleChannel, pcs::PcsConfig, proof::StarkProof},
+    prover::backend::{simd::SimdBackend, BackendForChannel},
+};
+use utils::stwo::air::ProveError;
+
+use crate::{
+    components::ClaimedSum,
+    message::{gen_blocks, gen_blocks_for},
+};
+pub use crate::{
+    keccak::HashFunction,
+    prover::KeccakProver,
+    public::HashClaim,
+    verifier::{
+        preprocessed_root, verify_keccak256, verify_sha3_256, verify_with, KeccakVerifierError,
+    },
+};
+
+/// Largest supported trace log size: each row does 14728 lookups into the 8-bit XOR table, and
+/// the multiplicities must fit in M31.
+pub const MAX_LOG_SIZE: u32 = 17;
+
+/// Errors of the Keccak prover.
+pub type KeccakProverError = ProveError<ClaimedSum>;
+
+/// Proves the Keccak-256 digest of each message.
+///
+/// Each message is padded and split into blocks of the rate, see [`message::gen_blocks`], and
+/// each block is proven in its own row: the absorption of its message words followed by the
+/// Keccak-f[1600] permutation. Blocks of the same message are linked through the chain relation.
+/// The batch is padded with the single block of the empty message, which emits no public claim:
+/// each block claims its position in its message and its message words and each message its
+/// digest, see [`public`]. Verify with [`verify_keccak256`].
+pub fn prove_keccak256<MC: MerkleChannel, M: AsRef<[u8]>>(
+    messages: &[M],
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), KeccakProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    KeccakProver::<MC>::new(log_size, config)?.prove(&gen_blocks(messages))
+}
+
+/// Same as [`prove_keccak256`] for SHA3-256, which only changes the padding of the messages, see
+/// [`HashFunction`]. Verify with [`verify_sha3_256`].
+pub fn prove_sha3_256<MC: MerkleChannel, M: AsRef<[u8]>>(
+    messages: &[M],
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), KeccakProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    KeccakProver::<MC>::new(log_size, config)?
+        .prove(&gen_blocks_for(HashFunction::Sha3_256, messages))
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::Zero;
+    use stwo::{
+        core::{fields::qm31::SecureField, vcs::blake2_merkle::Blake2sMerkleChannel},
+        prover::backend::simd::m31::LOG_N_LANES,
+    };
+
+    use super::*;
+    use crate::{
+        keccak::{keccak256, sha3_256},
+        message::generate_blocks,
+    };
+
+    fn prove_messages(
+        messages: &[Vec<u8>],
+        log_size: u32,
+        config: PcsConfig,
+    ) -> (
+        StarkProof<<Blake2sMerkleChannel as MerkleChannel>::H>,
+        ClaimedSum,
+        Vec<HashClaim>,
+    ) {
+        let (proof, claimed_sum) =
+            prove_keccak256::<Blake2sMerkleChannel, _>(messages, log_size, config).unwrap();
+        let claims = messages
+            .iter()
+            .map(|message| HashClaim::new(message.clone()))
+            .collect();
+        (proof, claimed_sum, claims)
+    }
+
+    #[test_log::test]
+    fn test_verify_keccak256() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![vec![0xab; 300], b"abc".to_vec(), vec![], vec![0x42; 136]];
+        let (proof, claimed_sum, claims) = prove_messages(&messages, log_size, config);
+        for (message, claim) in messages.iter().zip(&claims) {
+            assert_eq!(claim.digest, keccak256(message));
+        }
+        assert_eq!(
+            hex::encode(claims[1].digest),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+        verify_keccak256::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_sha3_256() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec(), vec![0xa3; 200]];
+        let (proof, claimed_sum) =
+            prove_sha3_256::<Blake2sMerkleChannel, _>(&messages, log_size, config).unwrap();
+        let claims = messages
+            .iter()
+            .map(|message| HashClaim::with_hash_function(HashFunction::Sha3_256, message.clone()))
+            .collect::<Vec<_>>();
+        for (message, claim) in messages.iter().zip(&claims) {
+            assert_eq!(claim.digest, sha3_256(message));
+        }
+        assert_eq!(
+            hex::encode(claims[0].digest),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+
+        // The same proof does not attest the Keccak-256 padding.
+        assert!(verify_keccak256::<Blake2sMerkleChannel>(
+            proof.clone(),
+            log_size,
+            config,
+            &claimed_sum,
+            &claims
+        )
+        .is_err());
+        verify_sha3_256::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_keccak256_wrong_digest() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![vec![0xab; 300], b"abc".to_vec()];
+        let (proof, claimed_sum, mut claims) = prove_messages(&messages, log_size, config);
+        claims[1].digest[31] ^= 1;
+        assert!(verify_keccak256::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            &claims
+        )
+        .is_err());
+    }
+
+    #[test_log::test]
+    fn test_verify_keccak256_wrong_message() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec()];
+        let (proof, claimed_sum, _) = prove_messages(&messages, log_size, config);
+        let claims = [HashClaim {
+            message: b"abd".to_vec(),
+            digest: HashClaim::new(b"abc".to_vec()).digest,
+        }];
+        assert!(verify_keccak256::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            &claims
+        )
+        .is_err());
+    }
+
+    #[test_log::test]
+    fn test_verify_keccak256_missing_claim() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec(), b"def".to_vec()];
+        let (proof, claimed_sum, claims) = prove_messages(&messages, log_size, config);
+        assert!(verify_keccak256::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            &claims[..1]
+        )
+        .is_err());
+    }
+
+    #[test_log::test]
+    fn test_prove_keccak256_tampered_lane() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let mut blocks = gen_blocks(&[vec![0xab; 300]]);
+        blocks[1].state_in[7] ^= 1;
+        let err = KeccakProver::<Blake2sMerkleChannel>::new(log_size, config)
+            .unwrap()
+            .prove(&blocks)
+            .unwrap_err();
+        let KeccakProverError::UnbalancedLogup { sum, claimed_sum } = err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_ne!(sum, SecureField::zero());
+        assert_ne!(claimed_sum.permutation, SecureField::zero());
+    }
+
+    #[test]
+    fn test_prove_keccak256_invalid_log_size() {
+        let messages = [b"abc".to_vec()];
+        for log_size in [0, LOG_N_LANES - 1, MAX_LOG_SIZE + 1] {
+            assert!(matches!(
+                prove_keccak256::<Blake2sMerkleChannel, _>(
+                    &messages,
+                    log_size,
+                    PcsConfig::default()
+                ),
+                Err(KeccakProverError::InvalidLogSize { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_prove_keccak256_too_many_blocks() {
+        let log_size = LOG_N_LANES;
+        let blocks = generate_blocks((1 << log_size) + 1);
+        let prover = KeccakProver::<Blake2sMerkleChannel>::new(log_size, PcsConfig::default());
+        assert!(matches!(
+            prover.unwrap().prove(&blocks),
+            Err(KeccakProverError::TooManyBlocks { .. })
+        ));
+    }
+
+    #[test_log::test]
+    fn test_verify_keccak256_wrong_preprocessed_root() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec()];
+        let (mut proof, claimed_sum, claims) = prove_messages(&messages, log_size, config);
+        proof.commitments[0] = proof.commitments[1];
+        assert!(matches!(
+            verify_keccak256::<Blake2sMerkleChannel>(
+                proof,
+                log_size,
+                config,
+                &claimed_sum,
+                &claims
+            ),
+            Err(KeccakVerifierError::InvalidPreprocessedRoot)
+        ));
+    }
+}
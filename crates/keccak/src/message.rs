@@ -0,0 +1,219 @@
+//! Message handling: sponge padding and splitting of messages into chained blocks.
+//!
+//! Each trace row absorbs one [`Block`] and permutes the state. Blocks of the same message are
+//! linked through the chain relation: a block's output state is the next block's input state.
+
+use std::simd::u32x16;
+
+use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+use crate::keccak::{keccak_f, HashFunction, N_LANES, RATE, RATE_LANES};
+
+/// A message block, together with its position in the message and the state it is absorbed into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    /// Index of the message this block belongs to.
+    pub message_id: u32,
+    /// Index of the block in its message.
+    pub index: u32,
+    /// Whether this is the last block of its message.
+    pub is_last: bool,
+    /// Sponge state before absorbing the block.
+    pub state_in: [u64; N_LANES],
+    /// Rate lanes of the padded block, little-endian.
+    pub words: [u64; RATE_LANES],
+}
+
+impl Block {
+    pub const fn is_first(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Rate lanes of the state xored with the message words, i.e. the input of the permutation.
+    pub fn absorbed(&self) -> [u64; N_LANES] {
+        let mut state = self.state_in;
+        for (lane, word) in state.iter_mut().zip(self.words) {
+            *lane ^= word;
+        }
+        state
+    }
+
+    /// Sponge state after absorbing the block.
+    pub fn state_out(&self) -> [u64; N_LANES] {
+        let mut state = self.absorbed();
+        keccak_f(&mut state);
+        state
+    }
+
+    /// A standalone block used to pad the trace, i.e. the only block of the empty message for
+    /// Keccak-256.
+    pub const fn padding() -> Self {
+        let mut words = [0; RATE_LANES];
+        words[0] = HashFunction::Keccak256.domain() as u64;
+        words[RATE_LANES - 1] = 0x80 << 56;
+        Self {
+            message_id: 0,
+            index: 0,
+            is_last: true,
+            state_in: [0; N_LANES],
+            words,
+        }
+    }
+}
+
+impl Default for Block {
+    /// See [`Block::padding`].
+    fn default() -> Self {
+        Self::padding()
+    }
+}
+
+/// Pads a message with the pad10*1 rule after the domain bits of `hash_function`, see
+/// [`HashFunction::domain`], and splits it into blocks of [`RATE`] bytes. The padding always adds
+/// at least one byte, so a message whose length is a multiple of the rate gets a padding block of
+/// its own.
+pub fn pad_message(hash_function: HashFunction, message: &[u8]) -> Vec<[u64; RATE_LANES]> {
+    let mut padded = Vec::from(message);
+    padded.push(hash_function.domain());
+    padded.resize(padded.len().div_ceil(RATE) * RATE, 0);
+    *padded.last_mut().unwrap() |= 0x80;
+
+    padded
+        .chunks_exact(RATE)
+        .map(|chunk| {
+            std::array::from_fn(|i| u64::from_le_bytes(chunk[8 * i..8 * i + 8].try_into().unwrap()))
+        })
+        .collect()
+}
+
+/// Pads each message for Keccak-256 and chains its blocks, starting from the zero state.
+pub fn gen_blocks<M: AsRef<[u8]>>(messages: &[M]) -> Vec<Block> {
+    gen_blocks_for(HashFunction::Keccak256, messages)
+}
+
+/// Same as [`gen_blocks`], padding the messages for `hash_function`.
+pub fn gen_blocks_for<M: AsRef<[u8]>>(hash_function: HashFunction, messages: &[M]) -> Vec<Block> {
+    let mut blocks = vec![];
+    for (message_id, message) in messages.iter().enumerate() {
+        let padded = pad_message(hash_function, message.as_ref());
+        let n_blocks = padded.len();
+        let mut state = [0; N_LANES];
+        for (index, words) in padded.into_iter().enumerate() {
+            let block = Block {
+                message_id: message_id as u32,
+                index: index as u32,
+                is_last: index + 1 == n_blocks,
+                state_in: state,
+                words,
+            };
+            state = block.state_out();
+            blocks.push(block);
+        }
+    }
+    blocks
+}
+
+/// Little-endian bytes of the first 4 lanes of a state, i.e. the digest of a final state.
+pub fn to_bytes(state: &[u64; N_LANES]) -> [u8; 32] {
+    std::array::from_fn(|i| state[i / 8].to_le_bytes()[i % 8])
+}
+
+/// Digest of each message, i.e. the output state of its last block.
+pub fn digests(blocks: &[Block]) -> Vec<[u8; 32]> {
+    blocks
+        .iter()
+        .filter(|block| block.is_last)
+        .map(|block| to_bytes(&block.state_out()))
+        .collect()
+}
+
+/// Deterministic single-block messages, used for tests and benchmarks.
+///
+/// The 16-bit limb `i` of block `row` is `(i + row) & 0xffff`.
+pub fn generate_blocks(n_blocks: usize) -> Vec<Block> {
+    (0..n_blocks)
+        .map(|row| Block {
+            message_id: row as u32,
+            words: std::array::from_fn(|i| {
+                (0..4).fold(0, |acc, j| {
+                    acc + ((((4 * i + j + row) & 0xffff) as u64) << (16 * j))
+                })
+            }),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Builds a trace column from a value of each block, padding with [`Block::padding`] blocks up to
+/// `simd_size` SIMD rows.
+pub fn block_column(blocks: &[Block], simd_size: usize, f: impl Fn(&Block) -> u32) -> Vec<u32x16> {
+    let padding = Block::padding();
+    (0..simd_size)
+        .map(|simd_row| {
+            u32x16::from_array(std::array::from_fn(|lane| {
+                f(blocks
+                    .get((simd_row << LOG_N_LANES) + lane)
+                    .unwrap_or(&padding))
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad_message() {
+        let pad = |message: &[u8]| pad_message(HashFunction::Keccak256, message);
+        assert_eq!(pad(b""), [Block::padding().words]);
+        assert_eq!(pad(&[0; 135]).len(), 1);
+        assert_eq!(pad(&[0; 135])[0][RATE_LANES - 1], 0x81 << 56);
+        assert_eq!(pad(&[0; 136]).len(), 2);
+        assert_eq!(pad(&[0; 136])[1], Block::padding().words);
+
+        let blocks = pad(b"abc");
+        assert_eq!(blocks[0][0], 0x01636261);
+        assert_eq!(blocks[0][1..RATE_LANES - 1], [0; RATE_LANES - 2]);
+
+        let blocks = pad_message(HashFunction::Sha3_256, b"abc");
+        assert_eq!(blocks[0][0], 0x06636261);
+        assert_eq!(blocks[0][RATE_LANES - 1], 0x80 << 56);
+    }
+
+    #[test]
+    fn test_gen_blocks() {
+        let messages = [vec![0xab; 300], b"abc".to_vec(), vec![], vec![0x42; 136]];
+        let blocks = gen_blocks(&messages);
+        assert_eq!(blocks.len(), 3 + 1 + 1 + 2);
+        assert!(blocks[2].is_last && !blocks[1].is_last);
+        assert!(blocks[3].is_first() && blocks[3].is_last);
+        for (block, next) in blocks.iter().zip(&blocks[1..]) {
+            if !block.is_last {
+                assert_eq!(next.state_in, block.state_out());
+            }
+        }
+
+        let expected = [
+            "315f259936b44c2fd956d917deacbaa548f17a9d26d17df4fa2bdec09966e007",
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            "d12041652b653d6dcf334f07f06152f1e04e7f7ffbaa5786b84e41a45cd70be1",
+        ];
+        for (digest, expected) in digests(&blocks).iter().zip(expected) {
+            assert_eq!(hex::encode(digest), expected);
+        }
+    }
+
+    #[test]
+    fn test_padding_block() {
+        assert_eq!(gen_blocks(&[b""]), [Block::padding()]);
+    }
+
+    #[test]
+    fn test_generate_blocks() {
+        let blocks = generate_blocks(2);
+        assert_eq!(blocks[1].words[0], 0x0004_0003_0002_0001);
+        assert_eq!(blocks[1].words[16] >> 48, 68);
+    }
+}
@@ -0,0 +1,111 @@
+//! Splits of 64-bit lanes into pieces for the rotations of θ and ρ.
+//!
+//! XORs and AND-NOTs are looked up byte by byte, so lanes are committed as bytes. A rotation left
+//! by `r = 8q + s` moves each byte up by `q` bytes, and the top `s` bits of each byte to the next
+//! one: the lanes to rotate are committed as the pieces of their bytes cut at bit `8 - s`, so
+//! that the bytes of the lane and of its rotation are both linear combinations of pieces. Lanes
+//! rotated by a multiple of 8 are committed as bytes, their rotation only moving them around.
+//!
+//! The pieces are range checked with [`crate::preprocessed::split`].
+
+use std::simd::{num::SimdUint, u32x16, u64x16};
+
+/// Rotation of the column parities by θ.
+pub const THETA_ROTATION: u32 = 1;
+
+/// Bytes of each lane of `x`, low byte first.
+#[inline(always)]
+pub fn bytes_u64x16(x: u64x16) -> [u32x16; 8] {
+    std::array::from_fn(|k| ((x >> (8 * k as u64)) & u64x16::splat(0xff)).cast())
+}
+
+/// Pieces of the bytes of each lane of `x` cut at bit `8 - r % 8`: the low pieces, then the high
+/// pieces, low byte first.
+#[inline(always)]
+pub fn split_u64x16(x: u64x16, r: u32) -> [[u32x16; 8]; 2] {
+    let width = 8 - r % 8;
+    let bytes = bytes_u64x16(x);
+    [
+        bytes.map(|byte| byte & u32x16::splat((1 << width) - 1)),
+        bytes.map(|byte| byte >> width),
+    ]
+}
+
+/// Lanes made of `bytes`, low byte first. This is the inverse of [`bytes_u64x16`].
+#[inline(always)]
+pub fn lane_u64x16(bytes: [u32x16; 8]) -> u64x16 {
+    bytes
+        .iter()
+        .enumerate()
+        .fold(u64x16::splat(0), |acc, (k, byte)| {
+            acc | (byte.cast::<u64>() << (8 * k as u64))
+        })
+}
+
+/// Bytes holding the pieces of each byte of the lane rotated left by `r`: byte `k` of the
+/// rotation is the low piece of byte `low`, shifted by `r % 8`, plus the high piece of byte
+/// `high`.
+pub const fn rotl_sources(r: u32) -> [(usize, usize); 8] {
+    let q = (r / 8) as usize;
+    let mut sources = [(0, 0); 8];
+    let mut k = 0;
+    while k < 8 {
+        sources[k] = ((k + 8 - q) % 8, (k + 7 - q) % 8);
+        k += 1;
+    }
+    sources
+}
+
+/// Whether lanes rotated by `r` are committed as pieces rather than bytes.
+pub const fn is_split(r: u32) -> bool {
+    r % 8 != 0
+}
+
+/// Number of trace columns of a lane rotated by `r`.
+pub const fn n_columns(r: u32) -> usize {
+    if is_split(r) {
+        16
+    } else {
+        8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keccak::{rotl_u64x16, RHO};
+
+    #[test]
+    fn test_partitions() {
+        let x = u64x16::from_array(std::array::from_fn(|i| {
+            0x9e3779b97f4a7c15_u64.wrapping_mul(i as u64 + 1)
+        }));
+        assert_eq!(lane_u64x16(bytes_u64x16(x)), x);
+
+        for r in RHO.into_iter().chain([THETA_ROTATION]) {
+            let rotated = rotl_u64x16(x, r);
+            if !is_split(r) {
+                let bytes = bytes_u64x16(x);
+                let sources = rotl_sources(r);
+                assert_eq!(
+                    lane_u64x16(std::array::from_fn(|k| bytes[sources[k].0])),
+                    rotated
+                );
+                continue;
+            }
+
+            let shift = r % 8;
+            let [low, high] = split_u64x16(x, r);
+            for (low, high) in low.iter().zip(&high) {
+                assert!(low.simd_lt(u32x16::splat(1 << (8 - shift))).all());
+                assert!(high.simd_lt(u32x16::splat(1 << shift)).all());
+            }
+
+            // The bytes of the lane and of its rotation are linear combinations of the pieces
+            let bytes = std::array::from_fn(|k| low[k] + (high[k] << (8 - shift)));
+            assert_eq!(lane_u64x16(bytes), x);
+            let bytes = rotl_sources(r).map(|(l, h)| (low[l] << shift) + high[h]);
+            assert_eq!(lane_u64x16(bytes), rotated);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! AND-NOT table of χ, on bytes.
+//!
+//! The table has a row `[a, b, !a & b]` for each pair of bytes, the row index being
+//! `(a << 8) + b`.
+
+use std::simd::u32x16;
+
+use stwo::core::channel::Channel;
+use stwo_constraint_framework::relation;
+use utils::trace_columns;
+
+// [a, b, !a & b]
+const N_COLUMNS: usize = 3;
+
+pub const LOG_SIZE: u32 = 16;
+
+relation!(AndNot8, N_COLUMNS);
+
+trace_columns!(AndNotColumns, a, b, c);
+
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub and_not_8: AndNot8,
+}
+
+impl Relation {
+    pub fn dummy() -> Self {
+        Self {
+            and_not_8: AndNot8::dummy(),
+        }
+    }
+
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            and_not_8: AndNot8::draw(channel),
+        }
+    }
+}
+
+/// Row `(a << 8) + b` is `[a, b, !a & b]`.
+pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
+    let mut all_columns = vec![vec![], vec![], vec![]];
+    for i in (0..1 << LOG_SIZE).step_by(u32x16::LEN) {
+        let index = u32x16::from_array(std::array::from_fn(|j| i + j as u32));
+        let a = index >> 8;
+        let b = index & u32x16::splat(0xff);
+        all_columns[0].push(a);
+        all_columns[1].push(b);
+        all_columns[2].push(!a & b);
+    }
+    all_columns
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+    use super::*;
+
+    #[test]
+    fn test_gen_column_simd() {
+        let columns = gen_column_simd();
+        assert_eq!(columns.len(), AndNotColumns::SIZE);
+        assert!(columns
+            .iter()
+            .all(|column| column.len().ilog2() == LOG_SIZE - LOG_N_LANES));
+
+        let row = (0x12 << 8) + 0x34;
+        assert_eq!(columns[0][row / 16][row % 16], 0x12);
+        assert_eq!(columns[1][row / 16][row % 16], 0x34);
+        assert_eq!(columns[2][row / 16][row % 16], 0x24);
+    }
+}
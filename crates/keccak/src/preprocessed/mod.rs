@@ -0,0 +1,82 @@
+//! A collection of preprocessed columns, whose values are publicly acknowledged, and independent of
+//! the proof.
+//!
+//! They are similar to regular components but are entirely known by the verifier.
+use itertools::Itertools;
+use stwo::{
+    core::fields::m31::BaseField,
+    prover::{
+        backend::simd::{m31::LOG_N_LANES, SimdBackend},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::preprocessed_columns::PreProcessedColumnId;
+use utils::circle_evaluation_u32x16;
+
+pub mod and_not;
+pub mod split;
+pub mod xor;
+
+pub struct PreProcessedTrace {
+    pub trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    pub ids: Vec<PreProcessedColumnId>,
+}
+
+impl PreProcessedTrace {
+    pub fn new(log_size: u32) -> Self {
+        let mut trace = Vec::new();
+        let mut ids = Vec::new();
+        debug_assert!(log_size >= LOG_N_LANES);
+        let chunk_size = 1 << (log_size - LOG_N_LANES);
+
+        // Helper macro to process each module once
+        macro_rules! collect_columns {
+            ($mod:ident, $($id_ty:ident),+) => {{
+                let module_cols = $mod::gen_column_simd();
+
+                // Collect all ids from all provided id types
+                let mut module_ids = Vec::new();
+                $(
+                    module_ids.extend($mod::$id_ty::to_ids(None));
+                )+
+
+                for (id_base, col) in module_ids.into_iter().zip_eq(module_cols.into_iter()) {
+                    for (suffix, chunk) in col.chunks(chunk_size).enumerate() {
+                        trace.push(circle_evaluation_u32x16!(chunk));
+                        ids.push(PreProcessedColumnId {
+                            id: format!("{}_{}", id_base.id, suffix),
+                        });
+                    }
+                }
+            }};
+        }
+
+        collect_columns!(xor, XorColumns);
+        collect_columns!(and_not, AndNotColumns);
+        collect_columns!(split, SplitColumns);
+
+        Self { trace, ids }
+    }
+
+    pub fn log_sizes(&self) -> Vec<u32> {
+        self.trace
+            .iter()
+            .map(|eval| eval.domain.log_size())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let trace = PreProcessedTrace::new(8);
+        assert!(trace.trace.iter().map(|t| t.data.len()).max().unwrap() <= 1 << (8 - LOG_N_LANES));
+        assert_eq!(trace.ids.len(), trace.trace.len());
+        // The XOR and AND-NOT tables are split into 2^8 chunks, the split table into 2^3.
+        assert_eq!(trace.trace.len(), 2 * 3 * (1 << 8) + 3 * (1 << 3));
+        assert!(trace.log_sizes().iter().all(|log_size| *log_size == 8));
+    }
+}
@@ -0,0 +1,80 @@
+//! Range checks of the pieces of the rotated lanes, see [`crate::partitions`].
+//!
+//! For each shift `s` in `0..8`, the table has a row `[s, low, high]` for each byte, cut at bit
+//! `8 - s`: `low` is made of its `8 - s` low bits and `high` of its `s` high bits. The row index
+//! is `(s << 8) + byte`. Looking up `[s, low, high]` checks that both pieces fit in their width.
+
+use std::simd::u32x16;
+
+use stwo::core::channel::Channel;
+use stwo_constraint_framework::relation;
+use utils::trace_columns;
+
+// [shift, low, high]
+const N_COLUMNS: usize = 3;
+
+/// Log size of the table: 8 shifts of 256 bytes.
+pub const LOG_SIZE: u32 = 11;
+
+relation!(Byte, N_COLUMNS);
+
+trace_columns!(SplitColumns, shift, low, high);
+
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub byte: Byte,
+}
+
+impl Relation {
+    pub fn dummy() -> Self {
+        Self {
+            byte: Byte::dummy(),
+        }
+    }
+
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            byte: Byte::draw(channel),
+        }
+    }
+}
+
+/// Row `(shift << 8) + byte` is `[shift, low, high]`, see the module documentation.
+pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
+    let mut all_columns = vec![vec![], vec![], vec![]];
+    for i in (0..1 << LOG_SIZE).step_by(u32x16::LEN) {
+        let index = u32x16::from_array(std::array::from_fn(|j| i + j as u32));
+        let shift = index >> 8;
+        let byte = index & u32x16::splat(0xff);
+        let width = u32x16::splat(8) - shift;
+        all_columns[0].push(shift);
+        all_columns[1].push(byte & ((u32x16::splat(1) << width) - u32x16::splat(1)));
+        all_columns[2].push(byte >> width);
+    }
+    all_columns
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+    use super::*;
+
+    #[test]
+    fn test_gen_column_simd() {
+        let columns = gen_column_simd();
+        assert_eq!(columns.len(), SplitColumns::SIZE);
+        assert!(columns
+            .iter()
+            .all(|column| column.len().ilog2() == LOG_SIZE - LOG_N_LANES));
+
+        let row = (3 << 8) + 0b1010_1101;
+        assert_eq!(columns[0][row / 16][row % 16], 3);
+        assert_eq!(columns[1][row / 16][row % 16], 0b0_1101);
+        assert_eq!(columns[2][row / 16][row % 16], 0b101);
+
+        // A null shift leaves the whole byte in the low piece
+        assert_eq!(columns[1][0xab / 16][0xab % 16], 0xab);
+        assert_eq!(columns[2][0xab / 16][0xab % 16], 0);
+    }
+}
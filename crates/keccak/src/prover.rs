@@ -0,0 +1,70 @@
+//! Prover side of the Keccak AIR.
+//!
+//! The preprocessed trace, the twiddles and the commitment of the preprocessed tree only depend
+//! on the log size and the commitment scheme config. A [`KeccakProver`] computes them once and
+//! shares them across all the proofs it generates.
+
+use stwo::{
+    core::{channel::MerkleChannel, pcs::PcsConfig, proof::StarkProof, vcs::MerkleHasher},
+    prover::backend::{
+        simd::{m31::LOG_N_LANES, SimdBackend},
+        BackendForChannel,
+    },
+};
+use utils::stwo::air::Prover;
+
+use crate::{
+    components::{ClaimedSum, KeccakAir},
+    message::Block,
+    preprocessed::PreProcessedTrace,
+    KeccakProverError, MAX_LOG_SIZE,
+};
+
+/// Proves batches of Keccak blocks for a fixed log size and config.
+pub struct KeccakProver<MC: MerkleChannel>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    prover: Prover<MC>,
+}
+
+impl<MC: MerkleChannel> KeccakProver<MC>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    pub fn new(log_size: u32, config: PcsConfig) -> Result<Self, KeccakProverError> {
+        KeccakProverError::check_params(log_size, LOG_N_LANES, MAX_LOG_SIZE, &config)?;
+        let preprocessed_trace = PreProcessedTrace::new(log_size);
+        Ok(Self {
+            prover: Prover::new(
+                log_size,
+                config,
+                preprocessed_trace.trace,
+                preprocessed_trace.ids,
+            ),
+        })
+    }
+
+    pub const fn log_size(&self) -> u32 {
+        self.prover.log_size()
+    }
+
+    pub const fn config(&self) -> PcsConfig {
+        self.prover.config()
+    }
+
+    /// Merkle root of the preprocessed trace, i.e. the first commitment of every proof.
+    pub fn preprocessed_root(&self) -> <MC::H as MerkleHasher>::Hash {
+        self.prover.preprocessed_root()
+    }
+
+    /// Proves the absorption and permutation of each block, see [`crate::prove_keccak256`]. The
+    /// blocks of each message must be chained as by [`crate::message::gen_blocks`].
+    pub fn prove(
+        &self,
+        blocks: &[Block],
+    ) -> Result<(StarkProof<MC::H>, ClaimedSum), KeccakProverError> {
+        KeccakProverError::check_n_blocks(blocks.len(), self.log_size())?;
+        self.prover.prove(&KeccakAir, blocks)
+    }
+}
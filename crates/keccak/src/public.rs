@@ -0,0 +1,198 @@
+//! Public inputs and outputs of the proof.
+//!
+//! The permutation component emits two kinds of claims, consumed by the verifier:
+//! - a message claim for each block, made of its position in the message and its padded message
+//!   words,
+//! - an output claim for each message, made of its digest.
+//!
+//! The verifier rebuilds the same claims from the public messages and digests, so the logup sum
+//! only balances if the proof covers exactly these messages and digests. The sponge states between
+//! the blocks of a message are linked inside the AIR and never claimed.
+
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use stwo::core::{
+    channel::Channel,
+    fields::{m31::M31, qm31::SecureField, FieldExpOps},
+};
+use stwo_constraint_framework::Relation;
+
+use crate::{
+    keccak::{digest, HashFunction, DIGEST_SIZE, RATE_SIZE},
+    message::{pad_message, to_bytes, Block},
+    relations::Relations,
+};
+
+/// [message_id, block_index, is_last, message words], the message words as 16-bit limbs.
+pub const MESSAGE_CLAIM_SIZE: usize = 3 + RATE_SIZE;
+
+/// [message_id, digest], as 16-bit limbs.
+pub const OUTPUT_CLAIM_SIZE: usize = 1 + DIGEST_SIZE;
+
+/// Claims of a proof, see the module documentation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Claims {
+    pub messages: Vec<[u32; MESSAGE_CLAIM_SIZE]>,
+    pub outputs: Vec<[u32; OUTPUT_CLAIM_SIZE]>,
+}
+
+/// A message together with its claimed digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashClaim {
+    pub message: Vec<u8>,
+    pub digest: [u8; 32],
+}
+
+impl HashClaim {
+    /// Claims the actual Keccak-256 digest of `message`.
+    pub fn new(message: impl Into<Vec<u8>>) -> Self {
+        Self::with_hash_function(HashFunction::Keccak256, message)
+    }
+
+    /// Claims the actual digest of `message` for `hash_function`.
+    pub fn with_hash_function(hash_function: HashFunction, message: impl Into<Vec<u8>>) -> Self {
+        let message = message.into();
+        let digest = digest(hash_function, &message);
+        Self { message, digest }
+    }
+}
+
+/// Splits bytes into little-endian 16-bit limbs.
+fn to_limbs(bytes: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    bytes
+        .chunks_exact(2)
+        .map(|limb| limb[0] as u32 + ((limb[1] as u32) << 8))
+}
+
+fn message_claim(block: &Block) -> [u32; MESSAGE_CLAIM_SIZE] {
+    let bytes = block
+        .words
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect::<Vec<_>>();
+    [block.message_id, block.index, block.is_last as u32]
+        .into_iter()
+        .chain(to_limbs(&bytes))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+fn output_claim(message_id: u32, digest: &[u8; 32]) -> [u32; OUTPUT_CLAIM_SIZE] {
+    [message_id]
+        .into_iter()
+        .chain(to_limbs(digest))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+/// Claims emitted by the permutation component for the given blocks.
+pub fn block_claims(blocks: &[Block]) -> Claims {
+    let mut claims = Claims::default();
+    for block in blocks {
+        claims.messages.push(message_claim(block));
+        if block.is_last {
+            claims.outputs.push(output_claim(
+                block.message_id,
+                &to_bytes(&block.state_out()),
+            ));
+        }
+    }
+    claims
+}
+
+/// Claims expected by the verifier, which only pads the messages for `hash_function`. Message ids
+/// follow the order of the claims, see [`crate::message::gen_blocks_for`].
+pub fn hash_claims(hash_function: HashFunction, claims: &[HashClaim]) -> Claims {
+    let mut result = Claims::default();
+    for (message_id, claim) in claims.iter().enumerate() {
+        let message_id = message_id as u32;
+        let padded = pad_message(hash_function, &claim.message);
+        let n_blocks = padded.len();
+        for (index, words) in padded.into_iter().enumerate() {
+            // The input state is not claimed
+            let block = Block {
+                message_id,
+                index: index as u32,
+                is_last: index + 1 == n_blocks,
+                words,
+                ..Default::default()
+            };
+            result.messages.push(message_claim(&block));
+        }
+        result.outputs.push(output_claim(message_id, &claim.digest));
+    }
+    result
+}
+
+pub fn mix_into(claims: &Claims, channel: &mut impl Channel) {
+    channel.mix_u32s(&[claims.messages.len() as u32]);
+    for claim in &claims.messages {
+        channel.mix_u32s(claim);
+    }
+    channel.mix_u32s(&[claims.outputs.len() as u32]);
+    for claim in &claims.outputs {
+        channel.mix_u32s(claim);
+    }
+}
+
+/// Logup sum of the verifier consuming the claims, to be added to the components claimed sum.
+pub fn logup_sum(claims: &Claims, relations: &Relations) -> SecureField {
+    let messages = claims.messages.iter().map(|claim| {
+        let denom: SecureField = relations.message_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    let outputs = claims.outputs.iter().map(|claim| {
+        let denom: SecureField = relations.output_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    messages
+        .chain(outputs)
+        .fold(SecureField::zero(), |acc, frac| acc - frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{gen_blocks, gen_blocks_for};
+
+    #[test]
+    fn test_hash_claims_match_block_claims() {
+        let messages = [
+            b"abc".to_vec(),
+            vec![0x42; 100],
+            vec![],
+            vec![0x43; 136],
+            vec![0x44; 1000],
+        ];
+        for hash_function in [HashFunction::Keccak256, HashFunction::Sha3_256] {
+            let claims = messages
+                .iter()
+                .map(|message| HashClaim::with_hash_function(hash_function, message.clone()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                hash_claims(hash_function, &claims),
+                block_claims(&gen_blocks_for(hash_function, &messages))
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_claims_wrong_digest() {
+        let mut claim = HashClaim::new(b"abc".to_vec());
+        claim.digest[0] ^= 1;
+        assert_ne!(
+            hash_claims(HashFunction::Keccak256, &[claim]),
+            block_claims(&gen_blocks(&[b"abc".to_vec()]))
+        );
+    }
+
+    #[test]
+    fn test_output_claim() {
+        let claims = hash_claims(HashFunction::Keccak256, &[HashClaim::new(b"")]);
+        // c5d2460186f7233c...
+        assert_eq!(claims.outputs[0][..3], [0, 0xd2c5, 0x0146]);
+        assert_eq!(claims.messages[0][..4], [0, 0, 1, 0x0001]);
+    }
+}
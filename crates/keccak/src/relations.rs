@@ -0,0 +1,61 @@
+use stwo::core::channel::Channel;
+
+use crate::preprocessed::{and_not, split, xor};
+
+mod chain {
+    use stwo_constraint_framework::relation;
+
+    use crate::components::STATE_SIZE;
+    // [message_id, block_index, state]
+    relation!(Relation, 2 + STATE_SIZE);
+}
+
+mod message_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::MESSAGE_CLAIM_SIZE;
+    // [message_id, block_index, is_last, message words]
+    relation!(Relation, MESSAGE_CLAIM_SIZE);
+}
+
+mod output_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::OUTPUT_CLAIM_SIZE;
+    // [message_id, digest]
+    relation!(Relation, OUTPUT_CLAIM_SIZE);
+}
+
+#[derive(Clone)]
+pub struct Relations {
+    pub xor: xor::Relation,
+    pub and_not: and_not::Relation,
+    pub split: split::Relation,
+    pub chain: chain::Relation,
+    pub message_claim: message_claim::Relation,
+    pub output_claim: output_claim::Relation,
+}
+
+impl Relations {
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            xor: xor::Relation::draw(channel),
+            and_not: and_not::Relation::draw(channel),
+            split: split::Relation::draw(channel),
+            chain: chain::Relation::draw(channel),
+            message_claim: message_claim::Relation::draw(channel),
+            output_claim: output_claim::Relation::draw(channel),
+        }
+    }
+
+    pub fn dummy() -> Self {
+        Self {
+            xor: xor::Relation::dummy(),
+            and_not: and_not::Relation::dummy(),
+            split: split::Relation::dummy(),
+            chain: chain::Relation::dummy(),
+            message_claim: message_claim::Relation::dummy(),
+            output_claim: output_claim::Relation::dummy(),
+        }
+    }
+}
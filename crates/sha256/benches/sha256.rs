@@ -1,8 +1,15 @@
 #[cfg(feature = "peak-alloc")]
 use peak_alloc::PeakAlloc;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use sha256::prove_sha256;
-use stwo::core::pcs::PcsConfig;
+use sha256::{message::generate_blocks, Sha256Prover};
+use stwo::{
+    core::{
+        channel::MerkleChannel,
+        pcs::PcsConfig,
+        vcs::{blake2_merkle::Blake2sMerkleChannel, poseidon252_merkle::Poseidon252MerkleChannel},
+    },
+    prover::backend::{simd::SimdBackend, BackendForChannel},
+};
 use tracing::info;
 
 #[cfg(feature = "peak-alloc")]
@@ -23,22 +30,28 @@ fn main() {
 const N_ITER: &[usize] = &[6, 7, 8];
 
 #[divan::bench(
+    types = [Blake2sMerkleChannel, Poseidon252MerkleChannel],
     consts = N_ITER,
     args = [13, 14],
     sample_count = 1
 )]
-fn bench_sha256<const N_ITER: usize>(bencher: divan::Bencher, log_size: u32) {
+fn bench_sha256<MC: MerkleChannel, const N_ITER: usize>(bencher: divan::Bencher, log_size: u32)
+where
+    SimdBackend: BackendForChannel<MC>,
+{
     #[cfg(feature = "parallel")]
     info!("Stwo Parallel");
     #[cfg(not(feature = "parallel"))]
     info!("Stwo Non-parallel");
 
+    let blocks = generate_blocks(1 << log_size);
+    let prover = Sha256Prover::<MC>::new(log_size, PcsConfig::default()).unwrap();
     bencher.bench(|| {
         #[cfg(feature = "peak-alloc")]
         PEAK_ALLOC.reset_peak_usage();
         (0..N_ITER)
             .into_par_iter()
-            .map(|_| prove_sha256(log_size, PcsConfig::default()))
+            .map(|_| prover.prove(&blocks).unwrap())
             .collect::<Vec<_>>();
         #[cfg(feature = "peak-alloc")]
         {
@@ -0,0 +1,326 @@
+//! Command-line interface to the SHA-256 prover.
+//!
+//! Messages are read one per line, as raw bytes or hex-encoded with `--hex`. Raw lines cannot
+//! hold a newline and blank lines are skipped: hex-encode the messages to prove messages with
+//! newlines or empty messages, an empty line being the empty message in `--hex` mode. The proof is
+//! written as a [`Sha256Proof`] envelope and the public claims as a JSON list of (message, digest)
+//! pairs.
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+    time::Instant,
+};
+
+use clap::{Args, Parser, Subcommand};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use sha256::{
+    config::{estimate_proof_size, security_bits, SecurityPreset},
+    message::{gen_blocks, generate_blocks},
+    proof::{ProofChannel, Sha256Proof},
+    public::HashClaim,
+    HashFunction, Sha256Prover,
+};
+use stwo::{
+    core::{pcs::PcsConfig, vcs::blake2_merkle::Blake2sMerkleChannel},
+    prover::backend::simd::m31::LOG_N_LANES,
+};
+use tracing::{info, Level};
+
+type Error = Box<dyn std::error::Error>;
+
+#[derive(Parser)]
+#[command(
+    name = "sha256-prove",
+    about = "Prove and verify SHA-256 hashes with stwo"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prove the SHA-256 digests of a list of messages.
+    Prove {
+        /// File with one message per line, reads stdin if omitted.
+        #[arg(long)]
+        input: Option<PathBuf>,
+        /// Decode each line as hex, an empty line being the empty message.
+        #[arg(long)]
+        hex: bool,
+        /// Output proof file.
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+        /// Output public claims file.
+        #[arg(long, default_value = "claims.json")]
+        claims: PathBuf,
+        /// Write the proof as JSON instead of binary.
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        params: Params,
+    },
+    /// Verify a proof file.
+    Verify {
+        /// Proof file.
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+        /// Public claims file, checked against the claims of the proof.
+        #[arg(long)]
+        claims: Option<PathBuf>,
+        /// Read the proof as JSON instead of binary.
+        #[arg(long)]
+        json: bool,
+        /// Hash function the proof must attest (sha256, sha224 or sha256d).
+        #[arg(long, default_value = "sha256")]
+        hash_function: HashFunction,
+        #[command(flatten)]
+        params: Params,
+    },
+    /// Measure the prover throughput on generated blocks.
+    Bench {
+        /// Number of proofs generated in parallel.
+        #[arg(long, default_value_t = 1)]
+        n_iter: u32,
+        #[command(flatten)]
+        params: Params,
+    },
+}
+
+#[derive(Args)]
+struct Params {
+    /// Security preset (fast-dev, 96 or 128), overridden by --blowup and --pow-bits.
+    #[arg(long)]
+    preset: Option<SecurityPreset>,
+    /// Log2 of the number of rows, i.e. of compressed blocks.
+    #[arg(long)]
+    log_size: Option<u32>,
+    /// Log2 of the FRI blowup factor.
+    #[arg(long)]
+    blowup: Option<u32>,
+    /// Proof of work bits.
+    #[arg(long)]
+    pow_bits: Option<u32>,
+    /// Directory caching the preprocessed tables across runs of the prover.
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+}
+
+impl Params {
+    fn config(&self) -> PcsConfig {
+        let mut config = self
+            .preset
+            .map_or_else(PcsConfig::default, SecurityPreset::pcs_config);
+        if let Some(blowup) = self.blowup {
+            config.fri_config.log_blowup_factor = blowup;
+        }
+        if let Some(pow_bits) = self.pow_bits {
+            config.pow_bits = pow_bits;
+        }
+        config
+    }
+
+    fn prover(
+        &self,
+        log_size: u32,
+        config: PcsConfig,
+    ) -> Result<Sha256Prover<Blake2sMerkleChannel>, Error> {
+        Ok(match &self.cache_dir {
+            Some(dir) => Sha256Prover::with_cache_dir(log_size, config, dir)?,
+            None => Sha256Prover::new(log_size, config)?,
+        })
+    }
+}
+
+fn read_messages(input: Option<PathBuf>, hex: bool) -> Result<Vec<Vec<u8>>, Error> {
+    let mut data = Vec::new();
+    match input {
+        Some(path) => data = fs::read(path)?,
+        None => {
+            io::stdin().read_to_end(&mut data)?;
+        }
+    }
+    parse_messages(&data, hex)
+}
+
+/// Splits `data` into lines, the newline ending the last line being optional. Blank lines are
+/// skipped in raw mode, and are empty messages in hex mode.
+fn parse_messages(data: &[u8], hex: bool) -> Result<Vec<Vec<u8>>, Error> {
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+    let data = data.strip_suffix(b"\n").unwrap_or(data);
+    data.split(|byte| *byte == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| hex || !line.is_empty())
+        .map(|line| {
+            if hex {
+                Ok(hex::decode(line)?)
+            } else {
+                Ok(line.to_vec())
+            }
+        })
+        .collect()
+}
+
+fn prove(
+    input: Option<PathBuf>,
+    hex: bool,
+    proof_path: PathBuf,
+    claims_path: PathBuf,
+    json: bool,
+    params: Params,
+) -> Result<(), Error> {
+    let messages = read_messages(input, hex)?;
+    let blocks = gen_blocks(&messages);
+    let log_size = params
+        .log_size
+        .unwrap_or_else(|| blocks.len().next_power_of_two().ilog2().max(LOG_N_LANES));
+    let config = params.config();
+    info!(
+        "Proving {} messages ({} blocks), log size {}",
+        messages.len(),
+        blocks.len(),
+        log_size
+    );
+    info!(
+        "Security bits: {}, estimated proof size: {} bytes",
+        security_bits(&config),
+        estimate_proof_size(log_size, &config)
+    );
+
+    let (proof, claimed_sum) = params.prover(log_size, config)?.prove(&blocks)?;
+    let claims = messages.into_iter().map(HashClaim::new).collect::<Vec<_>>();
+    fs::write(&claims_path, serde_json::to_string_pretty(&claims)?)?;
+
+    let proof =
+        Sha256Proof::new::<Blake2sMerkleChannel>(proof, log_size, config, claimed_sum, claims);
+    if json {
+        fs::write(&proof_path, proof.to_json()?)?;
+    } else {
+        fs::write(&proof_path, proof.to_bytes()?)?;
+    }
+    info!(
+        "Proof written to {}, claims written to {}",
+        proof_path.display(),
+        claims_path.display()
+    );
+    Ok(())
+}
+
+fn verify(
+    proof_path: PathBuf,
+    claims_path: Option<PathBuf>,
+    json: bool,
+    hash_function: HashFunction,
+    params: Params,
+) -> Result<(), Error> {
+    let proof: Sha256Proof = if json {
+        Sha256Proof::from_json(&fs::read_to_string(proof_path)?)?
+    } else {
+        Sha256Proof::from_bytes(&fs::read(proof_path)?)?
+    };
+    if proof.channel != ProofChannel::Blake2s {
+        return Err(format!(
+            "Proof committed with the {:?} channel, only Blake2s proofs are supported",
+            proof.channel
+        )
+        .into());
+    }
+
+    if let Some(claims_path) = claims_path {
+        let claims: Vec<HashClaim> = serde_json::from_str(&fs::read_to_string(claims_path)?)?;
+        if claims != proof.claims {
+            return Err("Public claims do not match the proof".into());
+        }
+    }
+    // Parameters given on the command line are expectations on the proof.
+    if hash_function != proof.hash_function {
+        return Err(format!("Proof hash function is {:?}", proof.hash_function).into());
+    }
+    if params
+        .log_size
+        .is_some_and(|log_size| log_size != proof.log_size)
+    {
+        return Err(format!("Proof log size is {}", proof.log_size).into());
+    }
+    if params
+        .blowup
+        .is_some_and(|blowup| blowup != proof.config.fri_config.log_blowup_factor)
+        || params
+            .pow_bits
+            .is_some_and(|pow_bits| pow_bits != proof.config.pow_bits)
+    {
+        return Err(format!("Proof config is {:?}", proof.config).into());
+    }
+
+    let n_claims = proof.claims.len();
+    proof.verify::<Blake2sMerkleChannel>()?;
+    info!("Proof of {} {:?} hashes verified", n_claims, hash_function);
+    Ok(())
+}
+
+fn bench(n_iter: u32, params: Params) -> Result<(), Error> {
+    let log_size = params.log_size.unwrap_or(13);
+    let config = params.config();
+    let blocks = generate_blocks(1 << log_size);
+    info!("Log size: {}", log_size);
+    info!("Number of iterations: {}", n_iter);
+
+    let prover = params.prover(log_size, config)?;
+    let start = Instant::now();
+    (0..n_iter)
+        .into_par_iter()
+        .map(|_| prover.prove(&blocks))
+        .collect::<Result<Vec<_>, _>>()?;
+    info!(
+        "Throughput {:?}",
+        (1 << log_size) as f32 * n_iter as f32 / start.elapsed().as_secs_f32()
+    );
+    Ok(())
+}
+
+fn main() -> Result<(), Error> {
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+
+    match Cli::parse().command {
+        Command::Prove {
+            input,
+            hex,
+            proof,
+            claims,
+            json,
+            params,
+        } => prove(input, hex, proof, claims, json, params),
+        Command::Verify {
+            proof,
+            claims,
+            json,
+            hash_function,
+            params,
+        } => verify(proof, claims, json, hash_function, params),
+        Command::Bench { n_iter, params } => bench(n_iter, params),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_messages() {
+        assert_eq!(
+            parse_messages(b"abc\n\r\ndef", false).unwrap(),
+            [b"abc".to_vec(), b"def".to_vec()]
+        );
+        assert_eq!(
+            parse_messages(b"616263\n\n0a\r\n", true).unwrap(),
+            [b"abc".to_vec(), vec![], b"\n".to_vec()]
+        );
+        assert!(parse_messages(b"", true).unwrap().is_empty());
+        assert_eq!(parse_messages(b"\n", true).unwrap(), [Vec::<u8>::new()]);
+        assert!(parse_messages(b"zz\n", true).is_err());
+    }
+}
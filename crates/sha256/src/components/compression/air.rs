@@ -1,17 +1,25 @@
-use num_traits::One;
+use num_traits::{One, Zero};
 use stwo::core::fields::m31::M31;
 use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval};
 use utils::add_to_relation;
 
 use crate::{
-    components::{compression::columns::RoundColumnsOwned, W_SIZE},
+    components::{
+        compression::columns::{ChainColumnsOwned, RoundColumnsOwned},
+        STATE_SIZE, W_SIZE,
+    },
+    message::pad_message,
     relations::Relations,
-    sha256::{H, K, N_COMPRESSION_ROUNDS},
+    sha256::{HashFunction, CHUNK_SIZE, K, N_COMPRESSION_ROUNDS},
 };
 
 pub type Component = FrameworkComponent<Eval>;
 
-fn eval_compression_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relations) {
+fn eval_compression_constraints<E: EvalAtRow>(
+    eval: &mut E,
+    relations: &Relations,
+    hash_function: HashFunction,
+) {
     let w: [E::F; W_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
 
     let k: [E::F; K.len() * 2] = K
@@ -26,17 +34,101 @@ fn eval_compression_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relation
         .try_into()
         .unwrap();
 
-    let mut hash_buffer: [E::F; H.len() * 2] = H
-        .iter()
-        .flat_map(|h| {
-            [
-                E::F::from(M31::from(h & 0xffff)),
-                E::F::from(M31::from(h >> 16)),
-            ]
-        })
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap();
+    let ChainColumnsOwned {
+        message_id,
+        block_index,
+        is_first,
+        is_last,
+        is_active,
+        is_linked_left,
+        is_linked_right,
+        is_linked_out,
+        link_message_id,
+        link_position,
+        is_unclaimed_left,
+        is_unclaimed_right,
+        is_copied_left,
+        is_copied_right,
+        copy_left_message_id,
+        copy_left_position,
+        copy_right_message_id,
+        copy_right_position,
+        is_chained_in,
+        is_chained_out,
+    } = ChainColumnsOwned::<<E as EvalAtRow>::F>::from_eval(eval);
+    let h_in: [E::F; STATE_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
+
+    eval.add_constraint(is_first.clone() * (is_first.clone() - E::F::one()));
+    eval.add_constraint(is_last.clone() * (is_last.clone() - E::F::one()));
+    eval.add_constraint(is_active.clone() * (is_active.clone() - E::F::one()));
+    eval.add_constraint(is_linked_left.clone() * (is_linked_left.clone() - E::F::one()));
+    eval.add_constraint(is_linked_right.clone() * (is_linked_right.clone() - E::F::one()));
+    eval.add_constraint(is_linked_out.clone() * (is_linked_out.clone() - E::F::one()));
+    // Padding blocks are standalone, so that they emit no output claim
+    eval.add_constraint((E::F::one() - is_active.clone()) * (E::F::one() - is_last.clone()));
+    // Only active blocks are linked, and only the digest of a message is linked out
+    eval.add_constraint(is_linked_left.clone() * (E::F::one() - is_active.clone()));
+    eval.add_constraint(is_linked_right.clone() * (E::F::one() - is_active.clone()));
+    eval.add_constraint(is_linked_out.clone() * (E::F::one() - is_active.clone()));
+    eval.add_constraint(is_linked_out.clone() * (E::F::one() - is_last.clone()));
+    eval.add_constraint((E::F::one() - is_linked_out.clone()) * link_message_id.clone());
+    eval.add_constraint((E::F::one() - is_linked_out.clone()) * link_position.clone());
+    // Only active blocks have unclaimed or copied halves, and a linked half is not unclaimed
+    for (is_unclaimed, is_copied, is_linked) in [
+        (&is_unclaimed_left, &is_copied_left, &is_linked_left),
+        (&is_unclaimed_right, &is_copied_right, &is_linked_right),
+    ] {
+        eval.add_constraint(is_unclaimed.clone() * (is_unclaimed.clone() - E::F::one()));
+        eval.add_constraint(is_copied.clone() * (is_copied.clone() - E::F::one()));
+        eval.add_constraint(is_unclaimed.clone() * (E::F::one() - is_active.clone()));
+        eval.add_constraint(is_copied.clone() * (E::F::one() - is_active.clone()));
+        eval.add_constraint(is_unclaimed.clone() * is_linked.clone());
+    }
+    for (is_copied, copy_message_id, copy_position) in [
+        (&is_copied_left, &copy_left_message_id, &copy_left_position),
+        (
+            &is_copied_right,
+            &copy_right_message_id,
+            &copy_right_position,
+        ),
+    ] {
+        eval.add_constraint((E::F::one() - is_copied.clone()) * copy_message_id.clone());
+        eval.add_constraint((E::F::one() - is_copied.clone()) * copy_position.clone());
+    }
+    // A chained in block is a whole 32-byte message: its left half is the digest of the previous
+    // message, consumed below, and its right half is the constant padding. A chained out digest is
+    // not linked.
+    eval.add_constraint(is_chained_in.clone() * (is_chained_in.clone() - E::F::one()));
+    eval.add_constraint(is_chained_out.clone() * (is_chained_out.clone() - E::F::one()));
+    eval.add_constraint(is_chained_in.clone() * (E::F::one() - is_active.clone()));
+    eval.add_constraint(is_chained_in.clone() * (E::F::one() - is_first.clone()));
+    eval.add_constraint(is_chained_in.clone() * (E::F::one() - is_last.clone()));
+    eval.add_constraint(
+        is_chained_in.clone()
+            * (is_linked_left.clone()
+                + is_linked_right.clone()
+                + is_unclaimed_left.clone()
+                + is_unclaimed_right.clone()),
+    );
+    let padding = pad_message(&[0; 32])[0];
+    for (i, limb) in w[CHUNK_SIZE / 2..CHUNK_SIZE].iter().enumerate() {
+        let padding_limb = (padding[8 + i / 2] >> (16 * (i % 2))) & 0xffff;
+        eval.add_constraint(
+            is_chained_in.clone() * (limb.clone() - E::F::from(M31::from(padding_limb))),
+        );
+    }
+    eval.add_constraint(is_chained_out.clone() * (E::F::one() - is_active.clone()));
+    eval.add_constraint(is_chained_out.clone() * (E::F::one() - is_last.clone()));
+    eval.add_constraint(is_chained_out.clone() * is_linked_out.clone());
+    // The first block of a message has index 0 and starts from the initial hash value
+    eval.add_constraint(is_first.clone() * block_index.clone());
+    let iv = hash_function.iv();
+    for (i, h) in h_in.iter().enumerate() {
+        let iv_limb = (iv[i / 2] >> (16 * (i % 2))) & 0xffff;
+        eval.add_constraint(is_first.clone() * (h.clone() - E::F::from(M31::from(iv_limb))));
+    }
+
+    let mut hash_buffer: [E::F; STATE_SIZE] = h_in.clone();
 
     let minus_one = -E::EF::one();
     for round in 0..N_COMPRESSION_ROUNDS {
@@ -364,6 +456,32 @@ fn eval_compression_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relation
         hash_buffer[15] = g_high.clone(); // h_high
     }
 
+    // Feed-forward: the output chaining value is the digest for the last block of a message
+    let h_out: [E::F; STATE_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
+    let carry: [E::F; STATE_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
+    for i in 0..STATE_SIZE / 2 {
+        eval.add_constraint(
+            h_out[2 * i].clone() + carry[2 * i].clone() * E::F::from(M31::from(1 << 16))
+                - h_in[2 * i].clone()
+                - hash_buffer[2 * i].clone(),
+        );
+        eval.add_constraint(
+            h_out[2 * i + 1].clone() + carry[2 * i + 1].clone() * E::F::from(M31::from(1 << 16))
+                - h_in[2 * i + 1].clone()
+                - hash_buffer[2 * i + 1].clone()
+                - carry[2 * i].clone(),
+        );
+    }
+    for (h_out, carry) in h_out.iter().zip(carry.iter()) {
+        add_to_relation!(
+            eval,
+            relations.range_check_add.add_4,
+            minus_one,
+            h_out,
+            carry
+        );
+    }
+
     // Consume W emitted by scheduling
     eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
         &relations.w,
@@ -371,6 +489,164 @@ fn eval_compression_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relation
         &w,
     ));
 
+    // Consume the input chaining value, unless this is the first block of the message
+    let chain_in = [message_id.clone(), block_index.clone()]
+        .into_iter()
+        .chain(h_in)
+        .collect::<Vec<_>>();
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.chain,
+        E::EF::from(is_first - E::F::one()),
+        &chain_in,
+    ));
+
+    // Emit the output chaining value, unless this is the last block of the message
+    let chain_out = [message_id.clone(), block_index.clone() + E::F::one()]
+        .into_iter()
+        .chain(h_out.clone())
+        .collect::<Vec<_>>();
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.chain,
+        E::EF::from(E::F::one() - is_last.clone()),
+        &chain_out,
+    ));
+
+    // Emit the public claims of the block, consumed by the verifier: each half of its message words
+    // unless it is linked, unclaimed or chained in, and the digest of its message if it is the last
+    // block and its digest is neither linked nor chained out. Only the digest limbs of the output
+    // chaining value are claimed, the others are zero.
+    let left_position = block_index * E::F::from(M31::from(2));
+    let right_position = left_position.clone() + E::F::one();
+    let (left, right) = w[..CHUNK_SIZE].split_at(CHUNK_SIZE / 2);
+    let input_left = [message_id.clone(), left_position]
+        .into_iter()
+        .chain(left.iter().cloned())
+        .collect::<Vec<_>>();
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.input_claim,
+        E::EF::from(
+            is_active.clone() - is_linked_left.clone() - is_unclaimed_left - is_chained_in.clone(),
+        ),
+        &input_left,
+    ));
+    let input_right = [message_id.clone(), right_position]
+        .into_iter()
+        .chain(right.iter().cloned())
+        .collect::<Vec<_>>();
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.input_claim,
+        E::EF::from(
+            is_active.clone()
+                - is_linked_right.clone()
+                - is_unclaimed_right
+                - is_chained_in.clone(),
+        ),
+        &input_right,
+    ));
+    let digest_size = 2 * hash_function.digest_words();
+    let output_claim = [message_id.clone()]
+        .into_iter()
+        .chain(h_out[..digest_size].iter().cloned())
+        .chain((digest_size..STATE_SIZE).map(|_| E::F::zero()))
+        .collect::<Vec<_>>();
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.output_claim,
+        E::EF::from(
+            is_last + is_active - E::F::one() - is_linked_out.clone() - is_chained_out.clone(),
+        ),
+        &output_claim,
+    ));
+
+    // Emit the digest to link, together with the public link claim which fixes where it goes, and
+    // consume the linked halves of the message words
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.link_claim,
+        E::EF::from(is_linked_out.clone()),
+        &[
+            message_id.clone(),
+            link_message_id.clone(),
+            link_position.clone(),
+        ],
+    ));
+    let link_out = [link_message_id, link_position]
+        .into_iter()
+        .chain(h_out.clone())
+        .collect::<Vec<_>>();
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.link,
+        E::EF::from(is_linked_out),
+        &link_out,
+    ));
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.link,
+        -E::EF::from(is_linked_left),
+        &input_left,
+    ));
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.link,
+        -E::EF::from(is_linked_right),
+        &input_right,
+    ));
+
+    // Emit the copied halves, together with the public copy claims which fix where they go
+    for (is_copied, position, copy_message_id, copy_position, half) in [
+        (
+            is_copied_left,
+            &input_left[1],
+            copy_left_message_id,
+            copy_left_position,
+            &input_left[2..],
+        ),
+        (
+            is_copied_right,
+            &input_right[1],
+            copy_right_message_id,
+            copy_right_position,
+            &input_right[2..],
+        ),
+    ] {
+        eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+            &relations.copy_claim,
+            E::EF::from(is_copied.clone()),
+            &[
+                message_id.clone(),
+                position.clone(),
+                copy_message_id.clone(),
+                copy_position.clone(),
+            ],
+        ));
+        let copy = [copy_message_id, copy_position]
+            .into_iter()
+            .chain(half.iter().cloned())
+            .collect::<Vec<_>>();
+        eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+            &relations.link,
+            E::EF::from(is_copied),
+            &copy,
+        ));
+    }
+
+    // Emit the digest chained to the next message, and consume the chained left half. Neither is
+    // claimed: the chain only ends with a public output claim.
+    let chained_out = [message_id.clone() + E::F::one()]
+        .into_iter()
+        .chain(h_out)
+        .collect::<Vec<_>>();
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.chained,
+        E::EF::from(is_chained_out),
+        &chained_out,
+    ));
+    let chained_in = [message_id]
+        .into_iter()
+        .chain(left.iter().cloned())
+        .collect::<Vec<_>>();
+    eval.add_to_relation(stwo_constraint_framework::RelationEntry::new(
+        &relations.chained,
+        -E::EF::from(is_chained_in),
+        &chained_in,
+    ));
+
     eval.finalize_logup_in_pairs();
 }
 
@@ -378,6 +654,7 @@ fn eval_compression_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relation
 pub struct Eval {
     pub log_size: u32,
     pub relations: Relations,
+    pub hash_function: HashFunction,
 }
 impl FrameworkEval for Eval {
     fn log_size(&self) -> u32 {
@@ -387,7 +664,7 @@ impl FrameworkEval for Eval {
         self.log_size() + 1
     }
     fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
-        eval_compression_constraints(&mut eval, &self.relations);
+        eval_compression_constraints(&mut eval, &self.relations, self.hash_function);
         eval
     }
 }
@@ -399,21 +676,80 @@ mod tests {
     use stwo_constraint_framework::assert_constraints_on_polys;
 
     use super::*;
-    use crate::components::{
-        compression::witness::{gen_interaction_trace, gen_trace},
-        scheduling::witness::gen_trace as gen_scheduling_trace,
+    use crate::{
+        components::{
+            compression::{
+                columns::{ChainColumns, RoundColumns},
+                witness::{gen_interaction_trace, gen_trace},
+            },
+            scheduling::witness::gen_trace as gen_scheduling_trace,
+            soundness::Harness,
+        },
+        hash_chain::chain_blocks,
+        message::{gen_blocks_for, generate_blocks},
     };
 
+    /// Names of the trace columns, following the layout of the witness.
+    fn column_names() -> Vec<String> {
+        let limbs = |prefix: &'static str, size: usize| {
+            (0..size).map(move |i| format!("{prefix}{}_{}", i / 2, ["low", "high"][i % 2]))
+        };
+        let chain = ChainColumns::NAMES
+            .iter()
+            .map(|name| format!("chain_{name}"));
+        let rounds = (0..N_COMPRESSION_ROUNDS).flat_map(|t| {
+            RoundColumns::NAMES
+                .iter()
+                .map(move |name| format!("round{t}_{name}"))
+        });
+        limbs("w", W_SIZE)
+            .chain(chain)
+            .chain(limbs("h_in", STATE_SIZE))
+            .chain(rounds)
+            .chain(limbs("h_out", STATE_SIZE))
+            .chain(limbs("carry", STATE_SIZE))
+            .collect()
+    }
+
     #[test]
     fn test_compression_constraints() {
         const LOG_N_ROWS: u32 = 4;
 
         // Trace.
-        let (scheduling_trace, _) = gen_scheduling_trace(LOG_N_ROWS);
-        let (trace, lookup_data) = gen_trace(&scheduling_trace);
+        let blocks = generate_blocks(1 << LOG_N_ROWS);
+        let (scheduling_trace, _) = gen_scheduling_trace(LOG_N_ROWS, &blocks);
+        let (trace, lookup_data) = gen_trace(&scheduling_trace, &blocks, HashFunction::Sha256);
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) =
+            gen_interaction_trace(&lookup_data, &relations, HashFunction::Sha256);
+
+        let traces = TreeVec::new(vec![vec![], trace, interaction_trace]);
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(LOG_N_ROWS),
+            |mut eval| {
+                eval_compression_constraints(&mut eval, &relations, HashFunction::Sha256);
+            },
+            claimed_sum,
+        );
+    }
+
+    #[test]
+    fn test_compression_constraints_linked_blocks() {
+        const LOG_N_ROWS: u32 = 4;
+
+        // Two headers hashed twice, the inner digests are linked to the second hashes.
+        let blocks = gen_blocks_for(HashFunction::Sha256d, &[vec![0x42; 80], vec![0x43; 80]]);
+        let (scheduling_trace, _) = gen_scheduling_trace(LOG_N_ROWS, &blocks);
+        let (trace, lookup_data) = gen_trace(&scheduling_trace, &blocks, HashFunction::Sha256d);
 
         let relations = Relations::dummy();
-        let (interaction_trace, claimed_sum) = gen_interaction_trace(&lookup_data, &relations);
+        let (interaction_trace, claimed_sum) =
+            gen_interaction_trace(&lookup_data, &relations, HashFunction::Sha256d);
 
         let traces = TreeVec::new(vec![vec![], trace, interaction_trace]);
         let trace_polys =
@@ -423,9 +759,73 @@ mod tests {
             &trace_polys,
             CanonicCoset::new(LOG_N_ROWS),
             |mut eval| {
-                eval_compression_constraints(&mut eval, &relations);
+                eval_compression_constraints(&mut eval, &relations, HashFunction::Sha256d);
             },
             claimed_sum,
         );
     }
+
+    #[test]
+    fn test_compression_constraints_chained_blocks() {
+        const LOG_N_ROWS: u32 = 4;
+
+        // A two-block seed hashed 14 times, each digest is chained into the next block.
+        let blocks = chain_blocks(&[0x42; 80], 14);
+        let (scheduling_trace, _) = gen_scheduling_trace(LOG_N_ROWS, &blocks);
+        let (trace, lookup_data) = gen_trace(&scheduling_trace, &blocks, HashFunction::Sha256);
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) =
+            gen_interaction_trace(&lookup_data, &relations, HashFunction::Sha256);
+
+        let traces = TreeVec::new(vec![vec![], trace, interaction_trace]);
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(LOG_N_ROWS),
+            |mut eval| {
+                eval_compression_constraints(&mut eval, &relations, HashFunction::Sha256);
+            },
+            claimed_sum,
+        );
+    }
+
+    #[test]
+    fn test_compression_soundness() {
+        const LOG_N_ROWS: u32 = 4;
+
+        // Every row is an active standalone block.
+        let blocks = generate_blocks(1 << LOG_N_ROWS);
+        let (scheduling_trace, _) = gen_scheduling_trace(LOG_N_ROWS, &blocks);
+        let (trace, lookup_data) = gen_trace(&scheduling_trace, &blocks, HashFunction::Sha256);
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) =
+            gen_interaction_trace(&lookup_data, &relations, HashFunction::Sha256);
+
+        let mut harness = Harness::new(column_names(), vec![], trace, interaction_trace, |polys| {
+            assert_constraints_on_polys(
+                polys,
+                CanonicCoset::new(LOG_N_ROWS),
+                |mut eval| {
+                    eval_compression_constraints(&mut eval, &relations, HashFunction::Sha256);
+                },
+                claimed_sum,
+            )
+        });
+
+        // Everything but the rounds between the first and the last one, which are checked with
+        // slow-tests. The last round and the feed-forward limbs cover the end of the block.
+        let first_round_end = W_SIZE + ChainColumns::SIZE + STATE_SIZE + RoundColumns::SIZE;
+        let output_start = first_round_end + RoundColumns::SIZE * (N_COMPRESSION_ROUNDS - 1);
+        let last_round_start = output_start - RoundColumns::SIZE;
+        let unconstrained = harness.unconstrained(1, |col| {
+            cfg!(feature = "slow-tests") || col < first_round_end || col >= last_round_start
+        });
+        assert!(
+            unconstrained.is_empty(),
+            "Unconstrained columns: {unconstrained:?}"
+        );
+    }
 }
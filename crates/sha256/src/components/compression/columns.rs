@@ -1,5 +1,57 @@
 use utils::trace_columns;
 
+trace_columns!(
+    ChainColumns,
+    message_id,
+    block_index,
+    is_first,
+    is_last,
+    is_active,
+    is_linked_left,
+    is_linked_right,
+    is_linked_out,
+    link_message_id,
+    link_position,
+    is_unclaimed_left,
+    is_unclaimed_right,
+    is_copied_left,
+    is_copied_right,
+    copy_left_message_id,
+    copy_left_position,
+    copy_right_message_id,
+    copy_right_position,
+    is_chained_in,
+    is_chained_out
+);
+
+trace_columns!(
+    ChainInteractionColumns,
+    message_id,
+    next_message_id,
+    block_index,
+    next_block_index,
+    left_position,
+    right_position,
+    is_first,
+    is_last,
+    is_active,
+    is_linked_left,
+    is_linked_right,
+    is_linked_out,
+    link_message_id,
+    link_position,
+    is_unclaimed_left,
+    is_unclaimed_right,
+    is_copied_left,
+    is_copied_right,
+    copy_left_message_id,
+    copy_left_position,
+    copy_right_message_id,
+    copy_right_position,
+    is_chained_in,
+    is_chained_out
+);
+
 trace_columns!(
     RoundColumns,
     e_i0_low,
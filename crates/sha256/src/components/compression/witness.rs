@@ -6,6 +6,7 @@
 use std::simd::u32x16;
 
 use itertools::izip;
+use num_traits::One;
 use stwo::{
     core::{
         fields::{m31::BaseField, qm31::QM31},
@@ -16,33 +17,51 @@ use stwo::{
         backend::simd::{
             column::BaseColumn,
             m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
             SimdBackend,
         },
         poly::{circle::CircleEvaluation, BitReversedOrder},
     },
 };
 use stwo_constraint_framework::{LogupTraceGenerator, Relation};
-use utils::{combine, consume_col, consume_pair};
+use utils::{combine, consume_pair, simd::for_each_row_chunk, write_col, write_pair};
 
 use crate::{
     components::{
-        compression::columns::{RoundColumns, RoundInteractionColumns},
-        W_SIZE,
+        compression::columns::{
+            ChainColumns, ChainInteractionColumns, RoundColumns, RoundInteractionColumns,
+        },
+        STATE_SIZE, W_SIZE,
     },
+    message::{block_column, padded_block_column, Block},
     partitions::{pext_u32x16, BigSigma0, BigSigma1},
     relations::Relations,
     sha256::{
-        big_sigma_0_u32x16, big_sigma_1_u32x16, ch_left_u32x16, ch_right_u32x16, maj_u32x16, H, K,
-        N_COMPRESSION_ROUNDS,
+        big_sigma_0_u32x16, big_sigma_1_u32x16, ch_left_u32x16, ch_right_u32x16, maj_u32x16,
+        HashFunction, CHUNK_SIZE, K, N_COMPRESSION_ROUNDS,
     },
 };
 
-const N_COLUMNS: usize = W_SIZE + RoundColumns::SIZE * N_COMPRESSION_ROUNDS;
-const N_INTERACTION_COLUMNS: usize = W_SIZE + RoundInteractionColumns::SIZE * N_COMPRESSION_ROUNDS;
+// Trace layout: W, chain columns, input state, rounds, output state and feed-forward carries.
+const H_IN_OFFSET: usize = W_SIZE + ChainColumns::SIZE;
+const ROUNDS_OFFSET: usize = H_IN_OFFSET + STATE_SIZE;
+const H_OUT_OFFSET: usize = ROUNDS_OFFSET + RoundColumns::SIZE * N_COMPRESSION_ROUNDS;
+const CARRY_OFFSET: usize = H_OUT_OFFSET + STATE_SIZE;
+const N_COLUMNS: usize = CARRY_OFFSET + STATE_SIZE;
+
+// Lookup data layout: W, rounds, chain columns, input and output states and feed-forward carries.
+const CHAIN_INTERACTION_OFFSET: usize =
+    W_SIZE + RoundInteractionColumns::SIZE * N_COMPRESSION_ROUNDS;
+const H_IN_INTERACTION_OFFSET: usize = CHAIN_INTERACTION_OFFSET + ChainInteractionColumns::SIZE;
+pub const H_OUT_INTERACTION_OFFSET: usize = H_IN_INTERACTION_OFFSET + STATE_SIZE;
+pub const CARRY_INTERACTION_OFFSET: usize = H_OUT_INTERACTION_OFFSET + STATE_SIZE;
+const N_INTERACTION_COLUMNS: usize = CARRY_INTERACTION_OFFSET + STATE_SIZE;
 
 #[allow(clippy::type_complexity)]
 pub fn gen_trace(
     w: &ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    blocks: &[Block],
+    hash_function: HashFunction,
 ) -> (
     ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
     Vec<Vec<u32x16>>,
@@ -51,10 +70,10 @@ pub fn gen_trace(
 
     // Initialize vec for all groups of columns
     let mut evals: Vec<Vec<u32x16>> = (0..N_COLUMNS)
-        .map(|_| Vec::with_capacity(simd_size))
+        .map(|_| vec![u32x16::splat(0); simd_size])
         .collect::<Vec<_>>();
     let mut lookup_data: Vec<Vec<u32x16>> = (0..N_INTERACTION_COLUMNS)
-        .map(|_| Vec::with_capacity(simd_size))
+        .map(|_| vec![u32x16::splat(0); simd_size])
         .collect::<Vec<_>>();
 
     // Generate round constants
@@ -65,19 +84,6 @@ pub fn gen_trace(
         .try_into()
         .unwrap();
 
-    // Get initial hash value
-    let mut hash_buffer: [Vec<u32x16>; H.len() * 2] = H
-        .iter()
-        .flat_map(|h| {
-            [
-                vec![u32x16::splat(h & 0xffff); simd_size],
-                vec![u32x16::splat(h >> 16); simd_size],
-            ]
-        })
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap();
-
     // Fill initial trace and lookup data
     evals
         .iter_mut()
@@ -104,295 +110,436 @@ pub fn gen_trace(
                 .collect();
         });
 
-    for round in 0..N_COMPRESSION_ROUNDS {
-        let index = W_SIZE + round * RoundColumns::SIZE;
-        let interaction_index = W_SIZE + round * RoundInteractionColumns::SIZE;
-
-        let a_low = &hash_buffer[0].clone();
-        let a_high = &hash_buffer[1].clone();
-        let b_low = &hash_buffer[2].clone();
-        let b_high = &hash_buffer[3].clone();
-        let c_low = &hash_buffer[4].clone();
-        let c_high = &hash_buffer[5].clone();
-        let d_low = &hash_buffer[6].clone();
-        let d_high = &hash_buffer[7].clone();
-        let e_low = &hash_buffer[8].clone();
-        let e_high = &hash_buffer[9].clone();
-        let f_low = &hash_buffer[10].clone();
-        let f_high = &hash_buffer[11].clone();
-        let g_low = &hash_buffer[12].clone();
-        let g_high = &hash_buffer[13].clone();
-        let h_low = &hash_buffer[14].clone();
-        let h_high = &hash_buffer[15].clone();
-
-        // Load K value
-        let k_low = k[2 * round];
-        let k_high = k[2 * round + 1];
-
-        for simd_row in 0..simd_size {
-            // Load W value
-            let w_low = evals[2 * round][simd_row];
-            let w_high = evals[2 * round + 1][simd_row];
-
-            // BIG_SIGMA1
-            // Decomposition over I0
-            let e_i0_low = e_low[simd_row] & u32x16::splat(BigSigma1::I0_L);
-            let e_i0_high = e_high[simd_row] & u32x16::splat(BigSigma1::I0_H);
-            let sigma_1 = big_sigma_1_u32x16(e_i0_low + (e_i0_high << 16));
-            let sigma_1_o0_low = sigma_1 & u32x16::splat(BigSigma1::O0_L);
-            let sigma_1_o0_high = (sigma_1 >> 16) & u32x16::splat(BigSigma1::O0_H);
-            let sigma_1_o20 = sigma_1 & u32x16::splat(BigSigma1::O2);
-            let sigma_1_o20_pext = pext_u32x16(sigma_1_o20, BigSigma1::O2);
-
-            // Decomposition over I1
-            let e_i1_low = e_low[simd_row] & u32x16::splat(BigSigma1::I1_L);
-            let e_i1_high = e_high[simd_row] & u32x16::splat(BigSigma1::I1_H);
-            let sigma_1 = big_sigma_1_u32x16(e_i1_low + (e_i1_high << 16));
-            let sigma_1_o1_low = sigma_1 & u32x16::splat(BigSigma1::O1_L);
-            let sigma_1_o1_high = (sigma_1 >> 16) & u32x16::splat(BigSigma1::O1_H);
-            let sigma_1_o21 = sigma_1 & u32x16::splat(BigSigma1::O2);
-            let sigma_1_o21_pext = pext_u32x16(sigma_1_o21, BigSigma1::O2);
-
-            // XOR the two O2 values
-            let big_sigma_1_o2 = sigma_1_o20 ^ sigma_1_o21;
-            let sigma_1_o2_low = big_sigma_1_o2 & u32x16::splat(0xffff);
-            let sigma_1_o2_high = big_sigma_1_o2 >> 16;
-
-            // Output
-            let sigma1_low = sigma_1_o0_low + sigma_1_o1_low + sigma_1_o2_low;
-            let sigma1_high = sigma_1_o0_high + sigma_1_o1_high + sigma_1_o2_high;
-
-            // CH
-            // left side
-            let f_i0_low = f_low[simd_row] & u32x16::splat(BigSigma1::I0_L);
-            let f_i0_high = f_high[simd_row] & u32x16::splat(BigSigma1::I0_H);
-            let f_i1_low = f_low[simd_row] & u32x16::splat(BigSigma1::I1_L);
-            let f_i1_high = f_high[simd_row] & u32x16::splat(BigSigma1::I1_H);
-            let ch_left_i0_low = ch_left_u32x16(e_i0_low, f_i0_low);
-            let ch_left_i0_high = ch_left_u32x16(e_i0_high, f_i0_high);
-            let ch_left_i1_low = ch_left_u32x16(e_i1_low, f_i1_low);
-            let ch_left_i1_high = ch_left_u32x16(e_i1_high, f_i1_high);
-
-            // right side
-            let g_i0_low = g_low[simd_row] & u32x16::splat(BigSigma1::I0_L);
-            let g_i0_high = g_high[simd_row] & u32x16::splat(BigSigma1::I0_H);
-            let g_i1_low = g_low[simd_row] & u32x16::splat(BigSigma1::I1_L);
-            let g_i1_high = g_high[simd_row] & u32x16::splat(BigSigma1::I1_H);
-            let ch_right_i0_low = ch_right_u32x16(e_i0_low, g_i0_low);
-            let ch_right_i0_high = ch_right_u32x16(e_i0_high, g_i0_high);
-            let ch_right_i1_low = ch_right_u32x16(e_i1_low, g_i1_low);
-            let ch_right_i1_high = ch_right_u32x16(e_i1_high, g_i1_high);
-
-            // Output
-            let ch_low = ch_left_i0_low + ch_left_i1_low + ch_right_i0_low + ch_right_i1_low;
-            let ch_high = ch_left_i0_high + ch_left_i1_high + ch_right_i0_high + ch_right_i1_high;
-
-            // BIG_SIGMA0
-            // Decomposition over I0
-            let a_i0_low = a_low[simd_row] & u32x16::splat(BigSigma0::I0_L);
-            let a_i0_high_0 = a_high[simd_row] & u32x16::splat(BigSigma0::I0_H0);
-            let a_i0_high_1 = (a_high[simd_row] >> 8) & u32x16::splat(BigSigma0::I0_H1);
-
-            let sigma_0 = big_sigma_0_u32x16(a_i0_low + (a_i0_high_0 << 16) + (a_i0_high_1 << 24));
-            let sigma_0_o0_low = sigma_0 & u32x16::splat(BigSigma0::O0_L);
-            let sigma_0_o0_high = (sigma_0 >> 16) & u32x16::splat(BigSigma0::O0_H);
-            let sigma_0_o20 = sigma_0 & u32x16::splat(BigSigma0::O2);
-            let sigma_0_o20_pext = pext_u32x16(sigma_0_o20, BigSigma0::O2);
-
-            // Decomposition over I1
-            let a_i1_low_0 = a_low[simd_row] & u32x16::splat(BigSigma0::I1_L0);
-            let a_i1_low_1 = (a_low[simd_row] >> 8) & u32x16::splat(BigSigma0::I1_L1);
-            let a_i1_high = a_high[simd_row] & u32x16::splat(BigSigma0::I1_H);
-
-            let sigma_0 = big_sigma_0_u32x16(a_i1_low_0 + (a_i1_low_1 << 8) + (a_i1_high << 16));
-            let sigma_0_o1_low = sigma_0 & u32x16::splat(BigSigma0::O1_L);
-            let sigma_0_o1_high = (sigma_0 >> 16) & u32x16::splat(BigSigma0::O1_H);
-            let sigma_0_o21 = sigma_0 & u32x16::splat(BigSigma0::O2);
-            let sigma_0_o21_pext = pext_u32x16(sigma_0_o21, BigSigma0::O2);
-
-            // XOR the two O2 values
-            let big_sigma_0_o2 = sigma_0_o20 ^ sigma_0_o21;
-            let sigma_0_o2_low = big_sigma_0_o2 & u32x16::splat(0xffff);
-            let sigma_0_o2_high = big_sigma_0_o2 >> 16;
-
-            // Output
-            let sigma_0_low = sigma_0_o0_low + sigma_0_o1_low + sigma_0_o2_low;
-            let sigma_0_high = sigma_0_o0_high + sigma_0_o1_high + sigma_0_o2_high;
-
-            // MAJ
-            let b_i0_low = b_low[simd_row] & u32x16::splat(BigSigma0::I0_L);
-            let b_i0_high_0 = b_high[simd_row] & u32x16::splat(BigSigma0::I0_H0);
-            let b_i0_high_1 = (b_high[simd_row] >> 8) & u32x16::splat(BigSigma0::I0_H1);
-            let b_i1_low_0 = b_low[simd_row] & u32x16::splat(BigSigma0::I1_L0);
-            let b_i1_low_1 = (b_low[simd_row] >> 8) & u32x16::splat(BigSigma0::I1_L1);
-            let b_i1_high = b_high[simd_row] & u32x16::splat(BigSigma0::I1_H);
-            let c_i0_low = c_low[simd_row] & u32x16::splat(BigSigma0::I0_L);
-            let c_i0_high_0 = c_high[simd_row] & u32x16::splat(BigSigma0::I0_H0);
-            let c_i0_high_1 = (c_high[simd_row] >> 8) & u32x16::splat(BigSigma0::I0_H1);
-            let c_i1_low_0 = c_low[simd_row] & u32x16::splat(BigSigma0::I1_L0);
-            let c_i1_low_1 = (c_low[simd_row] >> 8) & u32x16::splat(BigSigma0::I1_L1);
-            let c_i1_high = c_high[simd_row] & u32x16::splat(BigSigma0::I1_H);
-            let maj_i0_low = maj_u32x16(a_i0_low, b_i0_low, c_i0_low);
-            let maj_i0_high_0 = maj_u32x16(a_i0_high_0, b_i0_high_0, c_i0_high_0);
-            let maj_i0_high_1 = maj_u32x16(a_i0_high_1, b_i0_high_1, c_i0_high_1);
-            let maj_i1_low_0 = maj_u32x16(a_i1_low_0, b_i1_low_0, c_i1_low_0);
-            let maj_i1_low_1 = maj_u32x16(a_i1_low_1, b_i1_low_1, c_i1_low_1);
-            let maj_i1_high = maj_u32x16(a_i1_high, b_i1_high, c_i1_high);
-
-            // Output
-            let maj_low = maj_i0_low + maj_i1_low_0 + (maj_i1_low_1 << 8);
-            let maj_high = maj_i0_high_0 + (maj_i0_high_1 << 8) + maj_i1_high;
-
-            // TEMP
-            let temp1_low = h_low[simd_row] + sigma1_low + ch_low + k_low + w_low;
-            let temp1_high = h_high[simd_row] + sigma1_high + ch_high + k_high + w_high;
-            let temp2_low = sigma_0_low + maj_low;
-            let temp2_high = sigma_0_high + maj_high;
-
-            let e_carry_low = (temp1_low + d_low[simd_row]) >> 16;
-            let e_carry_high = (temp1_high + d_high[simd_row] + e_carry_low) >> 16;
-            let new_e_low = temp1_low + d_low[simd_row] - (e_carry_low << 16);
-            let new_e_high = temp1_high + d_high[simd_row] + e_carry_low - (e_carry_high << 16);
-            let a_carry_low = (temp1_low + temp2_low) >> 16;
-            let a_carry_high = (temp1_high + temp2_high + a_carry_low) >> 16;
-            let new_a_low = temp1_low + temp2_low - (a_carry_low << 16);
-            let new_a_high = temp1_high + temp2_high + a_carry_low - (a_carry_high << 16);
-
-            let trace_values: RoundColumns<u32x16> = RoundColumns {
-                e_i0_low: &e_i0_low,
-                e_i0_high: &e_i0_high,
-                sigma_1_o0_low: &sigma_1_o0_low,
-                sigma_1_o0_high: &sigma_1_o0_high,
-                sigma_1_o20_pext: &sigma_1_o20_pext,
-                sigma_1_o1_low: &sigma_1_o1_low,
-                sigma_1_o1_high: &sigma_1_o1_high,
-                sigma_1_o21_pext: &sigma_1_o21_pext,
-                sigma_1_o2_low: &sigma_1_o2_low,
-                sigma_1_o2_high: &sigma_1_o2_high,
-                f_i0_low: &f_i0_low,
-                f_i0_high: &f_i0_high,
-                ch_left_i0_low: &ch_left_i0_low,
-                ch_left_i0_high: &ch_left_i0_high,
-                ch_left_i1_low: &ch_left_i1_low,
-                ch_left_i1_high: &ch_left_i1_high,
-                g_i0_low: &g_i0_low,
-                g_i0_high: &g_i0_high,
-                ch_right_i0_low: &ch_right_i0_low,
-                ch_right_i0_high: &ch_right_i0_high,
-                ch_right_i1_low: &ch_right_i1_low,
-                ch_right_i1_high: &ch_right_i1_high,
-                a_i0_high_0: &a_i0_high_0,
-                a_i0_high_1: &a_i0_high_1,
-                a_i1_low_0: &a_i1_low_0,
-                a_i1_low_1: &a_i1_low_1,
-                sigma_0_o0_low: &sigma_0_o0_low,
-                sigma_0_o0_high: &sigma_0_o0_high,
-                sigma_0_o20_pext: &sigma_0_o20_pext,
-                sigma_0_o1_low: &sigma_0_o1_low,
-                sigma_0_o1_high: &sigma_0_o1_high,
-                sigma_0_o21_pext: &sigma_0_o21_pext,
-                sigma_0_o2_low: &sigma_0_o2_low,
-                sigma_0_o2_high: &sigma_0_o2_high,
-                b_i0_high_0: &b_i0_high_0,
-                b_i0_high_1: &b_i0_high_1,
-                b_i1_low_0: &b_i1_low_0,
-                b_i1_low_1: &b_i1_low_1,
-                c_i0_high_0: &c_i0_high_0,
-                c_i0_high_1: &c_i0_high_1,
-                c_i1_low_0: &c_i1_low_0,
-                c_i1_low_1: &c_i1_low_1,
-                maj_i0_low: &maj_i0_low,
-                maj_i0_high_0: &maj_i0_high_0,
-                maj_i0_high_1: &maj_i0_high_1,
-                maj_i1_low_0: &maj_i1_low_0,
-                maj_i1_low_1: &maj_i1_low_1,
-                maj_i1_high: &maj_i1_high,
-                e_carry_low: &e_carry_low,
-                e_carry_high: &e_carry_high,
-                a_carry_low: &a_carry_low,
-                a_carry_high: &a_carry_high,
-            };
-            for (i, value) in trace_values.iter().enumerate() {
-                evals[index + i].push(*value);
+    // Chain columns
+    let message_id = block_column(blocks, simd_size, |block| block.message_id);
+    let next_message_id = block_column(blocks, simd_size, |block| block.message_id + 1);
+    let block_index = block_column(blocks, simd_size, |block| block.index);
+    let next_block_index = block_column(blocks, simd_size, |block| block.index + 1);
+    let left_position = block_column(blocks, simd_size, |block| 2 * block.index);
+    let right_position = block_column(blocks, simd_size, |block| 2 * block.index + 1);
+    let is_first = block_column(blocks, simd_size, |block| block.is_first() as u32);
+    let is_last = block_column(blocks, simd_size, |block| block.is_last as u32);
+    let is_active = (0..simd_size)
+        .map(|simd_row| {
+            u32x16::from_array(std::array::from_fn(|lane| {
+                (((simd_row << LOG_N_LANES) + lane) < blocks.len()) as u32
+            }))
+        })
+        .collect::<Vec<_>>();
+    let is_linked_left = block_column(blocks, simd_size, |block| block.is_linked_left as u32);
+    let is_linked_right = block_column(blocks, simd_size, |block| block.is_linked_right as u32);
+    let is_linked_out = block_column(blocks, simd_size, |block| block.link.is_some() as u32);
+    let link_message_id = block_column(blocks, simd_size, |block| {
+        block.link.map_or(0, |link| link.message_id)
+    });
+    let link_position = block_column(blocks, simd_size, |block| {
+        block.link.map_or(0, |link| link.position)
+    });
+    let is_unclaimed_left = block_column(blocks, simd_size, |block| block.is_unclaimed_left as u32);
+    let is_unclaimed_right =
+        block_column(blocks, simd_size, |block| block.is_unclaimed_right as u32);
+    let is_copied_left = block_column(blocks, simd_size, |block| block.copy_left.is_some() as u32);
+    let is_copied_right =
+        block_column(blocks, simd_size, |block| block.copy_right.is_some() as u32);
+    let copy_left_message_id = block_column(blocks, simd_size, |block| {
+        block.copy_left.map_or(0, |link| link.message_id)
+    });
+    let copy_left_position = block_column(blocks, simd_size, |block| {
+        block.copy_left.map_or(0, |link| link.position)
+    });
+    let copy_right_message_id = block_column(blocks, simd_size, |block| {
+        block.copy_right.map_or(0, |link| link.message_id)
+    });
+    let copy_right_position = block_column(blocks, simd_size, |block| {
+        block.copy_right.map_or(0, |link| link.position)
+    });
+    let is_chained_in = block_column(blocks, simd_size, |block| block.is_chained_in as u32);
+    let is_chained_out = block_column(blocks, simd_size, |block| block.is_chained_out as u32);
+    let chain_values: ChainColumns<Vec<u32x16>> = ChainColumns {
+        message_id: &message_id,
+        block_index: &block_index,
+        is_first: &is_first,
+        is_last: &is_last,
+        is_active: &is_active,
+        is_linked_left: &is_linked_left,
+        is_linked_right: &is_linked_right,
+        is_linked_out: &is_linked_out,
+        link_message_id: &link_message_id,
+        link_position: &link_position,
+        is_unclaimed_left: &is_unclaimed_left,
+        is_unclaimed_right: &is_unclaimed_right,
+        is_copied_left: &is_copied_left,
+        is_copied_right: &is_copied_right,
+        copy_left_message_id: &copy_left_message_id,
+        copy_left_position: &copy_left_position,
+        copy_right_message_id: &copy_right_message_id,
+        copy_right_position: &copy_right_position,
+        is_chained_in: &is_chained_in,
+        is_chained_out: &is_chained_out,
+    };
+    for (i, value) in chain_values.iter().enumerate() {
+        evals[W_SIZE + i] = value.clone();
+    }
+    let chain_interaction_values: ChainInteractionColumns<Vec<u32x16>> = ChainInteractionColumns {
+        message_id: &message_id,
+        next_message_id: &next_message_id,
+        block_index: &block_index,
+        next_block_index: &next_block_index,
+        left_position: &left_position,
+        right_position: &right_position,
+        is_first: &is_first,
+        is_last: &is_last,
+        is_active: &is_active,
+        is_linked_left: &is_linked_left,
+        is_linked_right: &is_linked_right,
+        is_linked_out: &is_linked_out,
+        link_message_id: &link_message_id,
+        link_position: &link_position,
+        is_unclaimed_left: &is_unclaimed_left,
+        is_unclaimed_right: &is_unclaimed_right,
+        is_copied_left: &is_copied_left,
+        is_copied_right: &is_copied_right,
+        copy_left_message_id: &copy_left_message_id,
+        copy_left_position: &copy_left_position,
+        copy_right_message_id: &copy_right_message_id,
+        copy_right_position: &copy_right_position,
+        is_chained_in: &is_chained_in,
+        is_chained_out: &is_chained_out,
+    };
+    for (i, value) in chain_interaction_values.iter().enumerate() {
+        lookup_data[CHAIN_INTERACTION_OFFSET + i] = value.clone();
+    }
+
+    // Get initial hash value, padding blocks start from the IV of the hash function
+    let padding = Block::padding(hash_function);
+    let h_in: [Vec<u32x16>; STATE_SIZE] = std::array::from_fn(|i| {
+        padded_block_column(blocks, &padding, simd_size, |block| {
+            (block.h_in[i / 2] >> (16 * (i % 2))) & 0xffff
+        })
+    });
+    for (i, value) in h_in.into_iter().enumerate() {
+        lookup_data[H_IN_INTERACTION_OFFSET + i] = value.clone();
+        evals[H_IN_OFFSET + i] = value;
+    }
+
+    for_each_row_chunk(&mut evals, &mut lookup_data, |evals, lookup_data| {
+        for row in 0..evals[0].len() {
+            // The working state is kept in registers across the rounds
+            let mut hash_buffer: [u32x16; STATE_SIZE] =
+                std::array::from_fn(|i| evals[H_IN_OFFSET + i][row]);
+
+            for round in 0..N_COMPRESSION_ROUNDS {
+                let index = ROUNDS_OFFSET + round * RoundColumns::SIZE;
+                let interaction_index = W_SIZE + round * RoundInteractionColumns::SIZE;
+
+                let a_low = hash_buffer[0];
+                let a_high = hash_buffer[1];
+                let b_low = hash_buffer[2];
+                let b_high = hash_buffer[3];
+                let c_low = hash_buffer[4];
+                let c_high = hash_buffer[5];
+                let d_low = hash_buffer[6];
+                let d_high = hash_buffer[7];
+                let e_low = hash_buffer[8];
+                let e_high = hash_buffer[9];
+                let f_low = hash_buffer[10];
+                let f_high = hash_buffer[11];
+                let g_low = hash_buffer[12];
+                let g_high = hash_buffer[13];
+                let h_low = hash_buffer[14];
+                let h_high = hash_buffer[15];
+
+                // Load K and W values
+                let k_low = k[2 * round];
+                let k_high = k[2 * round + 1];
+                let w_low = evals[2 * round][row];
+                let w_high = evals[2 * round + 1][row];
+
+                // BIG_SIGMA1
+                // Decomposition over I0
+                let e_i0_low = e_low & u32x16::splat(BigSigma1::I0_L);
+                let e_i0_high = e_high & u32x16::splat(BigSigma1::I0_H);
+                let sigma_1 = big_sigma_1_u32x16(e_i0_low + (e_i0_high << 16));
+                let sigma_1_o0_low = sigma_1 & u32x16::splat(BigSigma1::O0_L);
+                let sigma_1_o0_high = (sigma_1 >> 16) & u32x16::splat(BigSigma1::O0_H);
+                let sigma_1_o20 = sigma_1 & u32x16::splat(BigSigma1::O2);
+                let sigma_1_o20_pext = pext_u32x16(sigma_1_o20, BigSigma1::O2);
+
+                // Decomposition over I1
+                let e_i1_low = e_low & u32x16::splat(BigSigma1::I1_L);
+                let e_i1_high = e_high & u32x16::splat(BigSigma1::I1_H);
+                let sigma_1 = big_sigma_1_u32x16(e_i1_low + (e_i1_high << 16));
+                let sigma_1_o1_low = sigma_1 & u32x16::splat(BigSigma1::O1_L);
+                let sigma_1_o1_high = (sigma_1 >> 16) & u32x16::splat(BigSigma1::O1_H);
+                let sigma_1_o21 = sigma_1 & u32x16::splat(BigSigma1::O2);
+                let sigma_1_o21_pext = pext_u32x16(sigma_1_o21, BigSigma1::O2);
+
+                // XOR the two O2 values
+                let big_sigma_1_o2 = sigma_1_o20 ^ sigma_1_o21;
+                let sigma_1_o2_low = big_sigma_1_o2 & u32x16::splat(0xffff);
+                let sigma_1_o2_high = big_sigma_1_o2 >> 16;
+
+                // Output
+                let sigma1_low = sigma_1_o0_low + sigma_1_o1_low + sigma_1_o2_low;
+                let sigma1_high = sigma_1_o0_high + sigma_1_o1_high + sigma_1_o2_high;
+
+                // CH
+                // left side
+                let f_i0_low = f_low & u32x16::splat(BigSigma1::I0_L);
+                let f_i0_high = f_high & u32x16::splat(BigSigma1::I0_H);
+                let f_i1_low = f_low & u32x16::splat(BigSigma1::I1_L);
+                let f_i1_high = f_high & u32x16::splat(BigSigma1::I1_H);
+                let ch_left_i0_low = ch_left_u32x16(e_i0_low, f_i0_low);
+                let ch_left_i0_high = ch_left_u32x16(e_i0_high, f_i0_high);
+                let ch_left_i1_low = ch_left_u32x16(e_i1_low, f_i1_low);
+                let ch_left_i1_high = ch_left_u32x16(e_i1_high, f_i1_high);
+
+                // right side
+                let g_i0_low = g_low & u32x16::splat(BigSigma1::I0_L);
+                let g_i0_high = g_high & u32x16::splat(BigSigma1::I0_H);
+                let g_i1_low = g_low & u32x16::splat(BigSigma1::I1_L);
+                let g_i1_high = g_high & u32x16::splat(BigSigma1::I1_H);
+                let ch_right_i0_low = ch_right_u32x16(e_i0_low, g_i0_low);
+                let ch_right_i0_high = ch_right_u32x16(e_i0_high, g_i0_high);
+                let ch_right_i1_low = ch_right_u32x16(e_i1_low, g_i1_low);
+                let ch_right_i1_high = ch_right_u32x16(e_i1_high, g_i1_high);
+
+                // Output
+                let ch_low = ch_left_i0_low + ch_left_i1_low + ch_right_i0_low + ch_right_i1_low;
+                let ch_high =
+                    ch_left_i0_high + ch_left_i1_high + ch_right_i0_high + ch_right_i1_high;
+
+                // BIG_SIGMA0
+                // Decomposition over I0
+                let a_i0_low = a_low & u32x16::splat(BigSigma0::I0_L);
+                let a_i0_high_0 = a_high & u32x16::splat(BigSigma0::I0_H0);
+                let a_i0_high_1 = (a_high >> 8) & u32x16::splat(BigSigma0::I0_H1);
+
+                let sigma_0 =
+                    big_sigma_0_u32x16(a_i0_low + (a_i0_high_0 << 16) + (a_i0_high_1 << 24));
+                let sigma_0_o0_low = sigma_0 & u32x16::splat(BigSigma0::O0_L);
+                let sigma_0_o0_high = (sigma_0 >> 16) & u32x16::splat(BigSigma0::O0_H);
+                let sigma_0_o20 = sigma_0 & u32x16::splat(BigSigma0::O2);
+                let sigma_0_o20_pext = pext_u32x16(sigma_0_o20, BigSigma0::O2);
+
+                // Decomposition over I1
+                let a_i1_low_0 = a_low & u32x16::splat(BigSigma0::I1_L0);
+                let a_i1_low_1 = (a_low >> 8) & u32x16::splat(BigSigma0::I1_L1);
+                let a_i1_high = a_high & u32x16::splat(BigSigma0::I1_H);
+
+                let sigma_0 =
+                    big_sigma_0_u32x16(a_i1_low_0 + (a_i1_low_1 << 8) + (a_i1_high << 16));
+                let sigma_0_o1_low = sigma_0 & u32x16::splat(BigSigma0::O1_L);
+                let sigma_0_o1_high = (sigma_0 >> 16) & u32x16::splat(BigSigma0::O1_H);
+                let sigma_0_o21 = sigma_0 & u32x16::splat(BigSigma0::O2);
+                let sigma_0_o21_pext = pext_u32x16(sigma_0_o21, BigSigma0::O2);
+
+                // XOR the two O2 values
+                let big_sigma_0_o2 = sigma_0_o20 ^ sigma_0_o21;
+                let sigma_0_o2_low = big_sigma_0_o2 & u32x16::splat(0xffff);
+                let sigma_0_o2_high = big_sigma_0_o2 >> 16;
+
+                // Output
+                let sigma_0_low = sigma_0_o0_low + sigma_0_o1_low + sigma_0_o2_low;
+                let sigma_0_high = sigma_0_o0_high + sigma_0_o1_high + sigma_0_o2_high;
+
+                // MAJ
+                let b_i0_low = b_low & u32x16::splat(BigSigma0::I0_L);
+                let b_i0_high_0 = b_high & u32x16::splat(BigSigma0::I0_H0);
+                let b_i0_high_1 = (b_high >> 8) & u32x16::splat(BigSigma0::I0_H1);
+                let b_i1_low_0 = b_low & u32x16::splat(BigSigma0::I1_L0);
+                let b_i1_low_1 = (b_low >> 8) & u32x16::splat(BigSigma0::I1_L1);
+                let b_i1_high = b_high & u32x16::splat(BigSigma0::I1_H);
+                let c_i0_low = c_low & u32x16::splat(BigSigma0::I0_L);
+                let c_i0_high_0 = c_high & u32x16::splat(BigSigma0::I0_H0);
+                let c_i0_high_1 = (c_high >> 8) & u32x16::splat(BigSigma0::I0_H1);
+                let c_i1_low_0 = c_low & u32x16::splat(BigSigma0::I1_L0);
+                let c_i1_low_1 = (c_low >> 8) & u32x16::splat(BigSigma0::I1_L1);
+                let c_i1_high = c_high & u32x16::splat(BigSigma0::I1_H);
+                let maj_i0_low = maj_u32x16(a_i0_low, b_i0_low, c_i0_low);
+                let maj_i0_high_0 = maj_u32x16(a_i0_high_0, b_i0_high_0, c_i0_high_0);
+                let maj_i0_high_1 = maj_u32x16(a_i0_high_1, b_i0_high_1, c_i0_high_1);
+                let maj_i1_low_0 = maj_u32x16(a_i1_low_0, b_i1_low_0, c_i1_low_0);
+                let maj_i1_low_1 = maj_u32x16(a_i1_low_1, b_i1_low_1, c_i1_low_1);
+                let maj_i1_high = maj_u32x16(a_i1_high, b_i1_high, c_i1_high);
+
+                // Output
+                let maj_low = maj_i0_low + maj_i1_low_0 + (maj_i1_low_1 << 8);
+                let maj_high = maj_i0_high_0 + (maj_i0_high_1 << 8) + maj_i1_high;
+
+                // TEMP
+                let temp1_low = h_low + sigma1_low + ch_low + k_low + w_low;
+                let temp1_high = h_high + sigma1_high + ch_high + k_high + w_high;
+                let temp2_low = sigma_0_low + maj_low;
+                let temp2_high = sigma_0_high + maj_high;
+
+                let e_carry_low = (temp1_low + d_low) >> 16;
+                let e_carry_high = (temp1_high + d_high + e_carry_low) >> 16;
+                let new_e_low = temp1_low + d_low - (e_carry_low << 16);
+                let new_e_high = temp1_high + d_high + e_carry_low - (e_carry_high << 16);
+                let a_carry_low = (temp1_low + temp2_low) >> 16;
+                let a_carry_high = (temp1_high + temp2_high + a_carry_low) >> 16;
+                let new_a_low = temp1_low + temp2_low - (a_carry_low << 16);
+                let new_a_high = temp1_high + temp2_high + a_carry_low - (a_carry_high << 16);
+
+                let trace_values: RoundColumns<u32x16> = RoundColumns {
+                    e_i0_low: &e_i0_low,
+                    e_i0_high: &e_i0_high,
+                    sigma_1_o0_low: &sigma_1_o0_low,
+                    sigma_1_o0_high: &sigma_1_o0_high,
+                    sigma_1_o20_pext: &sigma_1_o20_pext,
+                    sigma_1_o1_low: &sigma_1_o1_low,
+                    sigma_1_o1_high: &sigma_1_o1_high,
+                    sigma_1_o21_pext: &sigma_1_o21_pext,
+                    sigma_1_o2_low: &sigma_1_o2_low,
+                    sigma_1_o2_high: &sigma_1_o2_high,
+                    f_i0_low: &f_i0_low,
+                    f_i0_high: &f_i0_high,
+                    ch_left_i0_low: &ch_left_i0_low,
+                    ch_left_i0_high: &ch_left_i0_high,
+                    ch_left_i1_low: &ch_left_i1_low,
+                    ch_left_i1_high: &ch_left_i1_high,
+                    g_i0_low: &g_i0_low,
+                    g_i0_high: &g_i0_high,
+                    ch_right_i0_low: &ch_right_i0_low,
+                    ch_right_i0_high: &ch_right_i0_high,
+                    ch_right_i1_low: &ch_right_i1_low,
+                    ch_right_i1_high: &ch_right_i1_high,
+                    a_i0_high_0: &a_i0_high_0,
+                    a_i0_high_1: &a_i0_high_1,
+                    a_i1_low_0: &a_i1_low_0,
+                    a_i1_low_1: &a_i1_low_1,
+                    sigma_0_o0_low: &sigma_0_o0_low,
+                    sigma_0_o0_high: &sigma_0_o0_high,
+                    sigma_0_o20_pext: &sigma_0_o20_pext,
+                    sigma_0_o1_low: &sigma_0_o1_low,
+                    sigma_0_o1_high: &sigma_0_o1_high,
+                    sigma_0_o21_pext: &sigma_0_o21_pext,
+                    sigma_0_o2_low: &sigma_0_o2_low,
+                    sigma_0_o2_high: &sigma_0_o2_high,
+                    b_i0_high_0: &b_i0_high_0,
+                    b_i0_high_1: &b_i0_high_1,
+                    b_i1_low_0: &b_i1_low_0,
+                    b_i1_low_1: &b_i1_low_1,
+                    c_i0_high_0: &c_i0_high_0,
+                    c_i0_high_1: &c_i0_high_1,
+                    c_i1_low_0: &c_i1_low_0,
+                    c_i1_low_1: &c_i1_low_1,
+                    maj_i0_low: &maj_i0_low,
+                    maj_i0_high_0: &maj_i0_high_0,
+                    maj_i0_high_1: &maj_i0_high_1,
+                    maj_i1_low_0: &maj_i1_low_0,
+                    maj_i1_low_1: &maj_i1_low_1,
+                    maj_i1_high: &maj_i1_high,
+                    e_carry_low: &e_carry_low,
+                    e_carry_high: &e_carry_high,
+                    a_carry_low: &a_carry_low,
+                    a_carry_high: &a_carry_high,
+                };
+                for (i, value) in trace_values.iter().enumerate() {
+                    evals[index + i][row] = *value;
+                }
+
+                let interaction_values: RoundInteractionColumns<u32x16> = RoundInteractionColumns {
+                    e_i0_low: &e_i0_low,
+                    e_i0_high: &e_i0_high,
+                    sigma_1_o0_low: &sigma_1_o0_low,
+                    sigma_1_o0_high: &sigma_1_o0_high,
+                    sigma_1_o20_pext: &sigma_1_o20_pext,
+                    e_i1_low: &e_i1_low,
+                    e_i1_high: &e_i1_high,
+                    sigma_1_o1_low: &sigma_1_o1_low,
+                    sigma_1_o1_high: &sigma_1_o1_high,
+                    sigma_1_o21_pext: &sigma_1_o21_pext,
+                    sigma_1_o2_low: &sigma_1_o2_low,
+                    sigma_1_o2_high: &sigma_1_o2_high,
+                    f_i0_low: &f_i0_low,
+                    f_i0_high: &f_i0_high,
+                    f_i1_low: &f_i1_low,
+                    f_i1_high: &f_i1_high,
+                    ch_left_i0_low: &ch_left_i0_low,
+                    ch_left_i0_high: &ch_left_i0_high,
+                    ch_left_i1_low: &ch_left_i1_low,
+                    ch_left_i1_high: &ch_left_i1_high,
+                    g_i0_low: &g_i0_low,
+                    g_i0_high: &g_i0_high,
+                    g_i1_low: &g_i1_low,
+                    g_i1_high: &g_i1_high,
+                    ch_right_i0_low: &ch_right_i0_low,
+                    ch_right_i0_high: &ch_right_i0_high,
+                    ch_right_i1_low: &ch_right_i1_low,
+                    ch_right_i1_high: &ch_right_i1_high,
+                    a_i0_low: &a_i0_low,
+                    a_i0_high_0: &a_i0_high_0,
+                    a_i0_high_1: &a_i0_high_1,
+                    sigma_0_o0_low: &sigma_0_o0_low,
+                    sigma_0_o0_high: &sigma_0_o0_high,
+                    sigma_0_o20_pext: &sigma_0_o20_pext,
+                    a_i1_low_0: &a_i1_low_0,
+                    a_i1_low_1: &a_i1_low_1,
+                    a_i1_high: &a_i1_high,
+                    sigma_0_o1_low: &sigma_0_o1_low,
+                    sigma_0_o1_high: &sigma_0_o1_high,
+                    sigma_0_o21_pext: &sigma_0_o21_pext,
+                    sigma_0_o2_low: &sigma_0_o2_low,
+                    sigma_0_o2_high: &sigma_0_o2_high,
+                    b_i0_low: &b_i0_low,
+                    b_i0_high_0: &b_i0_high_0,
+                    b_i0_high_1: &b_i0_high_1,
+                    b_i1_low_0: &b_i1_low_0,
+                    b_i1_low_1: &b_i1_low_1,
+                    b_i1_high: &b_i1_high,
+                    c_i0_low: &c_i0_low,
+                    c_i0_high_0: &c_i0_high_0,
+                    c_i0_high_1: &c_i0_high_1,
+                    c_i1_low_0: &c_i1_low_0,
+                    c_i1_low_1: &c_i1_low_1,
+                    c_i1_high: &c_i1_high,
+                    maj_i0_low: &maj_i0_low,
+                    maj_i0_high_0: &maj_i0_high_0,
+                    maj_i0_high_1: &maj_i0_high_1,
+                    maj_i1_low_0: &maj_i1_low_0,
+                    maj_i1_low_1: &maj_i1_low_1,
+                    maj_i1_high: &maj_i1_high,
+                    e_carry_low: &e_carry_low,
+                    e_carry_high: &e_carry_high,
+                    new_e_low: &new_e_low,
+                    new_e_high: &new_e_high,
+                    a_carry_low: &a_carry_low,
+                    a_carry_high: &a_carry_high,
+                    new_a_low: &new_a_low,
+                    new_a_high: &new_a_high,
+                };
+                for (i, value) in interaction_values.iter().enumerate() {
+                    lookup_data[interaction_index + i][row] = *value;
+                }
+
+                hash_buffer = [
+                    new_a_low, new_a_high, a_low, a_high, b_low, b_high, c_low, c_high, new_e_low,
+                    new_e_high, e_low, e_high, f_low, f_high, g_low, g_high,
+                ];
             }
 
-            let interaction_values: RoundInteractionColumns<u32x16> = RoundInteractionColumns {
-                e_i0_low: &e_i0_low,
-                e_i0_high: &e_i0_high,
-                sigma_1_o0_low: &sigma_1_o0_low,
-                sigma_1_o0_high: &sigma_1_o0_high,
-                sigma_1_o20_pext: &sigma_1_o20_pext,
-                e_i1_low: &e_i1_low,
-                e_i1_high: &e_i1_high,
-                sigma_1_o1_low: &sigma_1_o1_low,
-                sigma_1_o1_high: &sigma_1_o1_high,
-                sigma_1_o21_pext: &sigma_1_o21_pext,
-                sigma_1_o2_low: &sigma_1_o2_low,
-                sigma_1_o2_high: &sigma_1_o2_high,
-                f_i0_low: &f_i0_low,
-                f_i0_high: &f_i0_high,
-                f_i1_low: &f_i1_low,
-                f_i1_high: &f_i1_high,
-                ch_left_i0_low: &ch_left_i0_low,
-                ch_left_i0_high: &ch_left_i0_high,
-                ch_left_i1_low: &ch_left_i1_low,
-                ch_left_i1_high: &ch_left_i1_high,
-                g_i0_low: &g_i0_low,
-                g_i0_high: &g_i0_high,
-                g_i1_low: &g_i1_low,
-                g_i1_high: &g_i1_high,
-                ch_right_i0_low: &ch_right_i0_low,
-                ch_right_i0_high: &ch_right_i0_high,
-                ch_right_i1_low: &ch_right_i1_low,
-                ch_right_i1_high: &ch_right_i1_high,
-                a_i0_low: &a_i0_low,
-                a_i0_high_0: &a_i0_high_0,
-                a_i0_high_1: &a_i0_high_1,
-                sigma_0_o0_low: &sigma_0_o0_low,
-                sigma_0_o0_high: &sigma_0_o0_high,
-                sigma_0_o20_pext: &sigma_0_o20_pext,
-                a_i1_low_0: &a_i1_low_0,
-                a_i1_low_1: &a_i1_low_1,
-                a_i1_high: &a_i1_high,
-                sigma_0_o1_low: &sigma_0_o1_low,
-                sigma_0_o1_high: &sigma_0_o1_high,
-                sigma_0_o21_pext: &sigma_0_o21_pext,
-                sigma_0_o2_low: &sigma_0_o2_low,
-                sigma_0_o2_high: &sigma_0_o2_high,
-                b_i0_low: &b_i0_low,
-                b_i0_high_0: &b_i0_high_0,
-                b_i0_high_1: &b_i0_high_1,
-                b_i1_low_0: &b_i1_low_0,
-                b_i1_low_1: &b_i1_low_1,
-                b_i1_high: &b_i1_high,
-                c_i0_low: &c_i0_low,
-                c_i0_high_0: &c_i0_high_0,
-                c_i0_high_1: &c_i0_high_1,
-                c_i1_low_0: &c_i1_low_0,
-                c_i1_low_1: &c_i1_low_1,
-                c_i1_high: &c_i1_high,
-                maj_i0_low: &maj_i0_low,
-                maj_i0_high_0: &maj_i0_high_0,
-                maj_i0_high_1: &maj_i0_high_1,
-                maj_i1_low_0: &maj_i1_low_0,
-                maj_i1_low_1: &maj_i1_low_1,
-                maj_i1_high: &maj_i1_high,
-                e_carry_low: &e_carry_low,
-                e_carry_high: &e_carry_high,
-                new_e_low: &new_e_low,
-                new_e_high: &new_e_high,
-                a_carry_low: &a_carry_low,
-                a_carry_high: &a_carry_high,
-                new_a_low: &new_a_low,
-                new_a_high: &new_a_high,
-            };
-            for (i, value) in interaction_values.iter().enumerate() {
-                lookup_data[interaction_index + i].push(*value);
+            // Feed-forward
+            for i in 0..STATE_SIZE / 2 {
+                let low = evals[H_IN_OFFSET + 2 * i][row] + hash_buffer[2 * i];
+                let carry_low = low >> 16;
+                let high = evals[H_IN_OFFSET + 2 * i + 1][row] + hash_buffer[2 * i + 1] + carry_low;
+                let carry_high = high >> 16;
+                let h_out_low = low - (carry_low << 16);
+                let h_out_high = high - (carry_high << 16);
+
+                evals[H_OUT_OFFSET + 2 * i][row] = h_out_low;
+                evals[H_OUT_OFFSET + 2 * i + 1][row] = h_out_high;
+                evals[CARRY_OFFSET + 2 * i][row] = carry_low;
+                evals[CARRY_OFFSET + 2 * i + 1][row] = carry_high;
+                lookup_data[H_OUT_INTERACTION_OFFSET + 2 * i][row] = h_out_low;
+                lookup_data[H_OUT_INTERACTION_OFFSET + 2 * i + 1][row] = h_out_high;
+                lookup_data[CARRY_INTERACTION_OFFSET + 2 * i][row] = carry_low;
+                lookup_data[CARRY_INTERACTION_OFFSET + 2 * i + 1][row] = carry_high;
             }
         }
-
-        update_hash_buffer(&mut hash_buffer, &evals, round);
-    }
+    });
 
     let domain = CanonicCoset::new(simd_size.ilog2() + LOG_N_LANES).circle_domain();
     let trace = evals
@@ -413,159 +560,11 @@ pub fn gen_trace(
     (trace, lookup_data)
 }
 
-/// Update the hash buffer with the values from the trace
-fn update_hash_buffer(hash_buffer: &mut [Vec<u32x16>], evals: &[Vec<u32x16>], round: usize) {
-    let d_low = &hash_buffer[6];
-    let d_high = &hash_buffer[7];
-    let h_low = &hash_buffer[14];
-    let h_high = &hash_buffer[15];
-
-    let w_low = evals[2 * round].clone();
-    let w_high = evals[2 * round + 1].clone();
-
-    let k_low = u32x16::splat(K[round] & 0xffff);
-    let k_high = u32x16::splat(K[round] >> 16);
-
-    let index = W_SIZE + RoundColumns::SIZE * round;
-    let RoundColumns {
-        e_i0_low: _,
-        e_i0_high: _,
-        sigma_1_o0_low,
-        sigma_1_o0_high,
-        sigma_1_o20_pext: _,
-        sigma_1_o1_low,
-        sigma_1_o1_high,
-        sigma_1_o21_pext: _,
-        sigma_1_o2_low,
-        sigma_1_o2_high,
-        f_i0_low: _,
-        f_i0_high: _,
-        ch_left_i0_low,
-        ch_left_i0_high,
-        ch_left_i1_low,
-        ch_left_i1_high,
-        g_i0_low: _,
-        g_i0_high: _,
-        ch_right_i0_low,
-        ch_right_i0_high,
-        ch_right_i1_low,
-        ch_right_i1_high,
-        a_i0_high_0: _,
-        a_i0_high_1: _,
-        a_i1_low_0: _,
-        a_i1_low_1: _,
-        sigma_0_o0_low,
-        sigma_0_o0_high,
-        sigma_0_o20_pext: _,
-        sigma_0_o1_low,
-        sigma_0_o1_high,
-        sigma_0_o21_pext: _,
-        sigma_0_o2_low,
-        sigma_0_o2_high,
-        b_i0_high_0: _,
-        b_i0_high_1: _,
-        b_i1_low_0: _,
-        b_i1_low_1: _,
-        c_i0_high_0: _,
-        c_i0_high_1: _,
-        c_i1_low_0: _,
-        c_i1_low_1: _,
-        maj_i0_low,
-        maj_i0_high_0,
-        maj_i0_high_1,
-        maj_i1_low_0,
-        maj_i1_low_1,
-        maj_i1_high,
-        e_carry_low,
-        e_carry_high,
-        a_carry_low,
-        a_carry_high,
-    } = RoundColumns::from_slice(&evals[index..(index + RoundColumns::SIZE)]);
-
-    let sigma1_low: Vec<u32x16> = izip!(sigma_1_o0_low, sigma_1_o1_low, sigma_1_o2_low)
-        .map(|(a, b, c)| a + b + c)
-        .collect();
-    let sigma1_high: Vec<u32x16> = izip!(sigma_1_o0_high, sigma_1_o1_high, sigma_1_o2_high)
-        .map(|(a, b, c)| a + b + c)
-        .collect();
-
-    let ch_low: Vec<u32x16> = izip!(
-        ch_left_i0_low,
-        ch_left_i1_low,
-        ch_right_i0_low,
-        ch_right_i1_low
-    )
-    .map(|(a, b, c, d)| a + b + c + d)
-    .collect();
-    let ch_high: Vec<u32x16> = izip!(
-        ch_left_i0_high,
-        ch_left_i1_high,
-        ch_right_i0_high,
-        ch_right_i1_high
-    )
-    .map(|(a, b, c, d)| a + b + c + d)
-    .collect();
-
-    let sigma_0_high: Vec<u32x16> = izip!(sigma_0_o0_high, sigma_0_o1_high, sigma_0_o2_high)
-        .map(|(a, b, c)| a + b + c)
-        .collect();
-    let sigma_0_low: Vec<u32x16> = izip!(sigma_0_o0_low, sigma_0_o1_low, sigma_0_o2_low)
-        .map(|(a, b, c)| a + b + c)
-        .collect();
-
-    let maj_high: Vec<u32x16> = izip!(maj_i0_high_0, maj_i0_high_1, maj_i1_high)
-        .map(|(a, b, c)| a + (b << 8) + c)
-        .collect();
-    let maj_low: Vec<u32x16> = izip!(maj_i0_low, maj_i1_low_0, maj_i1_low_1)
-        .map(|(a, b, c)| a + b + (c << 8))
-        .collect();
-
-    let temp1_high: Vec<u32x16> = izip!(h_high, sigma1_high, ch_high, w_high)
-        .map(|(a, b, c, d)| a + b + c + d + k_high)
-        .collect();
-    let temp1_low: Vec<u32x16> = izip!(h_low, sigma1_low, ch_low, w_low)
-        .map(|(a, b, c, d)| a + b + c + d + k_low)
-        .collect();
-
-    let temp2_high: Vec<u32x16> = izip!(sigma_0_high, maj_high).map(|(a, b)| a + b).collect();
-    let temp2_low: Vec<u32x16> = izip!(sigma_0_low, maj_low).map(|(a, b)| a + b).collect();
-
-    let e_low: Vec<u32x16> = izip!(d_low.clone(), temp1_low.clone(), e_carry_low)
-        .map(|(a, b, carry_low)| a + b - (carry_low << u32x16::splat(16)))
-        .collect();
-    let e_high: Vec<u32x16> = izip!(d_high, temp1_high.clone(), e_carry_low, e_carry_high)
-        .map(|(a, b, carry_low, carry_high)| a + b + carry_low - (carry_high << u32x16::splat(16)))
-        .collect();
-
-    let a_low: Vec<u32x16> = izip!(temp1_low, temp2_low, a_carry_low.clone())
-        .map(|(a, b, carry_low)| a + b - (carry_low << u32x16::splat(16)))
-        .collect();
-    let a_high = izip!(temp1_high, temp2_high, a_carry_low, a_carry_high)
-        .map(|(a, b, carry_low, carry_high)| a + b + carry_low - (carry_high << u32x16::splat(16)))
-        .collect();
-
-    hash_buffer[15] = hash_buffer[13].clone(); // h_high = g_high
-    hash_buffer[14] = hash_buffer[12].clone(); // h_low = g_low
-    hash_buffer[13] = hash_buffer[11].clone(); // g_high = f_high
-    hash_buffer[12] = hash_buffer[10].clone(); // g_low = f_low
-    hash_buffer[11] = hash_buffer[9].clone(); // f_high = e_high
-    hash_buffer[10] = hash_buffer[8].clone(); // f_low = e_low
-    hash_buffer[9] = e_high; // e_high = d_high + temp1_high
-    hash_buffer[8] = e_low; // e_low = d_low + temp1_low
-    hash_buffer[7] = hash_buffer[5].clone(); // d_high = c_high
-    hash_buffer[6] = hash_buffer[4].clone(); // d_low = c_low
-    hash_buffer[5] = hash_buffer[3].clone(); // c_high = b_high
-    hash_buffer[4] = hash_buffer[2].clone(); // c_low = b_low
-    hash_buffer[3] = hash_buffer[1].clone(); // b_high = a_high
-    hash_buffer[2] = hash_buffer[0].clone(); // b_low = a_low
-    hash_buffer[1] = a_high; // a_high = temp1_high + temp2_high
-    hash_buffer[0] = a_low; // a_low = temp1_low + temp2_low
-}
-
 #[allow(clippy::cognitive_complexity)]
 pub fn gen_interaction_trace(
     lookup_data: &[Vec<u32x16>],
     relations: &Relations,
+    hash_function: HashFunction,
 ) -> (
     ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
     QM31,
@@ -573,7 +572,9 @@ pub fn gen_interaction_trace(
     let simd_size = lookup_data[0].len();
     let mut interaction_trace = LogupTraceGenerator::new(simd_size.ilog2() + LOG_N_LANES);
 
-    for round in lookup_data[W_SIZE..].array_chunks::<{ RoundInteractionColumns::SIZE }>() {
+    for round in lookup_data[W_SIZE..CHAIN_INTERACTION_OFFSET]
+        .array_chunks::<{ RoundInteractionColumns::SIZE }>()
+    {
         let RoundInteractionColumns {
             e_i0_low,
             e_i0_high,
@@ -801,43 +802,440 @@ pub fn gen_interaction_trace(
         );
     }
 
-    // Consume W emitted by scheduling
+    // Range check the feed-forward additions
+    let feed_forward = izip!(
+        &lookup_data[H_OUT_INTERACTION_OFFSET..CARRY_INTERACTION_OFFSET],
+        &lookup_data[CARRY_INTERACTION_OFFSET..N_INTERACTION_COLUMNS]
+    )
+    .map(|(h_out, carry)| combine!(relations.range_check_add.add_4, [h_out, carry]))
+    .collect::<Vec<_>>();
+    for [d_0, d_1] in feed_forward.array_chunks::<2>() {
+        consume_pair!(d_0.iter().copied(), d_1.iter().copied(), interaction_trace);
+    }
+
+    // Consume W emitted by scheduling and the input chaining value
+    let ChainInteractionColumns {
+        message_id,
+        next_message_id,
+        block_index,
+        next_block_index,
+        left_position,
+        right_position,
+        is_first,
+        is_last,
+        is_active,
+        is_linked_left,
+        is_linked_right,
+        is_linked_out,
+        link_message_id,
+        link_position,
+        is_unclaimed_left,
+        is_unclaimed_right,
+        is_copied_left,
+        is_copied_right,
+        copy_left_message_id,
+        copy_left_position,
+        copy_right_message_id,
+        copy_right_position,
+        is_chained_in,
+        is_chained_out,
+    } = ChainInteractionColumns::from_slice(
+        &lookup_data[CHAIN_INTERACTION_OFFSET..H_IN_INTERACTION_OFFSET],
+    );
+    let h_in = &lookup_data[H_IN_INTERACTION_OFFSET..H_OUT_INTERACTION_OFFSET];
+    let h_out = &lookup_data[H_OUT_INTERACTION_OFFSET..CARRY_INTERACTION_OFFSET];
+
     let w = combine!(relations.w, &lookup_data[..W_SIZE]);
-    consume_col!(w, interaction_trace);
+    let chain_in = combine!(
+        relations.chain,
+        [message_id, block_index]
+            .into_iter()
+            .chain(h_in)
+            .collect::<Vec<_>>()
+    );
+    let chain_out = combine!(
+        relations.chain,
+        [message_id, next_block_index]
+            .into_iter()
+            .chain(h_out)
+            .collect::<Vec<_>>()
+    );
+
+    let one = PackedQM31::one();
+    write_pair!(
+        std::iter::repeat(-one),
+        w,
+        is_first.iter().map(|v| to_packed_qm31(*v) - one),
+        chain_in,
+        interaction_trace
+    );
+
+    // Emit the output chaining value and the public claims: each half of the message words
+    // unless it is linked, unclaimed or chained in, and the digest limbs of the output chaining
+    // value unless the digest is linked or chained out
+    let digest_size = 2 * hash_function.digest_words();
+    let zero = vec![u32x16::splat(0); simd_size];
+    let (left, right) = lookup_data[..CHUNK_SIZE].split_at(CHUNK_SIZE / 2);
+    let input_left = combine!(
+        relations.input_claim,
+        [message_id, left_position]
+            .into_iter()
+            .chain(left)
+            .collect::<Vec<_>>()
+    );
+    let input_right = combine!(
+        relations.input_claim,
+        [message_id, right_position]
+            .into_iter()
+            .chain(right)
+            .collect::<Vec<_>>()
+    );
+    let output_claim = combine!(
+        relations.output_claim,
+        [message_id]
+            .into_iter()
+            .chain(&h_out[..digest_size])
+            .chain(std::iter::repeat_n(&zero, STATE_SIZE - digest_size))
+            .collect::<Vec<_>>()
+    );
+    write_pair!(
+        is_last.iter().map(|v| one - to_packed_qm31(*v)),
+        chain_out,
+        izip!(is_active, is_linked_left, is_unclaimed_left, is_chained_in).map(|(a, l, p, c)| {
+            to_packed_qm31(*a) - to_packed_qm31(*l) - to_packed_qm31(*p) - to_packed_qm31(*c)
+        }),
+        input_left,
+        interaction_trace
+    );
+    write_pair!(
+        izip!(
+            is_active,
+            is_linked_right,
+            is_unclaimed_right,
+            is_chained_in
+        )
+        .map(|(a, l, p, c)| {
+            to_packed_qm31(*a) - to_packed_qm31(*l) - to_packed_qm31(*p) - to_packed_qm31(*c)
+        }),
+        input_right,
+        izip!(is_last, is_active, is_linked_out, is_chained_out).map(|(l, a, o, c)| {
+            to_packed_qm31(*l) + to_packed_qm31(*a) - one - to_packed_qm31(*o) - to_packed_qm31(*c)
+        }),
+        output_claim,
+        interaction_trace
+    );
+
+    // Emit the digest to link together with its public link claim, and consume the linked halves
+    let link_claim = combine!(
+        relations.link_claim,
+        [message_id, link_message_id, link_position]
+    );
+    let link_out = combine!(
+        relations.link,
+        [link_message_id, link_position]
+            .into_iter()
+            .chain(h_out)
+            .collect::<Vec<_>>()
+    );
+    let link_left = combine!(
+        relations.link,
+        [message_id, left_position]
+            .into_iter()
+            .chain(left)
+            .collect::<Vec<_>>()
+    );
+    let link_right = combine!(
+        relations.link,
+        [message_id, right_position]
+            .into_iter()
+            .chain(right)
+            .collect::<Vec<_>>()
+    );
+    write_pair!(
+        is_linked_out.iter().map(|v| to_packed_qm31(*v)),
+        link_claim,
+        is_linked_out.iter().map(|v| to_packed_qm31(*v)),
+        link_out,
+        interaction_trace
+    );
+    write_pair!(
+        is_linked_left.iter().map(|v| -to_packed_qm31(*v)),
+        link_left,
+        is_linked_right.iter().map(|v| -to_packed_qm31(*v)),
+        link_right,
+        interaction_trace
+    );
+
+    // Emit the copied halves together with their public copy claims
+    let copy_claim_left = combine!(
+        relations.copy_claim,
+        [
+            message_id,
+            left_position,
+            copy_left_message_id,
+            copy_left_position
+        ]
+    );
+    let copy_left = combine!(
+        relations.link,
+        [copy_left_message_id, copy_left_position]
+            .into_iter()
+            .chain(left)
+            .collect::<Vec<_>>()
+    );
+    let copy_claim_right = combine!(
+        relations.copy_claim,
+        [
+            message_id,
+            right_position,
+            copy_right_message_id,
+            copy_right_position
+        ]
+    );
+    let copy_right = combine!(
+        relations.link,
+        [copy_right_message_id, copy_right_position]
+            .into_iter()
+            .chain(right)
+            .collect::<Vec<_>>()
+    );
+    write_pair!(
+        is_copied_left.iter().map(|v| to_packed_qm31(*v)),
+        copy_claim_left,
+        is_copied_left.iter().map(|v| to_packed_qm31(*v)),
+        copy_left,
+        interaction_trace
+    );
+    write_pair!(
+        is_copied_right.iter().map(|v| to_packed_qm31(*v)),
+        copy_claim_right,
+        is_copied_right.iter().map(|v| to_packed_qm31(*v)),
+        copy_right,
+        interaction_trace
+    );
+
+    // Emit the digest chained to the next message, and consume the chained left half
+    let chained_out = combine!(
+        relations.chained,
+        [next_message_id]
+            .into_iter()
+            .chain(h_out)
+            .collect::<Vec<_>>()
+    );
+    let chained_in = combine!(
+        relations.chained,
+        [message_id].into_iter().chain(left).collect::<Vec<_>>()
+    );
+    write_pair!(
+        is_chained_out.iter().map(|v| to_packed_qm31(*v)),
+        chained_out,
+        is_chained_in.iter().map(|v| -to_packed_qm31(*v)),
+        chained_in,
+        interaction_trace
+    );
 
     interaction_trace.finalize_last()
 }
 
+#[inline(always)]
+fn to_packed_qm31(value: u32x16) -> PackedQM31 {
+    PackedQM31::from(unsafe { PackedM31::from_simd_unchecked(value) })
+}
+
 #[cfg(test)]
 mod tests {
+    use sha2::{Digest, Sha256};
+
     use super::*;
     use crate::{
         components::scheduling::witness::gen_trace as gen_schedule,
-        sha256::{process_chunk_u32x16, CHUNK_SIZE},
+        message::{gen_blocks, generate_blocks, to_bytes},
+        sha256::{process_chunk_u32x16, H},
     };
 
+    fn to_u32x16(
+        trace: &[CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>],
+    ) -> Vec<Vec<u32x16>> {
+        trace
+            .iter()
+            .map(|eval| {
+                eval.data
+                    .clone()
+                    .into_iter()
+                    .map(|x| x.into_simd())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Recomputes the working state of the next round from the values of the trace
+    fn update_hash_buffer(hash_buffer: &mut [Vec<u32x16>], evals: &[Vec<u32x16>], round: usize) {
+        let d_low = &hash_buffer[6];
+        let d_high = &hash_buffer[7];
+        let h_low = &hash_buffer[14];
+        let h_high = &hash_buffer[15];
+
+        let w_low = evals[2 * round].clone();
+        let w_high = evals[2 * round + 1].clone();
+
+        let k_low = u32x16::splat(K[round] & 0xffff);
+        let k_high = u32x16::splat(K[round] >> 16);
+
+        let index = ROUNDS_OFFSET + RoundColumns::SIZE * round;
+        let RoundColumns {
+            e_i0_low: _,
+            e_i0_high: _,
+            sigma_1_o0_low,
+            sigma_1_o0_high,
+            sigma_1_o20_pext: _,
+            sigma_1_o1_low,
+            sigma_1_o1_high,
+            sigma_1_o21_pext: _,
+            sigma_1_o2_low,
+            sigma_1_o2_high,
+            f_i0_low: _,
+            f_i0_high: _,
+            ch_left_i0_low,
+            ch_left_i0_high,
+            ch_left_i1_low,
+            ch_left_i1_high,
+            g_i0_low: _,
+            g_i0_high: _,
+            ch_right_i0_low,
+            ch_right_i0_high,
+            ch_right_i1_low,
+            ch_right_i1_high,
+            a_i0_high_0: _,
+            a_i0_high_1: _,
+            a_i1_low_0: _,
+            a_i1_low_1: _,
+            sigma_0_o0_low,
+            sigma_0_o0_high,
+            sigma_0_o20_pext: _,
+            sigma_0_o1_low,
+            sigma_0_o1_high,
+            sigma_0_o21_pext: _,
+            sigma_0_o2_low,
+            sigma_0_o2_high,
+            b_i0_high_0: _,
+            b_i0_high_1: _,
+            b_i1_low_0: _,
+            b_i1_low_1: _,
+            c_i0_high_0: _,
+            c_i0_high_1: _,
+            c_i1_low_0: _,
+            c_i1_low_1: _,
+            maj_i0_low,
+            maj_i0_high_0,
+            maj_i0_high_1,
+            maj_i1_low_0,
+            maj_i1_low_1,
+            maj_i1_high,
+            e_carry_low,
+            e_carry_high,
+            a_carry_low,
+            a_carry_high,
+        } = RoundColumns::from_slice(&evals[index..(index + RoundColumns::SIZE)]);
+
+        let sigma1_low: Vec<u32x16> = izip!(sigma_1_o0_low, sigma_1_o1_low, sigma_1_o2_low)
+            .map(|(a, b, c)| a + b + c)
+            .collect();
+        let sigma1_high: Vec<u32x16> = izip!(sigma_1_o0_high, sigma_1_o1_high, sigma_1_o2_high)
+            .map(|(a, b, c)| a + b + c)
+            .collect();
+
+        let ch_low: Vec<u32x16> = izip!(
+            ch_left_i0_low,
+            ch_left_i1_low,
+            ch_right_i0_low,
+            ch_right_i1_low
+        )
+        .map(|(a, b, c, d)| a + b + c + d)
+        .collect();
+        let ch_high: Vec<u32x16> = izip!(
+            ch_left_i0_high,
+            ch_left_i1_high,
+            ch_right_i0_high,
+            ch_right_i1_high
+        )
+        .map(|(a, b, c, d)| a + b + c + d)
+        .collect();
+
+        let sigma_0_high: Vec<u32x16> = izip!(sigma_0_o0_high, sigma_0_o1_high, sigma_0_o2_high)
+            .map(|(a, b, c)| a + b + c)
+            .collect();
+        let sigma_0_low: Vec<u32x16> = izip!(sigma_0_o0_low, sigma_0_o1_low, sigma_0_o2_low)
+            .map(|(a, b, c)| a + b + c)
+            .collect();
+
+        let maj_high: Vec<u32x16> = izip!(maj_i0_high_0, maj_i0_high_1, maj_i1_high)
+            .map(|(a, b, c)| a + (b << 8) + c)
+            .collect();
+        let maj_low: Vec<u32x16> = izip!(maj_i0_low, maj_i1_low_0, maj_i1_low_1)
+            .map(|(a, b, c)| a + b + (c << 8))
+            .collect();
+
+        let temp1_high: Vec<u32x16> = izip!(h_high, sigma1_high, ch_high, w_high)
+            .map(|(a, b, c, d)| a + b + c + d + k_high)
+            .collect();
+        let temp1_low: Vec<u32x16> = izip!(h_low, sigma1_low, ch_low, w_low)
+            .map(|(a, b, c, d)| a + b + c + d + k_low)
+            .collect();
+
+        let temp2_high: Vec<u32x16> = izip!(sigma_0_high, maj_high).map(|(a, b)| a + b).collect();
+        let temp2_low: Vec<u32x16> = izip!(sigma_0_low, maj_low).map(|(a, b)| a + b).collect();
+
+        let e_low: Vec<u32x16> = izip!(d_low.clone(), temp1_low.clone(), e_carry_low)
+            .map(|(a, b, carry_low)| a + b - (carry_low << u32x16::splat(16)))
+            .collect();
+        let e_high: Vec<u32x16> = izip!(d_high, temp1_high.clone(), e_carry_low, e_carry_high)
+            .map(|(a, b, carry_low, carry_high)| {
+                a + b + carry_low - (carry_high << u32x16::splat(16))
+            })
+            .collect();
+
+        let a_low: Vec<u32x16> = izip!(temp1_low, temp2_low, a_carry_low.clone())
+            .map(|(a, b, carry_low)| a + b - (carry_low << u32x16::splat(16)))
+            .collect();
+        let a_high = izip!(temp1_high, temp2_high, a_carry_low, a_carry_high)
+            .map(|(a, b, carry_low, carry_high)| {
+                a + b + carry_low - (carry_high << u32x16::splat(16))
+            })
+            .collect();
+
+        hash_buffer[15] = hash_buffer[13].clone(); // h_high = g_high
+        hash_buffer[14] = hash_buffer[12].clone(); // h_low = g_low
+        hash_buffer[13] = hash_buffer[11].clone(); // g_high = f_high
+        hash_buffer[12] = hash_buffer[10].clone(); // g_low = f_low
+        hash_buffer[11] = hash_buffer[9].clone(); // f_high = e_high
+        hash_buffer[10] = hash_buffer[8].clone(); // f_low = e_low
+        hash_buffer[9] = e_high; // e_high = d_high + temp1_high
+        hash_buffer[8] = e_low; // e_low = d_low + temp1_low
+        hash_buffer[7] = hash_buffer[5].clone(); // d_high = c_high
+        hash_buffer[6] = hash_buffer[4].clone(); // d_low = c_low
+        hash_buffer[5] = hash_buffer[3].clone(); // c_high = b_high
+        hash_buffer[4] = hash_buffer[2].clone(); // c_low = b_low
+        hash_buffer[3] = hash_buffer[1].clone(); // b_high = a_high
+        hash_buffer[2] = hash_buffer[0].clone(); // b_low = a_low
+        hash_buffer[1] = a_high; // a_high = temp1_high + temp2_high
+        hash_buffer[0] = a_low; // a_low = temp1_low + temp2_low
+    }
+
     #[test]
     fn test_gen_trace_columns_count() {
-        let (schedule, _) = gen_schedule(LOG_N_LANES);
-        let (trace, _) = gen_trace(&schedule);
+        let blocks = generate_blocks(1 << LOG_N_LANES);
+        let (schedule, _) = gen_schedule(LOG_N_LANES, &blocks);
+        let (trace, _) = gen_trace(&schedule, &blocks, HashFunction::Sha256);
         assert_eq!(trace.len(), N_COLUMNS);
     }
 
     #[test]
     fn test_gen_trace_values() {
         let log_size = LOG_N_LANES;
-        let (schedule, _) = gen_schedule(log_size);
-        let (trace, _) = gen_trace(&schedule);
-        let chunk = trace[0..CHUNK_SIZE]
-            .iter()
-            .map(|eval| {
-                eval.data
-                    .clone()
-                    .into_iter()
-                    .map(|x| x.into_simd())
-                    .collect::<Vec<u32x16>>()
-            })
-            .collect::<Vec<_>>();
+        let blocks = generate_blocks(1 << log_size);
+        let (schedule, _) = gen_schedule(log_size, &blocks);
+        let (trace, _) = gen_trace(&schedule, &blocks, HashFunction::Sha256);
+        let chunk = to_u32x16(&trace[0..CHUNK_SIZE]);
         let chunk = std::array::from_fn(|i| chunk[2 * i][0] + (chunk[2 * i + 1][0] << 16));
         let h = std::array::from_fn(|i| u32x16::splat(H[i]));
         let expected = process_chunk_u32x16(chunk, h);
@@ -854,14 +1252,7 @@ mod tests {
             .try_into()
             .unwrap();
 
-        let evals: [Vec<u32x16>; N_COLUMNS] = std::array::from_fn(|i| {
-            trace[i]
-                .data
-                .clone()
-                .into_iter()
-                .map(|x| x.into_simd())
-                .collect()
-        });
+        let evals = to_u32x16(&trace);
         for round in 0..64 {
             update_hash_buffer(&mut hash_buffer, &evals, round);
         }
@@ -872,4 +1263,47 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_gen_trace_chained_blocks() {
+        let blocks = gen_blocks(&[vec![0xab; 200], b"abc".to_vec()]);
+        let (schedule, _) = gen_schedule(LOG_N_LANES, &blocks);
+        let (trace, _) = gen_trace(&schedule, &blocks, HashFunction::Sha256);
+        let evals = to_u32x16(&trace);
+
+        for (row, block) in blocks.iter().enumerate() {
+            let h_in: [u32; 8] = std::array::from_fn(|i| {
+                evals[H_IN_OFFSET + 2 * i][0][row] + (evals[H_IN_OFFSET + 2 * i + 1][0][row] << 16)
+            });
+            let h_out: [u32; 8] = std::array::from_fn(|i| {
+                evals[H_OUT_OFFSET + 2 * i][0][row]
+                    + (evals[H_OUT_OFFSET + 2 * i + 1][0][row] << 16)
+            });
+            assert_eq!(h_in, block.h_in);
+            assert_eq!(h_out, block.h_out());
+        }
+    }
+
+    #[test]
+    fn test_gen_trace_digests() {
+        let messages = [b"abc".to_vec(), vec![0x42; 100], vec![]];
+        let blocks = gen_blocks(&messages);
+        let (schedule, _) = gen_schedule(LOG_N_LANES, &blocks);
+        let (trace, _) = gen_trace(&schedule, &blocks, HashFunction::Sha256);
+        let evals = to_u32x16(&trace[H_OUT_OFFSET..CARRY_OFFSET]);
+
+        let digests = blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| block.is_last)
+            .map(|(row, _)| {
+                to_bytes(std::array::from_fn(|i| {
+                    evals[2 * i][0][row] + (evals[2 * i + 1][0][row] << 16)
+                }))
+            })
+            .collect::<Vec<_>>();
+        for (digest, message) in digests.iter().zip(messages.iter()) {
+            assert_eq!(digest[..], Sha256::digest(message)[..]);
+        }
+    }
 }
@@ -1,35 +1,42 @@
 use std::simd::u32x16;
 
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
 use stwo::{
     core::{
         air::Component,
-        channel::MerkleChannel,
+        channel::Channel,
         fields::{m31::BaseField, qm31::SecureField},
         pcs::TreeVec,
-        poly::circle::CanonicCoset,
         ColumnVec,
     },
     prover::{
-        backend::{
-            simd::{m31::LOG_N_LANES, SimdBackend},
-            BackendForChannel, Column,
-        },
+        backend::simd::{m31::LOG_N_LANES, SimdBackend},
         poly::{circle::CircleEvaluation, BitReversedOrder},
-        CommitmentSchemeProver, ComponentProver,
+        ComponentProver,
     },
 };
 use stwo_constraint_framework::{
-    relation_tracker::{add_to_relation_entries, RelationSummary, RelationTrackerEntry},
+    relation_tracker::{add_to_relation_entries, RelationTrackerEntry},
     TraceLocationAllocator,
 };
 use tracing::{span, Level};
+use utils::stwo::air::{Air, AirComponents, LogupSum, Trace};
 
-use crate::relations::Relations;
+use crate::{
+    message::Block,
+    public::{self, Claims},
+    relations::Relations,
+    sha256::HashFunction,
+};
 pub const W_SIZE: usize = 128; // 128 u16 = 64 u32
+pub const STATE_SIZE: usize = 16; // 16 u16 = 8 u32
 
 pub mod compression;
 pub mod preprocessed;
 pub mod scheduling;
+#[cfg(test)]
+pub(crate) mod soundness;
 
 pub struct LookupData {
     pub scheduling: Vec<Vec<u32x16>>,
@@ -37,14 +44,38 @@ pub struct LookupData {
     pub preprocessed: preprocessed::Traces,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ClaimedSum {
     pub scheduling: SecureField,
     pub compression: SecureField,
     pub preprocessed: preprocessed::ClaimedSum,
 }
 
+impl ClaimedSum {
+    pub fn zero() -> Self {
+        Self {
+            scheduling: SecureField::zero(),
+            compression: SecureField::zero(),
+            preprocessed: preprocessed::ClaimedSum::zero(),
+        }
+    }
+}
+
+impl LogupSum for ClaimedSum {
+    fn sum(&self) -> SecureField {
+        self.scheduling + self.compression + self.preprocessed.sum()
+    }
+
+    fn mix_into(&self, channel: &mut impl Channel) {
+        channel.mix_felts(&[self.scheduling, self.compression]);
+        self.preprocessed.mix_into(channel);
+    }
+}
+
 pub fn gen_trace(
     log_size: u32,
+    blocks: &[Block],
+    hash_function: HashFunction,
 ) -> (
     ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
     LookupData,
@@ -52,12 +83,13 @@ pub fn gen_trace(
     assert!(log_size >= LOG_N_LANES);
 
     let span = span!(Level::INFO, "Scheduling").entered();
-    let (scheduling_trace, scheduling_lookup_data) = scheduling::witness::gen_trace(log_size);
+    let (scheduling_trace, scheduling_lookup_data) =
+        scheduling::witness::gen_trace(log_size, blocks);
     span.exit();
 
     let span = span!(Level::INFO, "Compression").entered();
     let (compression_trace, compression_lookup_data) =
-        compression::witness::gen_trace(&scheduling_trace);
+        compression::witness::gen_trace(&scheduling_trace, blocks, hash_function);
     span.exit();
 
     let span = span!(Level::INFO, "Preprocessed").entered();
@@ -85,6 +117,7 @@ pub fn gen_trace(
 pub fn gen_interaction_trace(
     lookup_data: LookupData,
     relations: &Relations,
+    hash_function: HashFunction,
 ) -> (
     ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
     ClaimedSum,
@@ -96,7 +129,11 @@ pub fn gen_interaction_trace(
 
     let span = span!(Level::INFO, "Compression").entered();
     let (compression_interaction_trace, compression_claimed_sum) =
-        compression::witness::gen_interaction_trace(&lookup_data.compression, relations);
+        compression::witness::gen_interaction_trace(
+            &lookup_data.compression,
+            relations,
+            hash_function,
+        );
     span.exit();
 
     let span = span!(Level::INFO, "Preprocessed").entered();
@@ -132,6 +169,7 @@ pub struct Components {
 impl Components {
     pub fn new(
         log_size: u32,
+        hash_function: HashFunction,
         location_allocator: &mut TraceLocationAllocator,
         relations: &Relations,
         claimed_sum: &ClaimedSum,
@@ -150,6 +188,7 @@ impl Components {
                 compression::air::Eval {
                     log_size,
                     relations: relations.clone(),
+                    hash_function,
                 },
                 claimed_sum.compression,
             ),
@@ -163,48 +202,96 @@ impl Components {
     }
 }
 
-impl Components {
-    pub fn provers(&self) -> Vec<&dyn ComponentProver<SimdBackend>> {
+impl AirComponents for Components {
+    fn provers(&self) -> Vec<&dyn ComponentProver<SimdBackend>> {
         let mut provers: Vec<&dyn ComponentProver<SimdBackend>> =
             vec![&self.scheduling, &self.compression];
         provers.extend(self.preprocessed.provers());
         provers
     }
 
-    pub fn track_relations<MC: MerkleChannel>(
-        &self,
-        commitment_scheme: &CommitmentSchemeProver<'_, SimdBackend, MC>,
-    ) -> RelationSummary
-    where
-        SimdBackend: BackendForChannel<MC>,
-    {
-        let evals = commitment_scheme.trace().polys.map(|tree| {
-            tree.iter()
-                .map(|poly| {
-                    poly.evaluate(CanonicCoset::new(poly.log_size()).circle_domain())
-                        .values
-                        .to_cpu()
-                })
-                .collect()
-        });
-        let evals = &evals.as_ref();
-        let trace = &evals.into();
-
-        let entries: Vec<RelationTrackerEntry> = itertools::chain!(
-            add_to_relation_entries(&self.scheduling, trace),
-            add_to_relation_entries(&self.compression, trace),
-        )
-        .chain(self.preprocessed.relation_entries(trace))
-        .collect();
-
-        RelationSummary::summarize_relations(&entries).cleaned()
+    fn components(&self) -> Vec<&dyn Component> {
+        let mut components: Vec<&dyn Component> = vec![&self.scheduling, &self.compression];
+        components.extend(self.preprocessed.components());
+        components
     }
 
-    pub fn trace_log_degree_bounds(&self) -> Vec<TreeVec<ColumnVec<u32>>> {
+    fn trace_log_degree_bounds(&self) -> Vec<TreeVec<ColumnVec<u32>>> {
         let mut log_degree_bounds: Vec<TreeVec<ColumnVec<u32>>> = Vec::new();
         log_degree_bounds.push(self.scheduling.trace_log_degree_bounds());
         log_degree_bounds.push(self.compression.trace_log_degree_bounds());
         log_degree_bounds.extend(self.preprocessed.trace_log_degree_bounds());
         log_degree_bounds
     }
+
+    fn relation_entries(&self, trace: &TreeVec<Vec<&Vec<BaseField>>>) -> Vec<RelationTrackerEntry> {
+        itertools::chain!(
+            add_to_relation_entries(&self.scheduling, trace),
+            add_to_relation_entries(&self.compression, trace),
+        )
+        .chain(self.preprocessed.relation_entries(trace))
+        .collect()
+    }
+}
+
+/// The SHA-256 AIR for a hash function built on its compression, proving batches of blocks.
+pub struct Sha256Air {
+    pub hash_function: HashFunction,
+}
+
+impl Air for Sha256Air {
+    type Input = [Block];
+    type Claims = Claims;
+    type Relations = Relations;
+    type LookupData = LookupData;
+    type ClaimedSum = ClaimedSum;
+    type Components = Components;
+
+    fn claims(&self, blocks: &[Block]) -> Claims {
+        public::block_claims(self.hash_function, blocks)
+    }
+
+    fn mix_claims(&self, claims: &Claims, channel: &mut impl Channel) {
+        public::mix_into(claims, channel);
+    }
+
+    fn draw_relations(&self, channel: &mut impl Channel) -> Relations {
+        Relations::draw(channel)
+    }
+
+    fn dummy_relations(&self) -> Relations {
+        Relations::dummy()
+    }
+
+    fn logup_sum(&self, claims: &Claims, relations: &Relations) -> SecureField {
+        public::logup_sum(claims, relations)
+    }
+
+    fn gen_trace(&self, log_size: u32, blocks: &[Block]) -> (Trace, LookupData) {
+        gen_trace(log_size, blocks, self.hash_function)
+    }
+
+    fn gen_interaction_trace(
+        &self,
+        lookup_data: LookupData,
+        relations: &Relations,
+    ) -> (Trace, ClaimedSum) {
+        gen_interaction_trace(lookup_data, relations, self.hash_function)
+    }
+
+    fn components(
+        &self,
+        log_size: u32,
+        location_allocator: &mut TraceLocationAllocator,
+        relations: &Relations,
+        claimed_sum: &ClaimedSum,
+    ) -> Components {
+        Components::new(
+            log_size,
+            self.hash_function,
+            location_allocator,
+            relations,
+            claimed_sum,
+        )
+    }
 }
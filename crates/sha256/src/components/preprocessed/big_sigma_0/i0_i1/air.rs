@@ -94,9 +94,11 @@ mod tests {
             preprocessed::big_sigma_0::i0_i1::witness::{gen_interaction_trace, gen_trace},
             scheduling::witness::gen_trace as gen_scheduling_trace,
         },
+        message::generate_blocks,
         preprocessed::{
             big_sigma_0, big_sigma_0::BigSigma0I0I1Columns as BigSigma0I0I1ColumnsBorrowed,
         },
+        sha256::HashFunction,
     };
 
     #[test_log::test]
@@ -104,8 +106,11 @@ mod tests {
         const LOG_N_SHA256: u32 = 8;
 
         // Trace.
-        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_SHA256);
-        let (_, compression_lookup_data) = gen_compression_trace(&scheduling_trace);
+        let blocks = generate_blocks(1 << LOG_N_SHA256);
+        let (scheduling_trace, scheduling_lookup_data) =
+            gen_scheduling_trace(LOG_N_SHA256, &blocks);
+        let (_, compression_lookup_data) =
+            gen_compression_trace(&scheduling_trace, &blocks, HashFunction::Sha256);
         let max_log_size = 10;
         let trace = gen_trace(
             max_log_size,
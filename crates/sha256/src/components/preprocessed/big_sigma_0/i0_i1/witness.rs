@@ -16,7 +16,7 @@ use stwo::{
     },
 };
 use stwo_constraint_framework::{LogupTraceGenerator, Relation};
-use utils::{aligned_vec, combine, simd::into_simd, write_pair};
+use utils::{combine, histogram::par_histograms, write_pair};
 
 use crate::{
     components::{
@@ -33,48 +33,44 @@ pub fn gen_trace(
     _scheduling_lookup_data: &[Vec<u32x16>],
     compression_lookup_data: &[Vec<u32x16>],
 ) -> Vec<Vec<u32x16>> {
-    // Dense counters for each relation
-    let mut i0_mult = aligned_vec![0u32; 1 << BigSigma0::I0.count_ones()];
-    let mut i1_mult = aligned_vec![0u32; 1 << BigSigma0::I1.count_ones()];
-
     // Aggregate over all compression lookups
-    for round in 0..N_COMPRESSION_ROUNDS {
-        let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
-        let end = start + CompressionInteractionColumns::SIZE;
-
-        let cols = CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
-
-        izip!(cols.a_i0_low, cols.a_i0_high_0, cols.a_i0_high_1).for_each(
-            |(a_i0_low, a_i0_high_0, a_i0_high_1)| {
-                let idx_i0 = pext_u32x16(
-                    a_i0_low + (a_i0_high_0 << 16) + (a_i0_high_1 << 24),
-                    BigSigma0::I0,
-                );
-                idx_i0
-                    .to_array()
-                    .iter()
-                    .for_each(|x| i0_mult[*x as usize] += 1);
-            },
-        );
-        izip!(cols.a_i1_low_0, cols.a_i1_low_1, cols.a_i1_high).for_each(
-            |(a_i1_low_0, a_i1_low_1, a_i1_high)| {
-                let idx_i1 = pext_u32x16(
-                    a_i1_low_0 + (a_i1_low_1 << 8) + (a_i1_high << 16),
-                    BigSigma0::I1,
-                );
-                idx_i1
-                    .to_array()
-                    .iter()
-                    .for_each(|x| i1_mult[*x as usize] += 1);
-            },
-        );
-    }
+    let [i0_mult, i1_mult] = par_histograms(
+        [BigSigma0::I0.count_ones(), BigSigma0::I1.count_ones()],
+        N_COMPRESSION_ROUNDS,
+        |round, [i0_mult, i1_mult]| {
+            let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
+            let end = start + CompressionInteractionColumns::SIZE;
+
+            let cols =
+                CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
+
+            izip!(cols.a_i0_low, cols.a_i0_high_0, cols.a_i0_high_1).for_each(
+                |(a_i0_low, a_i0_high_0, a_i0_high_1)| {
+                    let idx_i0 = pext_u32x16(
+                        a_i0_low + (a_i0_high_0 << 16) + (a_i0_high_1 << 24),
+                        BigSigma0::I0,
+                    );
+                    i0_mult.add(idx_i0);
+                },
+            );
+            izip!(cols.a_i1_low_0, cols.a_i1_low_1, cols.a_i1_high).for_each(
+                |(a_i1_low_0, a_i1_low_1, a_i1_high)| {
+                    let idx_i1 = pext_u32x16(
+                        a_i1_low_0 + (a_i1_low_1 << 8) + (a_i1_high << 16),
+                        BigSigma0::I1,
+                    );
+                    i1_mult.add(idx_i1);
+                },
+            );
+        },
+    );
 
     // Split into chunks of size log_size - LOG_N_LANES
-    into_simd(&i0_mult)
-        .chunks((1 << (log_size - LOG_N_LANES)) as usize)
-        .zip_eq(into_simd(&i1_mult).chunks((1 << (log_size - LOG_N_LANES)) as usize))
-        .flat_map(|(i0, i1)| [i0.to_vec(), i1.to_vec()])
+    i0_mult
+        .into_columns(log_size)
+        .into_iter()
+        .zip_eq(i1_mult.into_columns(log_size))
+        .flat_map(|(i0, i1)| [i0, i1])
         .collect()
 }
 
@@ -146,8 +142,10 @@ mod tests {
             preprocessed::big_sigma_0::i0_i1::witness::gen_trace,
             scheduling::witness::gen_trace as gen_scheduling_trace,
         },
+        message::generate_blocks,
         partitions::SubsetIterator,
         preprocessed::big_sigma_0,
+        sha256::HashFunction,
     };
 
     #[test]
@@ -201,8 +199,11 @@ mod tests {
     fn test_trace() {
         const LOG_N_SHA256: u32 = 8;
         let log_size = 21;
-        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_SHA256);
-        let (_, compression_lookup_data) = gen_compression_trace(&scheduling_trace);
+        let blocks = generate_blocks(1 << LOG_N_SHA256);
+        let (scheduling_trace, scheduling_lookup_data) =
+            gen_scheduling_trace(LOG_N_SHA256, &blocks);
+        let (_, compression_lookup_data) =
+            gen_compression_trace(&scheduling_trace, &blocks, HashFunction::Sha256);
         let trace = gen_trace(log_size, &scheduling_lookup_data, &compression_lookup_data);
         assert!(trace.iter().all(|t| t.len() == trace[0].len()));
     }
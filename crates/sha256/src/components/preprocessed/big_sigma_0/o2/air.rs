@@ -68,9 +68,11 @@ mod tests {
             preprocessed::big_sigma_0::o2::witness::{gen_interaction_trace, gen_trace},
             scheduling::witness::gen_trace as gen_scheduling_trace,
         },
+        message::generate_blocks,
         preprocessed::big_sigma_0::{
             self, BigSigma0I0I1Columns, BigSigma0O2Columns as BigSigma0O2ColumnsBorrowed,
         },
+        sha256::HashFunction,
     };
 
     #[test_log::test]
@@ -80,8 +82,10 @@ mod tests {
         // Trace.
         let big_sigma_0_cols = big_sigma_0::gen_column_simd();
 
-        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_ROWS);
-        let (_, compression_lookup_data) = gen_compression_trace(&scheduling_trace);
+        let blocks = generate_blocks(1 << LOG_N_ROWS);
+        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_ROWS, &blocks);
+        let (_, compression_lookup_data) =
+            gen_compression_trace(&scheduling_trace, &blocks, HashFunction::Sha256);
         let max_log_size = 10;
         let trace = gen_trace(
             max_log_size,
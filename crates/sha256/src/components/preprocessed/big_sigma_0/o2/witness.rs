@@ -16,7 +16,7 @@ use stwo::{
     },
 };
 use stwo_constraint_framework::{LogupTraceGenerator, Relation};
-use utils::{aligned_vec, combine, simd::into_simd, write_col, write_pair};
+use utils::{combine, histogram::par_histograms, write_col, write_pair};
 
 use crate::{
     components::{
@@ -33,30 +33,28 @@ pub fn gen_trace(
     _scheduling_lookup_data: &[Vec<u32x16>],
     compression_lookup_data: &[Vec<u32x16>],
 ) -> Vec<Vec<u32x16>> {
-    let mut o2_mult = aligned_vec![0u32; 1 << (BigSigma0::O2.count_ones() * 2)];
-
     // Aggregate over all scheduling lookups
-    for round in 0..N_COMPRESSION_ROUNDS {
-        let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
-        let end = start + CompressionInteractionColumns::SIZE;
+    let [o2_mult] = par_histograms(
+        [BigSigma0::O2.count_ones() * 2],
+        N_COMPRESSION_ROUNDS,
+        |round, [o2_mult]| {
+            let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
+            let end = start + CompressionInteractionColumns::SIZE;
 
-        let cols = CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
+            let cols =
+                CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
 
-        izip!(cols.sigma_0_o20_pext, cols.sigma_0_o21_pext).for_each(
-            |(sigma_0_o20_pext, sigma_0_o21_pext)| {
-                let idx_o2 = (sigma_0_o20_pext << BigSigma0::O2.count_ones()) + sigma_0_o21_pext;
-                idx_o2
-                    .to_array()
-                    .iter()
-                    .for_each(|x| o2_mult[*x as usize] += 1);
-            },
-        );
-    }
+            izip!(cols.sigma_0_o20_pext, cols.sigma_0_o21_pext).for_each(
+                |(sigma_0_o20_pext, sigma_0_o21_pext)| {
+                    let idx_o2 =
+                        (sigma_0_o20_pext << BigSigma0::O2.count_ones()) + sigma_0_o21_pext;
+                    o2_mult.add(idx_o2);
+                },
+            );
+        },
+    );
 
-    into_simd(&o2_mult)
-        .chunks((1 << (log_size - LOG_N_LANES)) as usize)
-        .map(|chunk| chunk.to_vec())
-        .collect()
+    o2_mult.into_columns(log_size)
 }
 
 pub fn gen_interaction_trace(
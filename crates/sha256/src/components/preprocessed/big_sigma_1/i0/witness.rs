@@ -16,7 +16,7 @@ use stwo::{
     },
 };
 use stwo_constraint_framework::{LogupTraceGenerator, Relation};
-use utils::{aligned_vec, combine, simd::into_simd, write_col, write_pair};
+use utils::{combine, histogram::par_histograms, write_col, write_pair};
 
 use crate::{
     components::{
@@ -33,28 +33,25 @@ pub fn gen_trace(
     _scheduling_lookup_data: &[Vec<u32x16>],
     compression_lookup_data: &[Vec<u32x16>],
 ) -> Vec<Vec<u32x16>> {
-    let mut i0_mult = aligned_vec![0u32; 1 << BigSigma1::I0.count_ones()];
-
     // Aggregate over all compression lookups
-    for round in 0..N_COMPRESSION_ROUNDS {
-        let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
-        let end = start + CompressionInteractionColumns::SIZE;
+    let [i0_mult] = par_histograms(
+        [BigSigma1::I0.count_ones()],
+        N_COMPRESSION_ROUNDS,
+        |round, [i0_mult]| {
+            let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
+            let end = start + CompressionInteractionColumns::SIZE;
 
-        let cols = CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
+            let cols =
+                CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
 
-        izip!(cols.e_i0_low, cols.e_i0_high).for_each(|(e_i0_low, e_i0_high)| {
-            let idx_i0 = pext_u32x16(e_i0_low + (e_i0_high << 16), BigSigma1::I0);
-            idx_i0
-                .to_array()
-                .iter()
-                .for_each(|x| i0_mult[*x as usize] += 1);
-        });
-    }
+            izip!(cols.e_i0_low, cols.e_i0_high).for_each(|(e_i0_low, e_i0_high)| {
+                let idx_i0 = pext_u32x16(e_i0_low + (e_i0_high << 16), BigSigma1::I0);
+                i0_mult.add(idx_i0);
+            });
+        },
+    );
 
-    into_simd(&i0_mult)
-        .chunks((1 << (log_size - LOG_N_LANES)) as usize)
-        .map(|chunk| chunk.to_vec())
-        .collect()
+    i0_mult.into_columns(log_size)
 }
 
 pub fn gen_interaction_trace(
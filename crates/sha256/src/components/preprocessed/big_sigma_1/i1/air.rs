@@ -74,9 +74,11 @@ mod tests {
             preprocessed::big_sigma_1::i1::witness::{gen_interaction_trace, gen_trace},
             scheduling::witness::gen_trace as gen_scheduling_trace,
         },
+        message::generate_blocks,
         preprocessed::big_sigma_1::{
             self, BigSigma1I0Columns, BigSigma1I1Columns as BigSigma1I1ColumnsBorrowed,
         },
+        sha256::HashFunction,
     };
 
     #[test_log::test]
@@ -86,8 +88,10 @@ mod tests {
         // Trace.
         let big_sigma_1_cols = big_sigma_1::gen_column_simd();
 
-        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_ROWS);
-        let (_, compression_lookup_data) = gen_compression_trace(&scheduling_trace);
+        let blocks = generate_blocks(1 << LOG_N_ROWS);
+        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_ROWS, &blocks);
+        let (_, compression_lookup_data) =
+            gen_compression_trace(&scheduling_trace, &blocks, HashFunction::Sha256);
         let max_log_size = 10;
         let trace = gen_trace(
             max_log_size,
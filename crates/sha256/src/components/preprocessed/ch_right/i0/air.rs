@@ -84,7 +84,9 @@ mod tests {
             preprocessed::ch_right::i0::witness::{gen_interaction_trace, gen_trace},
             scheduling::witness::gen_trace as gen_scheduling_trace,
         },
+        message::generate_blocks,
         preprocessed::{ch_right, ch_right::ChRightI0Columns as ChRightI0ColumnsBorrowed},
+        sha256::HashFunction,
     };
 
     #[test_log::test]
@@ -92,8 +94,11 @@ mod tests {
         const LOG_N_SHA256: u32 = 8;
 
         // Trace.
-        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_SHA256);
-        let (_, compression_lookup_data) = gen_compression_trace(&scheduling_trace);
+        let blocks = generate_blocks(1 << LOG_N_SHA256);
+        let (scheduling_trace, scheduling_lookup_data) =
+            gen_scheduling_trace(LOG_N_SHA256, &blocks);
+        let (_, compression_lookup_data) =
+            gen_compression_trace(&scheduling_trace, &blocks, HashFunction::Sha256);
         let max_log_size = 10;
         let trace = gen_trace(
             max_log_size,
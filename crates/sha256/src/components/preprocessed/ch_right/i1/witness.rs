@@ -16,7 +16,7 @@ use stwo::{
     },
 };
 use stwo_constraint_framework::{LogupTraceGenerator, Relation};
-use utils::{aligned_vec, combine, simd::into_simd, write_pair};
+use utils::{combine, histogram::par_histograms, write_pair};
 
 use crate::{
     components::{
@@ -33,41 +33,40 @@ pub fn gen_trace(
     _scheduling_lookup_data: &[Vec<u32x16>],
     compression_lookup_data: &[Vec<u32x16>],
 ) -> Vec<Vec<u32x16>> {
-    // Dense counters for each relation
-    let mut i1_low_mult = aligned_vec![0u32; 1 << (BigSigma1::I1_L.count_ones() * 2)];
-    let mut i1_high_mult = aligned_vec![0u32; 1 << (BigSigma1::I1_H.count_ones() * 2)];
-
     // Aggregate over all compression lookups
-    for round in 0..N_COMPRESSION_ROUNDS {
-        let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
-        let end = start + CompressionInteractionColumns::SIZE;
+    let [i1_low_mult, i1_high_mult] = par_histograms(
+        [
+            BigSigma1::I1_L.count_ones() * 2,
+            BigSigma1::I1_H.count_ones() * 2,
+        ],
+        N_COMPRESSION_ROUNDS,
+        |round, [i1_low_mult, i1_high_mult]| {
+            let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
+            let end = start + CompressionInteractionColumns::SIZE;
 
-        let cols = CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
+            let cols =
+                CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
 
-        izip!(cols.e_i1_low, cols.g_i1_low).for_each(|(e_i1_low, g_i1_low)| {
-            let idx_i1_low = (pext_u32x16(*e_i1_low, BigSigma1::I1_L)
-                << BigSigma1::I1_L.count_ones())
-                + pext_u32x16(*g_i1_low, BigSigma1::I1_L);
-            idx_i1_low
-                .to_array()
-                .iter()
-                .for_each(|x| i1_low_mult[*x as usize] += 1);
-        });
-        izip!(cols.e_i1_high, cols.g_i1_high).for_each(|(e_i1_high, g_i1_high)| {
-            let idx_i1_high = (pext_u32x16(*e_i1_high, BigSigma1::I1_H)
-                << BigSigma1::I1_H.count_ones())
-                + pext_u32x16(*g_i1_high, BigSigma1::I1_H);
-            idx_i1_high
-                .to_array()
-                .iter()
-                .for_each(|x| i1_high_mult[*x as usize] += 1);
-        });
-    }
+            izip!(cols.e_i1_low, cols.g_i1_low).for_each(|(e_i1_low, g_i1_low)| {
+                let idx_i1_low = (pext_u32x16(*e_i1_low, BigSigma1::I1_L)
+                    << BigSigma1::I1_L.count_ones())
+                    + pext_u32x16(*g_i1_low, BigSigma1::I1_L);
+                i1_low_mult.add(idx_i1_low);
+            });
+            izip!(cols.e_i1_high, cols.g_i1_high).for_each(|(e_i1_high, g_i1_high)| {
+                let idx_i1_high = (pext_u32x16(*e_i1_high, BigSigma1::I1_H)
+                    << BigSigma1::I1_H.count_ones())
+                    + pext_u32x16(*g_i1_high, BigSigma1::I1_H);
+                i1_high_mult.add(idx_i1_high);
+            });
+        },
+    );
 
-    into_simd(&i1_low_mult)
-        .chunks((1 << (log_size - LOG_N_LANES)) as usize)
-        .zip_eq(into_simd(&i1_high_mult).chunks((1 << (log_size - LOG_N_LANES)) as usize))
-        .flat_map(|(i1_low, i1_high)| [i1_low.to_vec(), i1_high.to_vec()])
+    i1_low_mult
+        .into_columns(log_size)
+        .into_iter()
+        .zip_eq(i1_high_mult.into_columns(log_size))
+        .flat_map(|(i1_low, i1_high)| [i1_low, i1_high])
         .collect()
 }
 
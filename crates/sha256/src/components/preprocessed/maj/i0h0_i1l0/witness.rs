@@ -16,7 +16,7 @@ use stwo::{
     },
 };
 use stwo_constraint_framework::{LogupTraceGenerator, Relation};
-use utils::{aligned_vec, combine, simd::into_simd, write_pair};
+use utils::{combine, histogram::par_histograms, write_pair};
 
 use crate::{
     components::{
@@ -33,52 +33,51 @@ pub fn gen_trace(
     _scheduling_lookup_data: &[Vec<u32x16>],
     compression_lookup_data: &[Vec<u32x16>],
 ) -> Vec<Vec<u32x16>> {
-    // Dense counters for each relation
-    let mut i0_high_0_mult = aligned_vec![0u32; 1 << (BigSigma0::I0_H0.count_ones() * 3)];
-    let mut i1_low_0_mult = aligned_vec![0u32; 1 << (BigSigma0::I1_L0.count_ones() * 3)];
-
     // Aggregate over all compression lookups
-    for round in 0..N_COMPRESSION_ROUNDS {
-        let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
-        let end = start + CompressionInteractionColumns::SIZE;
+    let [i0_high_0_mult, i1_low_0_mult] = par_histograms(
+        [
+            BigSigma0::I0_H0.count_ones() * 3,
+            BigSigma0::I1_L0.count_ones() * 3,
+        ],
+        N_COMPRESSION_ROUNDS,
+        |round, [i0_high_0_mult, i1_low_0_mult]| {
+            let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
+            let end = start + CompressionInteractionColumns::SIZE;
 
-        let cols = CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
+            let cols =
+                CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
 
-        izip!(cols.a_i0_high_0, cols.b_i0_high_0, cols.c_i0_high_0).for_each(
-            |(a_i0_high_0, b_i0_high_0, c_i0_high_0)| {
-                let a_pos = pext_u32x16(*a_i0_high_0, BigSigma0::I0_H0)
-                    << (BigSigma0::I0_H0.count_ones() * 2);
-                let b_pos =
-                    pext_u32x16(*b_i0_high_0, BigSigma0::I0_H0) << (BigSigma0::I0_H0.count_ones());
-                let c_pos = pext_u32x16(*c_i0_high_0, BigSigma0::I0_H0);
-                let idx_i0_high_0 = a_pos + b_pos + c_pos;
-                idx_i0_high_0
-                    .to_array()
-                    .iter()
-                    .for_each(|x| i0_high_0_mult[*x as usize] += 1);
-            },
-        );
+            izip!(cols.a_i0_high_0, cols.b_i0_high_0, cols.c_i0_high_0).for_each(
+                |(a_i0_high_0, b_i0_high_0, c_i0_high_0)| {
+                    let a_pos = pext_u32x16(*a_i0_high_0, BigSigma0::I0_H0)
+                        << (BigSigma0::I0_H0.count_ones() * 2);
+                    let b_pos = pext_u32x16(*b_i0_high_0, BigSigma0::I0_H0)
+                        << (BigSigma0::I0_H0.count_ones());
+                    let c_pos = pext_u32x16(*c_i0_high_0, BigSigma0::I0_H0);
+                    let idx_i0_high_0 = a_pos + b_pos + c_pos;
+                    i0_high_0_mult.add(idx_i0_high_0);
+                },
+            );
 
-        izip!(cols.a_i1_low_0, cols.b_i1_low_0, cols.c_i1_low_0).for_each(
-            |(a_i1_low_0, b_i1_low_0, c_i1_low_0)| {
-                let a_pos = pext_u32x16(*a_i1_low_0, BigSigma0::I1_L0)
-                    << (BigSigma0::I1_L0.count_ones() * 2);
-                let b_pos =
-                    pext_u32x16(*b_i1_low_0, BigSigma0::I1_L0) << (BigSigma0::I1_L0.count_ones());
-                let c_pos = pext_u32x16(*c_i1_low_0, BigSigma0::I1_L0);
-                let idx_i1_low_0 = a_pos + b_pos + c_pos;
-                idx_i1_low_0
-                    .to_array()
-                    .iter()
-                    .for_each(|x| i1_low_0_mult[*x as usize] += 1);
-            },
-        );
-    }
+            izip!(cols.a_i1_low_0, cols.b_i1_low_0, cols.c_i1_low_0).for_each(
+                |(a_i1_low_0, b_i1_low_0, c_i1_low_0)| {
+                    let a_pos = pext_u32x16(*a_i1_low_0, BigSigma0::I1_L0)
+                        << (BigSigma0::I1_L0.count_ones() * 2);
+                    let b_pos = pext_u32x16(*b_i1_low_0, BigSigma0::I1_L0)
+                        << (BigSigma0::I1_L0.count_ones());
+                    let c_pos = pext_u32x16(*c_i1_low_0, BigSigma0::I1_L0);
+                    let idx_i1_low_0 = a_pos + b_pos + c_pos;
+                    i1_low_0_mult.add(idx_i1_low_0);
+                },
+            );
+        },
+    );
 
-    into_simd(&i0_high_0_mult)
-        .chunks((1 << (log_size - LOG_N_LANES)) as usize)
-        .zip_eq(into_simd(&i1_low_0_mult).chunks((1 << (log_size - LOG_N_LANES)) as usize))
-        .flat_map(|(i0, i1)| [i0.to_vec(), i1.to_vec()])
+    i0_high_0_mult
+        .into_columns(log_size)
+        .into_iter()
+        .zip_eq(i1_low_0_mult.into_columns(log_size))
+        .flat_map(|(i0, i1)| [i0, i1])
         .collect()
 }
 
@@ -83,7 +83,9 @@ mod tests {
             preprocessed::maj::i0h1_i1l1::witness::{gen_interaction_trace, gen_trace},
             scheduling::witness::gen_trace as gen_scheduling_trace,
         },
+        message::generate_blocks,
         preprocessed::maj::{self, MajI0H1I1L1Columns as MajI0H1I1L1ColumnsBorrowed},
+        sha256::HashFunction,
     };
 
     #[test_log::test]
@@ -91,8 +93,10 @@ mod tests {
         const LOG_N_ROWS: u32 = 8;
 
         // Trace.
-        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_ROWS);
-        let (_, compression_lookup_data) = gen_compression_trace(&scheduling_trace);
+        let blocks = generate_blocks(1 << LOG_N_ROWS);
+        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_ROWS, &blocks);
+        let (_, compression_lookup_data) =
+            gen_compression_trace(&scheduling_trace, &blocks, HashFunction::Sha256);
         let max_log_size = 10;
         let trace = gen_trace(
             max_log_size,
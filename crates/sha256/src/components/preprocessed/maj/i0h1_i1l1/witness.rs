@@ -16,7 +16,7 @@ use stwo::{
     },
 };
 use stwo_constraint_framework::{LogupTraceGenerator, Relation};
-use utils::{aligned_vec, combine, simd::into_simd, write_pair};
+use utils::{combine, histogram::par_histograms, write_pair};
 
 use crate::{
     components::{
@@ -33,52 +33,51 @@ pub fn gen_trace(
     _scheduling_lookup_data: &[Vec<u32x16>],
     compression_lookup_data: &[Vec<u32x16>],
 ) -> Vec<Vec<u32x16>> {
-    // Dense counters for each relation
-    let mut i0_high_1_mult = aligned_vec![0u32; 1 << (BigSigma0::I0_H1.count_ones() * 3)];
-    let mut i1_low_1_mult = aligned_vec![0u32; 1 << (BigSigma0::I1_L1.count_ones() * 3)];
-
     // Aggregate over all compression lookups
-    for round in 0..N_COMPRESSION_ROUNDS {
-        let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
-        let end = start + CompressionInteractionColumns::SIZE;
+    let [i0_high_1_mult, i1_low_1_mult] = par_histograms(
+        [
+            BigSigma0::I0_H1.count_ones() * 3,
+            BigSigma0::I1_L1.count_ones() * 3,
+        ],
+        N_COMPRESSION_ROUNDS,
+        |round, [i0_high_1_mult, i1_low_1_mult]| {
+            let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
+            let end = start + CompressionInteractionColumns::SIZE;
 
-        let cols = CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
+            let cols =
+                CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
 
-        izip!(cols.a_i0_high_1, cols.b_i0_high_1, cols.c_i0_high_1).for_each(
-            |(a_i0_high_1, b_i0_high_1, c_i0_high_1)| {
-                let a_pos = pext_u32x16(*a_i0_high_1, BigSigma0::I0_H1)
-                    << (BigSigma0::I0_H1.count_ones() * 2);
-                let b_pos =
-                    pext_u32x16(*b_i0_high_1, BigSigma0::I0_H1) << (BigSigma0::I0_H1.count_ones());
-                let c_pos = pext_u32x16(*c_i0_high_1, BigSigma0::I0_H1);
-                let idx_i0_high_1 = a_pos + b_pos + c_pos;
-                idx_i0_high_1
-                    .to_array()
-                    .iter()
-                    .for_each(|x| i0_high_1_mult[*x as usize] += 1);
-            },
-        );
+            izip!(cols.a_i0_high_1, cols.b_i0_high_1, cols.c_i0_high_1).for_each(
+                |(a_i0_high_1, b_i0_high_1, c_i0_high_1)| {
+                    let a_pos = pext_u32x16(*a_i0_high_1, BigSigma0::I0_H1)
+                        << (BigSigma0::I0_H1.count_ones() * 2);
+                    let b_pos = pext_u32x16(*b_i0_high_1, BigSigma0::I0_H1)
+                        << (BigSigma0::I0_H1.count_ones());
+                    let c_pos = pext_u32x16(*c_i0_high_1, BigSigma0::I0_H1);
+                    let idx_i0_high_1 = a_pos + b_pos + c_pos;
+                    i0_high_1_mult.add(idx_i0_high_1);
+                },
+            );
 
-        izip!(cols.a_i1_low_1, cols.b_i1_low_1, cols.c_i1_low_1).for_each(
-            |(a_i1_low_1, b_i1_low_1, c_i1_low_1)| {
-                let a_pos = pext_u32x16(*a_i1_low_1, BigSigma0::I1_L1)
-                    << (BigSigma0::I1_L1.count_ones() * 2);
-                let b_pos =
-                    pext_u32x16(*b_i1_low_1, BigSigma0::I1_L1) << (BigSigma0::I1_L1.count_ones());
-                let c_pos = pext_u32x16(*c_i1_low_1, BigSigma0::I1_L1);
-                let idx_i1_low_1 = a_pos + b_pos + c_pos;
-                idx_i1_low_1
-                    .to_array()
-                    .iter()
-                    .for_each(|x| i1_low_1_mult[*x as usize] += 1);
-            },
-        );
-    }
+            izip!(cols.a_i1_low_1, cols.b_i1_low_1, cols.c_i1_low_1).for_each(
+                |(a_i1_low_1, b_i1_low_1, c_i1_low_1)| {
+                    let a_pos = pext_u32x16(*a_i1_low_1, BigSigma0::I1_L1)
+                        << (BigSigma0::I1_L1.count_ones() * 2);
+                    let b_pos = pext_u32x16(*b_i1_low_1, BigSigma0::I1_L1)
+                        << (BigSigma0::I1_L1.count_ones());
+                    let c_pos = pext_u32x16(*c_i1_low_1, BigSigma0::I1_L1);
+                    let idx_i1_low_1 = a_pos + b_pos + c_pos;
+                    i1_low_1_mult.add(idx_i1_low_1);
+                },
+            );
+        },
+    );
 
-    into_simd(&i0_high_1_mult)
-        .chunks((1 << (log_size - LOG_N_LANES)) as usize)
-        .zip_eq(into_simd(&i1_low_1_mult).chunks((1 << (log_size - LOG_N_LANES)) as usize))
-        .flat_map(|(i0, i1)| [i0.to_vec(), i1.to_vec()])
+    i0_high_1_mult
+        .into_columns(log_size)
+        .into_iter()
+        .zip_eq(i1_low_1_mult.into_columns(log_size))
+        .flat_map(|(i0, i1)| [i0, i1])
         .collect()
 }
 
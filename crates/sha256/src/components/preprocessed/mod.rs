@@ -3,13 +3,19 @@ mod big_sigma_1;
 mod ch_left;
 mod ch_right;
 mod maj;
+mod range_check_16;
 mod range_check_add;
 mod sigma_0;
 mod sigma_1;
 
-use crate::components;
+use utils::components;
 
 components!(
+    relations: crate::relations::Relations,
+    lookup_data: (
+        scheduling_lookup_data: &[Vec<u32x16>],
+        compression_lookup_data: &[Vec<u32x16>],
+    ),
     sigma_0::i0_i1,
     sigma_1::i0_i1,
     sigma_0::o2,
@@ -27,4 +33,5 @@ components!(
     maj::i0h1_i1l1,
     maj::i0l_i1h,
     range_check_add::range_check_add,
+    range_check_16::range_check_16,
 );
@@ -0,0 +1,188 @@
+use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval};
+use utils::add_to_relation;
+
+use crate::{
+    components::preprocessed::range_check_16::columns::ComponentColumnsOwned as ComponentColumns,
+    preprocessed::range_check_16::{self, RangeCheck16ColumnsOwned as RangeCheck16Columns},
+    relations::Relations,
+};
+
+pub type Component = FrameworkComponent<Eval>;
+
+fn eval_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relations, log_size: u32) {
+    let chunk_count = 1 << range_check_16::LOG_SIZE.saturating_sub(log_size);
+    for chunk in 0..chunk_count {
+        let ComponentColumns { limb_mult } =
+            ComponentColumns::<<E as EvalAtRow>::F>::from_eval(eval);
+        let RangeCheck16Columns { value } =
+            RangeCheck16Columns::<<E as EvalAtRow>::F>::from_ids(eval, Some(chunk));
+        add_to_relation!(
+            eval,
+            relations.range_check_16.limb,
+            E::EF::from(limb_mult),
+            value,
+        );
+    }
+    eval.finalize_logup_in_pairs();
+}
+
+#[derive(Clone)]
+pub struct Eval {
+    pub log_size: u32,
+    pub relations: Relations,
+}
+impl FrameworkEval for Eval {
+    fn log_size(&self) -> u32 {
+        range_check_16::LOG_SIZE.min(self.log_size)
+    }
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        range_check_16::LOG_SIZE.min(self.log_size) + 1
+    }
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        eval_constraints(&mut eval, &self.relations, self.log_size);
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::simd::u32x16;
+
+    use itertools::Itertools;
+    use num_traits::Zero;
+    use stwo::{
+        core::{
+            channel::Blake2sChannel,
+            fields::{m31::M31, qm31::SecureField, FieldExpOps},
+            pcs::TreeVec,
+            poly::circle::CanonicCoset,
+        },
+        prover::backend::simd::m31::LOG_N_LANES,
+    };
+    use stwo_constraint_framework::{assert_constraints_on_polys, Relation};
+    use utils::circle_evaluation_u32x16;
+
+    use super::*;
+    use crate::{
+        components::{
+            preprocessed::range_check_16::witness::{gen_interaction_trace, gen_trace},
+            scheduling::witness::gen_message_columns,
+        },
+        message::generate_blocks,
+        preprocessed::range_check_16::RangeCheck16Columns as RangeCheck16ColumnsBorrowed,
+    };
+
+    const LOG_N_ROWS: u32 = 8;
+
+    fn gen_message() -> Vec<Vec<u32x16>> {
+        gen_message_columns(LOG_N_ROWS, &generate_blocks(1 << LOG_N_ROWS))
+    }
+
+    /// Logup sum of the scheduling lookups of the message limbs.
+    fn lookups_sum(message: &[Vec<u32x16>], relations: &Relations) -> SecureField {
+        message
+            .iter()
+            .flatten()
+            .flat_map(|limbs| limbs.to_array())
+            .map(|limb| {
+                let den: SecureField = relations.range_check_16.limb.combine(&[M31::from(limb)]);
+                -den.inverse()
+            })
+            .fold(SecureField::zero(), |sum, frac| sum + frac)
+    }
+
+    #[test_log::test]
+    fn test_constraints() {
+        // Trace.
+        let message = gen_message();
+        let max_log_size = 10;
+        let trace = gen_trace(max_log_size, &message, &[]);
+
+        let simd_size = trace[0].len().ilog2();
+        let log_size = simd_size + LOG_N_LANES;
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) = gen_interaction_trace(&trace, &relations);
+
+        let range_check_16_cols = range_check_16::gen_column_simd();
+        let preprocessed_trace = RangeCheck16ColumnsBorrowed::from_slice(&range_check_16_cols)
+            .chunks((1 << simd_size) as usize)
+            .into_iter()
+            .flat_map(|c| c.iter().map(|c| circle_evaluation_u32x16!(c)))
+            .collect::<Vec<_>>();
+
+        let traces = TreeVec::new(vec![
+            preprocessed_trace,
+            trace
+                .into_iter()
+                .map(|c| circle_evaluation_u32x16!(c))
+                .collect::<Vec<_>>(),
+            interaction_trace,
+        ]);
+
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(log_size),
+            |mut eval| {
+                eval_constraints(&mut eval, &relations, log_size);
+            },
+            claimed_sum,
+        );
+    }
+
+    #[test]
+    fn test_lookups_balanced() {
+        let relations = Relations::draw(&mut Blake2sChannel::default());
+        let message = gen_message();
+        let (_, claimed_sum) =
+            gen_interaction_trace(&gen_trace(LOG_N_ROWS, &message, &[]), &relations);
+        assert_eq!(
+            claimed_sum + lookups_sum(&message, &relations),
+            SecureField::zero()
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_limb() {
+        let relations = Relations::draw(&mut Blake2sChannel::default());
+        let mut message = gen_message();
+        let limb = (1 << 16) + 5;
+        message[0][0][0] = limb;
+
+        // A 17-bit limb has no entry in the table: its multiplicity cannot be computed.
+        assert!(std::panic::catch_unwind(|| gen_trace(LOG_N_ROWS, &message, &[])).is_err());
+
+        // Counting it as any value of the table instead does not balance the lookups: start from
+        // the multiplicities of the trace where it is 0, and move that count to each value.
+        message[0][0][0] = 0;
+        let (_, claimed_sum) =
+            gen_interaction_trace(&gen_trace(LOG_N_ROWS, &message, &[]), &relations);
+        message[0][0][0] = limb;
+        let sum = claimed_sum + lookups_sum(&message, &relations);
+        let den = |value: u32| -> SecureField {
+            relations.range_check_16.limb.combine(&[M31::from(value)])
+        };
+        for value in 0..1 << range_check_16::LOG_SIZE {
+            assert_ne!(
+                sum - den(0).inverse() + den(value).inverse(),
+                SecureField::zero()
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrong_multiplicity() {
+        let relations = Relations::draw(&mut Blake2sChannel::default());
+        let message = gen_message();
+        let mut trace = gen_trace(LOG_N_ROWS, &message, &[]);
+        trace[0][0][0] += 1;
+        let (_, claimed_sum) = gen_interaction_trace(&trace, &relations);
+        assert_ne!(
+            claimed_sum + lookups_sum(&message, &relations),
+            SecureField::zero()
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::simd::u32x16;
+
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, histogram::par_histograms, write_col, write_pair};
+
+use crate::{
+    preprocessed::range_check_16::{self, RangeCheck16Columns},
+    relations::Relations,
+    sha256::CHUNK_SIZE,
+};
+
+pub fn gen_trace(
+    log_size: u32,
+    scheduling_lookup_data: &[Vec<u32x16>],
+    _compression_lookup_data: &[Vec<u32x16>],
+) -> Vec<Vec<u32x16>> {
+    // One task per message limb, the scheduled limbs are checked by range_check_add
+    let [limb_mult] = par_histograms(
+        [range_check_16::LOG_SIZE],
+        CHUNK_SIZE,
+        |limb, [limb_mult]| {
+            limb_mult.add_all(&scheduling_lookup_data[limb]);
+        },
+    );
+
+    limb_mult.into_columns(log_size)
+}
+
+pub fn gen_interaction_trace(
+    trace: &[Vec<u32x16>],
+    relations: &Relations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let preprocessed_columns = range_check_16::gen_column_simd();
+    let RangeCheck16Columns { value } = RangeCheck16Columns::from_slice(&preprocessed_columns[..]);
+
+    let simd_size = trace[0].len();
+    let log_size = simd_size.ilog2() + LOG_N_LANES;
+    let mut interaction_trace = LogupTraceGenerator::new(log_size);
+
+    let limb_den = combine!(relations.range_check_16.limb, [&value]);
+    let mut dens = limb_den.chunks(simd_size);
+
+    for [mult_0, mult_1] in trace.array_chunks::<2>() {
+        write_pair!(
+            mult_0
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            dens.next().unwrap().to_vec(),
+            mult_1
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            dens.next().unwrap().to_vec(),
+            interaction_trace
+        );
+    }
+
+    if trace.len() % 2 == 1 {
+        let mult = trace.last().unwrap();
+        write_col!(
+            mult.iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            dens.next().unwrap().to_vec(),
+            interaction_trace
+        );
+    }
+
+    interaction_trace.finalize_last()
+}
@@ -16,12 +16,16 @@ use stwo::{
     },
 };
 use stwo_constraint_framework::{LogupTraceGenerator, Relation};
-use utils::{aligned_vec, combine, simd::into_simd, write_col, write_pair};
+use utils::{combine, histogram::par_histograms, write_col, write_pair};
 
 use crate::{
     components::{
-        compression::columns::RoundInteractionColumns as CompressionInteractionColumns,
-        scheduling::columns::RoundInteractionColumns as SchedulingInteractionColumns, W_SIZE,
+        compression::{
+            columns::RoundInteractionColumns as CompressionInteractionColumns,
+            witness::{CARRY_INTERACTION_OFFSET, H_OUT_INTERACTION_OFFSET},
+        },
+        scheduling::columns::RoundInteractionColumns as SchedulingInteractionColumns,
+        STATE_SIZE, W_SIZE,
     },
     preprocessed::range_check_add::{self, RangeCheckAddColumns},
     relations::Relations,
@@ -33,70 +37,66 @@ pub fn gen_trace(
     scheduling_lookup_data: &[Vec<u32x16>],
     compression_lookup_data: &[Vec<u32x16>],
 ) -> Vec<Vec<u32x16>> {
-    // Dense counters for each relation
-    let mut carry_4_mult = aligned_vec![0u32; 1 << 19];
-    let mut carry_7_mult = aligned_vec![0u32; 1 << 19];
-    let mut carry_8_mult = aligned_vec![0u32; 1 << 19];
-
-    // Aggregate over all scheduling lookups
-    for round in 0..N_SCHEDULING_ROUNDS {
-        let start = W_SIZE + round * SchedulingInteractionColumns::SIZE;
-        let end = start + SchedulingInteractionColumns::SIZE;
-
-        let cols = SchedulingInteractionColumns::from_slice(&scheduling_lookup_data[start..end]);
-
-        izip!(cols.new_w_low, cols.carry_low).for_each(|(new_w_low, carry_low)| {
-            let idx = (new_w_low << 3) + carry_low;
-            idx.to_array()
-                .iter()
-                .for_each(|x| carry_4_mult[*x as usize] += 1);
-        });
-        izip!(cols.new_w_high, cols.carry_high).for_each(|(new_w_high, carry_high)| {
-            let idx = (new_w_high << 3) + carry_high;
-            idx.to_array()
-                .iter()
-                .for_each(|x| carry_4_mult[*x as usize] += 1);
-        });
-    }
-
-    // Aggregate over all compression lookups
-    for round in 0..N_COMPRESSION_ROUNDS {
-        let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
-        let end = start + CompressionInteractionColumns::SIZE;
-
-        let cols = CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
-
-        izip!(cols.new_e_low, cols.e_carry_low).for_each(|(new_e_low, e_carry_low)| {
-            let idx = (new_e_low << 3) + e_carry_low;
-            idx.to_array()
-                .iter()
-                .for_each(|x| carry_7_mult[*x as usize] += 1);
-        });
-        izip!(cols.new_e_high, cols.e_carry_high).for_each(|(new_e_high, e_carry_high)| {
-            let idx = (new_e_high << 3) + e_carry_high;
-            idx.to_array()
-                .iter()
-                .for_each(|x| carry_7_mult[*x as usize] += 1);
-        });
-        izip!(cols.new_a_low, cols.a_carry_low).for_each(|(new_a_low, a_carry_low)| {
-            let idx = (new_a_low << 3) + a_carry_low;
-            idx.to_array()
-                .iter()
-                .for_each(|x| carry_8_mult[*x as usize] += 1);
-        });
-        izip!(cols.new_a_high, cols.a_carry_high).for_each(|(new_a_high, a_carry_high)| {
-            let idx = (new_a_high << 3) + a_carry_high;
-            idx.to_array()
-                .iter()
-                .for_each(|x| carry_8_mult[*x as usize] += 1);
-        });
-    }
-
-    into_simd(&carry_4_mult)
-        .chunks((1 << (log_size - LOG_N_LANES)) as usize)
-        .zip(into_simd(&carry_7_mult).chunks((1 << (log_size - LOG_N_LANES)) as usize))
-        .zip(into_simd(&carry_8_mult).chunks((1 << (log_size - LOG_N_LANES)) as usize))
-        .flat_map(|((c4, c7), c8)| [c4.to_vec(), c7.to_vec(), c8.to_vec()])
+    // One task per scheduling round, one per compression round and one for the feed-forward
+    let n_tasks = N_SCHEDULING_ROUNDS + N_COMPRESSION_ROUNDS + 1;
+    let [carry_4_mult, carry_7_mult, carry_8_mult] = par_histograms(
+        [19; 3],
+        n_tasks,
+        |task, [carry_4_mult, carry_7_mult, carry_8_mult]| {
+            if task < N_SCHEDULING_ROUNDS {
+                let start = W_SIZE + task * SchedulingInteractionColumns::SIZE;
+                let end = start + SchedulingInteractionColumns::SIZE;
+
+                let cols =
+                    SchedulingInteractionColumns::from_slice(&scheduling_lookup_data[start..end]);
+
+                izip!(cols.new_w_low, cols.carry_low).for_each(|(new_w_low, carry_low)| {
+                    carry_4_mult.add((new_w_low << 3) + carry_low);
+                });
+                izip!(cols.new_w_high, cols.carry_high).for_each(|(new_w_high, carry_high)| {
+                    carry_4_mult.add((new_w_high << 3) + carry_high);
+                });
+            } else if task < N_SCHEDULING_ROUNDS + N_COMPRESSION_ROUNDS {
+                let round = task - N_SCHEDULING_ROUNDS;
+                let start = W_SIZE + round * CompressionInteractionColumns::SIZE;
+                let end = start + CompressionInteractionColumns::SIZE;
+
+                let cols =
+                    CompressionInteractionColumns::from_slice(&compression_lookup_data[start..end]);
+
+                izip!(cols.new_e_low, cols.e_carry_low).for_each(|(new_e_low, e_carry_low)| {
+                    carry_7_mult.add((new_e_low << 3) + e_carry_low);
+                });
+                izip!(cols.new_e_high, cols.e_carry_high).for_each(|(new_e_high, e_carry_high)| {
+                    carry_7_mult.add((new_e_high << 3) + e_carry_high);
+                });
+                izip!(cols.new_a_low, cols.a_carry_low).for_each(|(new_a_low, a_carry_low)| {
+                    carry_8_mult.add((new_a_low << 3) + a_carry_low);
+                });
+                izip!(cols.new_a_high, cols.a_carry_high).for_each(|(new_a_high, a_carry_high)| {
+                    carry_8_mult.add((new_a_high << 3) + a_carry_high);
+                });
+            } else {
+                izip!(
+                    &compression_lookup_data[H_OUT_INTERACTION_OFFSET..CARRY_INTERACTION_OFFSET],
+                    &compression_lookup_data
+                        [CARRY_INTERACTION_OFFSET..CARRY_INTERACTION_OFFSET + STATE_SIZE]
+                )
+                .for_each(|(h_out, carry)| {
+                    izip!(h_out, carry).for_each(|(h_out, carry)| {
+                        carry_4_mult.add((h_out << 3) + carry);
+                    });
+                });
+            }
+        },
+    );
+
+    carry_4_mult
+        .into_columns(log_size)
+        .into_iter()
+        .zip(carry_7_mult.into_columns(log_size))
+        .zip(carry_8_mult.into_columns(log_size))
+        .flat_map(|((c4, c7), c8)| [c4, c7, c8])
         .collect()
 }
 
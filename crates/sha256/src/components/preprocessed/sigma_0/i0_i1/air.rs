@@ -86,7 +86,9 @@ mod tests {
             preprocessed::sigma_0::i0_i1::witness::{gen_interaction_trace, gen_trace},
             scheduling::witness::gen_trace as gen_scheduling_trace,
         },
+        message::generate_blocks,
         preprocessed::sigma_0::{self, Sigma0I0I1Columns as Sigma0I0I1ColumnsBorrowed},
+        sha256::HashFunction,
     };
 
     #[test_log::test]
@@ -96,8 +98,10 @@ mod tests {
         // Trace.
         let sigma_0_cols = sigma_0::gen_column_simd();
 
-        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_ROWS);
-        let (_, compression_lookup_data) = gen_compression_trace(&scheduling_trace);
+        let blocks = generate_blocks(1 << LOG_N_ROWS);
+        let (scheduling_trace, scheduling_lookup_data) = gen_scheduling_trace(LOG_N_ROWS, &blocks);
+        let (_, compression_lookup_data) =
+            gen_compression_trace(&scheduling_trace, &blocks, HashFunction::Sha256);
         let max_log_size = 10;
         let trace = gen_trace(
             max_log_size,
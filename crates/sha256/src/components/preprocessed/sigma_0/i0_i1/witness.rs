@@ -16,7 +16,7 @@ use stwo::{
     },
 };
 use stwo_constraint_framework::{LogupTraceGenerator, Relation};
-use utils::{aligned_vec, combine, simd::into_simd, write_pair};
+use utils::{combine, histogram::par_histograms, write_pair};
 
 use crate::{
     components::{
@@ -33,37 +33,33 @@ pub fn gen_trace(
     scheduling_lookup_data: &[Vec<u32x16>],
     _compression_lookup_data: &[Vec<u32x16>],
 ) -> Vec<Vec<u32x16>> {
-    // Dense counters for each relation
-    let mut i0_mult = aligned_vec![0u32; 1 << Sigma0::I0.count_ones()];
-    let mut i1_mult = aligned_vec![0u32; 1 << Sigma0::I1.count_ones()];
-
     // Aggregate over all scheduling lookups
-    for round in 0..N_SCHEDULING_ROUNDS {
-        let start = W_SIZE + round * SchedulingInteractionColumns::SIZE;
-        let end = start + SchedulingInteractionColumns::SIZE;
-
-        let cols = SchedulingInteractionColumns::from_slice(&scheduling_lookup_data[start..end]);
-
-        izip!(cols.w_15_i0_low, cols.w_15_i0_high).for_each(|(w_15_i0_low, w_15_i0_high)| {
-            let idx_i0 = pext_u32x16(w_15_i0_low + (w_15_i0_high << 16), Sigma0::I0);
-            idx_i0
-                .to_array()
-                .iter()
-                .for_each(|x| i0_mult[*x as usize] += 1);
-        });
-        izip!(cols.w_15_i1_low, cols.w_15_i1_high).for_each(|(w_15_i1_low, w_15_i1_high)| {
-            let idx_i1 = pext_u32x16(w_15_i1_low + (w_15_i1_high << 16), Sigma0::I1);
-            idx_i1
-                .to_array()
-                .iter()
-                .for_each(|x| i1_mult[*x as usize] += 1);
-        });
-    }
+    let [i0_mult, i1_mult] = par_histograms(
+        [Sigma0::I0.count_ones(), Sigma0::I1.count_ones()],
+        N_SCHEDULING_ROUNDS,
+        |round, [i0_mult, i1_mult]| {
+            let start = W_SIZE + round * SchedulingInteractionColumns::SIZE;
+            let end = start + SchedulingInteractionColumns::SIZE;
+
+            let cols =
+                SchedulingInteractionColumns::from_slice(&scheduling_lookup_data[start..end]);
+
+            izip!(cols.w_15_i0_low, cols.w_15_i0_high).for_each(|(w_15_i0_low, w_15_i0_high)| {
+                let idx_i0 = pext_u32x16(w_15_i0_low + (w_15_i0_high << 16), Sigma0::I0);
+                i0_mult.add(idx_i0);
+            });
+            izip!(cols.w_15_i1_low, cols.w_15_i1_high).for_each(|(w_15_i1_low, w_15_i1_high)| {
+                let idx_i1 = pext_u32x16(w_15_i1_low + (w_15_i1_high << 16), Sigma0::I1);
+                i1_mult.add(idx_i1);
+            });
+        },
+    );
 
-    into_simd(&i0_mult)
-        .chunks((1 << (log_size - LOG_N_LANES)) as usize)
-        .zip_eq(into_simd(&i1_mult).chunks((1 << (log_size - LOG_N_LANES)) as usize))
-        .flat_map(|(i0, i1)| [i0.to_vec(), i1.to_vec()])
+    i0_mult
+        .into_columns(log_size)
+        .into_iter()
+        .zip_eq(i1_mult.into_columns(log_size))
+        .flat_map(|(i0, i1)| [i0, i1])
         .collect()
 }
 
@@ -6,7 +6,7 @@ use utils::add_to_relation;
 use crate::{
     components::{scheduling::columns::RoundColumnsOwned, W_SIZE},
     relations::Relations,
-    sha256::N_SCHEDULING_ROUNDS,
+    sha256::{CHUNK_SIZE, N_SCHEDULING_ROUNDS},
 };
 
 pub type Component = FrameworkComponent<Eval>;
@@ -15,6 +15,12 @@ fn eval_scheduling_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relations
     let w: [E::F; W_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
     let one = E::EF::one();
     let minus_one = -E::EF::one();
+
+    // Range check the message limbs, the scheduled ones are checked with their carries
+    for limb in &w[..CHUNK_SIZE] {
+        add_to_relation!(eval, relations.range_check_16.limb, minus_one, *limb);
+    }
+
     for t in 16..(16 + N_SCHEDULING_ROUNDS) {
         let cols = RoundColumnsOwned::<<E as EvalAtRow>::F>::from_eval(eval);
 
@@ -170,19 +176,52 @@ impl FrameworkEval for Eval {
 
 #[cfg(test)]
 mod tests {
+    use std::simd::u32x16;
+
     use itertools::Itertools;
-    use stwo::core::{pcs::TreeVec, poly::circle::CanonicCoset};
-    use stwo_constraint_framework::assert_constraints_on_polys;
+    use stwo::core::{
+        channel::Blake2sChannel,
+        fields::{qm31::SecureField, FieldExpOps},
+        pcs::TreeVec,
+        poly::circle::CanonicCoset,
+    };
+    use stwo_constraint_framework::{assert_constraints_on_polys, Relation};
+    use utils::stwo::air::LogupSum;
 
     use super::*;
-    use crate::components::scheduling::witness::{gen_interaction_trace, gen_trace};
+    use crate::{
+        components::{
+            compression, preprocessed,
+            scheduling::{
+                columns::RoundColumns,
+                witness::{
+                    gen_interaction_trace, gen_message_columns, gen_trace, gen_trace_from_message,
+                },
+            },
+            soundness::{Harness, LogupHarness},
+        },
+        message::{gen_linked_blocks, generate_blocks, Part},
+        public::{block_claims, logup_sum},
+        sha256::HashFunction,
+    };
+
+    /// Names of the trace columns, the message schedule limbs then the rounds.
+    fn column_names() -> Vec<String> {
+        let w = (0..W_SIZE).map(|i| format!("w{}_{}", i / 2, ["low", "high"][i % 2]));
+        let rounds = (16..16 + N_SCHEDULING_ROUNDS).flat_map(|t| {
+            RoundColumns::NAMES
+                .iter()
+                .map(move |name| format!("round{t}_{name}"))
+        });
+        w.chain(rounds).collect()
+    }
 
     #[test]
     fn test_scheduling_constraints() {
         const LOG_N_ROWS: u32 = 8;
 
         // Trace.
-        let (trace, lookup_data) = gen_trace(LOG_N_ROWS);
+        let (trace, lookup_data) = gen_trace(LOG_N_ROWS, &generate_blocks(1 << LOG_N_ROWS));
 
         let relations = Relations::dummy();
         let (interaction_trace, claimed_sum) = gen_interaction_trace(&lookup_data, &relations);
@@ -200,4 +239,145 @@ mod tests {
             claimed_sum,
         );
     }
+
+    #[test]
+    fn test_scheduling_soundness() {
+        const LOG_N_ROWS: u32 = 4;
+
+        // Every row is an active block.
+        let (trace, lookup_data) = gen_trace(LOG_N_ROWS, &generate_blocks(1 << LOG_N_ROWS));
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) = gen_interaction_trace(&lookup_data, &relations);
+
+        let mut harness = Harness::new(column_names(), vec![], trace, interaction_trace, |polys| {
+            assert_constraints_on_polys(
+                polys,
+                CanonicCoset::new(LOG_N_ROWS),
+                |mut eval| {
+                    eval_scheduling_constraints(&mut eval, &relations);
+                },
+                claimed_sum,
+            )
+        });
+
+        // The message, the first and the last round, all the rounds with slow-tests.
+        let last_round_start = W_SIZE + RoundColumns::SIZE * (N_SCHEDULING_ROUNDS - 1);
+        let unconstrained = harness.unconstrained(1, |col| {
+            cfg!(feature = "slow-tests")
+                || col < W_SIZE + RoundColumns::SIZE
+                || col >= last_round_start
+        });
+        assert!(
+            unconstrained.is_empty(),
+            "Unconstrained columns: {unconstrained:?}"
+        );
+    }
+
+    #[test]
+    fn test_scheduling_message_range_check() {
+        const LOG_N_ROWS: u32 = 4;
+
+        // The left half is unclaimed, like an HMAC key block: only the range check of the message
+        // limbs binds them to the 32-bit words hashed by the compression.
+        let hash_function = HashFunction::Sha256;
+        let blocks = gen_linked_blocks(
+            hash_function,
+            &[vec![
+                Part::Unclaimed(vec![0x42; 32]),
+                Part::Bytes(b"abc".to_vec()),
+            ]],
+        );
+        let claims = block_claims(hash_function, &blocks);
+        let relations = Relations::draw(&mut Blake2sChannel::default());
+
+        // Regenerates all the components from the message limbs. Without the range check, the
+        // scheduling no longer consumes the message limbs and the table no longer counts them.
+        let total_sum = |message: &[Vec<u32x16>], range_check: bool| {
+            let (trace, mut lookup_data) = gen_trace_from_message(LOG_N_ROWS, message);
+            let (compression_trace, compression_lookup_data) =
+                compression::witness::gen_trace(&trace, &blocks, hash_function);
+
+            let (interaction_trace, claimed_sum) = gen_interaction_trace(&lookup_data, &relations);
+            let polys = TreeVec::new(vec![vec![], trace, interaction_trace])
+                .map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+            assert_constraints_on_polys(
+                &polys,
+                CanonicCoset::new(LOG_N_ROWS),
+                |mut eval| {
+                    eval_scheduling_constraints(&mut eval, &relations);
+                },
+                claimed_sum,
+            );
+
+            let (compression_interaction_trace, compression_claimed_sum) =
+                compression::witness::gen_interaction_trace(
+                    &compression_lookup_data,
+                    &relations,
+                    hash_function,
+                );
+            let polys = TreeVec::new(vec![
+                vec![],
+                compression_trace,
+                compression_interaction_trace,
+            ])
+            .map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+            assert_constraints_on_polys(
+                &polys,
+                CanonicCoset::new(LOG_N_ROWS),
+                |eval| {
+                    compression::air::Eval {
+                        log_size: LOG_N_ROWS,
+                        relations: relations.clone(),
+                        hash_function,
+                    }
+                    .evaluate(eval);
+                },
+                compression_claimed_sum,
+            );
+
+            let mut sum = claimed_sum + compression_claimed_sum + logup_sum(&claims, &relations);
+            if !range_check {
+                // Each message limb is consumed once
+                for value in message.iter().flatten().flat_map(|limbs| limbs.to_array()) {
+                    let denom: SecureField =
+                        relations.range_check_16.limb.combine(&[M31::from(value)]);
+                    sum += denom.inverse();
+                }
+                for limbs in &mut lookup_data[..CHUNK_SIZE] {
+                    limbs.fill(u32x16::splat(0));
+                }
+            }
+            let tables =
+                preprocessed::gen_trace(LOG_N_ROWS, &lookup_data, &compression_lookup_data);
+            let (_, tables_claimed_sum) = preprocessed::gen_interaction_trace(&tables, &relations);
+            sum += tables_claimed_sum.sum();
+            if !range_check {
+                sum -= tables_claimed_sum.range_check_16_range_check_16;
+            }
+            sum
+        };
+
+        let names = (0..CHUNK_SIZE)
+            .map(|i| format!("w{}_{}", i / 2, ["low", "high"][i % 2]))
+            .collect_vec();
+        let message = gen_message_columns(LOG_N_ROWS, &blocks);
+
+        // Moving a carry keeps the words, hence the digest, but the low limb is out of range.
+        let harness = LogupHarness::new(names.clone(), message.clone(), |message| {
+            total_sum(message, true)
+        });
+        let unconstrained = harness.unconstrained(0, |col| col < 2);
+        assert!(
+            unconstrained.is_empty(),
+            "Unconstrained columns: {unconstrained:?}"
+        );
+
+        // The same mutation goes through without the range check.
+        let harness = LogupHarness::new(names, message, |message| total_sum(message, false));
+        let unconstrained = harness.unconstrained(0, |col| col < 2);
+        assert!(
+            unconstrained.contains(&"w0_low (MoveCarry)".to_string()),
+            "Unconstrained columns: {unconstrained:?}"
+        );
+    }
 }
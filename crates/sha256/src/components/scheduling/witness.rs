@@ -16,13 +16,14 @@ use stwo::{
     },
 };
 use stwo_constraint_framework::{LogupTraceGenerator, Relation};
-use utils::{combine, consume_pair, emit_col, simd::generate_simd_sequence_bulk};
+use utils::{combine, consume_pair, emit_col, simd::for_each_row_chunk};
 
 use crate::{
     components::{
         scheduling::columns::{RoundColumns, RoundInteractionColumns},
         W_SIZE,
     },
+    message::{block_column, Block},
     partitions::{pext_u32x16, Sigma0, Sigma1},
     relations::Relations,
     sha256::{small_sigma_0_u32x16, small_sigma_1_u32x16, CHUNK_SIZE, N_SCHEDULING_ROUNDS},
@@ -31,187 +32,211 @@ use crate::{
 const N_COLUMNS: usize = W_SIZE + RoundColumns::SIZE * N_SCHEDULING_ROUNDS;
 const N_INTERACTION_COLUMNS: usize = W_SIZE + RoundInteractionColumns::SIZE * N_SCHEDULING_ROUNDS;
 
+/// Generates the scheduling trace for a batch of message blocks.
+///
+/// Each block is split into 16-bit limbs in the `W_SIZE` layout (low limb first) and the batch
+/// is padded with default blocks up to `1 << log_size` rows.
 #[allow(clippy::type_complexity)]
 pub fn gen_trace(
     log_size: u32,
+    blocks: &[Block],
 ) -> (
     ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
     Vec<Vec<u32x16>>,
 ) {
     assert!(log_size >= LOG_N_LANES);
+    assert!(
+        blocks.len() <= 1 << log_size,
+        "Too many blocks: {} > {}",
+        blocks.len(),
+        1 << log_size
+    );
+
+    // Split the message blocks into limbs
+    gen_trace_from_message(log_size, &gen_message_columns(log_size, blocks))
+}
+
+/// Generates the scheduling trace from the message limbs, laid out as by
+/// [`gen_message_columns`].
+#[allow(clippy::type_complexity)]
+pub fn gen_trace_from_message(
+    log_size: u32,
+    message: &[Vec<u32x16>],
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    Vec<Vec<u32x16>>,
+) {
+    assert_eq!(message.len(), CHUNK_SIZE);
     let simd_size = 1 << (log_size - LOG_N_LANES);
 
     // Initialize vec for all groups of columns
     let mut evals: Vec<Vec<u32x16>> = (0..N_COLUMNS)
-        .map(|_| Vec::with_capacity(simd_size))
+        .map(|_| vec![u32x16::splat(0); simd_size])
         .collect::<Vec<_>>();
     let mut lookup_data: Vec<Vec<u32x16>> = (0..N_INTERACTION_COLUMNS)
-        .map(|_| Vec::with_capacity(simd_size))
+        .map(|_| vec![u32x16::splat(0); simd_size])
         .collect::<Vec<_>>();
 
-    // Generate random inputs
-    evals
-        .iter_mut()
-        .enumerate()
-        .take(CHUNK_SIZE)
-        .for_each(|(i, eval)| {
-            *eval = generate_simd_sequence_bulk(i, 1 << log_size);
-        });
-    lookup_data
-        .iter_mut()
-        .enumerate()
-        .take(CHUNK_SIZE)
-        .for_each(|(i, eval)| {
-            *eval = generate_simd_sequence_bulk(i, 1 << log_size);
-        });
-
-    for t in 16..(16 + N_SCHEDULING_ROUNDS) {
-        let index = W_SIZE + (t - 16) * RoundColumns::SIZE;
-        let interaction_index = W_SIZE + (t - 16) * RoundInteractionColumns::SIZE;
-
-        for simd_row in 0..simd_size {
-            // Load the W values
-            let w_16_low = evals[2 * (t - 16)][simd_row];
-            let w_16_high = evals[2 * (t - 16) + 1][simd_row];
-            let w_15_low = evals[2 * (t - 15)][simd_row];
-            let w_15_high = evals[2 * (t - 15) + 1][simd_row];
-            let w_7_low = evals[2 * (t - 7)][simd_row];
-            let w_7_high = evals[2 * (t - 7) + 1][simd_row];
-            let w_2_low = evals[2 * (t - 2)][simd_row];
-            let w_2_high = evals[2 * (t - 2) + 1][simd_row];
-
-            // SIGMA0
-            // Decomposition over I0
-            let w_15_i0_low = w_15_low & u32x16::splat(Sigma0::I0_L);
-            let w_15_i0_high = w_15_high & u32x16::splat(Sigma0::I0_H);
-            let sigma_0 = small_sigma_0_u32x16(w_15_i0_low + (w_15_i0_high << 16));
-            let sigma_0_o0_low = sigma_0 & u32x16::splat(Sigma0::O0_L);
-            let sigma_0_o0_high = (sigma_0 >> 16) & u32x16::splat(Sigma0::O0_H);
-            let sigma_0_o20 = sigma_0 & u32x16::splat(Sigma0::O2);
-            let sigma_0_o20_pext = pext_u32x16(sigma_0_o20, Sigma0::O2);
-
-            // Decomposition over I1
-            let w_15_i1_low = w_15_low & u32x16::splat(Sigma0::I1_L);
-            let w_15_i1_high = w_15_high & u32x16::splat(Sigma0::I1_H);
-            let sigma_0 = small_sigma_0_u32x16(w_15_i1_low + (w_15_i1_high << 16));
-            let sigma_0_o1_low = sigma_0 & u32x16::splat(Sigma0::O1_L);
-            let sigma_0_o1_high = (sigma_0 >> 16) & u32x16::splat(Sigma0::O1_H);
-            let sigma_0_o21 = sigma_0 & u32x16::splat(Sigma0::O2);
-            let sigma_0_o21_pext = pext_u32x16(sigma_0_o21, Sigma0::O2);
-
-            // XOR the two O2 values
-            let sigma_0_o2 = sigma_0_o20 ^ sigma_0_o21;
-            let sigma_0_o2_low = sigma_0_o2 & u32x16::splat(0xffff);
-            let sigma_0_o2_high = sigma_0_o2 >> 16;
-
-            // Compute sigma_0 output
-            let sigma_0_low = sigma_0_o0_low + sigma_0_o1_low + sigma_0_o2_low;
-            let sigma_0_high = sigma_0_o0_high + sigma_0_o1_high + sigma_0_o2_high;
-
-            // SIGMA1
-            // Decomposition over I0
-            let w_2_i0_low = w_2_low & u32x16::splat(Sigma1::I0_L);
-            let w_2_i0_high = w_2_high & u32x16::splat(Sigma1::I0_H);
-            let sigma_1 = small_sigma_1_u32x16(w_2_i0_low + (w_2_i0_high << 16));
-            let sigma_1_o0_low = sigma_1 & u32x16::splat(Sigma1::O0_L);
-            let sigma_1_o0_high = (sigma_1 >> 16) & u32x16::splat(Sigma1::O0_H);
-            let sigma_1_o20 = sigma_1 & u32x16::splat(Sigma1::O2);
-            let sigma_1_o20_pext = pext_u32x16(sigma_1_o20, Sigma1::O2);
-
-            // Decomposition over I1
-            let w_2_i1_low = w_2_low & u32x16::splat(Sigma1::I1_L);
-            let w_2_i1_high = w_2_high & u32x16::splat(Sigma1::I1_H);
-            let sigma_1 = small_sigma_1_u32x16(w_2_i1_low + (w_2_i1_high << 16));
-            let sigma_1_o1_low = sigma_1 & u32x16::splat(Sigma1::O1_L);
-            let sigma_1_o1_high = (sigma_1 >> 16) & u32x16::splat(Sigma1::O1_H);
-            let sigma_1_o21 = sigma_1 & u32x16::splat(Sigma1::O2);
-            let sigma_1_o21_pext = pext_u32x16(sigma_1_o21, Sigma1::O2);
-
-            // XOR the two O2 values
-            let sigma_1_o2 = sigma_1_o20 ^ sigma_1_o21;
-            let sigma_1_o2_low = sigma_1_o2 & u32x16::splat(0xffff);
-            let sigma_1_o2_high = sigma_1_o2 >> 16;
-
-            // Compute sigma_1 output
-            let sigma_1_low = sigma_1_o0_low + sigma_1_o1_low + sigma_1_o2_low;
-            let sigma_1_high = sigma_1_o0_high + sigma_1_o1_high + sigma_1_o2_high;
-
-            // Compute the final output
-            let round_low = w_16_low + sigma_0_low + w_7_low + sigma_1_low;
-            let round_high = w_16_high + sigma_0_high + w_7_high + sigma_1_high;
-            let carry_low = round_low >> 16;
-            let carry_high = (round_high + carry_low) >> 16;
-            let new_w_low = round_low - (carry_low << 16);
-            let new_w_high = round_high + carry_low - (carry_high << 16);
-
-            let trace_values: RoundColumns<u32x16> = RoundColumns {
-                w_15_i0_low: &w_15_i0_low,
-                w_15_i0_high: &w_15_i0_high,
-                sigma_0_o0_low: &sigma_0_o0_low,
-                sigma_0_o0_high: &sigma_0_o0_high,
-                sigma_0_o20_pext: &sigma_0_o20_pext,
-                sigma_0_o1_low: &sigma_0_o1_low,
-                sigma_0_o1_high: &sigma_0_o1_high,
-                sigma_0_o21_pext: &sigma_0_o21_pext,
-                sigma_0_o2_low: &sigma_0_o2_low,
-                sigma_0_o2_high: &sigma_0_o2_high,
-                w_2_i0_low: &w_2_i0_low,
-                w_2_i0_high: &w_2_i0_high,
-                sigma_1_o0_low: &sigma_1_o0_low,
-                sigma_1_o0_high: &sigma_1_o0_high,
-                sigma_1_o20_pext: &sigma_1_o20_pext,
-                sigma_1_o1_low: &sigma_1_o1_low,
-                sigma_1_o1_high: &sigma_1_o1_high,
-                sigma_1_o21_pext: &sigma_1_o21_pext,
-                sigma_1_o2_low: &sigma_1_o2_low,
-                sigma_1_o2_high: &sigma_1_o2_high,
-                carry_low: &carry_low,
-                carry_high: &carry_high,
-            };
-            for (i, value) in trace_values.iter().enumerate() {
-                evals[index + i].push(*value);
-            }
+    evals[..CHUNK_SIZE].clone_from_slice(message);
+    lookup_data[..CHUNK_SIZE].clone_from_slice(message);
 
-            let interaction_values: RoundInteractionColumns<u32x16> = RoundInteractionColumns {
-                w_15_i0_low: &w_15_i0_low,
-                w_15_i0_high: &w_15_i0_high,
-                sigma_0_o0_low: &sigma_0_o0_low,
-                sigma_0_o0_high: &sigma_0_o0_high,
-                sigma_0_o20_pext: &sigma_0_o20_pext,
-                w_15_i1_low: &w_15_i1_low,
-                w_15_i1_high: &w_15_i1_high,
-                sigma_0_o1_low: &sigma_0_o1_low,
-                sigma_0_o1_high: &sigma_0_o1_high,
-                sigma_0_o21_pext: &sigma_0_o21_pext,
-                sigma_0_o2_low: &sigma_0_o2_low,
-                sigma_0_o2_high: &sigma_0_o2_high,
-                w_2_i0_low: &w_2_i0_low,
-                w_2_i0_high: &w_2_i0_high,
-                sigma_1_o0_low: &sigma_1_o0_low,
-                sigma_1_o0_high: &sigma_1_o0_high,
-                sigma_1_o20_pext: &sigma_1_o20_pext,
-                w_2_i1_low: &w_2_i1_low,
-                w_2_i1_high: &w_2_i1_high,
-                sigma_1_o1_low: &sigma_1_o1_low,
-                sigma_1_o1_high: &sigma_1_o1_high,
-                sigma_1_o21_pext: &sigma_1_o21_pext,
-                sigma_1_o2_low: &sigma_1_o2_low,
-                sigma_1_o2_high: &sigma_1_o2_high,
-                new_w_low: &new_w_low,
-                new_w_high: &new_w_high,
-                carry_low: &carry_low,
-                carry_high: &carry_high,
-            };
-            for (i, value) in interaction_values.iter().enumerate() {
-                lookup_data[interaction_index + i].push(*value);
+    for_each_row_chunk(&mut evals, &mut lookup_data, |evals, lookup_data| {
+        for row in 0..evals[0].len() {
+            // Load the message limbs, the scheduled ones are kept in registers
+            let mut w = [u32x16::splat(0); W_SIZE];
+            for (i, limb) in w.iter_mut().take(CHUNK_SIZE).enumerate() {
+                *limb = evals[i][row];
             }
 
-            evals[2 * t].push(new_w_low);
-            evals[2 * t + 1].push(new_w_high);
-            lookup_data[2 * t].push(new_w_low);
-            lookup_data[2 * t + 1].push(new_w_high);
+            for t in 16..(16 + N_SCHEDULING_ROUNDS) {
+                let index = W_SIZE + (t - 16) * RoundColumns::SIZE;
+                let interaction_index = W_SIZE + (t - 16) * RoundInteractionColumns::SIZE;
+
+                // Load the W values
+                let w_16_low = w[2 * (t - 16)];
+                let w_16_high = w[2 * (t - 16) + 1];
+                let w_15_low = w[2 * (t - 15)];
+                let w_15_high = w[2 * (t - 15) + 1];
+                let w_7_low = w[2 * (t - 7)];
+                let w_7_high = w[2 * (t - 7) + 1];
+                let w_2_low = w[2 * (t - 2)];
+                let w_2_high = w[2 * (t - 2) + 1];
+
+                // SIGMA0
+                // Decomposition over I0
+                let w_15_i0_low = w_15_low & u32x16::splat(Sigma0::I0_L);
+                let w_15_i0_high = w_15_high & u32x16::splat(Sigma0::I0_H);
+                let sigma_0 = small_sigma_0_u32x16(w_15_i0_low + (w_15_i0_high << 16));
+                let sigma_0_o0_low = sigma_0 & u32x16::splat(Sigma0::O0_L);
+                let sigma_0_o0_high = (sigma_0 >> 16) & u32x16::splat(Sigma0::O0_H);
+                let sigma_0_o20 = sigma_0 & u32x16::splat(Sigma0::O2);
+                let sigma_0_o20_pext = pext_u32x16(sigma_0_o20, Sigma0::O2);
+
+                // Decomposition over I1
+                let w_15_i1_low = w_15_low & u32x16::splat(Sigma0::I1_L);
+                let w_15_i1_high = w_15_high & u32x16::splat(Sigma0::I1_H);
+                let sigma_0 = small_sigma_0_u32x16(w_15_i1_low + (w_15_i1_high << 16));
+                let sigma_0_o1_low = sigma_0 & u32x16::splat(Sigma0::O1_L);
+                let sigma_0_o1_high = (sigma_0 >> 16) & u32x16::splat(Sigma0::O1_H);
+                let sigma_0_o21 = sigma_0 & u32x16::splat(Sigma0::O2);
+                let sigma_0_o21_pext = pext_u32x16(sigma_0_o21, Sigma0::O2);
+
+                // XOR the two O2 values
+                let sigma_0_o2 = sigma_0_o20 ^ sigma_0_o21;
+                let sigma_0_o2_low = sigma_0_o2 & u32x16::splat(0xffff);
+                let sigma_0_o2_high = sigma_0_o2 >> 16;
+
+                // Compute sigma_0 output
+                let sigma_0_low = sigma_0_o0_low + sigma_0_o1_low + sigma_0_o2_low;
+                let sigma_0_high = sigma_0_o0_high + sigma_0_o1_high + sigma_0_o2_high;
+
+                // SIGMA1
+                // Decomposition over I0
+                let w_2_i0_low = w_2_low & u32x16::splat(Sigma1::I0_L);
+                let w_2_i0_high = w_2_high & u32x16::splat(Sigma1::I0_H);
+                let sigma_1 = small_sigma_1_u32x16(w_2_i0_low + (w_2_i0_high << 16));
+                let sigma_1_o0_low = sigma_1 & u32x16::splat(Sigma1::O0_L);
+                let sigma_1_o0_high = (sigma_1 >> 16) & u32x16::splat(Sigma1::O0_H);
+                let sigma_1_o20 = sigma_1 & u32x16::splat(Sigma1::O2);
+                let sigma_1_o20_pext = pext_u32x16(sigma_1_o20, Sigma1::O2);
+
+                // Decomposition over I1
+                let w_2_i1_low = w_2_low & u32x16::splat(Sigma1::I1_L);
+                let w_2_i1_high = w_2_high & u32x16::splat(Sigma1::I1_H);
+                let sigma_1 = small_sigma_1_u32x16(w_2_i1_low + (w_2_i1_high << 16));
+                let sigma_1_o1_low = sigma_1 & u32x16::splat(Sigma1::O1_L);
+                let sigma_1_o1_high = (sigma_1 >> 16) & u32x16::splat(Sigma1::O1_H);
+                let sigma_1_o21 = sigma_1 & u32x16::splat(Sigma1::O2);
+                let sigma_1_o21_pext = pext_u32x16(sigma_1_o21, Sigma1::O2);
+
+                // XOR the two O2 values
+                let sigma_1_o2 = sigma_1_o20 ^ sigma_1_o21;
+                let sigma_1_o2_low = sigma_1_o2 & u32x16::splat(0xffff);
+                let sigma_1_o2_high = sigma_1_o2 >> 16;
+
+                // Compute sigma_1 output
+                let sigma_1_low = sigma_1_o0_low + sigma_1_o1_low + sigma_1_o2_low;
+                let sigma_1_high = sigma_1_o0_high + sigma_1_o1_high + sigma_1_o2_high;
+
+                // Compute the final output
+                let round_low = w_16_low + sigma_0_low + w_7_low + sigma_1_low;
+                let round_high = w_16_high + sigma_0_high + w_7_high + sigma_1_high;
+                let carry_low = round_low >> 16;
+                let carry_high = (round_high + carry_low) >> 16;
+                let new_w_low = round_low - (carry_low << 16);
+                let new_w_high = round_high + carry_low - (carry_high << 16);
+
+                let trace_values: RoundColumns<u32x16> = RoundColumns {
+                    w_15_i0_low: &w_15_i0_low,
+                    w_15_i0_high: &w_15_i0_high,
+                    sigma_0_o0_low: &sigma_0_o0_low,
+                    sigma_0_o0_high: &sigma_0_o0_high,
+                    sigma_0_o20_pext: &sigma_0_o20_pext,
+                    sigma_0_o1_low: &sigma_0_o1_low,
+                    sigma_0_o1_high: &sigma_0_o1_high,
+                    sigma_0_o21_pext: &sigma_0_o21_pext,
+                    sigma_0_o2_low: &sigma_0_o2_low,
+                    sigma_0_o2_high: &sigma_0_o2_high,
+                    w_2_i0_low: &w_2_i0_low,
+                    w_2_i0_high: &w_2_i0_high,
+                    sigma_1_o0_low: &sigma_1_o0_low,
+                    sigma_1_o0_high: &sigma_1_o0_high,
+                    sigma_1_o20_pext: &sigma_1_o20_pext,
+                    sigma_1_o1_low: &sigma_1_o1_low,
+                    sigma_1_o1_high: &sigma_1_o1_high,
+                    sigma_1_o21_pext: &sigma_1_o21_pext,
+                    sigma_1_o2_low: &sigma_1_o2_low,
+                    sigma_1_o2_high: &sigma_1_o2_high,
+                    carry_low: &carry_low,
+                    carry_high: &carry_high,
+                };
+                for (i, value) in trace_values.iter().enumerate() {
+                    evals[index + i][row] = *value;
+                }
+
+                let interaction_values: RoundInteractionColumns<u32x16> = RoundInteractionColumns {
+                    w_15_i0_low: &w_15_i0_low,
+                    w_15_i0_high: &w_15_i0_high,
+                    sigma_0_o0_low: &sigma_0_o0_low,
+                    sigma_0_o0_high: &sigma_0_o0_high,
+                    sigma_0_o20_pext: &sigma_0_o20_pext,
+                    w_15_i1_low: &w_15_i1_low,
+                    w_15_i1_high: &w_15_i1_high,
+                    sigma_0_o1_low: &sigma_0_o1_low,
+                    sigma_0_o1_high: &sigma_0_o1_high,
+                    sigma_0_o21_pext: &sigma_0_o21_pext,
+                    sigma_0_o2_low: &sigma_0_o2_low,
+                    sigma_0_o2_high: &sigma_0_o2_high,
+                    w_2_i0_low: &w_2_i0_low,
+                    w_2_i0_high: &w_2_i0_high,
+                    sigma_1_o0_low: &sigma_1_o0_low,
+                    sigma_1_o0_high: &sigma_1_o0_high,
+                    sigma_1_o20_pext: &sigma_1_o20_pext,
+                    w_2_i1_low: &w_2_i1_low,
+                    w_2_i1_high: &w_2_i1_high,
+                    sigma_1_o1_low: &sigma_1_o1_low,
+                    sigma_1_o1_high: &sigma_1_o1_high,
+                    sigma_1_o21_pext: &sigma_1_o21_pext,
+                    sigma_1_o2_low: &sigma_1_o2_low,
+                    sigma_1_o2_high: &sigma_1_o2_high,
+                    new_w_low: &new_w_low,
+                    new_w_high: &new_w_high,
+                    carry_low: &carry_low,
+                    carry_high: &carry_high,
+                };
+                for (i, value) in interaction_values.iter().enumerate() {
+                    lookup_data[interaction_index + i][row] = *value;
+                }
+
+                w[2 * t] = new_w_low;
+                w[2 * t + 1] = new_w_high;
+                evals[2 * t][row] = new_w_low;
+                evals[2 * t + 1][row] = new_w_high;
+                lookup_data[2 * t][row] = new_w_low;
+                lookup_data[2 * t + 1][row] = new_w_high;
+            }
         }
-    }
+    });
 
     let domain = CanonicCoset::new(log_size).circle_domain();
     let trace = evals
@@ -232,6 +257,20 @@ pub fn gen_trace(
     (trace, lookup_data)
 }
 
+/// Splits the message blocks into `CHUNK_SIZE` columns of 16-bit limbs, padding with default
+/// blocks up to `1 << log_size` rows.
+pub fn gen_message_columns(log_size: u32, blocks: &[Block]) -> Vec<Vec<u32x16>> {
+    let simd_size = 1 << (log_size - LOG_N_LANES);
+    (0..CHUNK_SIZE)
+        .map(|limb| {
+            let (word, shift) = (limb / 2, 16 * (limb % 2));
+            block_column(blocks, simd_size, |block| {
+                (block.words[word] >> shift) & 0xffff
+            })
+        })
+        .collect()
+}
+
 pub fn gen_interaction_trace(
     lookup_data: &[Vec<u32x16>],
     relations: &Relations,
@@ -242,6 +281,13 @@ pub fn gen_interaction_trace(
     let simd_size = lookup_data[0].len();
     let mut interaction_trace = LogupTraceGenerator::new(simd_size.ilog2() + LOG_N_LANES);
 
+    // Range check the message limbs
+    for [low, high] in lookup_data[..CHUNK_SIZE].array_chunks::<2>() {
+        let low = combine!(relations.range_check_16.limb, [low]);
+        let high = combine!(relations.range_check_16.limb, [high]);
+        consume_pair!(interaction_trace; low, high);
+    }
+
     for round in lookup_data[W_SIZE..].array_chunks::<{ RoundInteractionColumns::SIZE }>() {
         let RoundInteractionColumns {
             w_15_i0_low,
@@ -359,19 +405,39 @@ mod tests {
     use stwo::prover::backend::Column;
 
     use super::*;
-    use crate::sha256::{small_sigma_0, small_sigma_1};
+    use crate::{
+        message::generate_blocks,
+        sha256::{small_sigma_0, small_sigma_1},
+    };
 
     #[test]
     fn test_gen_trace_columns_count() {
-        let (trace, _) = gen_trace(LOG_N_LANES + 3);
+        let (trace, _) = gen_trace(LOG_N_LANES + 3, &generate_blocks(1 << (LOG_N_LANES + 3)));
         assert_eq!(trace.len(), N_COLUMNS);
     }
 
+    #[test]
+    fn test_gen_message_columns() {
+        let blocks = generate_blocks(3);
+        let columns = gen_message_columns(LOG_N_LANES, &blocks);
+        assert_eq!(columns.len(), CHUNK_SIZE);
+        for (row, block) in blocks.iter().enumerate() {
+            for (i, word) in block.words.iter().enumerate() {
+                assert_eq!(columns[2 * i][0][row], word & 0xffff);
+                assert_eq!(columns[2 * i + 1][0][row], word >> 16);
+            }
+        }
+        // Padding rows are zero blocks
+        for column in columns.iter() {
+            assert!(column[0].to_array()[blocks.len()..].iter().all(|x| *x == 0));
+        }
+    }
+
     #[test]
     fn test_gen_trace_values() {
         let log_size = LOG_N_LANES;
         let size = 1 << log_size;
-        let (mut trace, _) = gen_trace(log_size);
+        let (mut trace, _) = gen_trace(log_size, &generate_blocks(size));
         let w = trace
             .drain(0..W_SIZE)
             .map(|eval| {
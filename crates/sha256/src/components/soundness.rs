@@ -0,0 +1,219 @@
+//! Soundness harness mutating honest traces.
+//!
+//! Each mutation changes one cell of the trace, or a pair of limbs, while the interaction trace
+//! is kept honest. A mutation is rejected when a constraint of the component fails on the mutated
+//! row: either an arithmetic constraint, or a logup constraint because the entries the component
+//! adds to a relation changed. Recomputing the interaction trace instead would move the claimed
+//! sum of the component, which no longer balances the other, honest, components.
+//!
+//! That mode cannot catch a missing or loose lookup, whose entries the honest interaction trace
+//! still balances. [`LogupHarness`] mutates the inputs of a component instead, regenerates the
+//! whole witness from them as a malicious prover would, and checks the constraints and the total
+//! claimed sum of all the components plus the public `logup_sum`.
+//!
+//! Mutations that are not rejected point to unconstrained columns, reported by their
+//! `trace_columns!` names.
+
+use std::{
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    simd::u32x16,
+};
+
+use itertools::Itertools;
+use num_traits::Zero;
+use stwo::{
+    core::{
+        fields::{
+            m31::{BaseField, M31},
+            qm31::SecureField,
+        },
+        pcs::TreeVec,
+    },
+    prover::{
+        backend::{
+            simd::{m31::N_LANES, SimdBackend},
+            Column,
+        },
+        poly::{
+            circle::{CircleEvaluation, CirclePoly},
+            BitReversedOrder,
+        },
+    },
+};
+
+const LIMB_SHIFT: u32 = 1 << 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// Flips the lowest bit of the cell.
+    Flip,
+    /// Adds 2^16 to the cell, i.e. a limb out of its range.
+    AddLimb,
+    /// Subtracts 2^16 from the cell, modulo p.
+    SubLimb,
+    /// Moves a carry from a `_high` limb to its `_low` limb, which keeps the 32-bit value.
+    MoveCarry,
+}
+
+impl Mutation {
+    /// Mutated value of the cell, the `_low` limb for [`Mutation::MoveCarry`].
+    fn apply(self, value: BaseField) -> BaseField {
+        match self {
+            Self::Flip => M31::from(value.0 ^ 1),
+            Self::AddLimb | Self::MoveCarry => value + M31::from(LIMB_SHIFT),
+            Self::SubLimb => value - M31::from(LIMB_SHIFT),
+        }
+    }
+}
+
+/// Mutations applied to every column.
+const CELL_MUTATIONS: [Mutation; 3] = [Mutation::Flip, Mutation::AddLimb, Mutation::SubLimb];
+
+/// The `_high` limb of each `_low` limb.
+fn high_limbs(names: &[String]) -> Vec<Option<usize>> {
+    let index = names
+        .iter()
+        .enumerate()
+        .map(|(col, name)| (name.as_str(), col))
+        .collect::<HashMap<_, _>>();
+    names
+        .iter()
+        .map(|name| {
+            name.strip_suffix("_low")
+                .and_then(|prefix| index.get(format!("{prefix}_high").as_str()).copied())
+        })
+        .collect()
+}
+
+pub struct Harness<F> {
+    names: Vec<String>,
+    trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    polys: TreeVec<Vec<CirclePoly<SimdBackend>>>,
+    check: F,
+}
+
+impl<F: Fn(&TreeVec<Vec<CirclePoly<SimdBackend>>>)> Harness<F> {
+    /// `names` are the names of the `trace` columns, `check` must panic when the constraints do
+    /// not hold on the polynomials, e.g. by calling `assert_constraints_on_polys`.
+    pub fn new(
+        names: Vec<String>,
+        preprocessed_trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        interaction_trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        check: F,
+    ) -> Self {
+        assert_eq!(names.len(), trace.len());
+        let polys = TreeVec::new(vec![preprocessed_trace, trace.clone(), interaction_trace])
+            .map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+        Self {
+            names,
+            trace,
+            polys,
+            check,
+        }
+    }
+
+    /// Mutates `row` in the columns selected by `columns`, and returns the mutations that are not
+    /// rejected as `"{column} ({mutation:?})"`.
+    pub fn unconstrained(&mut self, row: usize, columns: impl Fn(usize) -> bool) -> Vec<String> {
+        let high_limbs = high_limbs(&self.names);
+        let mut unconstrained = Vec::new();
+        for col in (0..self.trace.len()).filter(|col| columns(*col)) {
+            let value = self.trace[col].values.at(row);
+            for mutation in CELL_MUTATIONS {
+                if !self.is_rejected(row, &[(col, mutation.apply(value))]) {
+                    unconstrained.push(format!("{} ({mutation:?})", self.names[col]));
+                }
+            }
+            if let Some(high) = high_limbs[col] {
+                let cells = [
+                    (col, Mutation::MoveCarry.apply(value)),
+                    (high, self.trace[high].values.at(row) - M31::from(1)),
+                ];
+                if !self.is_rejected(row, &cells) {
+                    unconstrained.push(format!("{} ({:?})", self.names[col], Mutation::MoveCarry));
+                }
+            }
+        }
+
+        unconstrained
+    }
+
+    /// Sets the `cells` of `row` and checks the constraints, then restores the honest trace.
+    fn is_rejected(&mut self, row: usize, cells: &[(usize, BaseField)]) -> bool {
+        let honest = cells
+            .iter()
+            .map(|(col, value)| {
+                let mut eval = self.trace[*col].clone();
+                eval.values.set(row, *value);
+                std::mem::replace(&mut self.polys[1][*col], eval.interpolate())
+            })
+            .collect_vec();
+        // Rejected mutations panic. The panic hook is left alone since tests run in parallel, the
+        // test harness captures the messages.
+        let rejected = panic::catch_unwind(AssertUnwindSafe(|| (self.check)(&self.polys))).is_err();
+        for ((col, _), poly) in cells.iter().zip(honest) {
+            self.polys[1][*col] = poly;
+        }
+        rejected
+    }
+}
+
+pub struct LogupHarness<F> {
+    names: Vec<String>,
+    inputs: Vec<Vec<u32x16>>,
+    total_sum: F,
+}
+
+impl<F: Fn(&[Vec<u32x16>]) -> SecureField> LogupHarness<F> {
+    /// `names` are the names of the honest `inputs` columns. `total_sum` regenerates the witness
+    /// of all the components from the inputs, panics when the constraints of a component do not
+    /// hold, and returns the claimed sums of all the components plus the public `logup_sum`.
+    pub fn new(names: Vec<String>, inputs: Vec<Vec<u32x16>>, total_sum: F) -> Self {
+        assert_eq!(names.len(), inputs.len());
+        Self {
+            names,
+            inputs,
+            total_sum,
+        }
+    }
+
+    /// Mutates `row` in the input columns selected by `columns`, and returns the mutations that
+    /// are not rejected as `"{column} ({mutation:?})"`.
+    pub fn unconstrained(&self, row: usize, columns: impl Fn(usize) -> bool) -> Vec<String> {
+        let value = |col: usize| M31::from(self.inputs[col][row / N_LANES][row % N_LANES]);
+        let high_limbs = high_limbs(&self.names);
+        let mut unconstrained = Vec::new();
+        for col in (0..self.inputs.len()).filter(|col| columns(*col)) {
+            for mutation in CELL_MUTATIONS {
+                if !self.is_rejected(row, &[(col, mutation.apply(value(col)))]) {
+                    unconstrained.push(format!("{} ({mutation:?})", self.names[col]));
+                }
+            }
+            if let Some(high) = high_limbs[col] {
+                let cells = [
+                    (col, Mutation::MoveCarry.apply(value(col))),
+                    (high, value(high) - M31::from(1)),
+                ];
+                if !self.is_rejected(row, &cells) {
+                    unconstrained.push(format!("{} ({:?})", self.names[col], Mutation::MoveCarry));
+                }
+            }
+        }
+
+        unconstrained
+    }
+
+    /// Sets the `cells` of `row` in a copy of the inputs and checks the regenerated witness.
+    fn is_rejected(&self, row: usize, cells: &[(usize, BaseField)]) -> bool {
+        let mut inputs = self.inputs.clone();
+        for (col, value) in cells {
+            inputs[*col][row / N_LANES].as_mut_array()[row % N_LANES] = value.0;
+        }
+        // Witness generation panics on values out of the range of a table, which is a rejection
+        // too: no multiplicities balance them.
+        panic::catch_unwind(AssertUnwindSafe(|| (self.total_sum)(&inputs)))
+            .map_or(true, |sum| !sum.is_zero())
+    }
+}
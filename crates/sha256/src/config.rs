@@ -0,0 +1,147 @@
+//! Security presets for the commitment scheme, and estimates of the resulting proof.
+//!
+//! The conjectured security of a proof is `pow_bits + log_blowup_factor * n_queries`. A larger
+//! blowup factor needs fewer queries, hence smaller proofs, at the cost of a slower prover.
+
+use std::str::FromStr;
+
+use stwo::core::{fri::FriConfig, pcs::PcsConfig};
+use stwo_constraint_framework::TraceLocationAllocator;
+use utils::stwo::air::AirComponents;
+pub use utils::stwo::air::{is_supported, TWIDDLES_EXTRA_LOG_SIZE};
+
+use crate::{
+    components::{ClaimedSum, Components},
+    preprocessed,
+    relations::Relations,
+    sha256::HashFunction,
+};
+
+const HASH_SIZE: usize = 32;
+const BASE_FIELD_SIZE: usize = 4;
+const SECURE_FIELD_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityPreset {
+    /// Few queries and no proof of work, only meant for development.
+    FastDev,
+    /// 96 bits of conjectured security.
+    Bits96,
+    /// 128 bits of conjectured security.
+    Bits128,
+}
+
+impl SecurityPreset {
+    pub fn pcs_config(self) -> PcsConfig {
+        let (log_blowup_factor, n_queries, pow_bits) = match self {
+            Self::FastDev => (1, 3, 0),
+            Self::Bits96 => (2, 35, 26),
+            Self::Bits128 => (2, 51, 26),
+        };
+        let mut config = PcsConfig::default();
+        config.pow_bits = pow_bits;
+        config.fri_config = FriConfig::new(
+            config.fri_config.log_last_layer_degree_bound,
+            log_blowup_factor,
+            n_queries,
+        );
+        config
+    }
+}
+
+impl FromStr for SecurityPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fast-dev" => Ok(Self::FastDev),
+            "96" => Ok(Self::Bits96),
+            "128" => Ok(Self::Bits128),
+            _ => Err(format!(
+                "Unknown preset {s}, expected one of fast-dev, 96, 128"
+            )),
+        }
+    }
+}
+
+/// Conjectured security bits of a configuration.
+pub const fn security_bits(config: &PcsConfig) -> u32 {
+    config.pow_bits + config.fri_config.log_blowup_factor * config.fri_config.n_queries as u32
+}
+
+/// Estimated proof size in bytes.
+///
+/// This is an upper bound: Merkle authentication paths are counted once per query, while queries
+/// actually share the nodes close to the root.
+pub fn estimate_proof_size(log_size: u32, config: &PcsConfig) -> usize {
+    let log_blowup_factor = config.fri_config.log_blowup_factor;
+    let n_queries = config.fri_config.n_queries;
+
+    // Columns of each committed tree, the composition polynomial is split into secure columns.
+    let (preprocessed_ids, preprocessed_log_sizes) = preprocessed::columns(log_size);
+    let mut log_sizes = Components::new(
+        log_size,
+        HashFunction::Sha256,
+        &mut TraceLocationAllocator::new_with_preprocessed_columns(&preprocessed_ids),
+        &Relations::dummy(),
+        &ClaimedSum::zero(),
+    )
+    .log_sizes();
+    log_sizes[0] = preprocessed_log_sizes;
+    let composition_log_size = log_size + 1;
+    let trees = log_sizes
+        .iter()
+        .map(|columns| (columns.len(), columns.iter().copied().max().unwrap_or(0)))
+        .chain([(2 * 4, composition_log_size)]);
+
+    // Commitments, sampled values and decommitments of the trees.
+    let mut size = 0;
+    for (n_columns, max_log_size) in trees {
+        let path_length = (max_log_size + log_blowup_factor) as usize;
+        size += HASH_SIZE;
+        size += n_columns * 2 * SECURE_FIELD_SIZE;
+        size += n_queries * (path_length * HASH_SIZE + 2 * n_columns * BASE_FIELD_SIZE);
+    }
+
+    // FRI layers, down to the last layer polynomial.
+    let fri_log_size = composition_log_size + log_blowup_factor;
+    let last_layer_log_size = config.fri_config.log_last_layer_degree_bound + log_blowup_factor;
+    for layer_log_size in (last_layer_log_size..fri_log_size).rev() {
+        size += HASH_SIZE;
+        size += n_queries * (layer_log_size as usize * HASH_SIZE + SECURE_FIELD_SIZE);
+    }
+    size += (1 << config.fri_config.log_last_layer_degree_bound) * SECURE_FIELD_SIZE;
+
+    // Proof of work nonce.
+    size + 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_security_bits() {
+        assert_eq!(security_bits(&SecurityPreset::Bits96.pcs_config()), 96);
+        assert_eq!(security_bits(&SecurityPreset::Bits128.pcs_config()), 128);
+        assert!(security_bits(&SecurityPreset::FastDev.pcs_config()) < 96);
+    }
+
+    #[test]
+    fn test_preset_from_str() {
+        assert_eq!("fast-dev".parse(), Ok(SecurityPreset::FastDev));
+        assert_eq!("96".parse(), Ok(SecurityPreset::Bits96));
+        assert_eq!("128".parse(), Ok(SecurityPreset::Bits128));
+        assert!("64".parse::<SecurityPreset>().is_err());
+    }
+
+    #[test]
+    fn test_estimate_proof_size() {
+        let log_size = 8;
+        let fast = estimate_proof_size(log_size, &SecurityPreset::FastDev.pcs_config());
+        let bits_96 = estimate_proof_size(log_size, &SecurityPreset::Bits96.pcs_config());
+        let bits_128 = estimate_proof_size(log_size, &SecurityPreset::Bits128.pcs_config());
+        assert!(fast < bits_96);
+        assert!(bits_96 < bits_128);
+    }
+}
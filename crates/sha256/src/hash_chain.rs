@@ -0,0 +1,169 @@
+//! Iterated SHA-256 chains, `h_{i+1} = SHA256(h_i)`, proven with the SHA-256 AIR.
+//!
+//! The chain starts from the digest of a public seed, and each digest is hashed again as a 32-byte
+//! message, i.e. a single block chained in from the previous message, see
+//! [`Block::is_chained_in`]. The AIR routes each digest into the left half of the next block, and
+//! fixes its right half to the padding of a 32-byte message, so neither is claimed: only the seed
+//! and the final digest are public, and the verifier work does not depend on the number of
+//! iterations.
+//!
+//! Each iteration takes one row.
+
+use sha2::{Digest, Sha256};
+use stwo::{
+    core::{channel::MerkleChannel, pcs::PcsConfig, proof::StarkProof},
+    prover::backend::{simd::SimdBackend, BackendForChannel},
+};
+
+use crate::{
+    components::ClaimedSum,
+    message::{gen_linked_blocks, Block, Part},
+    public,
+    sha256::HashFunction,
+    verifier::Sha256Verifier,
+    Sha256Prover, Sha256ProverError, Sha256VerifierError,
+};
+
+/// Messages hashed along the chain: the seed, then each digest but the last one.
+///
+/// Panics if `n_iterations` is zero.
+pub fn chain_messages(seed: &[u8], n_iterations: usize) -> Vec<Vec<Part>> {
+    assert!(n_iterations > 0, "Empty hash chain");
+    std::iter::once(vec![Part::Bytes(seed.to_vec())])
+        .chain((1..n_iterations).map(|i| vec![Part::Digest(i as u32 - 1)]))
+        .collect()
+}
+
+/// Blocks of the chain: the blocks of the seed, then one chained in block per iteration.
+///
+/// Panics if `n_iterations` is zero.
+pub fn chain_blocks(seed: &[u8], n_iterations: usize) -> Vec<Block> {
+    let mut blocks = gen_linked_blocks(HashFunction::Sha256, &chain_messages(seed, n_iterations));
+    // The digests are chained in the AIR instead of linked publicly
+    for block in &mut blocks {
+        block.is_chained_out = block.link.take().is_some();
+        block.is_chained_in = std::mem::take(&mut block.is_linked_left);
+    }
+    blocks
+}
+
+/// Final digest of the chain, i.e. `seed` hashed `n_iterations` times.
+pub fn hash_chain(seed: &[u8], n_iterations: usize) -> [u8; 32] {
+    assert!(n_iterations > 0, "Empty hash chain");
+    let mut digest: [u8; 32] = Sha256::digest(seed).into();
+    for _ in 1..n_iterations {
+        digest = Sha256::digest(digest).into();
+    }
+    digest
+}
+
+/// Proves that `seed` hashed `n_iterations` times gives its final digest, see [`hash_chain`].
+/// Verify with [`verify_hash_chain`].
+pub fn prove_hash_chain<MC: MerkleChannel>(
+    seed: &[u8],
+    n_iterations: usize,
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha256ProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    Sha256Prover::<MC>::new(log_size, config)?.prove(&chain_blocks(seed, n_iterations))
+}
+
+/// Verifies that `proof` attests that `seed` hashed `n_iterations` times gives `end`.
+///
+/// Panics if `n_iterations` is zero.
+pub fn verify_hash_chain<MC: MerkleChannel>(
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    seed: &[u8],
+    n_iterations: usize,
+    end: &[u8; 32],
+) -> Result<(), Sha256VerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    let claims = public::chain_claims(seed, n_iterations, end);
+    Sha256Verifier::<MC>::new(log_size, config)?.verify_claims(
+        HashFunction::Sha256,
+        proof,
+        claimed_sum,
+        &claims,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::core::vcs::blake2_merkle::Blake2sMerkleChannel;
+
+    use super::*;
+    use crate::{message::digests, public::block_claims};
+
+    #[test]
+    fn test_hash_chain() {
+        let seed = b"seed";
+        assert_eq!(hash_chain(seed, 1)[..], Sha256::digest(seed)[..]);
+        assert_eq!(
+            hash_chain(seed, 3)[..],
+            Sha256::digest(Sha256::digest(Sha256::digest(seed)))[..]
+        );
+
+        let blocks = chain_blocks(seed, 100);
+        // One row per iteration.
+        assert_eq!(blocks.len(), 100);
+        assert_eq!(digests(&blocks), [hash_chain(seed, 100)]);
+    }
+
+    #[test]
+    fn test_chain_claims() {
+        let seed = b"seed";
+        for n_iterations in [1, 2, 100] {
+            let end = hash_chain(seed, n_iterations);
+            let claims = public::chain_claims(seed, n_iterations, &end);
+            assert_eq!(
+                claims,
+                block_claims(HashFunction::Sha256, &chain_blocks(seed, n_iterations))
+            );
+            // The two halves of the seed block and the final digest, whatever the length.
+            assert_eq!(claims.inputs.len(), 2);
+            assert_eq!(claims.outputs.len(), 1);
+            assert!(claims.links.is_empty() && claims.copies.is_empty());
+        }
+    }
+
+    #[test_log::test]
+    fn test_verify_hash_chain() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let seed = b"seed";
+        let n_iterations = 200;
+        let (proof, claimed_sum) =
+            prove_hash_chain::<Blake2sMerkleChannel>(seed, n_iterations, log_size, config).unwrap();
+        let end = hash_chain(seed, n_iterations);
+
+        // The proof does not attest a shorter chain.
+        assert!(verify_hash_chain::<Blake2sMerkleChannel>(
+            proof.clone(),
+            log_size,
+            config,
+            &claimed_sum,
+            seed,
+            n_iterations - 1,
+            &hash_chain(seed, n_iterations - 1)
+        )
+        .is_err());
+        verify_hash_chain::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            seed,
+            n_iterations,
+            &end,
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,221 @@
+//! HMAC-SHA256 (RFC 2104) proven with the SHA-256 AIR.
+//!
+//! `HMAC(K, m) = SHA256((K' ^ opad) || SHA256((K' ^ ipad) || m))`, where `K'` is the key padded
+//! with zeros to a block, or its digest if it is longer than a block.
+//!
+//! The key is not part of the public claims. Since any key gives some MAC, the proof also hashes
+//! the key blocks `(K' ^ ipad) || (K' ^ opad)`, whose digest is public: the key digest, which
+//! identifies the key, see [`key_digest`]. The key blocks are unclaimed halves copied into the
+//! inner and outer hashes, and the inner digest is linked to the outer message, see
+//! [`crate::message::Part`]. The verifier only needs the message, the key digest and the MAC.
+//!
+//! The key is not hidden from the verifier: stwo proofs are not zero-knowledge, the trace is
+//! committed without blinding and the opened values reveal limbs of the key blocks. The key digest
+//! is not hiding either, a low-entropy key can be found from it. Only share proofs and key digests
+//! with parties allowed to learn the key.
+
+use sha2::{Digest, Sha256};
+use stwo::{
+    core::{channel::MerkleChannel, pcs::PcsConfig, proof::StarkProof},
+    prover::backend::{simd::SimdBackend, BackendForChannel},
+};
+
+use crate::{
+    components::ClaimedSum,
+    message::{gen_linked_blocks, Part},
+    public,
+    sha256::HashFunction,
+    verifier::Sha256Verifier,
+    Sha256Prover, Sha256ProverError, Sha256VerifierError,
+};
+
+const BLOCK_SIZE: usize = 64;
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+/// The inner and outer key blocks, `(K' ^ ipad) || (K' ^ opad)`.
+pub fn key_blocks(key: &[u8]) -> [u8; 2 * BLOCK_SIZE] {
+    let mut padded_key = [0; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        padded_key[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        padded_key[..key.len()].copy_from_slice(key);
+    }
+    std::array::from_fn(|i| padded_key[i % BLOCK_SIZE] ^ [IPAD, OPAD][i / BLOCK_SIZE])
+}
+
+/// Public digest of `key`: the SHA-256 of its key blocks, see [`key_blocks`].
+pub fn key_digest(key: &[u8]) -> [u8; 32] {
+    Sha256::digest(key_blocks(key)).into()
+}
+
+/// HMAC-SHA256 of `message` with `key`.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let key_blocks = key_blocks(key);
+    let inner = Sha256::digest([&key_blocks[..BLOCK_SIZE], message].concat());
+    Sha256::digest([&key_blocks[BLOCK_SIZE..], &inner[..]].concat()).into()
+}
+
+/// Messages hashed to compute the MAC: the unclaimed key blocks, then the inner and outer hashes
+/// which copy them. The digests of the key blocks and of the outer hash are public, in this order.
+pub fn hmac_messages(key_blocks: &[u8; 2 * BLOCK_SIZE], message: &[u8]) -> Vec<Vec<Part>> {
+    let key_half = |position| Part::Copy {
+        message_id: 0,
+        position,
+    };
+    vec![
+        vec![Part::Unclaimed(key_blocks.to_vec())],
+        vec![key_half(0), key_half(1), Part::Bytes(message.to_vec())],
+        vec![key_half(2), key_half(3), Part::Digest(1)],
+    ]
+}
+
+/// Proves the HMAC-SHA256 of `message` with `key`, see the module documentation: the proof does
+/// not hide the key.
+/// Verify with [`verify_hmac_sha256`].
+pub fn prove_hmac_sha256<MC: MerkleChannel>(
+    key: &[u8],
+    message: &[u8],
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha256ProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    let blocks = gen_linked_blocks(
+        HashFunction::Sha256,
+        &hmac_messages(&key_blocks(key), message),
+    );
+    Sha256Prover::<MC>::new(log_size, config)?.prove(&blocks)
+}
+
+/// Verifies that `proof` attests that `mac` is the HMAC-SHA256 of `message` with the key whose
+/// digest is `key_digest`.
+pub fn verify_hmac_sha256<MC: MerkleChannel>(
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    message: &[u8],
+    key_digest: &[u8; 32],
+    mac: &[u8; 32],
+) -> Result<(), Sha256VerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    // The verifier only uses the length of the unclaimed key blocks.
+    let messages = hmac_messages(&[0; 2 * BLOCK_SIZE], message);
+    let claims = public::linked_claims(&messages, &[key_digest, mac]);
+    Sha256Verifier::<MC>::new(log_size, config)?.verify_claims(
+        HashFunction::Sha256,
+        proof,
+        claimed_sum,
+        &claims,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::core::vcs::blake2_merkle::Blake2sMerkleChannel;
+
+    use super::*;
+    use crate::{
+        message::digests,
+        public::{block_claims, linked_claims},
+    };
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231, test cases 2 and 6.
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex::encode(hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_hmac_blocks() {
+        let key = b"key";
+        let message = vec![0x42; 100];
+        let messages = hmac_messages(&key_blocks(key), &message);
+        let blocks = gen_linked_blocks(HashFunction::Sha256, &messages);
+        let public = [key_digest(key), hmac_sha256(key, &message)];
+        assert_eq!(digests(&blocks), public);
+
+        let claims = block_claims(HashFunction::Sha256, &blocks);
+        assert_eq!(claims, linked_claims(&messages, &public));
+        // The key blocks are only copied, the key halves are never claimed.
+        assert!(!claims
+            .inputs
+            .iter()
+            .any(|claim| claim[1] < 2 || (claim[0] == 0 && claim[1] < 4)));
+        assert_eq!(claims.copies.len(), 4);
+    }
+
+    #[test_log::test]
+    fn test_verify_hmac_sha256() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let key = b"secret key";
+        let message = b"GET /api/v1/orders?limit=10";
+        let (proof, claimed_sum) =
+            prove_hmac_sha256::<Blake2sMerkleChannel>(key, message, log_size, config).unwrap();
+        let mac = hmac_sha256(key, message);
+
+        let wrong_digest = key_digest(b"another key");
+        assert!(verify_hmac_sha256::<Blake2sMerkleChannel>(
+            proof.clone(),
+            log_size,
+            config,
+            &claimed_sum,
+            message,
+            &wrong_digest,
+            &mac
+        )
+        .is_err());
+        assert!(verify_hmac_sha256::<Blake2sMerkleChannel>(
+            proof.clone(),
+            log_size,
+            config,
+            &claimed_sum,
+            b"GET /api/v1/orders?limit=99",
+            &key_digest(key),
+            &mac
+        )
+        .is_err());
+        verify_hmac_sha256::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            message,
+            &key_digest(key),
+            &mac,
+        )
+        .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_prove_hmac_sha256_inconsistent_copy() {
+        let messages = hmac_messages(&key_blocks(b"key"), b"abc");
+        let mut blocks = gen_linked_blocks(HashFunction::Sha256, &messages);
+        // The first outer key half does not match the key blocks.
+        let outer = blocks
+            .iter()
+            .position(|block| block.message_id == 2)
+            .unwrap();
+        blocks[outer].words[0] ^= 1;
+        let prover = Sha256Prover::<Blake2sMerkleChannel>::new(8, PcsConfig::default()).unwrap();
+        assert!(matches!(
+            prover.prove(&blocks),
+            Err(Sha256ProverError::UnbalancedLogup { .. })
+        ));
+    }
+}
@@ -1,11 +1,19 @@
 #![allow(non_camel_case_types)]
 #![feature(portable_simd, array_chunks, iter_array_chunks)]
 pub mod components;
-pub mod macros;
+pub mod config;
+pub mod hash_chain;
+pub mod hmac;
+pub mod merkle;
+pub mod message;
 pub mod partitions;
 pub mod preprocessed;
+pub mod proof;
+pub mod prover;
+pub mod public;
 pub mod relations;
 pub mod sha256;
+pub mod verifier;
 
 #[cfg(feature = "peak-alloc")]
 use peak_alloc::PeakAlloc;
@@ -20,145 +28,461 @@ use tikv_jemallocator::Jemalloc;
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
-use num_traits::Zero;
 use stwo::{
-    core::{
-        channel::Blake2sChannel,
-        fields::qm31::SecureField,
-        pcs::PcsConfig,
-        poly::circle::CanonicCoset,
-        proof::StarkProof,
-        vcs::blake2_merkle::{Blake2sMerkleChannel, Blake2sMerkleHasher},
+    core::{channel::MerkleChannel, fields::qm31::SecureField, pcs::PcsConfig, proof::StarkProof},
+    prover::{
+        backend::{simd::SimdBackend, BackendForChannel},
+        ProvingError,
     },
-    prover::{backend::simd::SimdBackend, poly::circle::PolyOps, prove, CommitmentSchemeProver},
 };
-use stwo_constraint_framework::TraceLocationAllocator;
-use tracing::{debug, span, Level};
+use thiserror::Error;
+use utils::stwo::air::ProveError;
 
-use crate::{
-    components::{gen_interaction_trace, gen_trace},
-    preprocessed::PreProcessedTrace,
-    relations::Relations,
+use crate::{components::ClaimedSum, message::Block};
+pub use crate::{
+    hash_chain::{prove_hash_chain, verify_hash_chain},
+    hmac::{prove_hmac_sha256, verify_hmac_sha256},
+    merkle::{
+        prove_merkle, prove_merkle_inclusion, verify_merkle_inclusion, verify_merkle_root,
+        InclusionPath,
+    },
+    proof::Sha256Proof,
+    prover::Sha256Prover,
+    sha256::HashFunction,
+    verifier::{
+        preprocessed_root, verify_sha224, verify_sha256, verify_sha256d, verify_with,
+        Sha256Verifier, Sha256VerifierError,
+    },
 };
 
-pub fn prove_sha256(log_size: u32, config: PcsConfig) -> StarkProof<Blake2sMerkleHasher> {
-    // Precompute twiddles.
-    let span = span!(Level::INFO, "Precompute twiddles").entered();
-    let twiddles = SimdBackend::precompute_twiddles(
-        CanonicCoset::new(log_size + config.fri_config.log_blowup_factor + 2)
-            .circle_domain()
-            .half_coset,
-    );
-    span.exit();
-
-    // Setup protocol.
-    let channel = &mut Blake2sChannel::default();
-    let mut commitment_scheme =
-        CommitmentSchemeProver::<_, Blake2sMerkleChannel>::new(config, &twiddles);
-
-    // Preprocessed trace.
-    let span = span!(Level::INFO, "Constant").entered();
-    let span_1 = span!(Level::INFO, "Simd generation").entered();
-    let preprocessed_trace = PreProcessedTrace::new(log_size);
-    span_1.exit();
-    let span_2 = span!(Level::INFO, "Extend evals").entered();
-    let mut tree_builder = commitment_scheme.tree_builder();
-    tree_builder.extend_evals(preprocessed_trace.trace);
-    tree_builder.commit(channel);
-    span_2.exit();
-    span.exit();
-
-    // Trace.
-    let span = span!(Level::INFO, "Trace").entered();
-    let (trace, lookup_data) = gen_trace(log_size);
-    let span_1 = span!(Level::INFO, "Extend evals").entered();
-    let mut tree_builder = commitment_scheme.tree_builder();
-    tree_builder.extend_evals(trace);
-    tree_builder.commit(channel);
-    span_1.exit();
-    span.exit();
-
-    // Draw lookup elements.
-    let relations = Relations::draw(channel);
-
-    // Interaction trace.
-    let span = span!(Level::INFO, "Interaction").entered();
-    let (trace, claimed_sum) = gen_interaction_trace(lookup_data, &relations);
-    let span_1 = span!(Level::INFO, "Extend evals").entered();
-    let mut tree_builder = commitment_scheme.tree_builder();
-    tree_builder.extend_evals(trace);
-    tree_builder.commit(channel);
-    span_1.exit();
-    span.exit();
-
-    debug!(
-        "Columns count: {:?}",
-        commitment_scheme
-            .trees
-            .as_ref()
-            .map(|tree| tree.evaluations.len())
-    );
-    debug!(
-        "Columns length: {:?}",
-        commitment_scheme.trees.as_ref().map(|tree| {
-            let max_len = tree
-                .evaluations
-                .iter()
-                .map(|eval| eval.values.length.ilog2())
-                .collect::<Vec<_>>()
-                .iter()
-                .copied()
-                .max()
-                .unwrap();
-            assert!(max_len <= log_size + 1);
-            max_len
-        })
-    );
-
-    // Prove constraints.
-    let span = span!(Level::INFO, "Prove").entered();
-    let trace_allocator =
-        &mut TraceLocationAllocator::new_with_preprocessed_columns(&preprocessed_trace.ids);
-    let components =
-        components::Components::new(log_size, trace_allocator, &relations, &claimed_sum);
-
-    #[cfg(feature = "track-relations")]
-    println!(
-        "Trace log degree bounds: {:?}",
-        components.trace_log_degree_bounds()
-    );
-
-    if claimed_sum.scheduling + claimed_sum.compression + claimed_sum.preprocessed.sum()
-        != SecureField::zero()
-    {
-        #[cfg(feature = "track-relations")]
-        println!(
-            "Relation summary: {:?}",
-            components.track_relations(&commitment_scheme)
-        );
-        panic!(
-            "Relation summary is not zero: {}",
-            claimed_sum.scheduling + claimed_sum.compression + claimed_sum.preprocessed.sum()
-        );
-    }
+/// Largest supported trace log size: each row does at most 2^7 lookups into the same preprocessed
+/// table, and the multiplicities must fit in M31.
+pub const MAX_LOG_SIZE: u32 = 23;
 
-    let proof = prove(&components.provers(), channel, commitment_scheme);
-    if let Err(e) = proof {
-        panic!("Proof error: {e:?}");
+#[derive(Debug, Error)]
+pub enum Sha256ProverError {
+    #[error("Invalid log size: {0}")]
+    InvalidLogSize(u32),
+    #[error("Unsupported config for log size {log_size}: {config:?}")]
+    UnsupportedConfig { log_size: u32, config: PcsConfig },
+    #[error("Too many blocks: {n_blocks} do not fit in 2^{log_size} rows")]
+    TooManyBlocks { n_blocks: usize, log_size: u32 },
+    #[error("Empty Merkle tree")]
+    EmptyMerkleTree,
+    #[error("Invalid Merkle leaf length: {0} bytes")]
+    InvalidLeafLength(usize),
+    #[error("Invalid inclusion path for leaf {leaf_index} of a tree of size {tree_size}")]
+    InvalidInclusionPath { leaf_index: u64, tree_size: u64 },
+    #[error("Relation summary is not zero: {sum}")]
+    UnbalancedLogup {
+        sum: SecureField,
+        claimed_sum: ClaimedSum,
+    },
+    #[error("Preprocessed trace cache: {0}")]
+    Cache(#[from] bincode::Error),
+    #[error(transparent)]
+    Stwo(#[from] ProvingError),
+}
+
+impl From<ProveError<ClaimedSum>> for Sha256ProverError {
+    fn from(err: ProveError<ClaimedSum>) -> Self {
+        match err {
+            ProveError::InvalidLogSize { log_size, .. } => Self::InvalidLogSize(log_size),
+            ProveError::UnsupportedConfig { log_size, config } => {
+                Self::UnsupportedConfig { log_size, config }
+            }
+            ProveError::TooManyBlocks { n_blocks, log_size } => {
+                Self::TooManyBlocks { n_blocks, log_size }
+            }
+            ProveError::UnbalancedLogup { sum, claimed_sum } => {
+                Self::UnbalancedLogup { sum, claimed_sum }
+            }
+            ProveError::Stwo(err) => Self::Stwo(err),
+        }
     }
-    span.exit();
+}
+
+/// Proves the SHA-256 compression of each message block, see [`message::gen_blocks`] to split
+/// messages into chained blocks.
+///
+/// Blocks of the same message are linked through the chain relation, so they must appear in
+/// order and with consistent chaining values. The batch is padded with standalone zero blocks up
+/// to `1 << log_size` rows, which emit no public claim: each block claims its message words and
+/// each message its digest, see [`public`]. Returns the proof together with the claimed sums of
+/// the logup relations, which the verifier needs to rebuild the components (see
+/// [`verify_sha256`]).
+///
+/// The Merkle channel `MC` picks the hash used for commitments and Fiat-Shamir, e.g.
+/// `Blake2sMerkleChannel` for off-chain and `Poseidon252MerkleChannel` for on-chain verification.
+///
+/// This recomputes the preprocessed trace and its commitment, use a [`Sha256Prover`] to share
+/// them across proofs.
+pub fn prove_sha256<MC: MerkleChannel>(
+    blocks: &[Block],
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha256ProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    Sha256Prover::<MC>::new(log_size, config)?.prove(blocks)
+}
 
-    proof.unwrap()
+/// Same as [`prove_sha256`] for SHA-224, which shares the compression and the preprocessed tables
+/// of SHA-256: the blocks start from the SHA-224 initial hash value, see
+/// [`message::gen_blocks_for`], and only the first 7 words of each output chaining value are
+/// claimed. Verify with [`verify_sha224`].
+pub fn prove_sha224<MC: MerkleChannel>(
+    blocks: &[Block],
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha256ProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    Sha256Prover::<MC>::new(log_size, config)?.prove_with(HashFunction::Sha224, blocks)
+}
+
+/// Same as [`prove_sha256`] for SHA-256d, e.g. to prove Bitcoin block headers: each message is
+/// hashed, then its 32-byte digest is hashed again as a single-block message, see
+/// [`message::gen_blocks_for`] with [`HashFunction::Sha256d`].
+///
+/// The inner digest is linked to the words of the second hash inside the AIR: only the messages
+/// and the final digests are public. Note that Bitcoin displays block hashes byte-reversed.
+/// Verify with [`verify_sha256d`].
+pub fn prove_sha256d<MC: MerkleChannel>(
+    blocks: &[Block],
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha256ProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    Sha256Prover::<MC>::new(log_size, config)?.prove_with(HashFunction::Sha256d, blocks)
 }
 
 #[cfg(test)]
 mod tests {
     use std::{env, time::Instant};
 
+    use num_traits::Zero;
     use rayon::iter::{IntoParallelIterator, ParallelIterator};
-    use tracing::info;
+    use sha2::{Digest, Sha256};
+    use stwo::{
+        core::vcs::{
+            blake2_merkle::Blake2sMerkleChannel, poseidon252_merkle::Poseidon252MerkleChannel,
+        },
+        prover::backend::simd::m31::LOG_N_LANES,
+    };
+    use tracing::{info, span, Level};
 
     use super::*;
+    use crate::{
+        config::SecurityPreset,
+        message::{gen_blocks, gen_blocks_for, generate_blocks},
+        public::HashClaim,
+    };
+
+    fn prove_messages<MC: MerkleChannel>(
+        messages: &[Vec<u8>],
+        log_size: u32,
+        config: PcsConfig,
+    ) -> (StarkProof<MC::H>, ClaimedSum, Vec<HashClaim>)
+    where
+        SimdBackend: BackendForChannel<MC>,
+    {
+        let (proof, claimed_sum) =
+            prove_sha256::<MC>(&gen_blocks(messages), log_size, config).unwrap();
+        let claims = messages
+            .iter()
+            .map(|message| HashClaim::new(message.clone()))
+            .collect();
+        (proof, claimed_sum, claims)
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = (0..100).map(|i| vec![i as u8; i]).collect::<Vec<_>>();
+        let (proof, claimed_sum, claims) =
+            prove_messages::<Blake2sMerkleChannel>(&messages, log_size, config);
+        verify_sha256::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256_poseidon252() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec(), vec![0xab; 200]];
+        let (proof, claimed_sum, claims) =
+            prove_messages::<Poseidon252MerkleChannel>(&messages, log_size, config);
+        verify_sha256::<Poseidon252MerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256_padded_batch() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec(), vec![0xde, 0xad, 0xbe, 0xef]];
+        let (proof, claimed_sum, claims) =
+            prove_messages::<Blake2sMerkleChannel>(&messages, log_size, config);
+        verify_sha256::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256_multi_block_messages() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![vec![0xab; 200], b"abc".to_vec(), vec![]];
+        let (proof, claimed_sum, claims) =
+            prove_messages::<Blake2sMerkleChannel>(&messages, log_size, config);
+        verify_sha256::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256_wrong_digest() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![vec![0xab; 200], b"abc".to_vec()];
+        let (proof, claimed_sum, mut claims) =
+            prove_messages::<Blake2sMerkleChannel>(&messages, log_size, config);
+        claims[1].digest[31] ^= 1;
+        assert!(verify_sha256::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            &claims
+        )
+        .is_err());
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256_wrong_message() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec()];
+        let (proof, claimed_sum, _) =
+            prove_messages::<Blake2sMerkleChannel>(&messages, log_size, config);
+        let claims = [HashClaim {
+            message: b"abd".to_vec(),
+            digest: HashClaim::new(b"abc".to_vec()).digest,
+        }];
+        assert!(verify_sha256::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            &claims
+        )
+        .is_err());
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256_missing_claim() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec(), b"def".to_vec()];
+        let (proof, claimed_sum, claims) =
+            prove_messages::<Blake2sMerkleChannel>(&messages, log_size, config);
+        assert!(verify_sha256::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            &claims[..1]
+        )
+        .is_err());
+    }
+
+    #[test_log::test]
+    fn test_verify_sha224() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec(), vec![0xab; 200]];
+        let blocks = gen_blocks_for(HashFunction::Sha224, &messages);
+        let (proof, claimed_sum) =
+            prove_sha224::<Blake2sMerkleChannel>(&blocks, log_size, config).unwrap();
+        let claims = messages
+            .iter()
+            .map(|message| HashClaim::with_hash_function(HashFunction::Sha224, message.clone()))
+            .collect::<Vec<_>>();
+        assert!(claims.iter().all(|claim| claim.digest.len() == 28));
+
+        // The SHA-256 AIR starts from another initial hash value and claims 8 words.
+        let mut sha256_claims = claims.clone();
+        for claim in &mut sha256_claims {
+            claim.digest.extend([0; 4]);
+        }
+        assert!(verify_sha256::<Blake2sMerkleChannel>(
+            proof.clone(),
+            log_size,
+            config,
+            &claimed_sum,
+            &sha256_claims
+        )
+        .is_err());
+        verify_sha224::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_sha224_wrong_digest_length() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec()];
+        let (proof, claimed_sum, claims) =
+            prove_messages::<Blake2sMerkleChannel>(&messages, log_size, config);
+        assert!(matches!(
+            verify_sha224::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims),
+            Err(Sha256VerifierError::InvalidDigestLength(32))
+        ));
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256d() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let headers = vec![vec![0x42; 80], vec![0x43; 80]];
+        let blocks = gen_blocks_for(HashFunction::Sha256d, &headers);
+        let (proof, claimed_sum) =
+            prove_sha256d::<Blake2sMerkleChannel>(&blocks, log_size, config).unwrap();
+        let claims = headers
+            .iter()
+            .map(|header| HashClaim::with_hash_function(HashFunction::Sha256d, header.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            claims[0].digest[..],
+            Sha256::digest(Sha256::digest(&headers[0]))[..]
+        );
+
+        // The single SHA-256 of the headers is not what the proof attests.
+        let sha256_claims = headers
+            .iter()
+            .map(|header| HashClaim::new(header.clone()))
+            .collect::<Vec<_>>();
+        assert!(verify_sha256::<Blake2sMerkleChannel>(
+            proof.clone(),
+            log_size,
+            config,
+            &claimed_sum,
+            &sha256_claims
+        )
+        .is_err());
+        verify_sha256d::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_prove_sha256d_broken_link() {
+        let log_size = 8;
+        let mut blocks = gen_blocks_for(HashFunction::Sha256d, &[vec![0x42; 80]]);
+        blocks[2].words[0] ^= 1;
+        assert!(matches!(
+            prove_sha256d::<Blake2sMerkleChannel>(&blocks, log_size, PcsConfig::default()),
+            Err(Sha256ProverError::UnbalancedLogup { .. })
+        ));
+    }
+
+    #[test_log::test]
+    fn test_prove_sha256_broken_chain() {
+        let log_size = 8;
+        let mut blocks = gen_blocks(&[vec![0xab; 200]]);
+        blocks[2].h_in[0] ^= 1;
+        let err = prove_sha256::<Blake2sMerkleChannel>(&blocks, log_size, PcsConfig::default())
+            .unwrap_err();
+        let Sha256ProverError::UnbalancedLogup { sum, claimed_sum } = err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_ne!(sum, SecureField::zero());
+        assert_ne!(claimed_sum.compression, SecureField::zero());
+    }
+
+    #[test]
+    fn test_prove_sha256_invalid_log_size() {
+        let blocks = generate_blocks(1);
+        for log_size in [0, LOG_N_LANES - 1, MAX_LOG_SIZE + 1] {
+            assert!(matches!(
+                prove_sha256::<Blake2sMerkleChannel>(&blocks, log_size, PcsConfig::default()),
+                Err(Sha256ProverError::InvalidLogSize(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_prove_sha256_unsupported_config() {
+        let mut config = PcsConfig::default();
+        config.fri_config.log_blowup_factor = 10;
+        assert!(matches!(
+            prove_sha256::<Blake2sMerkleChannel>(&generate_blocks(1), MAX_LOG_SIZE, config),
+            Err(Sha256ProverError::UnsupportedConfig { .. })
+        ));
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256_presets() {
+        let log_size = 8;
+        let messages = vec![b"abc".to_vec()];
+        for preset in [
+            SecurityPreset::FastDev,
+            SecurityPreset::Bits96,
+            SecurityPreset::Bits128,
+        ] {
+            let config = preset.pcs_config();
+            let (proof, claimed_sum, claims) =
+                prove_messages::<Blake2sMerkleChannel>(&messages, log_size, config);
+            verify_sha256::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_prove_sha256_too_many_blocks() {
+        let log_size = LOG_N_LANES;
+        let blocks = generate_blocks((1 << log_size) + 1);
+        assert!(matches!(
+            prove_sha256::<Blake2sMerkleChannel>(&blocks, log_size, PcsConfig::default()),
+            Err(Sha256ProverError::TooManyBlocks { .. })
+        ));
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256_wrong_preprocessed_root() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec()];
+        let (mut proof, claimed_sum, claims) =
+            prove_messages::<Blake2sMerkleChannel>(&messages, log_size, config);
+        proof.commitments[0] = proof.commitments[1];
+        assert!(matches!(
+            verify_sha256::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims),
+            Err(Sha256VerifierError::InvalidPreprocessedRoot)
+        ));
+    }
+
+    #[test_log::test]
+    fn test_verify_sha256_wrong_claimed_sum() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec()];
+        let (proof, mut claimed_sum, claims) =
+            prove_messages::<Blake2sMerkleChannel>(&messages, log_size, config);
+        claimed_sum.scheduling += SecureField::from(1);
+        claimed_sum.compression -= SecureField::from(1);
+        assert!(verify_sha256::<Blake2sMerkleChannel>(
+            proof,
+            log_size,
+            config,
+            &claimed_sum,
+            &claims
+        )
+        .is_err());
+    }
 
     #[test_log::test]
     fn test_prove_sha256() {
@@ -180,15 +504,18 @@ mod tests {
 
         info!("Log size: {}", log_size);
         info!("Number of iterations: {}", n_iter);
+        let blocks = generate_blocks(1 << log_size);
 
         #[cfg(feature = "peak-alloc")]
         PEAK_ALLOC.reset_peak_usage();
         let span = span!(Level::INFO, "Prove").entered();
 
         let start = Instant::now();
+        let prover =
+            Sha256Prover::<Blake2sMerkleChannel>::new(log_size, PcsConfig::default()).unwrap();
         (0..n_iter)
             .into_par_iter()
-            .map(|_| prove_sha256(log_size, PcsConfig::default()))
+            .map(|_| prover.prove(&blocks).unwrap())
             .collect::<Vec<_>>();
         span.exit();
         info!(
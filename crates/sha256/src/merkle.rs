@@ -0,0 +1,665 @@
+//! Bitcoin block Merkle trees proven with the SHA-256 AIR.
+//!
+//! Each node is the SHA-256d of its children. The children digests are routed into the node
+//! through the link relation, see [`Part::Digest`], so that only the leaves and the root are
+//! public. The last node of an odd level is paired with itself: its digest is linked into the left
+//! half of the node, which is copied into the right half, see [`Part::Copy`].
+//!
+//! The link relation routes whole 32-byte halves, so trees hashing a prefix before the children,
+//! such as RFC 6962 trees, cannot be linked and are not supported.
+//!
+//! Besides whole trees, the path from a single leaf to the root can be proven with its siblings
+//! public, see [`InclusionPath`]. A tree of a single leaf has no node: its root is the leaf, and
+//! its proof attests no hash.
+
+use sha2::{Digest, Sha256};
+use stwo::{
+    core::{channel::MerkleChannel, pcs::PcsConfig, proof::StarkProof},
+    prover::backend::{simd::SimdBackend, BackendForChannel},
+};
+
+use crate::{
+    components::ClaimedSum,
+    message::{gen_linked_blocks, Part},
+    public,
+    sha256::HashFunction,
+    verifier::Sha256Verifier,
+    Sha256Prover, Sha256ProverError, Sha256VerifierError,
+};
+
+/// Length of the leaves, transaction ids in internal byte order.
+const LEAF_SIZE: usize = 32;
+
+/// Inclusion path of a leaf: the siblings of the nodes from the leaf to the root, bottom-up, as in
+/// Bitcoin merkle branches.
+///
+/// A node paired with itself is listed as its own sibling. That entry is not used, the proof pairs
+/// the node with itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionPath {
+    pub leaf_index: u64,
+    pub tree_size: u64,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// Position of the sibling of a node on an inclusion path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sibling {
+    Left,
+    Right,
+    /// Node paired with itself.
+    Itself,
+}
+
+impl InclusionPath {
+    /// Position of each sibling, or `None` if the path does not match the leaf index and the tree
+    /// size.
+    fn sides(&self) -> Option<Vec<Sibling>> {
+        if self.leaf_index >= self.tree_size {
+            return None;
+        }
+        let depth = u64::BITS - (self.tree_size - 1).leading_zeros();
+        if self.siblings.len() != depth as usize {
+            return None;
+        }
+        let mut width = self.tree_size;
+        let sides = (0..depth).map(|level| {
+            let index = self.leaf_index >> level;
+            let side = match index % 2 {
+                1 => Sibling::Left,
+                _ if index == width - 1 => Sibling::Itself,
+                _ => Sibling::Right,
+            };
+            width = width.div_ceil(2);
+            side
+        });
+        Some(sides.collect())
+    }
+}
+
+/// Hashes the nodes of a tree, natively or as messages to prove.
+trait TreeHasher {
+    type Node: Copy + PartialEq;
+
+    fn leaf(&mut self, leaf: &[u8]) -> Self::Node;
+    fn node(&mut self, left: Self::Node, right: Self::Node) -> Self::Node;
+
+    fn tree<M: AsRef<[u8]>>(&mut self, leaves: &[M]) -> Self::Node {
+        assert!(!leaves.is_empty(), "Empty Merkle tree");
+        let mut level = leaves
+            .iter()
+            .map(|leaf| self.leaf(leaf.as_ref()))
+            .collect::<Vec<_>>();
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| self.node(pair[0], *pair.last().unwrap()))
+                .collect();
+        }
+        level[0]
+    }
+
+    fn path(&mut self, leaf: &[u8], path: &InclusionPath) -> Option<Self::Node> {
+        let mut node = self.leaf(leaf);
+        for (side, sibling) in path.sides()?.into_iter().zip(&path.siblings) {
+            node = match side {
+                Sibling::Left => {
+                    let sibling = self.leaf(sibling);
+                    self.node(sibling, node)
+                }
+                Sibling::Right => {
+                    let sibling = self.leaf(sibling);
+                    self.node(node, sibling)
+                }
+                Sibling::Itself => self.node(node, node),
+            };
+        }
+        Some(node)
+    }
+}
+
+struct NativeHasher;
+
+impl TreeHasher for NativeHasher {
+    type Node = [u8; 32];
+
+    fn leaf(&mut self, leaf: &[u8]) -> [u8; 32] {
+        leaf.try_into().expect("Leaves are 32-byte transaction ids")
+    }
+
+    fn node(&mut self, left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+        Sha256::digest(Sha256::digest([left, right].concat())).into()
+    }
+}
+
+/// A node hashed as messages: public bytes, or the digest of a message linked into its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    Public([u8; 32]),
+    Linked(u32),
+}
+
+impl Node {
+    fn part(self) -> Part {
+        match self {
+            Self::Public(digest) => Part::Bytes(digest.to_vec()),
+            Self::Linked(message_id) => Part::Digest(message_id),
+        }
+    }
+}
+
+/// Messages hashed to compute a root.
+#[derive(Default)]
+struct MessageHasher {
+    messages: Vec<Vec<Part>>,
+}
+
+impl MessageHasher {
+    fn push(&mut self, message: Vec<Part>) -> u32 {
+        self.messages.push(message);
+        self.messages.len() as u32 - 1
+    }
+
+    /// Claims of the hashed messages, whose root is `node`. The digest of the root message is
+    /// `root`, so that the proof only verifies against the expected one. A public `node` is a
+    /// single leaf, which must be the root.
+    fn claims(self, node: Node, root: &[u8; 32]) -> Result<public::Claims, Sha256VerifierError> {
+        match node {
+            Node::Public(leaf) if leaf != *root => Err(Sha256VerifierError::InvalidMerkleRoot),
+            Node::Public(_) => Ok(public::Claims::default()),
+            Node::Linked(_) => Ok(public::linked_claims(&self.messages, &[root])),
+        }
+    }
+}
+
+impl TreeHasher for MessageHasher {
+    type Node = Node;
+
+    fn leaf(&mut self, leaf: &[u8]) -> Node {
+        Node::Public(NativeHasher.leaf(leaf))
+    }
+
+    fn node(&mut self, left: Node, right: Node) -> Node {
+        if matches!(left, Node::Linked(_)) && left == right {
+            // Each digest is linked once, the right half is a copy of the left one
+            let inner = self.messages.len() as u32;
+            let copy = Part::Copy {
+                message_id: inner,
+                position: 0,
+            };
+            self.push(vec![left.part(), copy]);
+            return Node::Linked(self.push(vec![Part::Digest(inner)]));
+        }
+        let inner = self.push(vec![left.part(), right.part()]);
+        Node::Linked(self.push(vec![Part::Digest(inner)]))
+    }
+}
+
+/// Checks that there is a leaf and that they all are 32 bytes long, the conditions under which
+/// the functions of this module do not panic.
+fn check_leaves<M: AsRef<[u8]>>(leaves: &[M]) -> Result<(), LeavesError> {
+    if leaves.is_empty() {
+        return Err(LeavesError::Empty);
+    }
+    match leaves.iter().find(|leaf| leaf.as_ref().len() != LEAF_SIZE) {
+        Some(leaf) => Err(LeavesError::InvalidLength(leaf.as_ref().len())),
+        None => Ok(()),
+    }
+}
+
+enum LeavesError {
+    Empty,
+    InvalidLength(usize),
+}
+
+impl From<LeavesError> for Sha256ProverError {
+    fn from(err: LeavesError) -> Self {
+        match err {
+            LeavesError::Empty => Self::EmptyMerkleTree,
+            LeavesError::InvalidLength(len) => Self::InvalidLeafLength(len),
+        }
+    }
+}
+
+impl From<LeavesError> for Sha256VerifierError {
+    fn from(err: LeavesError) -> Self {
+        match err {
+            LeavesError::Empty => Self::EmptyMerkleTree,
+            LeavesError::InvalidLength(len) => Self::InvalidLeafLength(len),
+        }
+    }
+}
+
+/// Messages hashed to compute the root of `leaves`, see the module documentation. The root is the
+/// digest of the last message, or the leaf of a single-leaf tree, which has no message.
+///
+/// Panics if there are no leaves, or if a leaf is not 32 bytes long.
+pub fn merkle_messages<M: AsRef<[u8]>>(leaves: &[M]) -> Vec<Vec<Part>> {
+    let mut hasher = MessageHasher::default();
+    hasher.tree(leaves);
+    hasher.messages
+}
+
+/// Merkle root of `leaves`.
+///
+/// Panics if there are no leaves, or if a leaf is not 32 bytes long.
+pub fn merkle_root<M: AsRef<[u8]>>(leaves: &[M]) -> [u8; 32] {
+    NativeHasher.tree(leaves)
+}
+
+/// Inclusion path of the leaf `leaf_index` of `leaves`, empty for a single-leaf tree.
+///
+/// Panics if the leaf is out of the tree, or if a leaf is not 32 bytes long.
+pub fn inclusion_path<M: AsRef<[u8]>>(leaves: &[M], leaf_index: u64) -> InclusionPath {
+    assert!(leaf_index < leaves.len() as u64, "Leaf out of the tree");
+    let mut hasher = NativeHasher;
+    let mut level = leaves
+        .iter()
+        .map(|leaf| hasher.leaf(leaf.as_ref()))
+        .collect::<Vec<_>>();
+    let mut index = leaf_index as usize;
+    let mut siblings = Vec::new();
+    while level.len() > 1 {
+        siblings.push(level[(index ^ 1).min(level.len() - 1)]);
+        level = level
+            .chunks(2)
+            .map(|pair| hasher.node(pair[0], *pair.last().unwrap()))
+            .collect();
+        index /= 2;
+    }
+    InclusionPath {
+        leaf_index,
+        tree_size: leaves.len() as u64,
+        siblings,
+    }
+}
+
+/// Root of the tree containing `leaf` at the end of `path`, or `None` if the path does not match
+/// its leaf index and tree size.
+///
+/// Panics if the leaf is not 32 bytes long.
+pub fn inclusion_root(leaf: &[u8], path: &InclusionPath) -> Option<[u8; 32]> {
+    NativeHasher.path(leaf, path)
+}
+
+/// Messages hashed to compute the root at the end of `path`, or `None` if the path is invalid,
+/// see [`inclusion_root`].
+pub fn inclusion_messages(leaf: &[u8], path: &InclusionPath) -> Option<Vec<Vec<Part>>> {
+    let mut hasher = MessageHasher::default();
+    hasher.path(leaf, path)?;
+    Some(hasher.messages)
+}
+
+/// Proves that `leaves` hash to their Merkle root, see [`merkle_messages`] for the hashed
+/// messages. Verify with [`verify_merkle_root`].
+///
+/// Each node takes three rows: two for the 64-byte node and its padding, one for the second hash.
+pub fn prove_merkle<MC: MerkleChannel, M: AsRef<[u8]>>(
+    leaves: &[M],
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha256ProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    check_leaves(leaves)?;
+    let blocks = gen_linked_blocks(HashFunction::Sha256, &merkle_messages(leaves));
+    Sha256Prover::<MC>::new(log_size, config)?.prove(&blocks)
+}
+
+/// Verifies that `proof` attests that `leaves` hash to the Merkle `root`.
+pub fn verify_merkle_root<MC: MerkleChannel, M: AsRef<[u8]>>(
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    leaves: &[M],
+    root: &[u8; 32],
+) -> Result<(), Sha256VerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    check_leaves(leaves)?;
+    let mut hasher = MessageHasher::default();
+    let node = hasher.tree(leaves);
+    let claims = hasher.claims(node, root)?;
+    verify_claims::<MC>(proof, log_size, config, claimed_sum, &claims)
+}
+
+/// Proves that `leaf` is included in the tree whose root is at the end of `path`. Verify with
+/// [`verify_merkle_inclusion`].
+pub fn prove_merkle_inclusion<MC: MerkleChannel>(
+    leaf: &[u8],
+    path: &InclusionPath,
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha256ProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    check_leaves(&[leaf])?;
+    let messages =
+        inclusion_messages(leaf, path).ok_or(Sha256ProverError::InvalidInclusionPath {
+            leaf_index: path.leaf_index,
+            tree_size: path.tree_size,
+        })?;
+    let blocks = gen_linked_blocks(HashFunction::Sha256, &messages);
+    Sha256Prover::<MC>::new(log_size, config)?.prove(&blocks)
+}
+
+/// Verifies that `proof` attests that `leaf` and `path` hash to the Merkle `root`.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_merkle_inclusion<MC: MerkleChannel>(
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    leaf: &[u8],
+    path: &InclusionPath,
+    root: &[u8; 32],
+) -> Result<(), Sha256VerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    check_leaves(&[leaf])?;
+    let mut hasher = MessageHasher::default();
+    let Some(node) = hasher.path(leaf, path) else {
+        return Err(Sha256VerifierError::InvalidInclusionPath {
+            leaf_index: path.leaf_index,
+            tree_size: path.tree_size,
+        });
+    };
+    let claims = hasher.claims(node, root)?;
+    verify_claims::<MC>(proof, log_size, config, claimed_sum, &claims)
+}
+
+fn verify_claims<MC: MerkleChannel>(
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    claims: &public::Claims,
+) -> Result<(), Sha256VerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    Sha256Verifier::<MC>::new(log_size, config)?.verify_claims(
+        HashFunction::Sha256,
+        proof,
+        claimed_sum,
+        claims,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::core::vcs::blake2_merkle::Blake2sMerkleChannel;
+
+    use super::*;
+    use crate::public::block_claims;
+
+    /// Transaction ids of Bitcoin block 100000, in internal byte order, and its Merkle root.
+    const BLOCK_100000_TXIDS: [&str; 4] = [
+        "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+        "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+        "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+        "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+    ];
+    const BLOCK_100000_ROOT: &str =
+        "f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766";
+
+    /// Decodes a hash displayed in RPC byte order, i.e. reversed.
+    fn from_rpc_hex(hex: &str) -> [u8; 32] {
+        let mut bytes: [u8; 32] = hex::decode(hex).unwrap().try_into().unwrap();
+        bytes.reverse();
+        bytes
+    }
+
+    fn block_100000() -> (Vec<[u8; 32]>, [u8; 32]) {
+        let txids = BLOCK_100000_TXIDS.map(from_rpc_hex).to_vec();
+        (txids, from_rpc_hex(BLOCK_100000_ROOT))
+    }
+
+    fn txids(n_leaves: usize) -> Vec<[u8; 32]> {
+        (0..n_leaves)
+            .map(|i| Sha256::digest([i as u8]).into())
+            .collect()
+    }
+
+    #[test]
+    fn test_bitcoin_root() {
+        let (txids, root) = block_100000();
+        assert_eq!(merkle_root(&txids), root);
+
+        // The third transaction is paired with itself.
+        let node = |left: [u8; 32], right: [u8; 32]| -> [u8; 32] {
+            Sha256::digest(Sha256::digest([left, right].concat())).into()
+        };
+        assert_eq!(
+            merkle_root(&txids[..3]),
+            node(node(txids[0], txids[1]), node(txids[2], txids[2]))
+        );
+    }
+
+    #[test]
+    fn test_bitcoin_messages() {
+        let messages = merkle_messages(&txids(3));
+        // Two nodes on the first level, the third leaf is paired with itself, then the root.
+        assert_eq!(messages.len(), 3 * 2);
+        assert!(matches!(messages[0][..], [Part::Bytes(_), Part::Bytes(_)]));
+        assert_eq!(messages[1], [Part::Digest(0)]);
+        assert_eq!(messages[3], [Part::Digest(2)]);
+        assert_eq!(messages[4], [Part::Digest(1), Part::Digest(3)]);
+        assert_eq!(messages[5], [Part::Digest(4)]);
+
+        // A linked node paired with itself is copied.
+        let messages = merkle_messages(&txids(5));
+        assert!(messages.contains(&vec![
+            Part::Digest(5),
+            Part::Copy {
+                message_id: 8,
+                position: 0
+            }
+        ]));
+    }
+
+    #[test]
+    fn test_inclusion_path() {
+        for n_leaves in 1..=8 {
+            let leaves = txids(n_leaves);
+            let root = merkle_root(&leaves);
+            for (leaf_index, leaf) in leaves.iter().enumerate() {
+                let mut path = inclusion_path(&leaves, leaf_index as u64);
+                assert_eq!(inclusion_root(leaf, &path), Some(root));
+                path.siblings.push(root);
+                assert_eq!(inclusion_root(leaf, &path), None);
+            }
+        }
+
+        // A single leaf is the root, with an empty path.
+        let path = inclusion_path(&txids(1), 0);
+        assert!(path.siblings.is_empty());
+        assert_eq!(inclusion_messages(&txids(1)[0], &path), Some(vec![]));
+    }
+
+    #[test]
+    fn test_merkle_blocks() {
+        let leaves = txids(7);
+        let mut hasher = MessageHasher::default();
+        let node = hasher.tree(&leaves);
+        let blocks = gen_linked_blocks(HashFunction::Sha256, &hasher.messages);
+        let root = merkle_root(&leaves);
+        assert_eq!(
+            hasher.claims(node, &root).unwrap(),
+            block_claims(HashFunction::Sha256, &blocks)
+        );
+    }
+
+    #[test_log::test]
+    fn test_verify_merkle_root() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let leaves = txids(11);
+        let (proof, claimed_sum) =
+            prove_merkle::<Blake2sMerkleChannel, _>(&leaves, log_size, config).unwrap();
+        let root = merkle_root(&leaves);
+        let verify = |leaves: &[[u8; 32]], root: &[u8; 32]| {
+            verify_merkle_root::<Blake2sMerkleChannel, _>(
+                proof.clone(),
+                log_size,
+                config,
+                &claimed_sum,
+                leaves,
+                root,
+            )
+        };
+
+        let mut wrong_root = root;
+        wrong_root[0] ^= 1;
+        assert!(verify(&leaves, &wrong_root).is_err());
+        let mut wrong_leaves = leaves.clone();
+        wrong_leaves.swap(0, 1);
+        assert!(verify(&wrong_leaves, &root).is_err());
+        verify(&leaves, &root).unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_merkle_root_single_leaf() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let leaves = txids(1);
+        let (proof, claimed_sum) =
+            prove_merkle::<Blake2sMerkleChannel, _>(&leaves, log_size, config).unwrap();
+        let verify = |root: &[u8; 32]| {
+            verify_merkle_root::<Blake2sMerkleChannel, _>(
+                proof.clone(),
+                log_size,
+                config,
+                &claimed_sum,
+                &leaves,
+                root,
+            )
+        };
+
+        let mut wrong_root = leaves[0];
+        wrong_root[0] ^= 1;
+        assert!(matches!(
+            verify(&wrong_root),
+            Err(Sha256VerifierError::InvalidMerkleRoot)
+        ));
+        verify(&leaves[0]).unwrap();
+    }
+
+    #[test_log::test]
+    fn test_merkle_invalid_leaves() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let leaves = txids(2);
+        let (proof, claimed_sum) =
+            prove_merkle::<Blake2sMerkleChannel, _>(&leaves, log_size, config).unwrap();
+        let root = merkle_root(&leaves);
+        let verify = |leaves: &[Vec<u8>]| {
+            verify_merkle_root::<Blake2sMerkleChannel, _>(
+                proof.clone(),
+                log_size,
+                config,
+                &claimed_sum,
+                leaves,
+                &root,
+            )
+        };
+
+        assert!(matches!(
+            verify(&[]),
+            Err(Sha256VerifierError::EmptyMerkleTree)
+        ));
+        assert!(matches!(
+            verify(&[leaves[0].to_vec(), vec![0; 31]]),
+            Err(Sha256VerifierError::InvalidLeafLength(31))
+        ));
+        assert!(matches!(
+            prove_merkle::<Blake2sMerkleChannel, Vec<u8>>(&[], log_size, config),
+            Err(Sha256ProverError::EmptyMerkleTree)
+        ));
+        let path = inclusion_path(&leaves, 0);
+        assert!(matches!(
+            prove_merkle_inclusion::<Blake2sMerkleChannel>(&[0; 33], &path, log_size, config),
+            Err(Sha256ProverError::InvalidLeafLength(33))
+        ));
+        assert!(matches!(
+            verify_merkle_inclusion::<Blake2sMerkleChannel>(
+                proof,
+                log_size,
+                config,
+                &claimed_sum,
+                &[0; 33],
+                &path,
+                &root,
+            ),
+            Err(Sha256VerifierError::InvalidLeafLength(33))
+        ));
+    }
+
+    #[test_log::test]
+    fn test_verify_merkle_inclusion() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let (txids, root) = block_100000();
+        let path = inclusion_path(&txids, 2);
+        let (proof, claimed_sum) =
+            prove_merkle_inclusion::<Blake2sMerkleChannel>(&txids[2], &path, log_size, config)
+                .unwrap();
+        let verify = |leaf: &[u8], path: &InclusionPath, root: &[u8; 32]| {
+            verify_merkle_inclusion::<Blake2sMerkleChannel>(
+                proof.clone(),
+                log_size,
+                config,
+                &claimed_sum,
+                leaf,
+                path,
+                root,
+            )
+        };
+
+        let mut wrong_root = root;
+        wrong_root[0] ^= 1;
+        assert!(verify(&txids[2], &path, &wrong_root).is_err());
+        assert!(verify(&txids[3], &path, &root).is_err());
+        let mut wrong_path = path.clone();
+        wrong_path.siblings.pop();
+        assert!(matches!(
+            verify(&txids[2], &wrong_path, &root),
+            Err(Sha256VerifierError::InvalidInclusionPath { .. })
+        ));
+        verify(&txids[2], &path, &root).unwrap();
+
+        assert!(matches!(
+            prove_merkle_inclusion::<Blake2sMerkleChannel>(
+                &txids[2],
+                &wrong_path,
+                log_size,
+                config
+            ),
+            Err(Sha256ProverError::InvalidInclusionPath { .. })
+        ));
+    }
+
+    #[test_log::test]
+    fn test_prove_merkle_broken_link() {
+        let leaves = txids(4);
+        let messages = merkle_messages(&leaves);
+        let mut blocks = gen_linked_blocks(HashFunction::Sha256, &messages);
+        // The root node, whose first half is linked from its left child
+        let root_inner = messages.len() as u32 - 2;
+        let node = blocks
+            .iter()
+            .position(|block| block.message_id == root_inner)
+            .unwrap();
+        blocks[node].words[3] ^= 1;
+        let prover = Sha256Prover::<Blake2sMerkleChannel>::new(8, PcsConfig::default()).unwrap();
+        assert!(matches!(
+            prover.prove(&blocks),
+            Err(Sha256ProverError::UnbalancedLogup { .. })
+        ));
+    }
+}
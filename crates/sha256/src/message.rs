@@ -0,0 +1,606 @@
+//! Message handling: FIPS 180-4 padding and splitting of messages into chained blocks.
+//!
+//! Each trace row compresses one [`Block`]. Blocks of the same message are linked through the
+//! chain relation: a block's output chaining value is the next block's input chaining value.
+//!
+//! A message can also contain the digest of an earlier message of the batch, see [`Part`], e.g.
+//! the inner digest of SHA-256d or the children of a Merkle tree node. The digest fills 8 words of
+//! a block, the left or right half, and is routed there through the link relation so that it is
+//! never public.
+//!
+//! Halves of a message can also be left out of the public claims, and copied into later messages
+//! through the same relation, e.g. the key blocks of HMAC.
+
+use std::{collections::BTreeMap, simd::u32x16};
+
+use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+use crate::sha256::{process_chunk, HashFunction};
+
+/// A message block, together with its position in the message and its input chaining value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    /// Index of the message this block belongs to.
+    pub message_id: u32,
+    /// Index of the block in its message.
+    pub index: u32,
+    /// Whether this is the last block of its message.
+    pub is_last: bool,
+    /// Chaining value the compression starts from.
+    pub h_in: [u32; 8],
+    /// Message words, big-endian.
+    pub words: [u32; 16],
+    /// Whether the first 8 words are the digest of an earlier message.
+    pub is_linked_left: bool,
+    /// Whether the last 8 words are the digest of an earlier message.
+    pub is_linked_right: bool,
+    /// Whether the first 8 words are left out of the public claims.
+    pub is_unclaimed_left: bool,
+    /// Whether the last 8 words are left out of the public claims.
+    pub is_unclaimed_right: bool,
+    /// Where the first 8 words are copied to, see [`Part::Copy`].
+    pub copy_left: Option<Link>,
+    /// Where the last 8 words are copied to, see [`Part::Copy`].
+    pub copy_right: Option<Link>,
+    /// Where the output chaining value is linked to, for the last block of a message whose digest
+    /// is hashed again.
+    pub link: Option<Link>,
+    /// Whether the block is a whole 32-byte message made of the digest of the previous message,
+    /// see [`crate::hash_chain`]. Its right half is the constant padding.
+    pub is_chained_in: bool,
+    /// Whether the digest of the message is hashed by the next message, which is chained in, for
+    /// the last block of a message. See [`Block::is_chained_in`].
+    pub is_chained_out: bool,
+}
+
+/// Destination of a linked digest or of a copied half: the half `position % 2` of the block
+/// `position / 2` of a message, i.e. its 32 bytes starting at `32 * position`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Link {
+    pub message_id: u32,
+    pub position: u32,
+}
+
+/// Part of a message built from public bytes and digests of earlier messages.
+///
+/// All parts but [`Part::Bytes`] must start at a multiple of 32 bytes, and fill whole halves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part {
+    Bytes(Vec<u8>),
+    /// SHA-256 digest of the message with this index. Each digest can be linked once.
+    Digest(u32),
+    /// Bytes left out of the public claims, whose length must be a multiple of 32. The verifier only
+    /// uses the length. They are not hidden: proofs are not zero-knowledge, and the opened trace
+    /// values reveal them.
+    Unclaimed(Vec<u8>),
+    /// The 32 bytes of the half `position` of the padded message with this index, see [`Link`].
+    /// The message can be the current one if the half comes earlier, e.g. a Merkle node paired
+    /// with itself. Each half can be copied once.
+    Copy {
+        message_id: u32,
+        position: u32,
+    },
+}
+
+impl Part {
+    /// Length of the part in bytes.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Bytes(bytes) | Self::Unclaimed(bytes) => bytes.len(),
+            Self::Digest(_) | Self::Copy { .. } => 32,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Destinations of the digests and of the copied halves of a batch of messages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Links {
+    /// Link of the digest of each message which is part of a later message.
+    pub digests: Vec<Option<Link>>,
+    /// Link of each copied half, by message id and position.
+    pub copies: BTreeMap<(u32, u32), Link>,
+}
+
+impl Block {
+    pub const fn is_first(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Output chaining value, i.e. the compression of `words` from `h_in` with feed-forward.
+    pub fn h_out(&self) -> [u32; 8] {
+        process_chunk(self.words, self.h_in)
+    }
+
+    /// A standalone zero block used to pad the trace: it starts from the initial hash value of
+    /// `hash_function` and is not chained to any other block.
+    pub const fn padding(hash_function: HashFunction) -> Self {
+        Self {
+            message_id: 0,
+            index: 0,
+            is_last: true,
+            h_in: hash_function.iv(),
+            words: [0; 16],
+            is_linked_left: false,
+            is_linked_right: false,
+            is_unclaimed_left: false,
+            is_unclaimed_right: false,
+            copy_left: None,
+            copy_right: None,
+            link: None,
+            is_chained_in: false,
+            is_chained_out: false,
+        }
+    }
+}
+
+impl Default for Block {
+    /// The SHA-256 padding block, see [`Block::padding`].
+    fn default() -> Self {
+        Self::padding(HashFunction::Sha256)
+    }
+}
+
+/// Pads a message according to FIPS 180-4 and splits it into 512-bit blocks.
+pub fn pad_message(message: &[u8]) -> Vec<[u32; 16]> {
+    let mut padded = Vec::from(message);
+    padded.push(0x80);
+    while !(padded.len() + 8).is_multiple_of(64) {
+        padded.push(0x00);
+    }
+    padded.extend_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+
+    padded
+        .chunks_exact(64)
+        .map(|chunk| {
+            std::array::from_fn(|i| u32::from_be_bytes(chunk[4 * i..4 * i + 4].try_into().unwrap()))
+        })
+        .collect()
+}
+
+/// Pads each message and chains its blocks, starting from the SHA-256 initial hash value.
+pub fn gen_blocks<M: AsRef<[u8]>>(messages: &[M]) -> Vec<Block> {
+    gen_blocks_for(HashFunction::Sha256, messages)
+}
+
+/// Same as [`gen_blocks`], starting from the initial hash value of `hash_function`. SHA-224 pads
+/// its messages like SHA-256.
+///
+/// With several hashes per message, see [`HashFunction::n_hashes`], message `i` gets the ids
+/// `i * n_hashes..(i + 1) * n_hashes`, one for each hash.
+pub fn gen_blocks_for<M: AsRef<[u8]>>(hash_function: HashFunction, messages: &[M]) -> Vec<Block> {
+    gen_linked_blocks(hash_function, &hash_parts(hash_function, messages))
+}
+
+/// Messages hashed by `hash_function`: each message, followed by its digest for each additional
+/// hash.
+pub fn hash_parts<M: AsRef<[u8]>>(hash_function: HashFunction, messages: &[M]) -> Vec<Vec<Part>> {
+    let n_hashes = hash_function.n_hashes();
+    messages
+        .iter()
+        .enumerate()
+        .flat_map(|(i, message)| {
+            let first = (i * n_hashes) as u32;
+            std::iter::once(vec![Part::Bytes(message.as_ref().to_vec())])
+                .chain((first..first + n_hashes as u32 - 1).map(|id| vec![Part::Digest(id)]))
+        })
+        .collect()
+}
+
+/// Links of the digest and copy parts of the messages, see [`Part`].
+///
+/// Panics if a digest does not come from an earlier message, if a copy does not come from an
+/// earlier message or an earlier half of the same message, if a part other than bytes is not
+/// aligned on 32 bytes or does not fill whole halves, or if a digest or a half is linked twice.
+pub fn message_links(messages: &[Vec<Part>]) -> Links {
+    let mut links = Links {
+        digests: vec![None; messages.len()],
+        copies: BTreeMap::new(),
+    };
+    let mut n_halves = Vec::with_capacity(messages.len());
+    for (message_id, parts) in messages.iter().enumerate() {
+        let mut offset = 0;
+        for part in parts {
+            let link = Link {
+                message_id: message_id as u32,
+                position: (offset / 32) as u32,
+            };
+            match part {
+                Part::Bytes(_) => {}
+                Part::Digest(source) => {
+                    let source = *source as usize;
+                    assert!(source < message_id, "Digest of a later message: {source}");
+                    assert!(offset.is_multiple_of(32), "Unaligned digest: {source}");
+                    assert!(
+                        links.digests[source].is_none(),
+                        "Digest linked twice: {source}"
+                    );
+                    links.digests[source] = Some(link);
+                }
+                Part::Unclaimed(bytes) => {
+                    assert!(offset.is_multiple_of(32), "Unaligned unclaimed part");
+                    assert!(bytes.len().is_multiple_of(32), "Partial unclaimed half");
+                }
+                Part::Copy {
+                    message_id: source,
+                    position,
+                } => {
+                    assert!(
+                        (*source as usize) <= message_id,
+                        "Copy of a later message: {source}"
+                    );
+                    let source_halves = match n_halves.get(*source as usize) {
+                        Some(n_halves) => *n_halves,
+                        // An earlier half of the same message
+                        None => (offset / 32) as u32,
+                    };
+                    assert!(*position < source_halves, "Copy out of the message");
+                    assert!(offset.is_multiple_of(32), "Unaligned copy: {source}");
+                    let previous = links.copies.insert((*source, *position), link);
+                    assert!(
+                        previous.is_none(),
+                        "Half copied twice: {source}, {position}"
+                    );
+                }
+            }
+            offset += part.len();
+        }
+        // FIPS 180-4 padding appends at least 9 bytes
+        n_halves.push(2 * (offset + 9).div_ceil(64) as u32);
+    }
+    links
+}
+
+/// Positions of the halves of a message which are linked, i.e. its digest and copy parts.
+pub fn linked_positions(parts: &[Part]) -> Vec<u32> {
+    part_positions(parts, |part| {
+        matches!(part, Part::Digest(_) | Part::Copy { .. })
+    })
+}
+
+/// Positions of the unclaimed halves of a message.
+pub fn unclaimed_positions(parts: &[Part]) -> Vec<u32> {
+    part_positions(parts, |part| matches!(part, Part::Unclaimed(_)))
+}
+
+fn part_positions(parts: &[Part], filter: impl Fn(&Part) -> bool) -> Vec<u32> {
+    let mut offset = 0;
+    let mut positions = Vec::new();
+    for part in parts {
+        if filter(part) {
+            positions.extend((offset / 32..(offset + part.len()) / 32).map(|p| p as u32));
+        }
+        offset += part.len();
+    }
+    positions
+}
+
+/// Pads the messages and chains their blocks from the initial hash value of `hash_function`, and
+/// routes the digest and copy parts of the messages through links. See [`message_links`] for the
+/// valid parts.
+pub fn gen_linked_blocks(hash_function: HashFunction, messages: &[Vec<Part>]) -> Vec<Block> {
+    let links = message_links(messages);
+    let mut digests: Vec<[u8; 32]> = Vec::with_capacity(messages.len());
+    let mut padded: Vec<Vec<[u32; 16]>> = Vec::with_capacity(messages.len());
+    let mut blocks = Vec::new();
+    for (message_id, parts) in messages.iter().enumerate() {
+        let mut message = Vec::new();
+        for part in parts {
+            match part {
+                Part::Bytes(bytes) | Part::Unclaimed(bytes) => message.extend_from_slice(bytes),
+                Part::Digest(source) => message.extend_from_slice(&digests[*source as usize]),
+                Part::Copy {
+                    message_id: source,
+                    position,
+                } if *source as usize == message_id => {
+                    let start = 32 * *position as usize;
+                    message.extend_from_within(start..start + 32);
+                }
+                Part::Copy {
+                    message_id: source,
+                    position,
+                } => {
+                    let words = &padded[*source as usize][*position as usize / 2];
+                    let half = 8 * (*position as usize % 2);
+                    message.extend(
+                        words[half..half + 8]
+                            .iter()
+                            .flat_map(|word| word.to_be_bytes()),
+                    );
+                }
+            }
+        }
+        let linked = linked_positions(parts);
+        let unclaimed = unclaimed_positions(parts);
+        let mut message_blocks = message_blocks(hash_function, message_id as u32, &message);
+        for block in &mut message_blocks {
+            let (left, right) = (2 * block.index, 2 * block.index + 1);
+            block.is_linked_left = linked.contains(&left);
+            block.is_linked_right = linked.contains(&right);
+            block.is_unclaimed_left = unclaimed.contains(&left);
+            block.is_unclaimed_right = unclaimed.contains(&right);
+            block.copy_left = links.copies.get(&(message_id as u32, left)).copied();
+            block.copy_right = links.copies.get(&(message_id as u32, right)).copied();
+        }
+        let last = message_blocks.last_mut().unwrap();
+        last.link = links.digests[message_id];
+        digests.push(to_bytes(last.h_out()));
+        padded.push(message_blocks.iter().map(|block| block.words).collect());
+        blocks.extend(message_blocks);
+    }
+    blocks
+}
+
+/// Unlinked blocks of a message.
+fn message_blocks(hash_function: HashFunction, message_id: u32, message: &[u8]) -> Vec<Block> {
+    let chunks = pad_message(message);
+    let n_chunks = chunks.len();
+    let mut h_in = hash_function.iv();
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, words)| {
+            let block = Block {
+                message_id,
+                index: index as u32,
+                is_last: index == n_chunks - 1,
+                h_in,
+                words,
+                ..Block::padding(hash_function)
+            };
+            h_in = block.h_out();
+            block
+        })
+        .collect()
+}
+
+/// Big-endian bytes of a hash state.
+pub fn to_bytes(hash: [u32; 8]) -> [u8; 32] {
+    std::array::from_fn(|i| hash[i / 4].to_be_bytes()[i % 4])
+}
+
+/// Digest of each message, i.e. the output chaining value of its last block. Digests hashed
+/// again are skipped.
+pub fn digests(blocks: &[Block]) -> Vec<[u8; 32]> {
+    blocks
+        .iter()
+        .filter(|block| block.is_last && block.link.is_none() && !block.is_chained_out)
+        .map(|block| to_bytes(block.h_out()))
+        .collect()
+}
+
+/// Deterministic single-block messages, used for tests and benchmarks.
+///
+/// The 16-bit limb `i` of block `row` is `(i + row) & 0xffff`.
+pub fn generate_blocks(n_blocks: usize) -> Vec<Block> {
+    (0..n_blocks)
+        .map(|row| Block {
+            message_id: row as u32,
+            words: std::array::from_fn(|i| {
+                let low = (2 * i + row) as u32 & 0xffff;
+                let high = (2 * i + 1 + row) as u32 & 0xffff;
+                low + (high << 16)
+            }),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Builds a trace column from a value of each block, padding with default blocks up to
+/// `simd_size` SIMD rows.
+pub fn block_column(blocks: &[Block], simd_size: usize, f: impl Fn(&Block) -> u32) -> Vec<u32x16> {
+    padded_block_column(blocks, &Block::default(), simd_size, f)
+}
+
+/// Same as [`block_column`], padding with `padding` blocks.
+pub fn padded_block_column(
+    blocks: &[Block],
+    padding: &Block,
+    simd_size: usize,
+    f: impl Fn(&Block) -> u32,
+) -> Vec<u32x16> {
+    (0..simd_size)
+        .map(|simd_row| {
+            u32x16::from_array(std::array::from_fn(|lane| {
+                f(blocks
+                    .get((simd_row << LOG_N_LANES) + lane)
+                    .unwrap_or(padding))
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha224, Sha256};
+
+    use super::*;
+    use crate::sha256::H;
+
+    #[test]
+    fn test_pad_message() {
+        assert_eq!(pad_message(b"").len(), 1);
+        assert_eq!(pad_message(&[0; 55]).len(), 1);
+        assert_eq!(pad_message(&[0; 56]).len(), 2);
+        assert_eq!(pad_message(&[0; 64]).len(), 2);
+        assert_eq!(pad_message(&[0; 120]).len(), 3);
+
+        let chunks = pad_message(b"abc");
+        assert_eq!(chunks[0][0], 0x61626380);
+        assert_eq!(chunks[0][15], 24);
+    }
+
+    #[test]
+    fn test_gen_blocks() {
+        let messages: Vec<Vec<u8>> = vec![
+            b"".to_vec(),
+            b"hello world".to_vec(),
+            (0..200).map(|i| i as u8).collect(),
+        ];
+        let blocks = gen_blocks(&messages);
+        assert_eq!(blocks.len(), 1 + 1 + 4);
+
+        for (message_id, message) in messages.iter().enumerate() {
+            let message_blocks = blocks
+                .iter()
+                .filter(|block| block.message_id == message_id as u32)
+                .collect::<Vec<_>>();
+            assert!(message_blocks[0].is_first());
+            assert_eq!(message_blocks[0].h_in, H);
+            for (i, pair) in message_blocks.windows(2).enumerate() {
+                assert_eq!(pair[0].index, i as u32);
+                assert!(!pair[0].is_last);
+                assert_eq!(pair[0].h_out(), pair[1].h_in);
+            }
+            let last = message_blocks.last().unwrap();
+            assert!(last.is_last);
+            assert_eq!(to_bytes(last.h_out())[..], Sha256::digest(message)[..]);
+        }
+    }
+
+    #[test]
+    fn test_gen_blocks_sha224() {
+        let messages = [b"abc".to_vec(), vec![0x42; 100]];
+        let blocks = gen_blocks_for(HashFunction::Sha224, &messages);
+        assert_eq!(blocks[0].h_in, HashFunction::Sha224.iv());
+        let digests = blocks
+            .iter()
+            .filter(|block| block.is_last)
+            .map(|block| HashFunction::Sha224.digest(block.h_out()))
+            .collect::<Vec<_>>();
+        for (digest, message) in digests.iter().zip(messages.iter()) {
+            assert_eq!(digest[..], Sha224::digest(message)[..]);
+        }
+    }
+
+    #[test]
+    fn test_gen_blocks_sha256d() {
+        let header = [0x42; 80];
+        let blocks = gen_blocks_for(HashFunction::Sha256d, &[header]);
+        assert_eq!(blocks.len(), 2 + 1);
+        assert_eq!(
+            blocks[1].link,
+            Some(Link {
+                message_id: 1,
+                position: 0
+            })
+        );
+        assert_eq!(blocks[2].message_id, 1);
+        assert!(blocks[2].is_linked_left && !blocks[2].is_linked_right);
+        assert_eq!(blocks[2].words[..8], blocks[1].h_out());
+        assert_eq!(blocks[2].words[8..], pad_message(&[0; 32])[0][8..]);
+
+        let digests = digests(&blocks);
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests[0][..], Sha256::digest(Sha256::digest(header))[..]);
+    }
+
+    #[test]
+    fn test_gen_linked_blocks() {
+        // Both halves of the first block of message 2, and the second block of message 3.
+        let messages = vec![
+            vec![Part::Bytes(b"abc".to_vec())],
+            vec![Part::Bytes(b"def".to_vec())],
+            vec![Part::Digest(0), Part::Digest(1)],
+            vec![Part::Bytes(vec![0x42; 64]), Part::Digest(2)],
+        ];
+        let blocks = gen_linked_blocks(HashFunction::Sha256, &messages);
+        let last = |message_id| {
+            blocks
+                .iter()
+                .filter(|block| block.message_id == message_id)
+                .next_back()
+                .unwrap()
+        };
+        assert_eq!(last(0).link.unwrap().position, 0);
+        assert_eq!(last(1).link.unwrap().position, 1);
+        assert_eq!(last(2).link.unwrap().position, 2);
+        assert!(last(3).link.is_none());
+        assert_eq!(linked_positions(&messages[3]), [2]);
+
+        let node = [Sha256::digest(b"abc"), Sha256::digest(b"def")].concat();
+        let message = [vec![0x42; 64], Sha256::digest(&node).to_vec()].concat();
+        assert_eq!(digests(&blocks), [to_bytes(last(3).h_out())]);
+        assert_eq!(digests(&blocks)[0][..], Sha256::digest(message)[..]);
+        assert!(blocks[2].is_linked_left && blocks[2].is_linked_right);
+        assert!(blocks[5].index == 1 && blocks[5].is_linked_left && !blocks[5].is_linked_right);
+    }
+
+    #[test]
+    fn test_gen_linked_blocks_self_copy() {
+        // The digest of message 0 fills both halves of message 1.
+        let messages = vec![
+            vec![Part::Bytes(b"abc".to_vec())],
+            vec![
+                Part::Digest(0),
+                Part::Copy {
+                    message_id: 1,
+                    position: 0,
+                },
+            ],
+        ];
+        let blocks = gen_linked_blocks(HashFunction::Sha256, &messages);
+        assert!(blocks[1].is_linked_left && blocks[1].is_linked_right);
+        assert_eq!(
+            blocks[1].copy_left,
+            Some(Link {
+                message_id: 1,
+                position: 1
+            })
+        );
+        let digest = Sha256::digest(b"abc");
+        assert_eq!(
+            digests(&blocks)[0][..],
+            Sha256::digest([digest, digest].concat())[..]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Copy out of the message")]
+    fn test_gen_linked_blocks_copy_later_half() {
+        gen_linked_blocks(
+            HashFunction::Sha256,
+            &[vec![
+                Part::Copy {
+                    message_id: 0,
+                    position: 1,
+                },
+                Part::Bytes(vec![0; 32]),
+            ]],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unaligned digest")]
+    fn test_gen_linked_blocks_unaligned() {
+        gen_linked_blocks(
+            HashFunction::Sha256,
+            &[
+                vec![Part::Bytes(b"abc".to_vec())],
+                vec![Part::Bytes(vec![0; 16]), Part::Digest(0)],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_digests() {
+        let messages = [b"abc".to_vec(), vec![0x42; 100]];
+        let digests = digests(&gen_blocks(&messages));
+        assert_eq!(digests.len(), messages.len());
+        for (digest, message) in digests.iter().zip(messages.iter()) {
+            assert_eq!(digest[..], Sha256::digest(message)[..]);
+        }
+    }
+
+    #[test]
+    fn test_block_column() {
+        let blocks = generate_blocks(3);
+        let column = block_column(&blocks, 1, |block| block.message_id);
+        assert_eq!(column.len(), 1);
+        assert_eq!(column[0][..3], [0, 1, 2]);
+        assert!(column[0][3..].iter().all(|x| *x == 0));
+    }
+}
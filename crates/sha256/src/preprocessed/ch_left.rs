@@ -13,6 +13,22 @@ use crate::{
 // [e, f, val]
 const N_COLUMNS: usize = 3;
 
+/// Input partitions of the tables, in column order.
+const PARTITIONS: [u32; 4] = [
+    BigSigma1Partitions::I0_L,
+    BigSigma1Partitions::I0_H,
+    BigSigma1Partitions::I1_L,
+    BigSigma1Partitions::I1_H,
+];
+
+/// Number of columns and log size of each table, in column order.
+pub const TABLES: [(usize, u32); 4] = [
+    (N_COLUMNS, 2 * PARTITIONS[0].count_ones()),
+    (N_COLUMNS, 2 * PARTITIONS[1].count_ones()),
+    (N_COLUMNS, 2 * PARTITIONS[2].count_ones()),
+    (N_COLUMNS, 2 * PARTITIONS[3].count_ones()),
+];
+
 relation!(CH_LEFT_I0_L, N_COLUMNS);
 relation!(CH_LEFT_I0_H, N_COLUMNS);
 relation!(CH_LEFT_I1_L, N_COLUMNS);
@@ -85,15 +101,7 @@ impl Relation {
 pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
     let mut all_columns: Vec<Vec<u32x16>> = vec![Vec::new(); ChLeftColumns::SIZE];
 
-    for (i, partition) in [
-        BigSigma1Partitions::I0_L,
-        BigSigma1Partitions::I0_H,
-        BigSigma1Partitions::I1_L,
-        BigSigma1Partitions::I1_H,
-    ]
-    .iter()
-    .enumerate()
-    {
+    for (i, partition) in PARTITIONS.iter().enumerate() {
         // lookup
         let tuples: Vec<(u32x16, u32x16, u32x16)> = iproduct!(
             SubsetIterator::new(*partition),
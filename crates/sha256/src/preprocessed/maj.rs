@@ -13,6 +13,26 @@ use crate::{
 // [a, b, c, val]
 const N_COLUMNS: usize = 4;
 
+/// Input partitions of the tables, in column order.
+const PARTITIONS: [u32; 6] = [
+    BigSigma0Partitions::I0_L,
+    BigSigma0Partitions::I1_H,
+    BigSigma0Partitions::I0_H0,
+    BigSigma0Partitions::I1_L0,
+    BigSigma0Partitions::I0_H1,
+    BigSigma0Partitions::I1_L1,
+];
+
+/// Number of columns and log size of each table, in column order.
+pub const TABLES: [(usize, u32); 6] = [
+    (N_COLUMNS, 3 * PARTITIONS[0].count_ones()),
+    (N_COLUMNS, 3 * PARTITIONS[1].count_ones()),
+    (N_COLUMNS, 3 * PARTITIONS[2].count_ones()),
+    (N_COLUMNS, 3 * PARTITIONS[3].count_ones()),
+    (N_COLUMNS, 3 * PARTITIONS[4].count_ones()),
+    (N_COLUMNS, 3 * PARTITIONS[5].count_ones()),
+];
+
 relation!(MAJ_I0_L, N_COLUMNS);
 relation!(MAJ_I0_H0, N_COLUMNS);
 relation!(MAJ_I0_H1, N_COLUMNS);
@@ -121,17 +141,7 @@ impl Relation {
 pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
     let mut all_columns: Vec<Vec<u32x16>> = vec![Vec::new(); MajColumns::SIZE];
 
-    for (i, partition) in [
-        BigSigma0Partitions::I0_L,
-        BigSigma0Partitions::I1_H,
-        BigSigma0Partitions::I0_H0,
-        BigSigma0Partitions::I1_L0,
-        BigSigma0Partitions::I0_H1,
-        BigSigma0Partitions::I1_L1,
-    ]
-    .iter()
-    .enumerate()
-    {
+    for (i, partition) in PARTITIONS.iter().enumerate() {
         // lookup
         let tuples: Vec<(u32x16, u32x16, u32x16, u32x16)> = iproduct!(
             SubsetIterator::new(*partition),
@@ -2,31 +2,137 @@
 //! the proof.
 //!
 //! They are similar to regular components but are entirely known by the verifier.
-use itertools::Itertools;
+use std::{fs, io, path::Path, simd::u32x16};
+
+use itertools::{repeat_n, Itertools};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use stwo::{
     core::fields::m31::BaseField,
     prover::{
-        backend::simd::{m31::LOG_N_LANES, SimdBackend},
+        backend::{
+            simd::{m31::LOG_N_LANES, SimdBackend},
+            Column,
+        },
         poly::{circle::CircleEvaluation, BitReversedOrder},
     },
 };
 use stwo_constraint_framework::preprocessed_columns::PreProcessedColumnId;
 use utils::circle_evaluation_u32x16;
 
+use crate::proof::PROOF_VERSION;
+
 pub mod big_sigma_0;
 pub mod big_sigma_1;
 pub mod ch_left;
 pub mod ch_right;
 pub mod maj;
+pub mod range_check_16;
 pub mod range_check_add;
 pub mod sigma_0;
 pub mod sigma_1;
 
+/// Hex-encoded [`PreProcessedTrace::digest`] of the tables. Any change of the tables changes the
+/// preprocessed commitment of all proofs: bump [`PROOF_VERSION`] along with this digest.
+pub const TABLES_DIGEST: &str = "20e9601e3f204365246367d4977ce3b1dba5975b978f47ade30b5f488db1cfcf";
+
+/// Calls `$callback!(module, IdTypes...)` for each table module, in trace order.
+macro_rules! for_each_table {
+    ($callback:ident) => {
+        $callback!(big_sigma_0, BigSigma0I0I1Columns, BigSigma0O2Columns);
+        $callback!(
+            big_sigma_1,
+            BigSigma1I0Columns,
+            BigSigma1I1Columns,
+            BigSigma1O2Columns
+        );
+        $callback!(ch_left, ChLeftI0Columns, ChLeftI1Columns);
+        $callback!(ch_right, ChRightI0Columns, ChRightI1Columns);
+        $callback!(
+            maj,
+            MajI0LI1HColumns,
+            MajI0H0I1L0Columns,
+            MajI0H1I1L1Columns
+        );
+        $callback!(range_check_add, RangeCheckAddColumns);
+        $callback!(range_check_16, RangeCheck16Columns);
+        $callback!(sigma_0, Sigma0I0I1Columns, Sigma0O2Columns);
+        $callback!(sigma_1, Sigma1I0I1Columns, Sigma1O2Columns);
+    };
+}
+
+/// Ids and log sizes of the columns of [`PreProcessedTrace::new`], derived from the table sizes
+/// without generating them.
+pub fn columns(log_size: u32) -> (Vec<PreProcessedColumnId>, Vec<u32>) {
+    let mut ids = Vec::new();
+    let mut log_sizes = Vec::new();
+
+    macro_rules! table_columns {
+        ($mod:ident, $($id_ty:ident),+) => {{
+            let mut module_ids = Vec::new();
+            $(
+                module_ids.extend($mod::$id_ty::to_ids(None));
+            )+
+            let table_log_sizes = $mod::TABLES
+                .iter()
+                .flat_map(|&(n_columns, table_log_size)| repeat_n(table_log_size, n_columns));
+
+            // Tables are split into columns of at most 2^log_size rows.
+            for (id_base, table_log_size) in module_ids.into_iter().zip_eq(table_log_sizes) {
+                for suffix in 0..1 << table_log_size.saturating_sub(log_size) {
+                    ids.push(PreProcessedColumnId {
+                        id: format!("{}_{}", id_base.id, suffix),
+                    });
+                    log_sizes.push(table_log_size.min(log_size));
+                }
+            }
+        }};
+    }
+
+    for_each_table!(table_columns);
+
+    (ids, log_sizes)
+}
+
 pub struct PreProcessedTrace {
     pub trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
     pub ids: Vec<PreProcessedColumnId>,
 }
 
+/// On-disk encoding of a preprocessed trace, tagged with the proof version so that a change of
+/// the tables invalidates the cache.
+#[derive(Serialize, Deserialize)]
+struct CachedTrace {
+    version: u32,
+    log_size: u32,
+    ids: Vec<String>,
+    columns: Vec<Vec<u32>>,
+}
+
+impl CachedTrace {
+    /// Whether the cache holds the tables for `log_size`: the columns must have the expected ids
+    /// and sizes, and their values the pinned [`TABLES_DIGEST`].
+    fn is_valid(&self, log_size: u32) -> bool {
+        let (ids, log_sizes) = columns(log_size);
+        if self.version != PROOF_VERSION
+            || self.log_size != log_size
+            || !self.ids.iter().eq(ids.iter().map(|id| &id.id))
+            || !self
+                .columns
+                .iter()
+                .map(Vec::len)
+                .eq(log_sizes.iter().map(|log_size| 1 << log_size))
+        {
+            return false;
+        }
+        let mut hasher = Sha256::new();
+        for value in self.columns.iter().flatten() {
+            hasher.update(value.to_le_bytes());
+        }
+        hex::encode(hasher.finalize()) == TABLES_DIGEST
+    }
+}
+
 impl PreProcessedTrace {
     #[allow(clippy::cognitive_complexity)]
     pub fn new(log_size: u32) -> Self {
@@ -58,27 +164,86 @@ impl PreProcessedTrace {
         }
 
         // Generate everything in lockstep
-        collect_columns!(big_sigma_0, BigSigma0I0I1Columns, BigSigma0O2Columns);
-        collect_columns!(
-            big_sigma_1,
-            BigSigma1I0Columns,
-            BigSigma1I1Columns,
-            BigSigma1O2Columns
-        );
-        collect_columns!(ch_left, ChLeftI0Columns, ChLeftI1Columns);
-        collect_columns!(ch_right, ChRightI0Columns, ChRightI1Columns);
-        collect_columns!(
-            maj,
-            MajI0LI1HColumns,
-            MajI0H0I1L0Columns,
-            MajI0H1I1L1Columns
-        );
-        collect_columns!(range_check_add, RangeCheckAddColumns);
-        collect_columns!(sigma_0, Sigma0I0I1Columns, Sigma0O2Columns);
-        collect_columns!(sigma_1, Sigma1I0I1Columns, Sigma1O2Columns);
+        for_each_table!(collect_columns);
 
         Self { trace, ids }
     }
+
+    /// Loads the trace cached in `dir`, or generates it and writes it there if the cache is
+    /// missing, stale or corrupted.
+    pub fn load_or_new(log_size: u32, dir: &Path) -> Result<Self, bincode::Error> {
+        let path = dir.join(format!("preprocessed_{log_size}.bin"));
+        match fs::read(&path) {
+            Ok(bytes) => {
+                if let Ok(cached) = bincode::deserialize::<CachedTrace>(&bytes) {
+                    if cached.is_valid(log_size) {
+                        return Ok(Self::from_cache(cached));
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let trace = Self::new(log_size);
+        fs::create_dir_all(dir)?;
+        fs::write(&path, bincode::serialize(&trace.to_cache(log_size))?)?;
+        Ok(trace)
+    }
+
+    fn to_cache(&self, log_size: u32) -> CachedTrace {
+        CachedTrace {
+            version: PROOF_VERSION,
+            log_size,
+            ids: self.ids.iter().map(|id| id.id.clone()).collect(),
+            columns: self
+                .trace
+                .iter()
+                .map(|eval| eval.values.to_cpu().into_iter().map(|x| x.0).collect())
+                .collect(),
+        }
+    }
+
+    fn from_cache(cached: CachedTrace) -> Self {
+        let trace = cached
+            .columns
+            .iter()
+            .map(|column| {
+                let column = column
+                    .array_chunks::<16>()
+                    .map(|chunk| u32x16::from_array(*chunk))
+                    .collect::<Vec<_>>();
+                circle_evaluation_u32x16!(column)
+            })
+            .collect();
+        let ids = cached
+            .ids
+            .into_iter()
+            .map(|id| PreProcessedColumnId { id })
+            .collect();
+        Self { trace, ids }
+    }
+
+    pub fn log_sizes(&self) -> Vec<u32> {
+        self.trace
+            .iter()
+            .map(|eval| eval.domain.log_size())
+            .collect()
+    }
+
+    /// SHA-256 digest of the tables, i.e. of the values of all the columns in trace order.
+    ///
+    /// Tables are split into consecutive columns of at most `2^log_size` rows, so the digest does
+    /// not depend on the log size.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for eval in &self.trace {
+            for value in eval.values.to_cpu() {
+                hasher.update(value.0.to_le_bytes());
+            }
+        }
+        hasher.finalize().into()
+    }
 }
 
 #[cfg(test)]
@@ -90,4 +255,64 @@ mod tests {
         let trace = PreProcessedTrace::new(8);
         assert!(trace.trace.iter().map(|t| t.data.len()).max().unwrap() <= 1 << (8 - LOG_N_LANES));
     }
+
+    #[test]
+    fn test_columns() {
+        for log_size in [8, 17] {
+            let trace = PreProcessedTrace::new(log_size);
+            assert_eq!(columns(log_size), (trace.ids.clone(), trace.log_sizes()));
+        }
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let trace = PreProcessedTrace::new(8);
+        let cached = PreProcessedTrace::from_cache(trace.to_cache(8));
+        assert_eq!(cached.ids, trace.ids);
+        assert_eq!(cached.log_sizes(), trace.log_sizes());
+        for (cached, eval) in cached.trace.iter().zip_eq(&trace.trace) {
+            assert_eq!(cached.values.to_cpu(), eval.values.to_cpu());
+        }
+    }
+
+    #[test]
+    fn test_digest() {
+        assert_eq!(
+            hex::encode(PreProcessedTrace::new(8).digest()),
+            TABLES_DIGEST
+        );
+        assert_eq!(
+            hex::encode(PreProcessedTrace::new(12).digest()),
+            TABLES_DIGEST
+        );
+    }
+
+    #[test]
+    fn test_corrupted_cache() {
+        let log_size = 8;
+        let dir = std::env::temp_dir().join(format!("sha256-cache-{}", std::process::id()));
+        let path = dir.join(format!("preprocessed_{log_size}.bin"));
+        let trace = PreProcessedTrace::new(log_size);
+        assert!(trace.to_cache(log_size).is_valid(log_size));
+
+        let tampers: [fn(&mut CachedTrace); 2] = [
+            // Not a whole number of SIMD rows.
+            |cached| cached.columns[0].truncate(17),
+            |cached| cached.columns[0][5] ^= 1,
+        ];
+        fs::create_dir_all(&dir).unwrap();
+        for tamper in tampers {
+            let mut cached = trace.to_cache(log_size);
+            tamper(&mut cached);
+            assert!(!cached.is_valid(log_size));
+            fs::write(&path, bincode::serialize(&cached).unwrap()).unwrap();
+
+            // The corrupted cache is regenerated and rewritten.
+            let loaded = PreProcessedTrace::load_or_new(log_size, &dir).unwrap();
+            assert_eq!(loaded.digest(), trace.digest());
+            let rewritten = bincode::deserialize::<CachedTrace>(&fs::read(&path).unwrap());
+            assert!(rewritten.unwrap().is_valid(log_size));
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
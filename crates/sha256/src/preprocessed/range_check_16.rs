@@ -0,0 +1,60 @@
+use std::simd::u32x16;
+
+use stwo::{core::channel::Channel, prover::backend::simd::m31::LOG_N_LANES};
+use stwo_constraint_framework::relation;
+use utils::trace_columns;
+
+// [value]
+const N_COLUMNS: usize = 1;
+
+/// Log size of the table, all the 16-bit values.
+pub const LOG_SIZE: u32 = 16;
+
+/// Number of columns and log size of each table, in column order.
+pub const TABLES: [(usize, u32); 1] = [(N_COLUMNS, LOG_SIZE)];
+
+relation!(Limb, N_COLUMNS);
+
+trace_columns!(RangeCheck16Columns, value);
+
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub limb: Limb,
+}
+
+impl Relation {
+    pub fn dummy() -> Self {
+        Self {
+            limb: Limb::dummy(),
+        }
+    }
+
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            limb: Limb::draw(channel),
+        }
+    }
+}
+
+pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
+    let value = (0..1 << (LOG_SIZE - LOG_N_LANES))
+        .map(|i| u32x16::from_array(std::array::from_fn(|j| i * 16 + j as u32)))
+        .collect();
+    vec![value]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen_column_simd() {
+        let columns = gen_column_simd();
+        assert_eq!(columns.len(), RangeCheck16Columns::SIZE);
+        assert_eq!(columns[0].len().ilog2(), LOG_SIZE - LOG_N_LANES);
+        assert!(columns[0]
+            .iter()
+            .flat_map(|v| v.to_array())
+            .eq(0..1 << LOG_SIZE));
+    }
+}
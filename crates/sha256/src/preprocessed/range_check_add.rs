@@ -1,6 +1,6 @@
 use std::simd::u32x16;
 
-use stwo::core::channel::Channel;
+use stwo::{core::channel::Channel, prover::backend::simd::m31::LOG_N_LANES};
 use stwo_constraint_framework::relation;
 use utils::trace_columns;
 
@@ -13,6 +13,12 @@ relation!(Add8, N_COLUMNS);
 
 trace_columns!(RangeCheckAddColumns, value, carry_4, carry_7, carry_8,);
 
+/// Log size of the table, all the 16-bit values with each of their carries.
+const LOG_SIZE: u32 = 19;
+
+/// Number of columns and log size of each table, in column order.
+pub const TABLES: [(usize, u32); 1] = [(RangeCheckAddColumns::SIZE, LOG_SIZE)];
+
 #[derive(Debug, Clone)]
 pub struct Relation {
     pub add_4: Add4,
@@ -39,7 +45,7 @@ impl Relation {
 }
 
 pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
-    const N: usize = 1 << 15;
+    const N: usize = 1 << (LOG_SIZE - LOG_N_LANES);
     let mut all_columns = vec![
         Vec::with_capacity(N),
         Vec::with_capacity(N),
@@ -51,7 +57,7 @@ pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
     let carry_7 = u32x16::from_array([0, 1, 2, 3, 4, 5, 6, 0, 0, 1, 2, 3, 4, 5, 6, 0]);
     let carry_8 = u32x16::from_array([0, 1, 2, 3, 4, 5, 6, 7, 0, 1, 2, 3, 4, 5, 6, 7]);
 
-    for i in 0..1 << 15 {
+    for i in 0..N as u32 {
         let value = u32x16::from_array(std::array::from_fn(|j| i * 2 + (j > 7) as u32));
         all_columns[0].push(value);
         all_columns[1].push(carry_4);
@@ -64,8 +70,6 @@ pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
 
 #[cfg(test)]
 mod tests {
-    use stwo::prover::backend::simd::m31::LOG_N_LANES;
-
     use super::*;
 
     #[test]
@@ -77,9 +81,9 @@ mod tests {
     fn test_gen_column_simd() {
         let columns = gen_column_simd();
         assert_eq!(columns.len(), RangeCheckAddColumns::SIZE);
-        assert_eq!(columns[0].len().ilog2(), 19 - LOG_N_LANES);
-        assert_eq!(columns[1].len().ilog2(), 19 - LOG_N_LANES);
-        assert_eq!(columns[2].len().ilog2(), 19 - LOG_N_LANES);
-        assert_eq!(columns[3].len().ilog2(), 19 - LOG_N_LANES);
+        assert_eq!(columns[0].len().ilog2(), LOG_SIZE - LOG_N_LANES);
+        assert_eq!(columns[1].len().ilog2(), LOG_SIZE - LOG_N_LANES);
+        assert_eq!(columns[2].len().ilog2(), LOG_SIZE - LOG_N_LANES);
+        assert_eq!(columns[3].len().ilog2(), LOG_SIZE - LOG_N_LANES);
     }
 }
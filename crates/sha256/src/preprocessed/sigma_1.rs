@@ -14,6 +14,13 @@ const N_IO_COLUMNS: usize = 5;
 const N_I1_COLUMNS: usize = 5;
 const N_O2_COLUMNS: usize = 4;
 
+/// Number of columns and log size of each table, in column order.
+pub const TABLES: [(usize, u32); 3] = [
+    (N_IO_COLUMNS, Sigma1Partitions::I0.count_ones()),
+    (N_I1_COLUMNS, Sigma1Partitions::I1.count_ones()),
+    (N_O2_COLUMNS, 2 * Sigma1Partitions::O2.count_ones()),
+];
+
 relation!(SIGMA_1_I0, N_IO_COLUMNS);
 relation!(SIGMA_1_I1, N_I1_COLUMNS);
 relation!(SIGMA_1_O2, N_O2_COLUMNS);
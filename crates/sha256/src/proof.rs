@@ -0,0 +1,273 @@
+//! Serialization of SHA-256 proofs.
+//!
+//! A [`Sha256Proof`] bundles everything the verifier needs besides the AIR itself. The encoding
+//! starts with [`PROOF_VERSION`], which is checked before decoding the rest of the envelope.
+
+use serde::{Deserialize, Serialize};
+use stwo::{
+    core::{
+        channel::MerkleChannel,
+        pcs::PcsConfig,
+        proof::StarkProof,
+        vcs::{
+            blake2_merkle::{Blake2sMerkleChannel, Blake2sMerkleHasher},
+            blake3_merkle::Blake3MerkleChannel,
+            poseidon252_merkle::Poseidon252MerkleChannel,
+            MerkleHasher,
+        },
+    },
+    prover::backend::{simd::SimdBackend, BackendForChannel},
+};
+use thiserror::Error;
+
+use crate::{
+    components::ClaimedSum,
+    public::HashClaim,
+    sha256::HashFunction,
+    verifier::{verify_with, Sha256VerifierError},
+};
+
+/// Version of the envelope layout, to bump when a released proof or AIR changes.
+pub const PROOF_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum ProofFormatError {
+    #[error("Unsupported proof version: {0}")]
+    UnsupportedVersion(u32),
+    #[error(transparent)]
+    Bincode(#[from] bincode::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Merkle channel a proof is committed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofChannel {
+    Blake2s,
+    Blake3,
+    Poseidon252,
+}
+
+/// Merkle channels whose proofs can be wrapped in a [`Sha256Proof`].
+pub trait EnvelopeChannel: MerkleChannel {
+    const CHANNEL: ProofChannel;
+}
+
+impl EnvelopeChannel for Blake2sMerkleChannel {
+    const CHANNEL: ProofChannel = ProofChannel::Blake2s;
+}
+
+impl EnvelopeChannel for Blake3MerkleChannel {
+    const CHANNEL: ProofChannel = ProofChannel::Blake3;
+}
+
+impl EnvelopeChannel for Poseidon252MerkleChannel {
+    const CHANNEL: ProofChannel = ProofChannel::Poseidon252;
+}
+
+/// Proof envelope, generic over the Merkle hasher of the channel used to prove.
+///
+/// The channel itself is recorded as well: Blake2s and Blake3 hashes have the same encoding, so
+/// the hasher type alone does not tell which channel the proof must be verified with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sha256Proof<H: MerkleHasher = Blake2sMerkleHasher> {
+    pub version: u32,
+    pub channel: ProofChannel,
+    pub log_size: u32,
+    pub config: PcsConfig,
+    pub hash_function: HashFunction,
+    pub claimed_sum: ClaimedSum,
+    pub claims: Vec<HashClaim>,
+    pub proof: StarkProof<H>,
+}
+
+/// Leading field of the envelope, decoded on its own to reject unknown versions.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+impl<H: MerkleHasher> Sha256Proof<H> {
+    /// Wraps a proof committed with the Merkle channel `MC`.
+    pub fn new<MC: EnvelopeChannel<H = H>>(
+        proof: StarkProof<H>,
+        log_size: u32,
+        config: PcsConfig,
+        claimed_sum: ClaimedSum,
+        claims: Vec<HashClaim>,
+    ) -> Self {
+        Self {
+            version: PROOF_VERSION,
+            channel: MC::CHANNEL,
+            log_size,
+            config,
+            hash_function: HashFunction::Sha256,
+            claimed_sum,
+            claims,
+            proof,
+        }
+    }
+
+    /// Sets the hash function the claims are checked against, SHA-256 by default.
+    pub const fn with_hash_function(mut self, hash_function: HashFunction) -> Self {
+        self.hash_function = hash_function;
+        self
+    }
+
+    /// Verifies the proof with the Merkle channel `MC`, which must be the one it was committed
+    /// with.
+    pub fn verify<MC: EnvelopeChannel<H = H>>(self) -> Result<(), Sha256VerifierError>
+    where
+        SimdBackend: BackendForChannel<MC>,
+    {
+        if self.channel != MC::CHANNEL {
+            return Err(Sha256VerifierError::ChannelMismatch {
+                expected: MC::CHANNEL,
+                found: self.channel,
+            });
+        }
+        verify_with::<MC>(
+            self.hash_function,
+            self.proof,
+            self.log_size,
+            self.config,
+            &self.claimed_sum,
+            &self.claims,
+        )
+    }
+}
+
+impl<H: MerkleHasher> Sha256Proof<H>
+where
+    Self: Serialize + for<'de> Deserialize<'de>,
+{
+    /// Compact binary encoding.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProofFormatError> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofFormatError> {
+        check_version(bincode::deserialize::<Header>(bytes)?.version)?;
+        Ok(bincode::deserialize(bytes)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, ProofFormatError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ProofFormatError> {
+        check_version(serde_json::from_str::<Header>(json)?.version)?;
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+fn check_version(version: u32) -> Result<(), ProofFormatError> {
+    if version != PROOF_VERSION {
+        return Err(ProofFormatError::UnsupportedVersion(version));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::core::vcs::blake3_merkle::Blake3MerkleHasher;
+
+    use super::*;
+    use crate::{
+        message::{gen_blocks, gen_blocks_for},
+        prove_sha224, prove_sha256,
+    };
+
+    fn prove(messages: &[Vec<u8>]) -> Sha256Proof {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let (proof, claimed_sum) =
+            prove_sha256::<Blake2sMerkleChannel>(&gen_blocks(messages), log_size, config).unwrap();
+        let claims = messages
+            .iter()
+            .map(|message| HashClaim::new(message.clone()))
+            .collect();
+        Sha256Proof::new::<Blake2sMerkleChannel>(proof, log_size, config, claimed_sum, claims)
+    }
+
+    #[test_log::test]
+    fn test_bytes_round_trip() {
+        let proof = prove(&[b"abc".to_vec(), vec![0xab; 200]]);
+        let bytes = proof.to_bytes().unwrap();
+        let decoded = Sha256Proof::<Blake2sMerkleHasher>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+        decoded.verify::<Blake2sMerkleChannel>().unwrap();
+    }
+
+    #[test_log::test]
+    fn test_json_round_trip() {
+        let proof = prove(&[b"abc".to_vec(), vec![0xab; 200]]);
+        let json = proof.to_json().unwrap();
+        let decoded = Sha256Proof::<Blake2sMerkleHasher>::from_json(&json).unwrap();
+        assert_eq!(decoded.to_json().unwrap(), json);
+        decoded.verify::<Blake2sMerkleChannel>().unwrap();
+    }
+
+    #[test_log::test]
+    fn test_sha224_round_trip() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = [b"abc".to_vec()];
+        let blocks = gen_blocks_for(HashFunction::Sha224, &messages);
+        let (proof, claimed_sum) =
+            prove_sha224::<Blake2sMerkleChannel>(&blocks, log_size, config).unwrap();
+        let claims = vec![HashClaim::with_hash_function(
+            HashFunction::Sha224,
+            messages[0].clone(),
+        )];
+        let proof =
+            Sha256Proof::new::<Blake2sMerkleChannel>(proof, log_size, config, claimed_sum, claims)
+                .with_hash_function(HashFunction::Sha224);
+
+        let decoded =
+            Sha256Proof::<Blake2sMerkleHasher>::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.hash_function, HashFunction::Sha224);
+        decoded.verify::<Blake2sMerkleChannel>().unwrap();
+    }
+
+    #[test_log::test]
+    fn test_blake3_round_trip() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = [b"abc".to_vec(), vec![0xab; 200]];
+        let (proof, claimed_sum) =
+            prove_sha256::<Blake3MerkleChannel>(&gen_blocks(&messages), log_size, config).unwrap();
+        let claims = messages.iter().cloned().map(HashClaim::new).collect();
+        let proof =
+            Sha256Proof::new::<Blake3MerkleChannel>(proof, log_size, config, claimed_sum, claims);
+        let bytes = proof.to_bytes().unwrap();
+
+        // Blake3 hashes decode as Blake2s ones, the recorded channel tells them apart.
+        let decoded = Sha256Proof::<Blake2sMerkleHasher>::from_bytes(&bytes).unwrap();
+        assert!(matches!(
+            decoded.verify::<Blake2sMerkleChannel>(),
+            Err(Sha256VerifierError::ChannelMismatch {
+                expected: ProofChannel::Blake2s,
+                found: ProofChannel::Blake3,
+            })
+        ));
+
+        let decoded = Sha256Proof::<Blake3MerkleHasher>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.channel, ProofChannel::Blake3);
+        decoded.verify::<Blake3MerkleChannel>().unwrap();
+    }
+
+    #[test_log::test]
+    fn test_unsupported_version() {
+        let mut proof = prove(&[b"abc".to_vec()]);
+        proof.version = PROOF_VERSION + 1;
+        assert!(matches!(
+            Sha256Proof::<Blake2sMerkleHasher>::from_bytes(&proof.to_bytes().unwrap()),
+            Err(ProofFormatError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            Sha256Proof::<Blake2sMerkleHasher>::from_json(&proof.to_json().unwrap()),
+            Err(ProofFormatError::UnsupportedVersion(_))
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+//! Prover side of the SHA-256 AIR.
+//!
+//! The preprocessed trace, the twiddles and the commitment of the preprocessed tree only depend
+//! on the log size and the commitment scheme config. A [`Sha256Prover`] computes them once and
+//! shares them across all the proofs it generates.
+
+use std::path::Path;
+
+use stwo::{
+    core::{channel::MerkleChannel, pcs::PcsConfig, proof::StarkProof, vcs::MerkleHasher},
+    prover::backend::{
+        simd::{m31::LOG_N_LANES, SimdBackend},
+        BackendForChannel,
+    },
+};
+use utils::stwo::air::Prover;
+
+use crate::{
+    components::{ClaimedSum, Sha256Air},
+    config,
+    message::Block,
+    preprocessed::PreProcessedTrace,
+    sha256::HashFunction,
+    Sha256ProverError, MAX_LOG_SIZE,
+};
+
+/// Proves batches of SHA-256 blocks for a fixed log size and config.
+pub struct Sha256Prover<MC: MerkleChannel>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    prover: Prover<MC>,
+}
+
+impl<MC: MerkleChannel> Sha256Prover<MC>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    pub fn new(log_size: u32, config: PcsConfig) -> Result<Self, Sha256ProverError> {
+        check_params(log_size, &config)?;
+        Ok(Self::from_preprocessed_trace(
+            log_size,
+            config,
+            PreProcessedTrace::new(log_size),
+        ))
+    }
+
+    /// Same as [`Self::new`], with the preprocessed tables cached in `dir`, see
+    /// [`PreProcessedTrace::load_or_new`].
+    pub fn with_cache_dir(
+        log_size: u32,
+        config: PcsConfig,
+        dir: &Path,
+    ) -> Result<Self, Sha256ProverError> {
+        check_params(log_size, &config)?;
+        Ok(Self::from_preprocessed_trace(
+            log_size,
+            config,
+            PreProcessedTrace::load_or_new(log_size, dir)?,
+        ))
+    }
+
+    fn from_preprocessed_trace(
+        log_size: u32,
+        config: PcsConfig,
+        preprocessed_trace: PreProcessedTrace,
+    ) -> Self {
+        Self {
+            prover: Prover::new(
+                log_size,
+                config,
+                preprocessed_trace.trace,
+                preprocessed_trace.ids,
+            ),
+        }
+    }
+
+    pub const fn log_size(&self) -> u32 {
+        self.prover.log_size()
+    }
+
+    pub const fn config(&self) -> PcsConfig {
+        self.prover.config()
+    }
+
+    /// Merkle root of the preprocessed trace, i.e. the first commitment of every proof.
+    pub fn preprocessed_root(&self) -> <MC::H as MerkleHasher>::Hash {
+        self.prover.preprocessed_root()
+    }
+
+    /// See [`crate::prove_sha256`].
+    pub fn prove(
+        &self,
+        blocks: &[Block],
+    ) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha256ProverError> {
+        self.prove_with(HashFunction::Sha256, blocks)
+    }
+
+    /// Same as [`Self::prove`] for any hash function built on the SHA-256 compression, see
+    /// [`crate::prove_sha224`]. The blocks must start from its initial hash value.
+    pub fn prove_with(
+        &self,
+        hash_function: HashFunction,
+        blocks: &[Block],
+    ) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha256ProverError> {
+        let log_size = self.log_size();
+        if blocks.len() > 1 << log_size {
+            return Err(Sha256ProverError::TooManyBlocks {
+                n_blocks: blocks.len(),
+                log_size,
+            });
+        }
+        Ok(self.prover.prove(&Sha256Air { hash_function }, blocks)?)
+    }
+}
+
+fn check_params(log_size: u32, config: &PcsConfig) -> Result<(), Sha256ProverError> {
+    if !(LOG_N_LANES..=MAX_LOG_SIZE).contains(&log_size) {
+        return Err(Sha256ProverError::InvalidLogSize(log_size));
+    }
+    if !config::is_supported(log_size, config) {
+        return Err(Sha256ProverError::UnsupportedConfig {
+            log_size,
+            config: *config,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::core::vcs::blake2_merkle::Blake2sMerkleChannel;
+
+    use super::*;
+    use crate::{message::gen_blocks, public::HashClaim, Sha256Verifier};
+
+    #[test_log::test]
+    fn test_prover_reuse() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let prover = Sha256Prover::<Blake2sMerkleChannel>::new(log_size, config).unwrap();
+        let verifier = Sha256Verifier::<Blake2sMerkleChannel>::new(log_size, config).unwrap();
+        assert_eq!(prover.preprocessed_root(), verifier.preprocessed_root());
+
+        for messages in [
+            vec![b"abc".to_vec()],
+            vec![vec![0xab; 200], b"def".to_vec()],
+        ] {
+            let (proof, claimed_sum) = prover.prove(&gen_blocks(&messages)).unwrap();
+            assert_eq!(proof.commitments[0], prover.preprocessed_root());
+            let claims = messages.into_iter().map(HashClaim::new).collect::<Vec<_>>();
+            verifier.verify(proof, &claimed_sum, &claims).unwrap();
+        }
+    }
+
+    #[test_log::test]
+    fn test_prover_with_cache_dir() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let dir = std::env::temp_dir().join(format!("sha256-prover-{}", std::process::id()));
+        let root = Sha256Prover::<Blake2sMerkleChannel>::new(log_size, config)
+            .unwrap()
+            .preprocessed_root();
+
+        // The first prover writes the cache, the second one reads it.
+        for _ in 0..2 {
+            let prover =
+                Sha256Prover::<Blake2sMerkleChannel>::with_cache_dir(log_size, config, &dir)
+                    .unwrap();
+            assert_eq!(prover.preprocessed_root(), root);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
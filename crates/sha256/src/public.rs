@@ -0,0 +1,353 @@
+//! Public inputs and outputs of the proof.
+//!
+//! The compression component emits four kinds of claims, consumed by the verifier:
+//! - an input claim for each half block, made of its message words,
+//! - an output claim for each message, made of its digest,
+//! - a link claim for each digest routed into a later message, see [`crate::message::Part`],
+//! - a copy claim for each half copied into a later message.
+//!
+//! Hash chains claim even less: the digests chained from a message to the next one, and the
+//! constant padding of the chained messages, are constrained inside the AIR, so only the seed and
+//! the final digest are claimed, see [`chain_claims`].
+//!
+//! The verifier rebuilds the same claims from the public messages and digests, so the logup sum
+//! only balances if the proof covers exactly these messages and digests. Intermediate chaining
+//! values are linked inside the AIR and never claimed, and neither are the digests hashed again,
+//! e.g. the inner digest of SHA-256d or the nodes of a Merkle tree: such a digest is neither the
+//! output of its message nor an input of the message it is part of. Only where it goes is public.
+//! Unclaimed halves are not claimed either, they are only bound by the digest of their message.
+//! This does not make them secret: the proof is not zero-knowledge, see [`Part::Unclaimed`].
+//!
+//! Only the digest words of the output chaining value are claimed, e.g. 7 words for SHA-224.
+
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use stwo::core::{
+    channel::Channel,
+    fields::{m31::M31, qm31::SecureField, FieldExpOps},
+};
+use stwo_constraint_framework::Relation;
+
+use crate::{
+    components::STATE_SIZE,
+    message::{
+        gen_blocks_for, hash_parts, linked_positions, message_links, pad_message,
+        unclaimed_positions, Block, Part,
+    },
+    relations::Relations,
+    sha256::{HashFunction, CHUNK_SIZE},
+};
+
+/// [message_id, position, half block words], as 16-bit limbs. The position of a half block is
+/// twice its block index, plus one for the right half.
+pub const INPUT_CLAIM_SIZE: usize = 2 + CHUNK_SIZE / 2;
+
+/// [message_id, digest], as 16-bit limbs. The limbs past the digest are zero.
+pub const OUTPUT_CLAIM_SIZE: usize = 1 + STATE_SIZE;
+
+/// [message_id, linked message_id, position], see [`crate::message::Link`].
+pub const LINK_CLAIM_SIZE: usize = 3;
+
+/// [message_id, position, linked message_id, linked position], see [`crate::message::Part::Copy`].
+pub const COPY_CLAIM_SIZE: usize = 4;
+
+/// Claims of a proof, see the module documentation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Claims {
+    pub inputs: Vec<[u32; INPUT_CLAIM_SIZE]>,
+    pub outputs: Vec<[u32; OUTPUT_CLAIM_SIZE]>,
+    pub links: Vec<[u32; LINK_CLAIM_SIZE]>,
+    pub copies: Vec<[u32; COPY_CLAIM_SIZE]>,
+}
+
+/// A message together with its claimed digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashClaim {
+    pub message: Vec<u8>,
+    pub digest: Vec<u8>,
+}
+
+impl HashClaim {
+    /// Claims the actual SHA-256 digest of `message`.
+    pub fn new(message: impl Into<Vec<u8>>) -> Self {
+        Self::with_hash_function(HashFunction::Sha256, message)
+    }
+
+    /// Claims the actual digest of `message` for `hash_function`.
+    pub fn with_hash_function(hash_function: HashFunction, message: impl Into<Vec<u8>>) -> Self {
+        let message = message.into();
+        let blocks = gen_blocks_for(hash_function, &[&message]);
+        let digest = hash_function.digest(blocks.last().unwrap().h_out());
+        Self { message, digest }
+    }
+}
+
+/// Splits 32-bit words into 16-bit limbs, low limb first.
+fn to_limbs(words: &[u32]) -> impl Iterator<Item = u32> + '_ {
+    words.iter().flat_map(|word| [word & 0xffff, word >> 16])
+}
+
+fn input_claim(message_id: u32, position: u32, words: &[u32]) -> [u32; INPUT_CLAIM_SIZE] {
+    [message_id, position]
+        .into_iter()
+        .chain(to_limbs(words))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+fn output_claim(message_id: u32, digest: &[u32]) -> [u32; OUTPUT_CLAIM_SIZE] {
+    [message_id]
+        .into_iter()
+        .chain(to_limbs(digest))
+        .chain(std::iter::repeat(0))
+        .take(OUTPUT_CLAIM_SIZE)
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+/// Claims emitted by the compression component for the given blocks.
+pub fn block_claims(hash_function: HashFunction, blocks: &[Block]) -> Claims {
+    let mut claims = Claims::default();
+    for block in blocks {
+        let halves = [
+            (
+                block.is_linked_left || block.is_unclaimed_left || block.is_chained_in,
+                block.copy_left,
+            ),
+            (
+                block.is_linked_right || block.is_unclaimed_right || block.is_chained_in,
+                block.copy_right,
+            ),
+        ];
+        for (half, (is_hidden, copy)) in halves.into_iter().enumerate() {
+            let position = 2 * block.index + half as u32;
+            if !is_hidden {
+                let words = &block.words[8 * half..8 * half + 8];
+                claims
+                    .inputs
+                    .push(input_claim(block.message_id, position, words));
+            }
+            if let Some(link) = copy {
+                claims
+                    .copies
+                    .push([block.message_id, position, link.message_id, link.position]);
+            }
+        }
+        if !block.is_last || block.is_chained_out {
+            continue;
+        }
+        match block.link {
+            Some(link) => claims
+                .links
+                .push([block.message_id, link.message_id, link.position]),
+            None => claims.outputs.push(output_claim(
+                block.message_id,
+                &block.h_out()[..hash_function.digest_words()],
+            )),
+        }
+    }
+    claims
+}
+
+/// Claims expected by the verifier, which only pads the messages: the claimed digest, which must
+/// be `4 * hash_function.digest_words()` bytes long, is the output of the last hash of each
+/// message. See [`crate::message::gen_blocks_for`] for the message ids.
+pub fn hash_claims(hash_function: HashFunction, claims: &[HashClaim]) -> Claims {
+    let messages = claims
+        .iter()
+        .map(|claim| &claim.message)
+        .collect::<Vec<_>>();
+    let digests = claims
+        .iter()
+        .map(|claim| claim.digest.as_slice())
+        .collect::<Vec<_>>();
+    linked_claims(&hash_parts(hash_function, &messages), &digests)
+}
+
+/// Claims expected by the verifier for messages built from public bytes, linked digests, unclaimed
+/// and copied halves, see [`crate::message::gen_linked_blocks`]. `digests` are the digests of the
+/// messages which are not linked, in order.
+///
+/// Panics if the parts are invalid, see [`crate::message::message_links`], or if the number of
+/// digests does not match.
+pub fn linked_claims<D: AsRef<[u8]>>(messages: &[Vec<Part>], digests: &[D]) -> Claims {
+    let links = message_links(messages);
+    let mut claims = Claims::default();
+    for (message_id, parts) in messages.iter().enumerate() {
+        claims
+            .inputs
+            .extend(message_input_claims(message_id as u32, parts));
+    }
+    let unlinked = (0..messages.len()).filter(|&message_id| links.digests[message_id].is_none());
+    assert_eq!(
+        unlinked.clone().count(),
+        digests.len(),
+        "Invalid number of digests"
+    );
+    for (message_id, digest) in unlinked.zip(digests) {
+        claims
+            .outputs
+            .push(digest_claim(message_id as u32, digest.as_ref()));
+    }
+    claims.links = links
+        .digests
+        .iter()
+        .enumerate()
+        .filter_map(|(message_id, link)| {
+            link.map(|link| [message_id as u32, link.message_id, link.position])
+        })
+        .collect();
+    claims.copies = links
+        .copies
+        .iter()
+        .map(|(&(message_id, position), link)| {
+            [message_id, position, link.message_id, link.position]
+        })
+        .collect();
+    claims
+}
+
+/// Claims expected by the verifier for a hash chain of `n_iterations` hashes starting from `seed`
+/// and ending with `end`, see [`crate::hash_chain`]: the seed is message 0, and the chained
+/// messages in between are neither claimed nor linked publicly.
+///
+/// Panics if `n_iterations` is zero.
+pub fn chain_claims(seed: &[u8], n_iterations: usize, end: &[u8; 32]) -> Claims {
+    assert!(n_iterations > 0, "Empty hash chain");
+    Claims {
+        inputs: message_input_claims(0, &[Part::Bytes(seed.to_vec())]),
+        outputs: vec![digest_claim(n_iterations as u32 - 1, end)],
+        ..Default::default()
+    }
+}
+
+/// Input claims of the public halves of a message.
+fn message_input_claims(message_id: u32, parts: &[Part]) -> Vec<[u32; INPUT_CLAIM_SIZE]> {
+    // Only the public bytes are claimed, the other positions are skipped
+    let message = parts
+        .iter()
+        .flat_map(|part| match part {
+            Part::Bytes(bytes) => bytes.clone(),
+            _ => vec![0; part.len()],
+        })
+        .collect::<Vec<_>>();
+    let hidden = [linked_positions(parts), unclaimed_positions(parts)].concat();
+    let mut inputs = Vec::new();
+    for (index, words) in pad_message(&message).iter().enumerate() {
+        for (half, words) in words.chunks_exact(8).enumerate() {
+            let position = (2 * index + half) as u32;
+            if !hidden.contains(&position) {
+                inputs.push(input_claim(message_id, position, words));
+            }
+        }
+    }
+    inputs
+}
+
+/// Output claim of a digest given as big-endian bytes.
+fn digest_claim(message_id: u32, digest: &[u8]) -> [u32; OUTPUT_CLAIM_SIZE] {
+    let digest = digest
+        .chunks_exact(4)
+        .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    output_claim(message_id, &digest)
+}
+
+pub fn mix_into(claims: &Claims, channel: &mut impl Channel) {
+    channel.mix_u32s(&[claims.inputs.len() as u32]);
+    for claim in &claims.inputs {
+        channel.mix_u32s(claim);
+    }
+    channel.mix_u32s(&[claims.outputs.len() as u32]);
+    for claim in &claims.outputs {
+        channel.mix_u32s(claim);
+    }
+    channel.mix_u32s(&[claims.links.len() as u32]);
+    for claim in &claims.links {
+        channel.mix_u32s(claim);
+    }
+    channel.mix_u32s(&[claims.copies.len() as u32]);
+    for claim in &claims.copies {
+        channel.mix_u32s(claim);
+    }
+}
+
+/// Logup sum of the verifier consuming the claims, to be added to the components claimed sum.
+pub fn logup_sum(claims: &Claims, relations: &Relations) -> SecureField {
+    let inputs = claims.inputs.iter().map(|claim| {
+        let denom: SecureField = relations.input_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    let outputs = claims.outputs.iter().map(|claim| {
+        let denom: SecureField = relations.output_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    let links = claims.links.iter().map(|claim| {
+        let denom: SecureField = relations.link_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    let copies = claims.copies.iter().map(|claim| {
+        let denom: SecureField = relations.copy_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    inputs
+        .chain(outputs)
+        .chain(links)
+        .chain(copies)
+        .fold(SecureField::zero(), |acc, frac| acc - frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::gen_blocks;
+
+    #[test]
+    fn test_hash_claims_match_block_claims() {
+        let messages = [b"abc".to_vec(), vec![0x42; 100], vec![]];
+        for hash_function in [
+            HashFunction::Sha256,
+            HashFunction::Sha224,
+            HashFunction::Sha256d,
+        ] {
+            let claims = messages
+                .iter()
+                .map(|message| HashClaim::with_hash_function(hash_function, message.clone()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                hash_claims(hash_function, &claims),
+                block_claims(hash_function, &gen_blocks_for(hash_function, &messages))
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_claims_wrong_digest() {
+        let mut claim = HashClaim::new(b"abc".to_vec());
+        claim.digest[0] ^= 1;
+        assert_ne!(
+            hash_claims(HashFunction::Sha256, &[claim]),
+            block_claims(HashFunction::Sha256, &gen_blocks(&[b"abc".to_vec()]))
+        );
+    }
+
+    #[test]
+    fn test_sha256d_claims() {
+        let header = vec![0x42; 80];
+        let claims = hash_claims(
+            HashFunction::Sha256d,
+            &[HashClaim::with_hash_function(HashFunction::Sha256d, header)],
+        );
+
+        // The header blocks, the padding half of the second hash and the final digest, the inner
+        // digest is not claimed.
+        assert_eq!(claims.inputs.len(), 4 + 1);
+        assert!(claims.inputs[..4].iter().all(|claim| claim[0] == 0));
+        assert_eq!(claims.inputs[4][..2], [1, 1]);
+        assert_eq!(claims.outputs.len(), 1);
+        assert_eq!(claims.outputs[0][0], 1);
+        assert_eq!(claims.links, [[0, 1, 0]]);
+    }
+}
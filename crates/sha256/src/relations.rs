@@ -1,7 +1,8 @@
 use stwo::core::channel::Channel;
 
 use crate::preprocessed::{
-    big_sigma_0, big_sigma_1, ch_left, ch_right, maj, range_check_add, sigma_0, sigma_1,
+    big_sigma_0, big_sigma_1, ch_left, ch_right, maj, range_check_16, range_check_add, sigma_0,
+    sigma_1,
 };
 
 mod w {
@@ -11,6 +12,62 @@ mod w {
     relation!(Relation, W_SIZE);
 }
 
+mod chain {
+    use stwo_constraint_framework::relation;
+
+    use crate::components::STATE_SIZE;
+    // [message_id, block_index, state]
+    relation!(Relation, 2 + STATE_SIZE);
+}
+
+mod link {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::INPUT_CLAIM_SIZE;
+    // [message_id, position, digest or copied half], the same layout as the input claims
+    relation!(Relation, INPUT_CLAIM_SIZE);
+}
+
+mod chained {
+    use stwo_constraint_framework::relation;
+
+    use crate::components::STATE_SIZE;
+    // [message_id, digest of the previous message]
+    relation!(Relation, 1 + STATE_SIZE);
+}
+
+mod input_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::INPUT_CLAIM_SIZE;
+    // [message_id, position, half block words]
+    relation!(Relation, INPUT_CLAIM_SIZE);
+}
+
+mod output_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::OUTPUT_CLAIM_SIZE;
+    // [message_id, digest]
+    relation!(Relation, OUTPUT_CLAIM_SIZE);
+}
+
+mod link_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::LINK_CLAIM_SIZE;
+    // [message_id, linked message_id, position]
+    relation!(Relation, LINK_CLAIM_SIZE);
+}
+
+mod copy_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::COPY_CLAIM_SIZE;
+    // [message_id, position, linked message_id, linked position]
+    relation!(Relation, COPY_CLAIM_SIZE);
+}
+
 #[derive(Clone)]
 pub struct Relations {
     pub sigma_0: sigma_0::Relation,
@@ -21,7 +78,15 @@ pub struct Relations {
     pub ch_right: ch_right::Relation,
     pub maj: maj::Relation,
     pub range_check_add: range_check_add::Relation,
+    pub range_check_16: range_check_16::Relation,
     pub w: w::Relation,
+    pub chain: chain::Relation,
+    pub link: link::Relation,
+    pub chained: chained::Relation,
+    pub input_claim: input_claim::Relation,
+    pub output_claim: output_claim::Relation,
+    pub link_claim: link_claim::Relation,
+    pub copy_claim: copy_claim::Relation,
 }
 
 impl Relations {
@@ -35,7 +100,15 @@ impl Relations {
             ch_right: ch_right::Relation::draw(channel),
             maj: maj::Relation::draw(channel),
             range_check_add: range_check_add::Relation::draw(channel),
+            range_check_16: range_check_16::Relation::draw(channel),
             w: w::Relation::draw(channel),
+            chain: chain::Relation::draw(channel),
+            link: link::Relation::draw(channel),
+            chained: chained::Relation::draw(channel),
+            input_claim: input_claim::Relation::draw(channel),
+            output_claim: output_claim::Relation::draw(channel),
+            link_claim: link_claim::Relation::draw(channel),
+            copy_claim: copy_claim::Relation::draw(channel),
         }
     }
 
@@ -49,7 +122,15 @@ impl Relations {
             ch_right: ch_right::Relation::dummy(),
             maj: maj::Relation::dummy(),
             range_check_add: range_check_add::Relation::dummy(),
+            range_check_16: range_check_16::Relation::dummy(),
             w: w::Relation::dummy(),
+            chain: chain::Relation::dummy(),
+            link: link::Relation::dummy(),
+            chained: chained::Relation::dummy(),
+            input_claim: input_claim::Relation::dummy(),
+            output_claim: output_claim::Relation::dummy(),
+            link_claim: link_claim::Relation::dummy(),
+            copy_claim: copy_claim::Relation::dummy(),
         }
     }
 }
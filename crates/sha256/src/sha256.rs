@@ -1,7 +1,9 @@
 //! SHA-256 functions
 
 use core::simd::Simd;
-use std::simd::u32x16;
+use std::{simd::u32x16, str::FromStr};
+
+use serde::{Deserialize, Serialize};
 
 pub const K: [u32; 64] = [
     1116352408, 1899447441, 3049323471, 3921009573, 961987163, 1508970993, 2453635748, 2870763221,
@@ -18,6 +20,71 @@ pub const H: [u32; 8] = [
     1779033703, 3144134277, 1013904242, 2773480762, 1359893119, 2600822924, 528734635, 1541459225,
 ];
 
+/// Initial hash value of SHA-224.
+pub const H_224: [u32; 8] = [
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
+
+/// Hash functions built on the SHA-256 compression, which only differ by their initial hash value,
+/// the length of their digest and how many times the message is hashed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum HashFunction {
+    #[default]
+    Sha256,
+    Sha224,
+    /// SHA-256 of the SHA-256 digest, e.g. for Bitcoin block headers.
+    Sha256d,
+}
+
+impl HashFunction {
+    pub const fn iv(self) -> [u32; 8] {
+        match self {
+            Self::Sha256 | Self::Sha256d => H,
+            Self::Sha224 => H_224,
+        }
+    }
+
+    /// Number of 32-bit words of the digest, a prefix of the final chaining value.
+    pub const fn digest_words(self) -> usize {
+        match self {
+            Self::Sha256 | Self::Sha256d => 8,
+            Self::Sha224 => 7,
+        }
+    }
+
+    /// Number of chained hashes of each message: every hash after the first one hashes the
+    /// digest of the previous one.
+    pub const fn n_hashes(self) -> usize {
+        match self {
+            Self::Sha256 | Self::Sha224 => 1,
+            Self::Sha256d => 2,
+        }
+    }
+
+    /// Big-endian digest of a final chaining value.
+    pub fn digest(self, hash: [u32; 8]) -> Vec<u8> {
+        hash[..self.digest_words()]
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect()
+    }
+}
+
+impl FromStr for HashFunction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha256" => Ok(Self::Sha256),
+            "sha224" => Ok(Self::Sha224),
+            "sha256d" => Ok(Self::Sha256d),
+            _ => Err(format!(
+                "Unknown hash function {s}, expected one of sha256, sha224, sha256d"
+            )),
+        }
+    }
+}
+
 pub const CHUNK_SIZE: usize = 32; // 16 u32 = 32 u16
 pub const N_SCHEDULING_ROUNDS: usize = 48; // 16..48
 pub const N_COMPRESSION_ROUNDS: usize = 64;
@@ -146,7 +213,10 @@ pub fn process_chunk(chunk: [u32; 16], mut hash: [u32; 8]) -> [u32; 8] {
 
     // Schedule
     for t in 16..64 {
-        w[t] = w[t - 16] + small_sigma_0(w[t - 15]) + w[t - 7] + small_sigma_1(w[t - 2])
+        w[t] = w[t - 16]
+            .wrapping_add(small_sigma_0(w[t - 15]))
+            .wrapping_add(w[t - 7])
+            .wrapping_add(small_sigma_1(w[t - 2]))
     }
 
     // Compression
@@ -159,25 +229,25 @@ pub fn process_chunk(chunk: [u32; 16], mut hash: [u32; 8]) -> [u32; 8] {
     let mut g = hash[6];
     let mut h = hash[7];
     for round in 0..64 {
-        let temp1 = h + big_sigma_1(e) + ch_left(e, f) + ch_right(e, g) + w[round] + K[round];
-        let temp2 = big_sigma_0(a) + maj(a, b, c);
+        let temp1 = h
+            .wrapping_add(big_sigma_1(e))
+            .wrapping_add(ch_left(e, f))
+            .wrapping_add(ch_right(e, g))
+            .wrapping_add(w[round])
+            .wrapping_add(K[round]);
+        let temp2 = big_sigma_0(a).wrapping_add(maj(a, b, c));
         h = g;
         g = f;
         f = e;
-        e = d + temp1;
+        e = d.wrapping_add(temp1);
         d = c;
         c = b;
         b = a;
-        a = temp1 + temp2;
+        a = temp1.wrapping_add(temp2);
+    }
+    for (hash, value) in hash.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *hash = hash.wrapping_add(value);
     }
-    hash[0] += a;
-    hash[1] += b;
-    hash[2] += c;
-    hash[3] += d;
-    hash[4] += e;
-    hash[5] += f;
-    hash[6] += g;
-    hash[7] += h;
     hash
 }
 
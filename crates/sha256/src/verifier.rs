@@ -0,0 +1,264 @@
+//! Verifier side of the SHA-256 AIR.
+//!
+//! Replays the prover's transcript: commitments are mixed into the channel in the same order,
+//! the relations are redrawn and the components are rebuilt before calling stwo's `verify`.
+//!
+//! The preprocessed trace is not sent by the prover: its commitment must match the canonical
+//! root of the SHA-256 tables, see [`preprocessed_root`]. A [`Sha256Verifier`] computes it once
+//! and checks all the proofs of a given log size and config against it.
+
+use stwo::{
+    core::{
+        channel::MerkleChannel, fields::qm31::SecureField, pcs::PcsConfig, proof::StarkProof,
+        vcs::MerkleHasher, verifier::VerificationError,
+    },
+    prover::backend::{
+        simd::{m31::LOG_N_LANES, SimdBackend},
+        BackendForChannel,
+    },
+};
+use thiserror::Error;
+use utils::stwo::air::{self, Verifier, VerifyError};
+
+use crate::{
+    components::{ClaimedSum, Sha256Air},
+    config,
+    preprocessed::{self, PreProcessedTrace},
+    proof::ProofChannel,
+    public::{self, Claims, HashClaim},
+    sha256::HashFunction,
+    MAX_LOG_SIZE,
+};
+
+#[derive(Debug, Error)]
+pub enum Sha256VerifierError {
+    #[error("Invalid log size: {0}")]
+    InvalidLogSize(u32),
+    #[error("Proof committed with the {found:?} channel, verified with {expected:?}")]
+    ChannelMismatch {
+        expected: ProofChannel,
+        found: ProofChannel,
+    },
+    #[error("Unsupported config for log size {log_size}: {config:?}")]
+    UnsupportedConfig { log_size: u32, config: PcsConfig },
+    #[error("Invalid proof: expected 3 commitments, got {0}")]
+    InvalidCommitmentsCount(usize),
+    #[error("Invalid proof: the preprocessed commitment does not match the SHA-256 tables")]
+    InvalidPreprocessedRoot,
+    #[error("Invalid digest length: {0} bytes")]
+    InvalidDigestLength(usize),
+    #[error("Too many hash claims: {0} half blocks do not fit in the trace")]
+    TooManyClaims(usize),
+    #[error("Invalid inclusion path for leaf {leaf_index} of a tree of size {tree_size}")]
+    InvalidInclusionPath { leaf_index: u64, tree_size: u64 },
+    #[error("Empty Merkle tree")]
+    EmptyMerkleTree,
+    #[error("Invalid Merkle leaf length: {0} bytes")]
+    InvalidLeafLength(usize),
+    #[error("Merkle root does not match the leaf of a single-leaf tree")]
+    InvalidMerkleRoot,
+    #[error("Relation summary is not zero: {0}")]
+    InvalidLogupSum(SecureField),
+    #[error(transparent)]
+    Stwo(#[from] VerificationError),
+}
+
+impl From<VerifyError> for Sha256VerifierError {
+    fn from(err: VerifyError) -> Self {
+        match err {
+            VerifyError::InvalidCommitmentsCount(n) => Self::InvalidCommitmentsCount(n),
+            VerifyError::InvalidPreprocessedRoot => Self::InvalidPreprocessedRoot,
+            VerifyError::InvalidLogupSum(sum) => Self::InvalidLogupSum(sum),
+            VerifyError::Stwo(err) => Self::Stwo(err),
+        }
+    }
+}
+
+/// Merkle root of the preprocessed trace for `log_size` and `config`, which must be the first
+/// commitment of every proof.
+///
+/// The root only depends on the Merkle hasher, the log size and the blowup factor. Deriving it
+/// generates and commits to all the tables: use a [`Sha256Verifier`] to check several proofs.
+/// `log_size` and `config` must be supported by the prover, see [`config::is_supported`].
+pub fn preprocessed_root<MC: MerkleChannel>(
+    log_size: u32,
+    config: PcsConfig,
+) -> <MC::H as MerkleHasher>::Hash
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    air::preprocessed_root::<MC>(PreProcessedTrace::new(log_size).trace, log_size, config)
+}
+
+/// Verifies proofs of SHA-256 blocks for a fixed log size and config.
+pub struct Sha256Verifier<MC: MerkleChannel> {
+    verifier: Verifier<MC>,
+}
+
+impl<MC: MerkleChannel> Sha256Verifier<MC>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    pub fn new(log_size: u32, config: PcsConfig) -> Result<Self, Sha256VerifierError> {
+        if !(LOG_N_LANES..=MAX_LOG_SIZE).contains(&log_size) {
+            return Err(Sha256VerifierError::InvalidLogSize(log_size));
+        }
+        if !config::is_supported(log_size, &config) {
+            return Err(Sha256VerifierError::UnsupportedConfig { log_size, config });
+        }
+        let (preprocessed_ids, preprocessed_log_sizes) = preprocessed::columns(log_size);
+        Ok(Self {
+            verifier: Verifier::new(
+                log_size,
+                config,
+                preprocessed_ids,
+                preprocessed_log_sizes,
+                preprocessed_root::<MC>(log_size, config),
+            ),
+        })
+    }
+
+    pub const fn log_size(&self) -> u32 {
+        self.verifier.log_size()
+    }
+
+    pub const fn config(&self) -> PcsConfig {
+        self.verifier.config()
+    }
+
+    /// Merkle root of the preprocessed trace, i.e. the first commitment of every proof.
+    pub fn preprocessed_root(&self) -> <MC::H as MerkleHasher>::Hash {
+        self.verifier.preprocessed_root()
+    }
+
+    /// See [`verify_sha256`].
+    pub fn verify(
+        &self,
+        proof: StarkProof<MC::H>,
+        claimed_sum: &ClaimedSum,
+        hash_claims: &[HashClaim],
+    ) -> Result<(), Sha256VerifierError> {
+        self.verify_with(HashFunction::Sha256, proof, claimed_sum, hash_claims)
+    }
+
+    /// See [`verify_with`].
+    pub fn verify_with(
+        &self,
+        hash_function: HashFunction,
+        proof: StarkProof<MC::H>,
+        claimed_sum: &ClaimedSum,
+        hash_claims: &[HashClaim],
+    ) -> Result<(), Sha256VerifierError> {
+        let digest_size = 4 * hash_function.digest_words();
+        if let Some(claim) = hash_claims
+            .iter()
+            .find(|claim| claim.digest.len() != digest_size)
+        {
+            return Err(Sha256VerifierError::InvalidDigestLength(claim.digest.len()));
+        }
+        let claims = public::hash_claims(hash_function, hash_claims);
+        self.verify_claims(hash_function, proof, claimed_sum, &claims)
+    }
+
+    /// Verifies that `proof` balances the given public claims, see [`public`].
+    pub(crate) fn verify_claims(
+        &self,
+        hash_function: HashFunction,
+        proof: StarkProof<MC::H>,
+        claimed_sum: &ClaimedSum,
+        claims: &Claims,
+    ) -> Result<(), Sha256VerifierError> {
+        // Each block claims at most its two halves
+        if claims.inputs.len() > 2 << self.log_size() {
+            return Err(Sha256VerifierError::TooManyClaims(claims.inputs.len()));
+        }
+        let air = Sha256Air { hash_function };
+        Ok(self.verifier.verify(&air, proof, claimed_sum, claims)?)
+    }
+}
+
+/// Verifies that `proof` attests the SHA-256 digest of each claimed message.
+pub fn verify_sha256<MC: MerkleChannel>(
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    hash_claims: &[HashClaim],
+) -> Result<(), Sha256VerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    verify_with::<MC>(
+        HashFunction::Sha256,
+        proof,
+        log_size,
+        config,
+        claimed_sum,
+        hash_claims,
+    )
+}
+
+/// Verifies that `proof` attests the SHA-224 digest of each claimed message.
+pub fn verify_sha224<MC: MerkleChannel>(
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    hash_claims: &[HashClaim],
+) -> Result<(), Sha256VerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    verify_with::<MC>(
+        HashFunction::Sha224,
+        proof,
+        log_size,
+        config,
+        claimed_sum,
+        hash_claims,
+    )
+}
+
+/// Verifies that `proof` attests the SHA-256d digest of each claimed message, i.e. the SHA-256
+/// of its SHA-256 digest. The inner digests are not part of the claims.
+pub fn verify_sha256d<MC: MerkleChannel>(
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    hash_claims: &[HashClaim],
+) -> Result<(), Sha256VerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    verify_with::<MC>(
+        HashFunction::Sha256d,
+        proof,
+        log_size,
+        config,
+        claimed_sum,
+        hash_claims,
+    )
+}
+
+/// Verifies that `proof` attests the `hash_function` digest of each claimed message.
+///
+/// Derives the preprocessed root on each call, see [`Sha256Verifier`] to verify several proofs.
+pub fn verify_with<MC: MerkleChannel>(
+    hash_function: HashFunction,
+    proof: StarkProof<MC::H>,
+    log_size: u32,
+    config: PcsConfig,
+    claimed_sum: &ClaimedSum,
+    hash_claims: &[HashClaim],
+) -> Result<(), Sha256VerifierError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    Sha256Verifier::<MC>::new(log_size, config)?.verify_with(
+        hash_function,
+        proof,
+        claimed_sum,
+        hash_claims,
+    )
+}
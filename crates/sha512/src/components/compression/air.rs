@@ -0,0 +1,251 @@
+use num_traits::{One, Zero};
+use stwo::core::fields::m31::M31;
+use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval, RelationEntry};
+
+use crate::{
+    components::{
+        compression::columns::ChainColumnsOwned,
+        lookups::{Lookups, Table},
+        word::air::{constant, eval_add, eval_sigma, eval_spread_word, limbs, SpreadWord, Word},
+        STATE_SIZE, W_SIZE,
+    },
+    partitions::{BIG_SIGMA_0, BIG_SIGMA_1},
+    relations::Relations,
+    sha512::{HashFunction, CHUNK_SIZE, K, N_COMPRESSION_ROUNDS},
+};
+
+pub type Component = FrameworkComponent<Eval>;
+
+fn eval_compression_constraints<E: EvalAtRow>(
+    eval: &mut E,
+    relations: &Relations,
+    hash_function: HashFunction,
+) {
+    let ChainColumnsOwned {
+        message_id,
+        block_index,
+        is_first,
+        is_last,
+        is_active,
+    } = ChainColumnsOwned::<<E as EvalAtRow>::F>::from_eval(eval);
+    let w: [E::F; W_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
+    let mut lookups = Lookups::new();
+    let h_in: [SpreadWord<E::F>; 8] = std::array::from_fn(|_| eval_spread_word(eval, &mut lookups));
+    let h_in_limbs: [Word<E::F>; 8] = h_in.each_ref().map(|word| limbs::<E>(&word.bytes));
+
+    eval.add_constraint(is_first.clone() * (is_first.clone() - E::F::one()));
+    eval.add_constraint(is_last.clone() * (is_last.clone() - E::F::one()));
+    eval.add_constraint(is_active.clone() * (is_active.clone() - E::F::one()));
+    // Padding blocks are standalone, so that they emit no output claim
+    eval.add_constraint((E::F::one() - is_active.clone()) * (E::F::one() - is_last.clone()));
+    // The first block of a message has index 0 and starts from the initial hash value
+    eval.add_constraint(is_first.clone() * block_index.clone());
+    for (h, iv) in h_in_limbs.iter().zip(hash_function.iv()) {
+        for (limb, iv_limb) in h.iter().zip(constant::<E>(iv)) {
+            eval.add_constraint(is_first.clone() * (limb.clone() - iv_limb));
+        }
+    }
+
+    // The values of `a` and `e` from round -3 on: `b`, `c` and `d` are the earlier values of
+    // `a`, and `f`, `g` and `h` those of `e`
+    let mut a = vec![
+        h_in[3].clone(),
+        h_in[2].clone(),
+        h_in[1].clone(),
+        h_in[0].clone(),
+    ];
+    let mut e = vec![
+        h_in[7].clone(),
+        h_in[6].clone(),
+        h_in[5].clone(),
+        h_in[4].clone(),
+    ];
+    let spread_ones = E::F::from(M31::from(0x5555));
+    for (t, k) in K.iter().enumerate() {
+        let [d, c, b, a_t] = [&a[t], &a[t + 1], &a[t + 2], &a[t + 3]];
+        let [h, g, f, e_t] = [&e[t], &e[t + 1], &e[t + 2], &e[t + 3]];
+
+        let sigma_1 = eval_sigma(eval, &mut lookups, BIG_SIGMA_1, &limbs::<E>(&e_t.bytes));
+        let sigma_0 = eval_sigma(eval, &mut lookups, BIG_SIGMA_0, &limbs::<E>(&a_t.bytes));
+
+        // Ch = (e & f) + (!e & g) and Maj, byte by byte from the spread forms
+        let ch_left: [E::F; 8] = std::array::from_fn(|_| eval.next_trace_mask());
+        let ch_right: [E::F; 8] = std::array::from_fn(|_| eval.next_trace_mask());
+        let maj: [E::F; 8] = std::array::from_fn(|_| eval.next_trace_mask());
+        for i in 0..8 {
+            let sum = e_t.spreads[i].clone() + f.spreads[i].clone();
+            lookups.push(Table::Odd, [sum, ch_left[i].clone()]);
+        }
+        for i in 0..8 {
+            let sum = spread_ones.clone() - e_t.spreads[i].clone() + g.spreads[i].clone();
+            lookups.push(Table::Odd, [sum, ch_right[i].clone()]);
+        }
+        for i in 0..8 {
+            let sum = a_t.spreads[i].clone() + b.spreads[i].clone() + c.spreads[i].clone();
+            lookups.push(Table::Odd, [sum, maj[i].clone()]);
+        }
+        let [ch_left, ch_right, maj] = [ch_left, ch_right, maj].map(|bytes| limbs::<E>(&bytes));
+
+        let k = constant::<E>(*k);
+        let w_t: Word<E::F> = std::array::from_fn(|l| w[4 * t + l].clone());
+        let h = limbs::<E>(&h.bytes);
+        let temp_1 = [&h, &sigma_1, &ch_left, &ch_right, &k, &w_t];
+
+        // e = d + temp1, a = temp1 + Σ0(a) + Maj
+        let new_e = eval_spread_word(eval, &mut lookups);
+        let d = limbs::<E>(&d.bytes);
+        let e_terms = [&d].into_iter().chain(temp_1).collect::<Vec<_>>();
+        eval_add(eval, &mut lookups, &e_terms, &limbs::<E>(&new_e.bytes));
+        let new_a = eval_spread_word(eval, &mut lookups);
+        let a_terms = temp_1
+            .into_iter()
+            .chain([&sigma_0, &maj])
+            .collect::<Vec<_>>();
+        eval_add(eval, &mut lookups, &a_terms, &limbs::<E>(&new_a.bytes));
+
+        a.push(new_a);
+        e.push(new_e);
+    }
+
+    // Feed-forward: h_out = h_in + [a, b, c, d, e, f, g, h]
+    let n = N_COMPRESSION_ROUNDS;
+    let working = [
+        &a[n + 3],
+        &a[n + 2],
+        &a[n + 1],
+        &a[n],
+        &e[n + 3],
+        &e[n + 2],
+        &e[n + 1],
+        &e[n],
+    ];
+    let mut h_out = Vec::with_capacity(STATE_SIZE);
+    for (h, v) in h_in_limbs.iter().zip(working) {
+        let h_out_word: Word<E::F> = std::array::from_fn(|_| eval.next_trace_mask());
+        eval_add(eval, &mut lookups, &[h, &limbs::<E>(&v.bytes)], &h_out_word);
+        h_out.extend(h_out_word);
+    }
+
+    lookups.add_to_eval(eval, relations);
+
+    // Consume the input chaining value, unless this is the first block of the message
+    let chain_in = [message_id.clone(), block_index.clone()]
+        .into_iter()
+        .chain(h_in_limbs.into_iter().flatten())
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.chain,
+        E::EF::from(is_first - E::F::one()),
+        &chain_in,
+    ));
+
+    // Emit the output chaining value, unless this is the last block of the message
+    let chain_out = [message_id.clone(), block_index.clone() + E::F::one()]
+        .into_iter()
+        .chain(h_out.clone())
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.chain,
+        E::EF::from(E::F::one() - is_last.clone()),
+        &chain_out,
+    ));
+
+    // Emit the public claims of the block, consumed by the verifier: its message words, and the
+    // digest of its message if it is the last block, zero padded for truncated digests
+    let input_claim = [message_id.clone(), block_index]
+        .into_iter()
+        .chain(w[..4 * CHUNK_SIZE].iter().cloned())
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.input_claim,
+        E::EF::from(is_active.clone()),
+        &input_claim,
+    ));
+    let digest_limbs = 4 * hash_function.digest_words();
+    let output_claim = [message_id]
+        .into_iter()
+        .chain(h_out.into_iter().take(digest_limbs))
+        .chain((digest_limbs..STATE_SIZE).map(|_| E::F::zero()))
+        .collect::<Vec<_>>();
+    eval.add_to_relation(RelationEntry::new(
+        &relations.output_claim,
+        E::EF::from(is_last + is_active - E::F::one()),
+        &output_claim,
+    ));
+
+    // Consume the schedule emitted by the scheduling component
+    eval.add_to_relation(RelationEntry::new(&relations.w, -E::EF::one(), &w));
+
+    eval.finalize_logup_in_pairs();
+}
+
+#[derive(Clone)]
+pub struct Eval {
+    pub log_size: u32,
+    pub relations: Relations,
+    pub hash_function: HashFunction,
+}
+impl FrameworkEval for Eval {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        self.log_size() + 1
+    }
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        eval_compression_constraints(&mut eval, &self.relations, self.hash_function);
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use stwo::core::{pcs::TreeVec, poly::circle::CanonicCoset};
+    use stwo_constraint_framework::assert_constraints_on_polys;
+
+    use super::*;
+    use crate::{
+        components::{
+            compression::witness::{gen_interaction_trace, gen_trace},
+            scheduling::witness::gen_trace as gen_scheduling_trace,
+        },
+        message::gen_blocks_for,
+    };
+
+    fn assert_compression_constraints(log_size: u32, hash_function: HashFunction) {
+        // Multi-block messages and padding rows.
+        let messages = [vec![0x42; 200], b"abc".to_vec(), vec![]];
+        let blocks = gen_blocks_for(hash_function, &messages);
+        let (_, scheduling_lookup_data) = gen_scheduling_trace(log_size, &blocks, hash_function);
+        let (trace, lookup_data) =
+            gen_trace(log_size, &blocks, &scheduling_lookup_data.w, hash_function);
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) =
+            gen_interaction_trace(&lookup_data, &relations, hash_function);
+
+        let traces = TreeVec::new(vec![vec![], trace, interaction_trace]);
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(log_size),
+            |mut eval| {
+                eval_compression_constraints(&mut eval, &relations, hash_function);
+            },
+            claimed_sum,
+        );
+    }
+
+    #[test]
+    fn test_compression_constraints() {
+        assert_compression_constraints(4, HashFunction::Sha512);
+    }
+
+    #[test]
+    fn test_compression_constraints_sha384() {
+        assert_compression_constraints(4, HashFunction::Sha384);
+    }
+}
@@ -0,0 +1,20 @@
+use utils::trace_columns;
+
+trace_columns!(
+    ChainColumns,
+    message_id,
+    block_index,
+    is_first,
+    is_last,
+    is_active
+);
+
+trace_columns!(
+    ChainInteractionColumns,
+    message_id,
+    block_index,
+    next_block_index,
+    is_first,
+    is_last,
+    is_active
+);
@@ -0,0 +1,24 @@
+//! The compression component is responsible for proving the SHA-512 compression function.
+//!
+//! This is, 80 rounds updating the working variables from the message schedule, followed by the
+//! feed-forward of the input chaining value. Only the new values of `a` and `e` are committed at
+//! each round, the other working variables being their earlier values.
+
+pub mod air;
+pub mod columns;
+pub mod witness;
+
+use crate::{
+    components::word::{sigma_size, CARRIES_SIZE, SPREAD_WORD_SIZE},
+    partitions::{BIG_SIGMA_0, BIG_SIGMA_1},
+};
+
+/// Columns of a compression round: Σ1(e), Σ0(a), the bytes of Ch and Maj, then the new values of
+/// `e` and `a` with the carries of their additions.
+pub const ROUND_SIZE: usize = sigma_size(BIG_SIGMA_1)
+    + sigma_size(BIG_SIGMA_0)
+    + 3 * 8
+    + 2 * (SPREAD_WORD_SIZE + CARRIES_SIZE);
+
+/// Columns of the feed-forward of a word: the limbs of the output word and the carries.
+pub const FEED_FORWARD_SIZE: usize = 4 + CARRIES_SIZE;
@@ -0,0 +1,436 @@
+use std::simd::u32x16;
+
+use itertools::Itertools;
+use num_traits::One;
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        poly::circle::CanonicCoset,
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            column::BaseColumn,
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, simd::for_each_row_chunk};
+
+use crate::{
+    components::{
+        compression::{
+            columns::{ChainColumns, ChainInteractionColumns},
+            FEED_FORWARD_SIZE, ROUND_SIZE,
+        },
+        lookups::{n_lookup_columns, FractionWriter, LookupColumns, LookupCounts, Lookups, Table},
+        word::{
+            witness::{
+                add_limbs, constant, from_bytes, write_carries, write_sigma, write_spread_word,
+                RowWriter,
+            },
+            SPREAD_WORD_SIZE,
+        },
+        STATE_SIZE, W_SIZE,
+    },
+    message::{block_column, Block},
+    partitions::{odd_bits_u32x16, BIG_SIGMA_0, BIG_SIGMA_1},
+    relations::Relations,
+    sha512::{HashFunction, CHUNK_SIZE, K, N_COMPRESSION_ROUNDS},
+};
+
+// Trace layout: chain columns, message schedule, input chaining value, rounds and feed-forward.
+const W_OFFSET: usize = ChainColumns::SIZE;
+const H_IN_OFFSET: usize = W_OFFSET + W_SIZE;
+const ROUNDS_OFFSET: usize = H_IN_OFFSET + 8 * SPREAD_WORD_SIZE;
+const FEED_FORWARD_OFFSET: usize = ROUNDS_OFFSET + ROUND_SIZE * N_COMPRESSION_ROUNDS;
+const N_COLUMNS: usize = FEED_FORWARD_OFFSET + 8 * FEED_FORWARD_SIZE;
+
+// Lookup data layout: chain columns, input and output chaining values, message schedule, then
+// the lookups into the tables.
+const H_IN_INTERACTION_OFFSET: usize = ChainInteractionColumns::SIZE;
+const H_OUT_INTERACTION_OFFSET: usize = H_IN_INTERACTION_OFFSET + STATE_SIZE;
+const W_INTERACTION_OFFSET: usize = H_OUT_INTERACTION_OFFSET + STATE_SIZE;
+const LOOKUPS_INTERACTION_OFFSET: usize = W_INTERACTION_OFFSET + W_SIZE;
+
+/// Lookups of the compression, by table: per round, two sigma evaluations with 8 even bits
+/// lookups each, the spread bytes of the new `a` and `e`, 24 odd bits lookups for Ch and Maj and
+/// two additions; then the spread bytes of the input chaining value and the feed-forward
+/// additions.
+pub const N_LOOKUPS: LookupCounts = [
+    0,
+    0,
+    4 * N_COMPRESSION_ROUNDS,
+    4 * N_COMPRESSION_ROUNDS,
+    16 * N_COMPRESSION_ROUNDS + 64,
+    16 * N_COMPRESSION_ROUNDS,
+    24 * N_COMPRESSION_ROUNDS,
+    8 * N_COMPRESSION_ROUNDS + 32,
+];
+
+pub struct LookupData {
+    /// Chain columns, input and output chaining values and message schedule.
+    pub columns: Vec<Vec<u32x16>>,
+    pub lookups: LookupColumns,
+}
+
+/// Generates the compression of `blocks`, whose message schedules are the limbs `w` generated by
+/// the scheduling component.
+#[allow(clippy::type_complexity)]
+pub fn gen_trace(
+    log_size: u32,
+    blocks: &[Block],
+    w: &[Vec<u32x16>],
+    hash_function: HashFunction,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    LookupData,
+) {
+    assert!(log_size >= LOG_N_LANES);
+    assert!(blocks.len() <= 1 << log_size);
+    let simd_size = 1 << (log_size - LOG_N_LANES);
+    assert_eq!(w.len(), W_SIZE);
+    assert_eq!(w[0].len(), simd_size);
+
+    // Initialize vec for all groups of columns
+    let mut evals: Vec<Vec<u32x16>> = (0..N_COLUMNS)
+        .map(|_| vec![u32x16::splat(0); simd_size])
+        .collect::<Vec<_>>();
+    let mut lookup_data: Vec<Vec<u32x16>> = (0..LOOKUPS_INTERACTION_OFFSET
+        + n_lookup_columns(&N_LOOKUPS))
+        .map(|_| vec![u32x16::splat(0); simd_size])
+        .collect::<Vec<_>>();
+
+    // Chain columns
+    let column = |f: fn(&Block) -> u32| block_column(hash_function, blocks, simd_size, f);
+    let message_id = column(|block| block.message_id);
+    let block_index = column(|block| block.index);
+    let next_block_index = column(|block| block.index + 1);
+    let is_first = column(|block| block.is_first() as u32);
+    let is_last = column(|block| block.is_last as u32);
+    let is_active = (0..simd_size)
+        .map(|simd_row| {
+            u32x16::from_array(std::array::from_fn(|lane| {
+                (((simd_row << LOG_N_LANES) + lane) < blocks.len()) as u32
+            }))
+        })
+        .collect::<Vec<_>>();
+    let chain_values: ChainColumns<Vec<u32x16>> = ChainColumns {
+        message_id: &message_id,
+        block_index: &block_index,
+        is_first: &is_first,
+        is_last: &is_last,
+        is_active: &is_active,
+    };
+    for (i, value) in chain_values.iter().enumerate() {
+        evals[i] = value.clone();
+    }
+    let chain_interaction_values: ChainInteractionColumns<Vec<u32x16>> = ChainInteractionColumns {
+        message_id: &message_id,
+        block_index: &block_index,
+        next_block_index: &next_block_index,
+        is_first: &is_first,
+        is_last: &is_last,
+        is_active: &is_active,
+    };
+    for (i, value) in chain_interaction_values.iter().enumerate() {
+        lookup_data[i] = value.clone();
+    }
+
+    // Input chaining value and message schedule
+    for i in 0..STATE_SIZE {
+        lookup_data[H_IN_INTERACTION_OFFSET + i] =
+            block_column(hash_function, blocks, simd_size, |block| {
+                ((block.h_in[i / 4] >> (16 * (i % 4))) & 0xffff) as u32
+            });
+    }
+    for (i, limbs) in w.iter().enumerate() {
+        evals[W_OFFSET + i] = limbs.clone();
+        lookup_data[W_INTERACTION_OFFSET + i] = limbs.clone();
+    }
+
+    for_each_row_chunk(&mut evals, &mut lookup_data, |evals, lookup_data| {
+        let mut lookups = Lookups::new();
+        for row in 0..evals[0].len() {
+            let w: [[u32x16; 4]; N_COMPRESSION_ROUNDS] =
+                std::array::from_fn(|t| std::array::from_fn(|l| evals[W_OFFSET + 4 * t + l][row]));
+            let h_in: [[u32x16; 4]; 8] = std::array::from_fn(|i| {
+                std::array::from_fn(|l| lookup_data[H_IN_INTERACTION_OFFSET + 4 * i + l][row])
+            });
+
+            let mut writer = RowWriter::new(&mut evals[H_IN_OFFSET..], row);
+            let h_in_spreads = h_in.map(|h| write_spread_word(&mut writer, &mut lookups, &h));
+
+            // The values of `a` and `e` from round -3 on, with their spread bytes
+            let mut a = [3, 2, 1, 0].map(|i| (h_in[i], h_in_spreads[i])).to_vec();
+            let mut e = [7, 6, 5, 4].map(|i| (h_in[i], h_in_spreads[i])).to_vec();
+            for t in 0..N_COMPRESSION_ROUNDS {
+                let [(d, _), (_, c_spreads), (_, b_spreads), (a_t, a_spreads)] =
+                    [a[t], a[t + 1], a[t + 2], a[t + 3]];
+                let [(h, _), (_, g_spreads), (_, f_spreads), (e_t, e_spreads)] =
+                    [e[t], e[t + 1], e[t + 2], e[t + 3]];
+
+                let sigma_1 = write_sigma(&mut writer, &mut lookups, BIG_SIGMA_1, &e_t);
+                let sigma_0 = write_sigma(&mut writer, &mut lookups, BIG_SIGMA_0, &a_t);
+
+                let spread_ones = u32x16::splat(0x5555);
+                let ch_left_sums: [u32x16; 8] =
+                    std::array::from_fn(|i| e_spreads[i] + f_spreads[i]);
+                let ch_right_sums: [u32x16; 8] =
+                    std::array::from_fn(|i| spread_ones - e_spreads[i] + g_spreads[i]);
+                let maj_sums: [u32x16; 8] =
+                    std::array::from_fn(|i| a_spreads[i] + b_spreads[i] + c_spreads[i]);
+                let [ch_left, ch_right, maj] =
+                    [ch_left_sums, ch_right_sums, maj_sums].map(|sums| {
+                        let bytes = sums.map(odd_bits_u32x16);
+                        writer.write_all(bytes);
+                        for (sum, byte) in sums.into_iter().zip(bytes) {
+                            lookups.push(Table::Odd, [sum, byte]);
+                        }
+                        from_bytes(&bytes)
+                    });
+
+                let k = constant(K[t]);
+                let temp_1 = [&h, &sigma_1, &ch_left, &ch_right, &k, &w[t]];
+
+                let e_terms = [&d].into_iter().chain(temp_1).collect_vec();
+                let (new_e, e_carries) = add_limbs(&e_terms);
+                let new_e_spreads = write_spread_word(&mut writer, &mut lookups, &new_e);
+                write_carries(&mut writer, &mut lookups, &new_e, e_carries);
+
+                let a_terms = temp_1.into_iter().chain([&sigma_0, &maj]).collect_vec();
+                let (new_a, a_carries) = add_limbs(&a_terms);
+                let new_a_spreads = write_spread_word(&mut writer, &mut lookups, &new_a);
+                write_carries(&mut writer, &mut lookups, &new_a, a_carries);
+
+                a.push((new_a, new_a_spreads));
+                e.push((new_e, new_e_spreads));
+            }
+
+            // Feed-forward
+            let n = N_COMPRESSION_ROUNDS;
+            let working = [
+                a[n + 3],
+                a[n + 2],
+                a[n + 1],
+                a[n],
+                e[n + 3],
+                e[n + 2],
+                e[n + 1],
+                e[n],
+            ];
+            for (i, (h, (v, _))) in h_in.iter().zip(working).enumerate() {
+                let (h_out, carries) = add_limbs(&[h, &v]);
+                writer.write_all(h_out);
+                write_carries(&mut writer, &mut lookups, &h_out, carries);
+                for (l, limb) in h_out.into_iter().enumerate() {
+                    lookup_data[H_OUT_INTERACTION_OFFSET + 4 * i + l][row] = limb;
+                }
+            }
+            writer.finish();
+
+            lookups.write_row(&mut lookup_data[LOOKUPS_INTERACTION_OFFSET..], row);
+            lookups.clear();
+        }
+    });
+
+    let domain = CanonicCoset::new(log_size).circle_domain();
+    let trace = evals
+        .into_iter()
+        .map(|eval| {
+            let column = BaseColumn::from_simd(
+                eval.into_iter()
+                    .map(|x| unsafe { PackedM31::from_simd_unchecked(x) })
+                    .collect(),
+            );
+            CircleEvaluation::<SimdBackend, BaseField, BitReversedOrder>::new(domain, column)
+        })
+        .collect::<Vec<_>>();
+
+    let lookups = lookup_data.split_off(LOOKUPS_INTERACTION_OFFSET);
+    (
+        trace,
+        LookupData {
+            columns: lookup_data,
+            lookups: LookupColumns::from_columns(lookups, &N_LOOKUPS),
+        },
+    )
+}
+
+pub fn gen_interaction_trace(
+    lookup_data: &LookupData,
+    relations: &Relations,
+    hash_function: HashFunction,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let columns = &lookup_data.columns;
+    let simd_size = columns[0].len();
+    let mut interaction_trace = LogupTraceGenerator::new(simd_size.ilog2() + LOG_N_LANES);
+    let mut writer = FractionWriter::new(&mut interaction_trace);
+
+    // Range check the additions, look up the sigma functions, Ch, Maj and the spread bytes
+    for denoms in lookup_data.lookups.denoms(relations) {
+        writer.consume(denoms);
+    }
+
+    let ChainInteractionColumns {
+        message_id,
+        block_index,
+        next_block_index,
+        is_first,
+        is_last,
+        is_active,
+    } = ChainInteractionColumns::from_slice(&columns[..H_IN_INTERACTION_OFFSET]);
+    let h_in = &columns[H_IN_INTERACTION_OFFSET..H_OUT_INTERACTION_OFFSET];
+    let h_out = &columns[H_OUT_INTERACTION_OFFSET..W_INTERACTION_OFFSET];
+    let w = &columns[W_INTERACTION_OFFSET..LOOKUPS_INTERACTION_OFFSET];
+
+    // Consume the input chaining value and emit the output one
+    let chain_in = combine!(
+        relations.chain,
+        [message_id, block_index]
+            .into_iter()
+            .chain(h_in)
+            .collect::<Vec<_>>()
+    );
+    let chain_out = combine!(
+        relations.chain,
+        [message_id, next_block_index]
+            .into_iter()
+            .chain(h_out)
+            .collect::<Vec<_>>()
+    );
+    let one = PackedQM31::one();
+    writer.write(
+        is_first.iter().map(|v| to_packed_qm31(*v) - one).collect(),
+        chain_in,
+    );
+    writer.write(
+        is_last.iter().map(|v| one - to_packed_qm31(*v)).collect(),
+        chain_out,
+    );
+
+    // Emit the public claims
+    let input_claim = combine!(
+        relations.input_claim,
+        [message_id, block_index]
+            .into_iter()
+            .chain(&w[..4 * CHUNK_SIZE])
+            .collect::<Vec<_>>()
+    );
+    let zeros = vec![u32x16::splat(0); simd_size];
+    let digest_limbs = 4 * hash_function.digest_words();
+    let output_claim = combine!(
+        relations.output_claim,
+        [message_id]
+            .into_iter()
+            .chain(&h_out[..digest_limbs])
+            .chain((digest_limbs..STATE_SIZE).map(|_| &zeros))
+            .collect::<Vec<_>>()
+    );
+    writer.write(
+        is_active.iter().map(|v| to_packed_qm31(*v)).collect(),
+        input_claim,
+    );
+    writer.write(
+        is_last
+            .iter()
+            .zip(is_active)
+            .map(|(l, a)| to_packed_qm31(*l) + to_packed_qm31(*a) - one)
+            .collect(),
+        output_claim,
+    );
+
+    // Consume the schedule
+    writer.consume(combine!(relations.w, w));
+    writer.finalize();
+
+    interaction_trace.finalize_last()
+}
+
+#[inline(always)]
+fn to_packed_qm31(value: u32x16) -> PackedQM31 {
+    PackedQM31::from(unsafe { PackedM31::from_simd_unchecked(value) })
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha384, Sha512};
+
+    use super::*;
+    use crate::{
+        components::scheduling::witness::gen_trace as gen_scheduling_trace,
+        message::{gen_blocks_for, Block},
+    };
+
+    /// Output chaining value of `row`, read from the lookup data.
+    fn h_out(lookup_data: &LookupData, row: usize) -> [u64; 8] {
+        std::array::from_fn(|i| {
+            (0..4)
+                .map(|l| {
+                    let limb = lookup_data.columns[H_OUT_INTERACTION_OFFSET + 4 * i + l][row / 16]
+                        [row % 16];
+                    (limb as u64) << (16 * l)
+                })
+                .sum()
+        })
+    }
+
+    fn gen_compression(
+        log_size: u32,
+        blocks: &[Block],
+        hash_function: HashFunction,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        LookupData,
+    ) {
+        let (_, scheduling_lookup_data) = gen_scheduling_trace(log_size, blocks, hash_function);
+        gen_trace(log_size, blocks, &scheduling_lookup_data.w, hash_function)
+    }
+
+    #[test]
+    fn test_gen_trace_columns_count() {
+        let blocks = gen_blocks_for(HashFunction::Sha512, &[b"abc"]);
+        let (trace, lookup_data) = gen_compression(4, &blocks, HashFunction::Sha512);
+        assert_eq!(trace.len(), N_COLUMNS);
+        assert_eq!(lookup_data.columns.len(), LOOKUPS_INTERACTION_OFFSET);
+    }
+
+    #[test]
+    fn test_gen_trace_chained_blocks() {
+        let messages = [vec![0x42; 300], b"abc".to_vec()];
+        for hash_function in [HashFunction::Sha512, HashFunction::Sha384] {
+            let blocks = gen_blocks_for(hash_function, &messages);
+            let (_, lookup_data) = gen_compression(4, &blocks, hash_function);
+
+            for (row, block) in blocks.iter().enumerate() {
+                assert_eq!(h_out(&lookup_data, row), block.h_out());
+            }
+
+            let last_rows = blocks.iter().positions(|block| block.is_last);
+            for (row, message) in last_rows.zip(&messages) {
+                let digest = hash_function.digest(h_out(&lookup_data, row));
+                match hash_function {
+                    HashFunction::Sha512 => assert_eq!(digest[..], Sha512::digest(message)[..]),
+                    HashFunction::Sha384 => assert_eq!(digest[..], Sha384::digest(message)[..]),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_gen_trace_padding_rows() {
+        // Rows past the blocks compress the padding block from the initial hash value.
+        let blocks = gen_blocks_for(HashFunction::Sha384, &[b"abc"]);
+        let (trace, lookup_data) = gen_compression(4, &blocks, HashFunction::Sha384);
+        let padding = Block::padding(HashFunction::Sha384);
+        assert_eq!(h_out(&lookup_data, 1), padding.h_out());
+        assert_eq!(trace.len(), N_COLUMNS);
+    }
+}
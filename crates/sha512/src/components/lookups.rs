@@ -0,0 +1,280 @@
+//! Lookups of the scheduling and compression components into the tables.
+//!
+//! The AIR and the witness of a component collect the values of their lookups in the same order,
+//! then add them table by table, so that the relation entries are batched the same way on both
+//! sides.
+
+use std::simd::u32x16;
+
+use itertools::Itertools;
+use num_traits::One;
+use stwo::prover::backend::simd::qm31::PackedQM31;
+use stwo_constraint_framework::{EvalAtRow, LogupTraceGenerator, Relation as _, RelationEntry};
+use utils::{combine, write_col, write_pair};
+
+use crate::{preprocessed::sigma, relations::Relations};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Table {
+    /// Parts of a sigma function, see [`crate::preprocessed::sigma`].
+    Sigma(usize),
+    Spread,
+    /// Even bits of the spread sums, see [`crate::preprocessed::spread_sum`].
+    Even,
+    /// Odd bits of the spread sums.
+    Odd,
+    RangeCheckAdd,
+}
+
+pub const N_TABLES: usize = 8;
+
+/// Tables in the order their lookups are added.
+pub const TABLES: [Table; N_TABLES] = [
+    Table::Sigma(0),
+    Table::Sigma(1),
+    Table::Sigma(2),
+    Table::Sigma(3),
+    Table::Spread,
+    Table::Even,
+    Table::Odd,
+    Table::RangeCheckAdd,
+];
+
+impl Table {
+    /// Number of values of a lookup.
+    pub const fn n_columns(self) -> usize {
+        match self {
+            Self::Sigma(sigma) => sigma::n_columns(sigma),
+            Self::Spread | Self::Even | Self::Odd | Self::RangeCheckAdd => 2,
+        }
+    }
+
+    const fn index(self) -> usize {
+        match self {
+            Self::Sigma(sigma) => sigma,
+            Self::Spread => 4,
+            Self::Even => 5,
+            Self::Odd => 6,
+            Self::RangeCheckAdd => 7,
+        }
+    }
+}
+
+/// Number of lookups into each table, in the order of [`TABLES`].
+pub type LookupCounts = [usize; N_TABLES];
+
+/// Number of columns of the values of `counts` lookups.
+pub const fn n_lookup_columns(counts: &LookupCounts) -> usize {
+    let mut n_columns = 0;
+    let mut i = 0;
+    while i < N_TABLES {
+        n_columns += counts[i] * TABLES[i].n_columns();
+        i += 1;
+    }
+    n_columns
+}
+
+/// Values of lookups grouped by table, the values of each lookup being consecutive.
+#[derive(Debug, Clone)]
+pub struct Lookups<T> {
+    values: [Vec<T>; N_TABLES],
+}
+
+/// Lookup data of a component: the columns of the values of its lookups.
+pub type LookupColumns = Lookups<Vec<u32x16>>;
+
+impl<T> Default for Lookups<T> {
+    fn default() -> Self {
+        Self {
+            values: std::array::from_fn(|_| Vec::new()),
+        }
+    }
+}
+
+impl<T> Lookups<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, table: Table, values: impl IntoIterator<Item = T>) {
+        let table_values = &mut self.values[table.index()];
+        let len = table_values.len();
+        table_values.extend(values);
+        debug_assert_eq!(table_values.len() - len, table.n_columns());
+    }
+
+    /// Values of the lookups into `table`.
+    pub fn get(&self, table: Table) -> impl Iterator<Item = &[T]> {
+        self.values[table.index()].chunks_exact(table.n_columns())
+    }
+
+    pub fn clear(&mut self) {
+        self.values.iter_mut().for_each(Vec::clear);
+    }
+}
+
+impl<F: Clone> Lookups<F> {
+    /// Consumes each lookup once, table by table.
+    pub fn add_to_eval<E: EvalAtRow<F = F>>(&self, eval: &mut E, relations: &Relations) {
+        let minus_one = -E::EF::one();
+        for table in TABLES {
+            for values in self.get(table) {
+                let multiplicity = minus_one.clone();
+                match table {
+                    Table::Sigma(sigma) => {
+                        relations
+                            .sigma
+                            .add_to_eval(eval, sigma, multiplicity, values)
+                    }
+                    Table::Spread => eval.add_to_relation(RelationEntry::new(
+                        &relations.spread.spread,
+                        multiplicity,
+                        values,
+                    )),
+                    Table::Even => eval.add_to_relation(RelationEntry::new(
+                        &relations.spread_sum.even,
+                        multiplicity,
+                        values,
+                    )),
+                    Table::Odd => eval.add_to_relation(RelationEntry::new(
+                        &relations.spread_sum.odd,
+                        multiplicity,
+                        values,
+                    )),
+                    Table::RangeCheckAdd => eval.add_to_relation(RelationEntry::new(
+                        &relations.range_check_add.add_8,
+                        multiplicity,
+                        values,
+                    )),
+                }
+            }
+        }
+    }
+}
+
+impl Lookups<u32x16> {
+    /// Writes the values of the lookups of `row`, table by table.
+    pub fn write_row(&self, columns: &mut [&mut [u32x16]], row: usize) {
+        for (column, value) in columns.iter_mut().zip_eq(self.values.iter().flatten()) {
+            column[row] = *value;
+        }
+    }
+}
+
+impl LookupColumns {
+    /// Splits the columns written by [`Lookups::write_row`] by table.
+    pub fn from_columns(columns: Vec<Vec<u32x16>>, counts: &LookupCounts) -> Self {
+        assert_eq!(columns.len(), n_lookup_columns(counts));
+        let mut columns = columns.into_iter();
+        Self {
+            values: std::array::from_fn(|i| {
+                columns
+                    .by_ref()
+                    .take(counts[i] * TABLES[i].n_columns())
+                    .collect()
+            }),
+        }
+    }
+
+    /// Denominators of the lookups, in the order of [`Lookups::add_to_eval`].
+    pub fn denoms<'a>(
+        &'a self,
+        relations: &'a Relations,
+    ) -> impl Iterator<Item = Vec<PackedQM31>> + 'a {
+        TABLES.into_iter().flat_map(move |table| {
+            self.get(table).map(move |columns| {
+                let columns = columns.iter().collect::<Vec<_>>();
+                match table {
+                    Table::Sigma(sigma) => relations.sigma.combine(sigma, &columns),
+                    Table::Spread => combine!(relations.spread.spread, columns),
+                    Table::Even => combine!(relations.spread_sum.even, columns),
+                    Table::Odd => combine!(relations.spread_sum.odd, columns),
+                    Table::RangeCheckAdd => combine!(relations.range_check_add.add_8, columns),
+                }
+            })
+        })
+    }
+}
+
+/// Lookups into `table` of all the components.
+pub fn table_lookups<'a>(components: &[&'a LookupColumns], table: Table) -> Vec<&'a [Vec<u32x16>]> {
+    components
+        .iter()
+        .flat_map(|lookups| lookups.get(table))
+        .collect()
+}
+
+/// Writes the fractions of relation entries two by two, as they are batched by
+/// `finalize_logup_in_pairs`.
+pub struct FractionWriter<'a> {
+    interaction_trace: &'a mut LogupTraceGenerator,
+    pending: Option<(Vec<PackedQM31>, Vec<PackedQM31>)>,
+}
+
+impl<'a> FractionWriter<'a> {
+    pub fn new(interaction_trace: &'a mut LogupTraceGenerator) -> Self {
+        Self {
+            interaction_trace,
+            pending: None,
+        }
+    }
+
+    pub fn write(&mut self, numerators: Vec<PackedQM31>, denoms: Vec<PackedQM31>) {
+        match self.pending.take() {
+            Some((numerators_0, denoms_0)) => {
+                write_pair!(
+                    numerators_0,
+                    denoms_0,
+                    numerators,
+                    denoms,
+                    self.interaction_trace
+                );
+            }
+            None => self.pending = Some((numerators, denoms)),
+        }
+    }
+
+    /// Consumes the entries of `denoms` once, e.g. a lookup of [`LookupColumns::denoms`].
+    pub fn consume(&mut self, denoms: Vec<PackedQM31>) {
+        let minus_one = -PackedQM31::one();
+        self.write(vec![minus_one; denoms.len()], denoms);
+    }
+
+    /// Writes the last fraction on its own column, if any.
+    pub fn finalize(mut self) {
+        if let Some((numerators, denoms)) = self.pending.take() {
+            write_col!(numerators, denoms, self.interaction_trace);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_columns() {
+        let counts = [1, 0, 0, 0, 2, 0, 0, 1];
+        let mut lookups = Lookups::new();
+        lookups.push(Table::RangeCheckAdd, [u32x16::splat(7), u32x16::splat(1)]);
+        lookups.push(Table::Spread, [u32x16::splat(3), u32x16::splat(5)]);
+        lookups.push(Table::Sigma(0), (0..5).map(u32x16::splat));
+        lookups.push(Table::Spread, [u32x16::splat(2), u32x16::splat(4)]);
+
+        let mut columns = vec![vec![u32x16::splat(0); 2]; n_lookup_columns(&counts)];
+        let mut rows = columns
+            .iter_mut()
+            .map(|column| column.as_mut_slice())
+            .collect::<Vec<_>>();
+        lookups.write_row(&mut rows, 1);
+
+        let columns = LookupColumns::from_columns(columns, &counts);
+        let spread = columns.get(Table::Spread).collect::<Vec<_>>();
+        assert_eq!(spread.len(), 2);
+        assert_eq!(spread[1][0][1], u32x16::splat(2));
+        assert_eq!(spread[1][1][0], u32x16::splat(0));
+        let sigma = columns.get(Table::Sigma(0)).next().unwrap();
+        assert_eq!(sigma[4][1], u32x16::splat(4));
+        assert_eq!(columns.get(Table::Even).count(), 0);
+    }
+}
@@ -0,0 +1,298 @@
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use stwo::{
+    core::{
+        air::Component,
+        channel::Channel,
+        fields::{m31::BaseField, qm31::SecureField},
+        pcs::TreeVec,
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{m31::LOG_N_LANES, SimdBackend},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        ComponentProver,
+    },
+};
+use stwo_constraint_framework::{
+    relation_tracker::{add_to_relation_entries, RelationTrackerEntry},
+    TraceLocationAllocator,
+};
+use tracing::{span, Level};
+use utils::stwo::air::{Air, AirComponents, LogupSum, Trace};
+
+use crate::{
+    message::Block,
+    public::{self, Claims},
+    relations::Relations,
+    sha512::HashFunction,
+};
+pub const STATE_SIZE: usize = 32; // 32 u16 = 8 u64
+pub const W_SIZE: usize = 320; // 320 u16 = 80 u64
+
+pub mod compression;
+pub mod lookups;
+pub mod preprocessed;
+pub mod scheduling;
+pub mod word;
+
+pub struct LookupData {
+    pub scheduling: scheduling::witness::LookupData,
+    pub compression: compression::witness::LookupData,
+    pub preprocessed: preprocessed::Traces,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClaimedSum {
+    pub scheduling: SecureField,
+    pub compression: SecureField,
+    pub preprocessed: preprocessed::ClaimedSum,
+}
+
+impl ClaimedSum {
+    pub fn zero() -> Self {
+        Self {
+            scheduling: SecureField::zero(),
+            compression: SecureField::zero(),
+            preprocessed: preprocessed::ClaimedSum::zero(),
+        }
+    }
+}
+
+impl LogupSum for ClaimedSum {
+    fn sum(&self) -> SecureField {
+        self.scheduling + self.compression + self.preprocessed.sum()
+    }
+
+    fn mix_into(&self, channel: &mut impl Channel) {
+        channel.mix_felts(&[self.scheduling, self.compression]);
+        self.preprocessed.mix_into(channel);
+    }
+}
+
+pub fn gen_trace(
+    log_size: u32,
+    blocks: &[Block],
+    hash_function: HashFunction,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    LookupData,
+) {
+    assert!(log_size >= LOG_N_LANES);
+
+    let span = span!(Level::INFO, "Scheduling").entered();
+    let (scheduling_trace, scheduling_lookup_data) =
+        scheduling::witness::gen_trace(log_size, blocks, hash_function);
+    span.exit();
+
+    let span = span!(Level::INFO, "Compression").entered();
+    let (compression_trace, compression_lookup_data) =
+        compression::witness::gen_trace(log_size, blocks, &scheduling_lookup_data.w, hash_function);
+    span.exit();
+
+    let span = span!(Level::INFO, "Preprocessed").entered();
+    let preprocessed_trace = preprocessed::gen_trace(
+        log_size,
+        &scheduling_lookup_data.lookups,
+        &compression_lookup_data.lookups,
+    );
+    span.exit();
+
+    let lookup_data = LookupData {
+        scheduling: scheduling_lookup_data,
+        compression: compression_lookup_data,
+        preprocessed: preprocessed_trace.clone(),
+    };
+
+    let mut trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> =
+        Vec::with_capacity(
+            scheduling_trace.len() + compression_trace.len() + preprocessed_trace.len(),
+        );
+    trace.extend(scheduling_trace);
+    trace.extend(compression_trace);
+    trace.extend(preprocessed_trace);
+
+    (trace, lookup_data)
+}
+
+pub fn gen_interaction_trace(
+    lookup_data: LookupData,
+    relations: &Relations,
+    hash_function: HashFunction,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    ClaimedSum,
+) {
+    let span = span!(Level::INFO, "Scheduling").entered();
+    let (scheduling_interaction_trace, scheduling_claimed_sum) =
+        scheduling::witness::gen_interaction_trace(&lookup_data.scheduling, relations);
+    span.exit();
+
+    let span = span!(Level::INFO, "Compression").entered();
+    let (compression_interaction_trace, compression_claimed_sum) =
+        compression::witness::gen_interaction_trace(
+            &lookup_data.compression,
+            relations,
+            hash_function,
+        );
+    span.exit();
+
+    let span = span!(Level::INFO, "Preprocessed").entered();
+    let (preprocessed_interaction_trace, preprocessed_claimed_sum) =
+        preprocessed::gen_interaction_trace(&lookup_data.preprocessed, relations);
+    span.exit();
+
+    let mut interaction_trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> =
+        Vec::with_capacity(
+            scheduling_interaction_trace.len()
+                + compression_interaction_trace.len()
+                + preprocessed_interaction_trace.len(),
+        );
+    interaction_trace.extend(scheduling_interaction_trace);
+    interaction_trace.extend(compression_interaction_trace);
+    interaction_trace.extend(preprocessed_interaction_trace);
+    (
+        interaction_trace,
+        ClaimedSum {
+            scheduling: scheduling_claimed_sum,
+            compression: compression_claimed_sum,
+            preprocessed: preprocessed_claimed_sum,
+        },
+    )
+}
+
+pub struct Components {
+    scheduling: scheduling::air::Component,
+    compression: compression::air::Component,
+    preprocessed: preprocessed::Components,
+}
+
+impl Components {
+    pub fn new(
+        log_size: u32,
+        hash_function: HashFunction,
+        location_allocator: &mut TraceLocationAllocator,
+        relations: &Relations,
+        claimed_sum: &ClaimedSum,
+    ) -> Self {
+        Self {
+            scheduling: scheduling::air::Component::new(
+                location_allocator,
+                scheduling::air::Eval {
+                    log_size,
+                    relations: relations.clone(),
+                },
+                claimed_sum.scheduling,
+            ),
+            compression: compression::air::Component::new(
+                location_allocator,
+                compression::air::Eval {
+                    log_size,
+                    relations: relations.clone(),
+                    hash_function,
+                },
+                claimed_sum.compression,
+            ),
+            preprocessed: preprocessed::Components::new(
+                log_size,
+                location_allocator,
+                relations.clone(),
+                &claimed_sum.preprocessed,
+            ),
+        }
+    }
+}
+
+impl AirComponents for Components {
+    fn provers(&self) -> Vec<&dyn ComponentProver<SimdBackend>> {
+        let mut provers: Vec<&dyn ComponentProver<SimdBackend>> =
+            vec![&self.scheduling, &self.compression];
+        provers.extend(self.preprocessed.provers());
+        provers
+    }
+
+    fn components(&self) -> Vec<&dyn Component> {
+        let mut components: Vec<&dyn Component> = vec![&self.scheduling, &self.compression];
+        components.extend(self.preprocessed.components());
+        components
+    }
+
+    fn trace_log_degree_bounds(&self) -> Vec<TreeVec<ColumnVec<u32>>> {
+        let mut log_degree_bounds: Vec<TreeVec<ColumnVec<u32>>> = vec![
+            self.scheduling.trace_log_degree_bounds(),
+            self.compression.trace_log_degree_bounds(),
+        ];
+        log_degree_bounds.extend(self.preprocessed.trace_log_degree_bounds());
+        log_degree_bounds
+    }
+
+    fn relation_entries(&self, trace: &TreeVec<Vec<&Vec<BaseField>>>) -> Vec<RelationTrackerEntry> {
+        add_to_relation_entries(&self.scheduling, trace)
+            .into_iter()
+            .chain(add_to_relation_entries(&self.compression, trace))
+            .chain(self.preprocessed.relation_entries(trace))
+            .collect()
+    }
+}
+
+/// The SHA-512 AIR for a hash function built on its compression, proving batches of blocks.
+pub struct Sha512Air {
+    pub hash_function: HashFunction,
+}
+
+impl Air for Sha512Air {
+    type Input = [Block];
+    type Claims = Claims;
+    type Relations = Relations;
+    type LookupData = LookupData;
+    type ClaimedSum = ClaimedSum;
+    type Components = Components;
+
+    fn claims(&self, blocks: &[Block]) -> Claims {
+        public::block_claims(self.hash_function, blocks)
+    }
+
+    fn mix_claims(&self, claims: &Claims, channel: &mut impl Channel) {
+        public::mix_into(claims, channel);
+    }
+
+    fn draw_relations(&self, channel: &mut impl Channel) -> Relations {
+        Relations::draw(channel)
+    }
+
+    fn dummy_relations(&self) -> Relations {
+        Relations::dummy()
+    }
+
+    fn logup_sum(&self, claims: &Claims, relations: &Relations) -> SecureField {
+        public::logup_sum(claims, relations)
+    }
+
+    fn gen_trace(&self, log_size: u32, blocks: &[Block]) -> (Trace, LookupData) {
+        gen_trace(log_size, blocks, self.hash_function)
+    }
+
+    fn gen_interaction_trace(
+        &self,
+        lookup_data: LookupData,
+        relations: &Relations,
+    ) -> (Trace, ClaimedSum) {
+        gen_interaction_trace(lookup_data, relations, self.hash_function)
+    }
+
+    fn components(
+        &self,
+        log_size: u32,
+        location_allocator: &mut TraceLocationAllocator,
+        relations: &Relations,
+        claimed_sum: &ClaimedSum,
+    ) -> Components {
+        Components::new(
+            log_size,
+            self.hash_function,
+            location_allocator,
+            relations,
+            claimed_sum,
+        )
+    }
+}
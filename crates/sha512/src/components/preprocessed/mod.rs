@@ -0,0 +1,24 @@
+//! Components of the tables, whose multiplicities count the lookups of the scheduling and
+//! compression components.
+
+mod range_check_add;
+mod sigma;
+mod spread;
+mod spread_sum;
+
+use utils::components;
+
+components!(
+    relations: crate::relations::Relations,
+    lookup_data: (
+        scheduling_lookups: &crate::components::lookups::LookupColumns,
+        compression_lookups: &crate::components::lookups::LookupColumns,
+    ),
+    sigma::small_sigma_0,
+    sigma::small_sigma_1,
+    sigma::big_sigma_0,
+    sigma::big_sigma_1,
+    spread::spread,
+    spread_sum::spread_sum,
+    range_check_add::range_check_add,
+);
@@ -0,0 +1,101 @@
+use std::simd::u32x16;
+
+use itertools::{izip, Itertools};
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, histogram::par_histograms, write_col, write_pair};
+
+use crate::{
+    components::lookups::{table_lookups, LookupColumns, Table},
+    preprocessed::range_check_add::{self, RangeCheckAddColumns, LOG_SIZE},
+    relations::Relations,
+};
+
+/// Multiplicities of the addition range check table, looked up by the additions of the
+/// scheduling and compression components.
+pub fn gen_trace(
+    log_size: u32,
+    scheduling_lookups: &LookupColumns,
+    compression_lookups: &LookupColumns,
+) -> Vec<Vec<u32x16>> {
+    // One task per lookup
+    let lookups = table_lookups(
+        &[scheduling_lookups, compression_lookups],
+        Table::RangeCheckAdd,
+    );
+    let [add_8_mult] = par_histograms([LOG_SIZE], lookups.len(), |task, [add_8_mult]| {
+        let [value, carry] = lookups[task] else {
+            unreachable!()
+        };
+        izip!(value, carry).for_each(|(value, carry)| {
+            add_8_mult.add((*value << 3) + *carry);
+        });
+    });
+
+    add_8_mult.into_columns(log_size)
+}
+
+pub fn gen_interaction_trace(
+    trace: &[Vec<u32x16>],
+    relations: &Relations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let preprocessed_columns = range_check_add::gen_column_simd();
+    let RangeCheckAddColumns { value, carry } =
+        RangeCheckAddColumns::from_slice(&preprocessed_columns[..]);
+
+    let simd_size = trace[0].len();
+    let log_size = simd_size.ilog2() + LOG_N_LANES;
+    let mut interaction_trace = LogupTraceGenerator::new(log_size);
+
+    let add_8_den = combine!(relations.range_check_add.add_8, [value, carry]);
+
+    for ([add_8_mult_0, add_8_mult_1], (add_8_den_0, add_8_den_1)) in trace
+        .array_chunks::<2>()
+        .zip(add_8_den.chunks(simd_size).tuples())
+    {
+        write_pair!(
+            add_8_mult_0
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            add_8_den_0.to_vec(),
+            add_8_mult_1
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            add_8_den_1.to_vec(),
+            interaction_trace
+        );
+    }
+
+    if trace.len() % 2 == 1 {
+        let add_8_mult = trace.last().unwrap();
+        let add_8_den_chunk = add_8_den.chunks(simd_size).last().unwrap();
+        write_col!(
+            add_8_mult
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            add_8_den_chunk.to_vec(),
+            interaction_trace
+        );
+    }
+
+    interaction_trace.finalize_last()
+}
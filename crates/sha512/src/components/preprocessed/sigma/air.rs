@@ -0,0 +1,140 @@
+use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval};
+
+use crate::{
+    components::preprocessed::sigma::columns::ComponentColumnsOwned as ComponentColumns,
+    preprocessed::sigma::{column_ids, LOG_SIZE},
+    relations::Relations,
+};
+
+pub type Component<const SIGMA: usize> = FrameworkComponent<Eval<SIGMA>>;
+
+fn eval_constraints<E: EvalAtRow, const SIGMA: usize>(
+    eval: &mut E,
+    relations: &Relations,
+    log_size: u32,
+) {
+    let chunk_count = 1 << LOG_SIZE.saturating_sub(log_size);
+    for chunk in 0..chunk_count {
+        let ComponentColumns { mult } = ComponentColumns::<<E as EvalAtRow>::F>::from_eval(eval);
+        let values = column_ids(SIGMA, chunk)
+            .into_iter()
+            .map(|id| eval.get_preprocessed_column(id))
+            .collect::<Vec<_>>();
+        relations
+            .sigma
+            .add_to_eval(eval, SIGMA, E::EF::from(mult), &values);
+    }
+    eval.finalize_logup_in_pairs();
+}
+
+/// The table of the parts of the sigma function `SIGMA` has `2^18` rows, see
+/// [`crate::preprocessed::sigma`].
+#[derive(Clone)]
+pub struct Eval<const SIGMA: usize> {
+    pub log_size: u32,
+    pub relations: Relations,
+}
+impl<const SIGMA: usize> FrameworkEval for Eval<SIGMA> {
+    fn log_size(&self) -> u32 {
+        LOG_SIZE.min(self.log_size)
+    }
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        LOG_SIZE.min(self.log_size) + 1
+    }
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        eval_constraints::<E, SIGMA>(&mut eval, &self.relations, self.log_size);
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use stwo::{
+        core::{pcs::TreeVec, poly::circle::CanonicCoset},
+        prover::backend::simd::m31::LOG_N_LANES,
+    };
+    use stwo_constraint_framework::assert_constraints_on_polys;
+    use utils::circle_evaluation_u32x16;
+
+    use super::*;
+    use crate::{
+        components::{
+            compression::witness::gen_trace as gen_compression_trace,
+            lookups::LookupColumns,
+            preprocessed::sigma::witness::{gen_interaction_trace, gen_trace},
+            scheduling::witness::gen_trace as gen_scheduling_trace,
+        },
+        message::gen_blocks,
+        partitions::{BIG_SIGMA_0, BIG_SIGMA_1, SMALL_SIGMA_0, SMALL_SIGMA_1},
+        preprocessed::sigma::gen_table_columns,
+        sha512::HashFunction,
+    };
+
+    fn assert_sigma_constraints<const SIGMA: usize>(
+        scheduling_lookups: &LookupColumns,
+        compression_lookups: &LookupColumns,
+    ) {
+        let max_log_size = 12;
+        let trace = gen_trace::<SIGMA>(max_log_size, scheduling_lookups, compression_lookups);
+
+        let simd_size = trace[0].len();
+        let log_size = simd_size.ilog2() + LOG_N_LANES;
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) = gen_interaction_trace::<SIGMA>(&trace, &relations);
+
+        // Chunks of the table, column by column inside each chunk
+        let table = gen_table_columns(SIGMA);
+        let preprocessed_trace = (0..trace.len())
+            .flat_map(|chunk| {
+                table.iter().map(move |column| {
+                    circle_evaluation_u32x16!(column[chunk * simd_size..(chunk + 1) * simd_size])
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let traces = TreeVec::new(vec![
+            preprocessed_trace,
+            trace
+                .into_iter()
+                .map(|c| circle_evaluation_u32x16!(c))
+                .collect::<Vec<_>>(),
+            interaction_trace,
+        ]);
+
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(log_size),
+            |mut eval| {
+                eval_constraints::<_, SIGMA>(&mut eval, &relations, log_size);
+            },
+            claimed_sum,
+        );
+    }
+
+    #[test_log::test]
+    fn test_constraints() {
+        const LOG_N_ROWS: u32 = 4;
+
+        let blocks = gen_blocks(&[vec![0x42; 200], b"abc".to_vec()]);
+        let (_, scheduling_lookup_data) =
+            gen_scheduling_trace(LOG_N_ROWS, &blocks, HashFunction::Sha512);
+        let (_, compression_lookup_data) = gen_compression_trace(
+            LOG_N_ROWS,
+            &blocks,
+            &scheduling_lookup_data.w,
+            HashFunction::Sha512,
+        );
+        let scheduling_lookups = &scheduling_lookup_data.lookups;
+        let compression_lookups = &compression_lookup_data.lookups;
+
+        assert_sigma_constraints::<SMALL_SIGMA_0>(scheduling_lookups, compression_lookups);
+        assert_sigma_constraints::<SMALL_SIGMA_1>(scheduling_lookups, compression_lookups);
+        assert_sigma_constraints::<BIG_SIGMA_0>(scheduling_lookups, compression_lookups);
+        assert_sigma_constraints::<BIG_SIGMA_1>(scheduling_lookups, compression_lookups);
+    }
+}
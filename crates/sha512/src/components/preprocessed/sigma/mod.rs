@@ -0,0 +1,62 @@
+//! Multiplicities of the sigma tables, one component per function.
+//!
+//! The components only differ by their function, so they share a generic AIR and witness,
+//! instantiated below inside their own namespaces to comply with the components! macro.
+
+pub mod air;
+pub mod columns;
+pub mod witness;
+
+macro_rules! sigma_components {
+    ($($name:ident: $sigma:ident),+ $(,)?) => {
+        $(
+            pub mod $name {
+                pub mod air {
+                    use crate::partitions::$sigma;
+
+                    pub type Eval = super::super::air::Eval<{ $sigma }>;
+                    pub type Component = super::super::air::Component<{ $sigma }>;
+                }
+
+                pub mod witness {
+                    use std::simd::u32x16;
+
+                    use crate::{
+                        components::{
+                            lookups::LookupColumns,
+                            preprocessed::sigma::witness::InteractionTrace,
+                        },
+                        partitions::$sigma,
+                        relations::Relations,
+                    };
+
+                    pub fn gen_trace(
+                        log_size: u32,
+                        scheduling_lookups: &LookupColumns,
+                        compression_lookups: &LookupColumns,
+                    ) -> Vec<Vec<u32x16>> {
+                        super::super::witness::gen_trace::<{ $sigma }>(
+                            log_size,
+                            scheduling_lookups,
+                            compression_lookups,
+                        )
+                    }
+
+                    pub fn gen_interaction_trace(
+                        trace: &[Vec<u32x16>],
+                        relations: &Relations,
+                    ) -> InteractionTrace {
+                        super::super::witness::gen_interaction_trace::<{ $sigma }>(trace, relations)
+                    }
+                }
+            }
+        )+
+    };
+}
+
+sigma_components!(
+    small_sigma_0: SMALL_SIGMA_0,
+    small_sigma_1: SMALL_SIGMA_1,
+    big_sigma_0: BIG_SIGMA_0,
+    big_sigma_1: BIG_SIGMA_1,
+);
@@ -0,0 +1,100 @@
+use std::simd::u32x16;
+
+use itertools::{izip, Itertools};
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::LogupTraceGenerator;
+use utils::{histogram::par_histograms, write_col, write_pair};
+
+use crate::{
+    components::lookups::{table_lookups, LookupColumns, Table},
+    preprocessed::sigma::{gen_table_columns, LOG_SIZE},
+    relations::Relations,
+};
+
+pub type InteractionTrace = (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+);
+
+/// Multiplicities of the table of the sigma function `SIGMA`, looked up by the scheduling or the
+/// compression component.
+pub fn gen_trace<const SIGMA: usize>(
+    log_size: u32,
+    scheduling_lookups: &LookupColumns,
+    compression_lookups: &LookupColumns,
+) -> Vec<Vec<u32x16>> {
+    // One task per lookup
+    let lookups = table_lookups(
+        &[scheduling_lookups, compression_lookups],
+        Table::Sigma(SIGMA),
+    );
+    let [mult] = par_histograms([LOG_SIZE], lookups.len(), |task, [mult]| {
+        let [limb, value, ..] = lookups[task] else {
+            unreachable!()
+        };
+        izip!(limb, value).for_each(|(limb, value)| mult.add((*limb << 16) + *value));
+    });
+
+    mult.into_columns(log_size)
+}
+
+pub fn gen_interaction_trace<const SIGMA: usize>(
+    trace: &[Vec<u32x16>],
+    relations: &Relations,
+) -> InteractionTrace {
+    let preprocessed_columns = gen_table_columns(SIGMA);
+
+    let simd_size = trace[0].len();
+    let log_size = simd_size.ilog2() + LOG_N_LANES;
+    let mut interaction_trace = LogupTraceGenerator::new(log_size);
+
+    let den = relations
+        .sigma
+        .combine(SIGMA, &preprocessed_columns.iter().collect::<Vec<_>>());
+
+    for ([mult_0, mult_1], (den_0, den_1)) in trace
+        .array_chunks::<2>()
+        .zip(den.chunks(simd_size).tuples())
+    {
+        write_pair!(
+            mult_0
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            den_0.to_vec(),
+            mult_1
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            den_1.to_vec(),
+            interaction_trace
+        );
+    }
+
+    if trace.len() % 2 == 1 {
+        let mult = trace.last().unwrap();
+        let den_chunk = den.chunks(simd_size).last().unwrap();
+        write_col!(
+            mult.iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            den_chunk.to_vec(),
+            interaction_trace
+        );
+    }
+
+    interaction_trace.finalize_last()
+}
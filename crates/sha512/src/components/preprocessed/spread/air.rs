@@ -0,0 +1,125 @@
+use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval};
+use utils::add_to_relation;
+
+use crate::{
+    components::preprocessed::spread::columns::ComponentColumnsOwned as ComponentColumns,
+    preprocessed::spread::{SpreadColumnsOwned as SpreadColumns, LOG_SIZE},
+    relations::Relations,
+};
+
+pub type Component = FrameworkComponent<Eval>;
+
+fn eval_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relations, log_size: u32) {
+    let chunk_count = 1 << LOG_SIZE.saturating_sub(log_size);
+    for chunk in 0..chunk_count {
+        let ComponentColumns { spread_mult } =
+            ComponentColumns::<<E as EvalAtRow>::F>::from_eval(eval);
+        let SpreadColumns { byte, spread } =
+            SpreadColumns::<<E as EvalAtRow>::F>::from_ids(eval, Some(chunk));
+        add_to_relation!(
+            eval,
+            relations.spread.spread,
+            E::EF::from(spread_mult),
+            byte,
+            spread,
+        );
+    }
+    eval.finalize_logup_in_pairs();
+}
+
+#[derive(Clone)]
+pub struct Eval {
+    pub log_size: u32,
+    pub relations: Relations,
+}
+impl FrameworkEval for Eval {
+    fn log_size(&self) -> u32 {
+        LOG_SIZE.min(self.log_size)
+    }
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        LOG_SIZE.min(self.log_size) + 1
+    }
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        eval_constraints(&mut eval, &self.relations, self.log_size);
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use stwo::{
+        core::{pcs::TreeVec, poly::circle::CanonicCoset},
+        prover::backend::simd::m31::LOG_N_LANES,
+    };
+    use stwo_constraint_framework::assert_constraints_on_polys;
+    use utils::circle_evaluation_u32x16;
+
+    use super::*;
+    use crate::{
+        components::{
+            compression::witness::gen_trace as gen_compression_trace,
+            preprocessed::spread::witness::{gen_interaction_trace, gen_trace},
+            scheduling::witness::gen_trace as gen_scheduling_trace,
+        },
+        message::gen_blocks,
+        preprocessed::spread::{self, SpreadColumns as SpreadColumnsBorrowed},
+        sha512::HashFunction,
+    };
+
+    #[test_log::test]
+    fn test_constraints() {
+        const LOG_N_ROWS: u32 = 4;
+
+        // Trace.
+        let blocks = gen_blocks(&[vec![0x42; 200], b"abc".to_vec()]);
+        let (_, scheduling_lookup_data) =
+            gen_scheduling_trace(LOG_N_ROWS, &blocks, HashFunction::Sha512);
+        let (_, compression_lookup_data) = gen_compression_trace(
+            LOG_N_ROWS,
+            &blocks,
+            &scheduling_lookup_data.w,
+            HashFunction::Sha512,
+        );
+        let max_log_size = 6;
+        let trace = gen_trace(
+            max_log_size,
+            &scheduling_lookup_data.lookups,
+            &compression_lookup_data.lookups,
+        );
+
+        let simd_size = trace[0].len().ilog2();
+        let log_size = simd_size + LOG_N_LANES;
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) = gen_interaction_trace(&trace, &relations);
+
+        let spread_cols = spread::gen_column_simd();
+        let preprocessed_trace = SpreadColumnsBorrowed::from_slice(&spread_cols)
+            .chunks((1 << simd_size) as usize)
+            .into_iter()
+            .flat_map(|c| c.iter().map(|c| circle_evaluation_u32x16!(c)))
+            .collect::<Vec<_>>();
+
+        let traces = TreeVec::new(vec![
+            preprocessed_trace,
+            trace
+                .into_iter()
+                .map(|c| circle_evaluation_u32x16!(c))
+                .collect::<Vec<_>>(),
+            interaction_trace,
+        ]);
+
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(log_size),
+            |mut eval| {
+                eval_constraints(&mut eval, &relations, log_size);
+            },
+            claimed_sum,
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use std::simd::u32x16;
+
+use itertools::Itertools;
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, histogram::par_histograms, write_col, write_pair};
+
+use crate::{
+    components::lookups::{table_lookups, LookupColumns, Table},
+    preprocessed::spread::{self, SpreadColumns, LOG_SIZE},
+    relations::Relations,
+};
+
+/// Multiplicities of the spread table, looked up by the spread words of the compression
+/// component.
+pub fn gen_trace(
+    log_size: u32,
+    scheduling_lookups: &LookupColumns,
+    compression_lookups: &LookupColumns,
+) -> Vec<Vec<u32x16>> {
+    // One task per lookup
+    let lookups = table_lookups(&[scheduling_lookups, compression_lookups], Table::Spread);
+    let [spread_mult] = par_histograms([LOG_SIZE], lookups.len(), |task, [spread_mult]| {
+        let [byte, _] = lookups[task] else {
+            unreachable!()
+        };
+        spread_mult.add_all(byte);
+    });
+
+    spread_mult.into_columns(log_size)
+}
+
+pub fn gen_interaction_trace(
+    trace: &[Vec<u32x16>],
+    relations: &Relations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let preprocessed_columns = spread::gen_column_simd();
+    let SpreadColumns { byte, spread } = SpreadColumns::from_slice(&preprocessed_columns[..]);
+
+    let simd_size = trace[0].len();
+    let log_size = simd_size.ilog2() + LOG_N_LANES;
+    let mut interaction_trace = LogupTraceGenerator::new(log_size);
+
+    let spread_den = combine!(relations.spread.spread, [byte, spread]);
+
+    for ([spread_mult_0, spread_mult_1], (spread_den_0, spread_den_1)) in trace
+        .array_chunks::<2>()
+        .zip(spread_den.chunks(simd_size).tuples())
+    {
+        write_pair!(
+            spread_mult_0
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            spread_den_0.to_vec(),
+            spread_mult_1
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            spread_den_1.to_vec(),
+            interaction_trace
+        );
+    }
+
+    if trace.len() % 2 == 1 {
+        let spread_mult = trace.last().unwrap();
+        let spread_den_chunk = spread_den.chunks(simd_size).last().unwrap();
+        write_col!(
+            spread_mult
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            spread_den_chunk.to_vec(),
+            interaction_trace
+        );
+    }
+
+    interaction_trace.finalize_last()
+}
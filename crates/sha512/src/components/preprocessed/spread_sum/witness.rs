@@ -0,0 +1,97 @@
+use std::simd::u32x16;
+
+use itertools::Itertools;
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, histogram::par_histograms, write_pair};
+
+use crate::{
+    components::lookups::{table_lookups, LookupColumns, Table},
+    preprocessed::spread_sum::{self, SpreadSumColumns, LOG_SIZE},
+    relations::Relations,
+};
+
+/// Multiplicities of the even and odd bits relations of the spread sum table, looked up by the
+/// sigma functions, and by Ch and Maj respectively. The columns of each chunk of the table are
+/// `[even_mult, odd_mult]`.
+pub fn gen_trace(
+    log_size: u32,
+    scheduling_lookups: &LookupColumns,
+    compression_lookups: &LookupColumns,
+) -> Vec<Vec<u32x16>> {
+    // One task per lookup, the even bits lookups first
+    let components = [scheduling_lookups, compression_lookups];
+    let even_lookups = table_lookups(&components, Table::Even);
+    let odd_lookups = table_lookups(&components, Table::Odd);
+    let n_even = even_lookups.len();
+    let [even_mult, odd_mult] = par_histograms(
+        [LOG_SIZE, LOG_SIZE],
+        n_even + odd_lookups.len(),
+        |task, [even_mult, odd_mult]| {
+            if task < n_even {
+                even_mult.add_all(&even_lookups[task][0]);
+            } else {
+                odd_mult.add_all(&odd_lookups[task - n_even][0]);
+            }
+        },
+    );
+
+    even_mult
+        .into_columns(log_size)
+        .into_iter()
+        .interleave(odd_mult.into_columns(log_size))
+        .collect()
+}
+
+pub fn gen_interaction_trace(
+    trace: &[Vec<u32x16>],
+    relations: &Relations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let preprocessed_columns = spread_sum::gen_column_simd();
+    let SpreadSumColumns { sum, even, odd } =
+        SpreadSumColumns::from_slice(&preprocessed_columns[..]);
+
+    let simd_size = trace[0].len();
+    let log_size = simd_size.ilog2() + LOG_N_LANES;
+    let mut interaction_trace = LogupTraceGenerator::new(log_size);
+
+    let even_den = combine!(relations.spread_sum.even, [sum, even]);
+    let odd_den = combine!(relations.spread_sum.odd, [sum, odd]);
+
+    for ([even_mult, odd_mult], (even_den, odd_den)) in trace
+        .array_chunks::<2>()
+        .zip(even_den.chunks(simd_size).zip(odd_den.chunks(simd_size)))
+    {
+        write_pair!(
+            even_mult
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            even_den.to_vec(),
+            odd_mult
+                .iter()
+                .map(|v| unsafe { PackedM31::from_simd_unchecked(*v) })
+                .map(PackedQM31::from),
+            odd_den.to_vec(),
+            interaction_trace
+        );
+    }
+
+    interaction_trace.finalize_last()
+}
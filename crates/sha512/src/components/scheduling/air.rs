@@ -0,0 +1,96 @@
+use num_traits::One;
+use stwo_constraint_framework::{EvalAtRow, FrameworkComponent, FrameworkEval, RelationEntry};
+
+use crate::{
+    components::{
+        lookups::Lookups,
+        word::air::{eval_add, eval_sigma, Word},
+        W_SIZE,
+    },
+    partitions::{SMALL_SIGMA_0, SMALL_SIGMA_1},
+    relations::Relations,
+    sha512::N_COMPRESSION_ROUNDS,
+};
+
+pub type Component = FrameworkComponent<Eval>;
+
+fn eval_scheduling_constraints<E: EvalAtRow>(eval: &mut E, relations: &Relations) {
+    let w: [E::F; W_SIZE] = std::array::from_fn(|_| eval.next_trace_mask());
+    let word = |t: usize| -> Word<E::F> { std::array::from_fn(|l| w[4 * t + l].clone()) };
+
+    // W[t] = W[t - 16] + σ0(W[t - 15]) + W[t - 7] + σ1(W[t - 2])
+    let mut lookups = Lookups::new();
+    for t in 16..N_COMPRESSION_ROUNDS {
+        let sigma_0 = eval_sigma(eval, &mut lookups, SMALL_SIGMA_0, &word(t - 15));
+        let sigma_1 = eval_sigma(eval, &mut lookups, SMALL_SIGMA_1, &word(t - 2));
+        eval_add(
+            eval,
+            &mut lookups,
+            &[&word(t - 16), &sigma_0, &word(t - 7), &sigma_1],
+            &word(t),
+        );
+    }
+    lookups.add_to_eval(eval, relations);
+
+    // Emit the schedule, consumed by the compression of the same block
+    eval.add_to_relation(RelationEntry::new(&relations.w, E::EF::one(), &w));
+
+    eval.finalize_logup_in_pairs();
+}
+
+#[derive(Clone)]
+pub struct Eval {
+    pub log_size: u32,
+    pub relations: Relations,
+}
+impl FrameworkEval for Eval {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        self.log_size() + 1
+    }
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        eval_scheduling_constraints(&mut eval, &self.relations);
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use stwo::core::{pcs::TreeVec, poly::circle::CanonicCoset};
+    use stwo_constraint_framework::assert_constraints_on_polys;
+
+    use super::*;
+    use crate::{
+        components::scheduling::witness::{gen_interaction_trace, gen_trace},
+        message::gen_blocks,
+        sha512::HashFunction,
+    };
+
+    #[test]
+    fn test_scheduling_constraints() {
+        const LOG_N_ROWS: u32 = 4;
+
+        let messages = [vec![0x42; 200], b"abc".to_vec(), vec![]];
+        let blocks = gen_blocks(&messages);
+        let (trace, lookup_data) = gen_trace(LOG_N_ROWS, &blocks, HashFunction::Sha512);
+
+        let relations = Relations::dummy();
+        let (interaction_trace, claimed_sum) = gen_interaction_trace(&lookup_data, &relations);
+
+        let traces = TreeVec::new(vec![vec![], trace, interaction_trace]);
+        let trace_polys =
+            traces.map(|trace| trace.into_iter().map(|c| c.interpolate()).collect_vec());
+
+        assert_constraints_on_polys(
+            &trace_polys,
+            CanonicCoset::new(LOG_N_ROWS),
+            |mut eval| {
+                eval_scheduling_constraints(&mut eval, &relations);
+            },
+            claimed_sum,
+        );
+    }
+}
@@ -0,0 +1,16 @@
+//! The scheduling component is responsible for proving the message schedule of each block.
+//!
+//! The 80 words of the schedule are committed as limbs, each of the 64 derived words being
+//! constrained from the sigma functions of earlier words. The schedule is emitted whole to the
+//! compression component of the same row.
+
+pub mod air;
+pub mod witness;
+
+use crate::{
+    components::word::{sigma_size, CARRIES_SIZE},
+    partitions::{SMALL_SIGMA_0, SMALL_SIGMA_1},
+};
+
+/// Columns of a scheduling round.
+pub const ROUND_SIZE: usize = sigma_size(SMALL_SIGMA_0) + sigma_size(SMALL_SIGMA_1) + CARRIES_SIZE;
@@ -0,0 +1,185 @@
+use std::simd::u32x16;
+
+use num_traits::One;
+use stwo::{
+    core::{
+        fields::{m31::BaseField, qm31::QM31},
+        poly::circle::CanonicCoset,
+        ColumnVec,
+    },
+    prover::{
+        backend::simd::{
+            column::BaseColumn,
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedQM31,
+            SimdBackend,
+        },
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+    },
+};
+use stwo_constraint_framework::{LogupTraceGenerator, Relation};
+use utils::{combine, simd::for_each_row_chunk};
+
+use crate::{
+    components::{
+        lookups::{n_lookup_columns, FractionWriter, LookupColumns, LookupCounts, Lookups},
+        scheduling::ROUND_SIZE,
+        word::witness::{add_limbs, write_carries, write_sigma, RowWriter},
+        W_SIZE,
+    },
+    message::{block_column, Block},
+    partitions::{SMALL_SIGMA_0, SMALL_SIGMA_1},
+    relations::Relations,
+    sha512::{HashFunction, CHUNK_SIZE, N_COMPRESSION_ROUNDS, N_SCHEDULING_ROUNDS},
+};
+
+// Trace layout: the schedule, then the rounds deriving its last 64 words.
+const ROUNDS_OFFSET: usize = W_SIZE;
+const N_COLUMNS: usize = ROUNDS_OFFSET + ROUND_SIZE * N_SCHEDULING_ROUNDS;
+
+/// Lookups of the scheduling rounds, by table: two sigma evaluations, each with 8 even bits
+/// lookups, and 4 limb additions per round.
+pub const N_LOOKUPS: LookupCounts = [
+    4 * N_SCHEDULING_ROUNDS,
+    4 * N_SCHEDULING_ROUNDS,
+    0,
+    0,
+    0,
+    16 * N_SCHEDULING_ROUNDS,
+    0,
+    4 * N_SCHEDULING_ROUNDS,
+];
+
+pub struct LookupData {
+    /// Limbs of the schedule.
+    pub w: Vec<Vec<u32x16>>,
+    pub lookups: LookupColumns,
+}
+
+#[allow(clippy::type_complexity)]
+pub fn gen_trace(
+    log_size: u32,
+    blocks: &[Block],
+    hash_function: HashFunction,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    LookupData,
+) {
+    assert!(log_size >= LOG_N_LANES);
+    assert!(blocks.len() <= 1 << log_size);
+    let simd_size = 1 << (log_size - LOG_N_LANES);
+
+    // Initialize vec for all groups of columns
+    let mut evals: Vec<Vec<u32x16>> = (0..N_COLUMNS)
+        .map(|_| vec![u32x16::splat(0); simd_size])
+        .collect::<Vec<_>>();
+    let mut lookup_data: Vec<Vec<u32x16>> = (0..W_SIZE + n_lookup_columns(&N_LOOKUPS))
+        .map(|_| vec![u32x16::splat(0); simd_size])
+        .collect::<Vec<_>>();
+
+    // Message words
+    for i in 0..4 * CHUNK_SIZE {
+        evals[i] = block_column(hash_function, blocks, simd_size, |block| {
+            ((block.words[i / 4] >> (16 * (i % 4))) & 0xffff) as u32
+        });
+    }
+
+    for_each_row_chunk(&mut evals, &mut lookup_data, |evals, lookup_data| {
+        let mut lookups = Lookups::new();
+        for row in 0..evals[0].len() {
+            let mut w: Vec<[u32x16; 4]> = (0..CHUNK_SIZE)
+                .map(|t| std::array::from_fn(|l| evals[4 * t + l][row]))
+                .collect();
+
+            let (w_columns, round_columns) = evals.split_at_mut(ROUNDS_OFFSET);
+            let mut writer = RowWriter::new(round_columns, row);
+            for t in CHUNK_SIZE..N_COMPRESSION_ROUNDS {
+                let sigma_0 = write_sigma(&mut writer, &mut lookups, SMALL_SIGMA_0, &w[t - 15]);
+                let sigma_1 = write_sigma(&mut writer, &mut lookups, SMALL_SIGMA_1, &w[t - 2]);
+                let (word, carries) = add_limbs(&[&w[t - 16], &sigma_0, &w[t - 7], &sigma_1]);
+                write_carries(&mut writer, &mut lookups, &word, carries);
+                w.push(word);
+            }
+            writer.finish();
+
+            for (i, limb) in w.iter().flatten().enumerate() {
+                w_columns[i][row] = *limb;
+                lookup_data[i][row] = *limb;
+            }
+            lookups.write_row(&mut lookup_data[W_SIZE..], row);
+            lookups.clear();
+        }
+    });
+
+    let domain = CanonicCoset::new(log_size).circle_domain();
+    let trace = evals
+        .into_iter()
+        .map(|eval| {
+            let column = BaseColumn::from_simd(
+                eval.into_iter()
+                    .map(|x| unsafe { PackedM31::from_simd_unchecked(x) })
+                    .collect(),
+            );
+            CircleEvaluation::<SimdBackend, BaseField, BitReversedOrder>::new(domain, column)
+        })
+        .collect::<Vec<_>>();
+
+    let lookups = lookup_data.split_off(W_SIZE);
+    (
+        trace,
+        LookupData {
+            w: lookup_data,
+            lookups: LookupColumns::from_columns(lookups, &N_LOOKUPS),
+        },
+    )
+}
+
+pub fn gen_interaction_trace(
+    lookup_data: &LookupData,
+    relations: &Relations,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    QM31,
+) {
+    let simd_size = lookup_data.w[0].len();
+    let mut interaction_trace = LogupTraceGenerator::new(simd_size.ilog2() + LOG_N_LANES);
+    let mut writer = FractionWriter::new(&mut interaction_trace);
+
+    // Range check the additions and look up the sigma functions
+    for denoms in lookup_data.lookups.denoms(relations) {
+        writer.consume(denoms);
+    }
+
+    // Emit the schedule
+    let w = combine!(relations.w, &lookup_data.w);
+    writer.write(vec![PackedQM31::one(); simd_size], w);
+    writer.finalize();
+
+    interaction_trace.finalize_last()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{message::gen_blocks, sha512::schedule};
+
+    #[test]
+    fn test_gen_trace_values() {
+        let blocks = gen_blocks(&[vec![0x42; 200], b"abc".to_vec()]);
+        let (trace, lookup_data) = gen_trace(4, &blocks, HashFunction::Sha512);
+        assert_eq!(trace.len(), N_COLUMNS);
+
+        for (row, block) in blocks.iter().enumerate() {
+            for (t, word) in schedule(block.words).into_iter().enumerate() {
+                let limbs = (0..4).map(|l| lookup_data.w[4 * t + l][row / 16][row % 16] as u64);
+                assert_eq!(
+                    limbs
+                        .enumerate()
+                        .map(|(l, limb)| limb << (16 * l))
+                        .sum::<u64>(),
+                    word
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use num_traits::Zero;
+use stwo::core::fields::m31::M31;
+use stwo_constraint_framework::EvalAtRow;
+
+use crate::{
+    components::lookups::{Lookups, Table},
+    partitions::SIGMAS,
+};
+
+/// A 64-bit word, as 16-bit limbs, lowest first.
+pub type Word<F> = [F; 4];
+
+/// A word committed as its bytes, lowest first, together with their spread forms.
+#[derive(Debug, Clone)]
+pub struct SpreadWord<F> {
+    pub bytes: [F; 8],
+    pub spreads: [F; 8],
+}
+
+pub fn constant<E: EvalAtRow>(value: u64) -> Word<E::F> {
+    std::array::from_fn(|l| E::F::from(M31::from(((value >> (16 * l)) & 0xffff) as u32)))
+}
+
+/// Limbs of the word made of `bytes`.
+pub fn limbs<E: EvalAtRow>(bytes: &[E::F; 8]) -> Word<E::F> {
+    let two_8 = E::F::from(M31::from(1 << 8));
+    std::array::from_fn(|l| bytes[2 * l].clone() + bytes[2 * l + 1].clone() * two_8.clone())
+}
+
+/// Reads a word committed as bytes and spread bytes. Looking up each byte with its spread form
+/// range checks the bytes.
+pub fn eval_spread_word<E: EvalAtRow>(
+    eval: &mut E,
+    lookups: &mut Lookups<E::F>,
+) -> SpreadWord<E::F> {
+    let bytes: [E::F; 8] = std::array::from_fn(|_| eval.next_trace_mask());
+    let spreads: [E::F; 8] = std::array::from_fn(|_| eval.next_trace_mask());
+    for (byte, spread) in bytes.iter().zip(&spreads) {
+        lookups.push(Table::Spread, [byte.clone(), spread.clone()]);
+    }
+    SpreadWord { bytes, spreads }
+}
+
+/// Constrains `sigma` of `x` and returns it.
+///
+/// Each limb of `x` is looked up with the spread sums of its part, and each output byte is the
+/// even bits of the sum of the parts reaching it, see [`crate::partitions`]. This range checks the
+/// limbs of `x` and the output bytes.
+pub fn eval_sigma<E: EvalAtRow>(
+    eval: &mut E,
+    lookups: &mut Lookups<E::F>,
+    sigma: usize,
+    x: &Word<E::F>,
+) -> Word<E::F> {
+    let function = SIGMAS[sigma];
+    let parts: [Vec<E::F>; 4] = std::array::from_fn(|_| {
+        (0..function.n_sums())
+            .map(|_| eval.next_trace_mask())
+            .collect()
+    });
+    let output: [E::F; 8] = std::array::from_fn(|_| eval.next_trace_mask());
+
+    let mut sums: [E::F; 8] = std::array::from_fn(|_| E::F::zero());
+    for (limb, (value, part)) in x.iter().zip(&parts).enumerate() {
+        let index = E::F::from(M31::from(limb as u32));
+        lookups.push(
+            Table::Sigma(sigma),
+            [index, value.clone()]
+                .into_iter()
+                .chain(part.iter().cloned()),
+        );
+        for (i, sum) in part.iter().enumerate() {
+            let byte = function.output_byte(limb, i);
+            sums[byte] = sums[byte].clone() + sum.clone();
+        }
+    }
+    for (sum, byte) in sums.into_iter().zip(&output) {
+        lookups.push(Table::Even, [sum, byte.clone()]);
+    }
+
+    limbs::<E>(&output)
+}
+
+/// Constrains `sum` to be the sum of `terms` modulo 2^64. The carries are committed after it and
+/// range checked together with the limbs of `sum`, which bounds the number of terms to 8.
+pub fn eval_add<E: EvalAtRow>(
+    eval: &mut E,
+    lookups: &mut Lookups<E::F>,
+    terms: &[&Word<E::F>],
+    sum: &Word<E::F>,
+) {
+    debug_assert!(terms.len() <= 8);
+    let carries: [E::F; 4] = std::array::from_fn(|_| eval.next_trace_mask());
+    let two_16 = E::F::from(M31::from(1 << 16));
+    for l in 0..4 {
+        let carry_in = match l {
+            0 => E::F::zero(),
+            _ => carries[l - 1].clone(),
+        };
+        let total = terms
+            .iter()
+            .fold(carry_in, |acc, term| acc + term[l].clone());
+        eval.add_constraint(total - sum[l].clone() - carries[l].clone() * two_16.clone());
+        lookups.push(Table::RangeCheckAdd, [sum[l].clone(), carries[l].clone()]);
+    }
+}
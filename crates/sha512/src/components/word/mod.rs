@@ -0,0 +1,21 @@
+//! Constraints and witness of the 64-bit word operations shared by the scheduling and compression
+//! components: spread words, sigma functions and additions.
+//!
+//! The AIR reads the columns of a row one after the other, and the witness writes them in the same
+//! order, see [`witness::RowWriter`]. Both collect the lookups of the row in the same order, see
+//! [`crate::components::lookups`].
+
+pub mod air;
+pub mod witness;
+
+/// Columns of a word committed as bytes and spread bytes.
+pub const SPREAD_WORD_SIZE: usize = 16;
+
+/// Columns of the carries of an addition.
+pub const CARRIES_SIZE: usize = 4;
+
+/// Columns of an evaluation of `sigma`: the spread sums of the parts of the 4 limbs, then the 8
+/// output bytes.
+pub const fn sigma_size(sigma: usize) -> usize {
+    4 * crate::partitions::SIGMAS[sigma].n_sums() + 8
+}
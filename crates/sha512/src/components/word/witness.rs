@@ -0,0 +1,185 @@
+use std::simd::u32x16;
+
+use crate::{
+    components::lookups::{Lookups, Table},
+    partitions::{even_bits_u32x16, spread_u32x16, SIGMAS},
+};
+
+/// Writes the trace columns of a row one after the other, in the order the AIR reads them.
+pub struct RowWriter<'a, 'b> {
+    columns: &'a mut [&'b mut [u32x16]],
+    row: usize,
+    next: usize,
+}
+
+impl<'a, 'b> RowWriter<'a, 'b> {
+    pub fn new(columns: &'a mut [&'b mut [u32x16]], row: usize) -> Self {
+        Self {
+            columns,
+            row,
+            next: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub fn write(&mut self, value: u32x16) {
+        self.columns[self.next][self.row] = value;
+        self.next += 1;
+    }
+
+    #[inline(always)]
+    pub fn write_all(&mut self, values: impl IntoIterator<Item = u32x16>) {
+        values.into_iter().for_each(|value| self.write(value));
+    }
+
+    /// Checks that all the columns were written.
+    pub fn finish(self) {
+        debug_assert_eq!(self.next, self.columns.len());
+    }
+}
+
+pub fn constant(value: u64) -> [u32x16; 4] {
+    std::array::from_fn(|l| u32x16::splat(((value >> (16 * l)) & 0xffff) as u32))
+}
+
+#[inline(always)]
+pub fn to_bytes(limbs: &[u32x16; 4]) -> [u32x16; 8] {
+    std::array::from_fn(|i| (limbs[i / 2] >> (8 * (i % 2) as u32)) & u32x16::splat(0xff))
+}
+
+#[inline(always)]
+pub fn from_bytes(bytes: &[u32x16; 8]) -> [u32x16; 4] {
+    std::array::from_fn(|l| bytes[2 * l] | (bytes[2 * l + 1] << 8))
+}
+
+/// Writes the bytes of `limbs` and their spread forms, see [`super::air::eval_spread_word`], and
+/// returns the spread forms.
+#[inline(always)]
+pub fn write_spread_word(
+    writer: &mut RowWriter<'_, '_>,
+    lookups: &mut Lookups<u32x16>,
+    limbs: &[u32x16; 4],
+) -> [u32x16; 8] {
+    let bytes = to_bytes(limbs);
+    let spreads = bytes.map(spread_u32x16);
+    writer.write_all(bytes);
+    writer.write_all(spreads);
+    for (byte, spread) in bytes.into_iter().zip(spreads) {
+        lookups.push(Table::Spread, [byte, spread]);
+    }
+    spreads
+}
+
+/// Writes the columns of `sigma` of `x`, see [`super::air::eval_sigma`], and returns it.
+#[inline(always)]
+pub fn write_sigma(
+    writer: &mut RowWriter<'_, '_>,
+    lookups: &mut Lookups<u32x16>,
+    sigma: usize,
+    x: &[u32x16; 4],
+) -> [u32x16; 4] {
+    let function = SIGMAS[sigma];
+    let parts: [[u32x16; 8]; 4] = std::array::from_fn(|limb| function.part_u32x16(limb, x[limb]));
+
+    let mut sums = [u32x16::splat(0); 8];
+    for (limb, part) in parts.iter().enumerate() {
+        for i in 0..function.n_sums() {
+            let byte = function.output_byte(limb, i);
+            writer.write(part[byte]);
+            sums[byte] += part[byte];
+        }
+    }
+    let output = sums.map(even_bits_u32x16);
+    writer.write_all(output);
+
+    for (limb, part) in parts.iter().enumerate() {
+        let values = (0..function.n_sums()).map(|i| part[function.output_byte(limb, i)]);
+        lookups.push(
+            Table::Sigma(sigma),
+            [u32x16::splat(limb as u32), x[limb]]
+                .into_iter()
+                .chain(values),
+        );
+    }
+    for (sum, byte) in sums.into_iter().zip(output) {
+        lookups.push(Table::Even, [sum, byte]);
+    }
+
+    from_bytes(&output)
+}
+
+/// Sum of `terms` modulo 2^64, together with the carries of its limbs.
+#[inline(always)]
+pub fn add_limbs(terms: &[&[u32x16; 4]]) -> ([u32x16; 4], [u32x16; 4]) {
+    let mut sum = [u32x16::splat(0); 4];
+    let mut carries = [u32x16::splat(0); 4];
+    let mut carry = u32x16::splat(0);
+    for l in 0..4 {
+        let total = terms.iter().fold(carry, |acc, term| acc + term[l]);
+        sum[l] = total & u32x16::splat(0xffff);
+        carry = total >> 16;
+        carries[l] = carry;
+    }
+    (sum, carries)
+}
+
+/// Writes the carries of the addition giving `sum`, see [`super::air::eval_add`].
+#[inline(always)]
+pub fn write_carries(
+    writer: &mut RowWriter<'_, '_>,
+    lookups: &mut Lookups<u32x16>,
+    sum: &[u32x16; 4],
+    carries: [u32x16; 4],
+) {
+    writer.write_all(carries);
+    for (limb, carry) in sum.iter().zip(carries) {
+        lookups.push(Table::RangeCheckAdd, [*limb, carry]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::partitions::BIG_SIGMA_1;
+
+    fn word(limbs: &[u32x16; 4]) -> u64 {
+        limbs
+            .iter()
+            .enumerate()
+            .map(|(l, limb)| (limb[0] as u64) << (16 * l))
+            .sum()
+    }
+
+    #[test]
+    fn test_add_limbs() {
+        let terms = [u64::MAX, 0x8000_0000_0000_0001, 0x0123_4567_89ab_cdef].map(constant);
+        let (sum, carries) = add_limbs(&terms.each_ref());
+        assert_eq!(
+            word(&sum),
+            u64::MAX
+                .wrapping_add(0x8000_0000_0000_0001)
+                .wrapping_add(0x0123_4567_89ab_cdef)
+        );
+        assert!(carries.iter().all(|carry| carry[0] < 8));
+        assert_eq!(from_bytes(&to_bytes(&sum)), sum);
+    }
+
+    #[test]
+    fn test_write_sigma() {
+        let x: u64 = 0xdeadbeefcafebabe;
+        let size = super::super::sigma_size(BIG_SIGMA_1);
+        let mut columns = vec![vec![u32x16::splat(0); 1]; size];
+        let mut rows = columns
+            .iter_mut()
+            .map(|column| column.as_mut_slice())
+            .collect::<Vec<_>>();
+        let mut writer = RowWriter::new(&mut rows, 0);
+        let mut lookups = Lookups::new();
+        let y = write_sigma(&mut writer, &mut lookups, BIG_SIGMA_1, &constant(x));
+        writer.finish();
+
+        assert_eq!(word(&y), SIGMAS[BIG_SIGMA_1].eval(x));
+        assert_eq!(lookups.get(Table::Sigma(BIG_SIGMA_1)).count(), 4);
+        assert_eq!(lookups.get(Table::Even).count(), 8);
+    }
+}
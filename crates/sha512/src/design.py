@@ -0,0 +1,179 @@
+#!/usr/bin/env python3.12
+
+"""
+Notebook to compute the input space splits of the SHA-512 sigma functions.
+
+Summary:
+- words are 64 bits, laid out as four 16-bit limbs
+- the two-way lattice split of the SHA-256 AIR does not carry over: with 64 input bits, a
+  partition keeping each lookup below 2**21 needs at least four parts
+- the best lattice splits in four parts leave 25 to 36 output bits affected by several parts,
+  which then need their own XOR lookups
+- the AIR splits on the 16-bit limbs instead: each limb is looked up on its own and gives, for
+  each output byte it reaches, the sum of the spread forms of its three terms. Summing the parts
+  of the four limbs gives the spread sum of each output byte, whose even bits are the output
+  byte, see `partitions.rs`. This trades the mixed bits for 3 to 7 sums per limb, and keeps the
+  sigma tables at 2**18 rows.
+"""
+
+# %% Imports
+import itertools
+import random
+
+# %% SHA512 functions
+MASK = (1 << 64) - 1
+
+SIGMA_PARAMS = {
+    "small_sigma_0": (1, 8, 7),
+    "small_sigma_1": (19, 61, 6),
+    "big_sigma_0": (28, 34, 39),
+    "big_sigma_1": (14, 18, 41),
+}
+
+
+def rotr(x, n):
+    """
+    Rotate right by n bits
+    """
+    return ((x >> n) | (x << (64 - n))) & MASK
+
+
+def small_sigma(x, *params):
+    """
+    SHA-512 σ function used in message scheduling.
+    """
+    return rotr(x, params[0]) ^ rotr(x, params[1]) ^ (x >> params[2])
+
+
+def big_sigma(x, *params):
+    """
+    SHA-512 Σ function used in the compression function.
+    """
+    return rotr(x, params[0]) ^ rotr(x, params[1]) ^ rotr(x, params[2])
+
+
+def sources(bit, name):
+    """
+    Input bits XORed into the output bit `bit` of the sigma function `name`.
+    """
+    a, b, c = SIGMA_PARAMS[name]
+    bits = [(bit + a) % 64, (bit + b) % 64]
+    if name.startswith("small"):
+        if bit + c < 64:
+            bits.append(bit + c)
+    else:
+        bits.append((bit + c) % 64)
+    return bits
+
+
+# %% Check the bit sources against the functions
+for name, params in SIGMA_PARAMS.items():
+    function = small_sigma if name.startswith("small") else big_sigma
+    for _ in range(100):
+        x = random.getrandbits(64)
+        y = function(x, *params)
+        for bit in range(64):
+            expected = 0
+            for source in sources(bit, name):
+                expected ^= (x >> source) & 1
+            assert (y >> bit) & 1 == expected
+
+# %% Compute input space splits
+#'
+#' As for SHA-256, bit i of the output is the XOR of bits i+n0, i+n1 and i+n2 of the input. A
+#' partition of the input bits is good when most output bits only depend on one part, so that
+#' they can be looked up from this part alone. We search the partitions following the 1D lattice
+#' (i * g + offset) mod 64, cut in four intervals of 16 bits.
+
+
+def mixed_bits(parts, name):
+    """
+    Number of output bits depending on several parts of the input.
+    """
+    owner = {bit: j for j, part in enumerate(parts) for bit in part}
+    return sum(len({owner[source] for source in sources(bit, name)}) > 1 for bit in range(64))
+
+
+def lattice_partition(g, offset, n_parts=4):
+    parts = [[] for _ in range(n_parts)]
+    for bit in range(64):
+        parts[((bit * g + offset) % 64) * n_parts // 64].append(bit)
+    return parts
+
+
+best_partitions = {}
+for name in SIGMA_PARAMS:
+    best_partitions[name] = min(
+        (
+            (mixed_bits(lattice_partition(g, offset), name), g, offset)
+            for g, offset in itertools.product(range(1, 64, 2), range(64))
+        )
+    )
+    print(name, best_partitions[name])
+
+#' small_sigma_0 (28, 1, 0)
+#' small_sigma_1 (25, 29, 0)
+#' big_sigma_0 (36, 11, 0)
+#' big_sigma_1 (36, 31, 0)
+#'
+#' With SHA-256, the mixed bits are a minority and are recombined with a single XOR lookup. Here,
+#' about half of the output bits are mixed, and they depend on up to three parts. The next step is
+#' to compare these lattice splits with a generic search, or with splits on more, smaller, parts
+#' at the cost of more lookups per round.
+
+
+# %% Limb partition
+def spread(byte):
+    return sum(((byte >> i) & 1) << (2 * i) for i in range(8))
+
+
+def even_bits(x):
+    return sum(((x >> (2 * i)) & 1) << i for i in range(8))
+
+
+def terms(x, name):
+    a, b, c = SIGMA_PARAMS[name]
+    last = x >> c if name.startswith("small") else rotr(x, c)
+    return [rotr(x, a), rotr(x, b), last]
+
+
+def limb_offsets(name):
+    """
+    Output bytes reached by a limb, relative to its first byte, over all limbs. The table stores
+    one sum per offset, some of them always zero for a given limb.
+    """
+    offsets = set()
+    for limb in range(4):
+        x = 0xFFFF << (16 * limb)
+        for byte in range(8):
+            if any((t >> (8 * byte)) & 0xFF for t in terms(x, name)):
+                offsets.add((byte - 2 * limb) % 8)
+    return sorted(offsets)
+
+
+def limb_sigma(x, name):
+    sums = [0] * 8
+    for limb in range(4):
+        part = ((x >> (16 * limb)) & 0xFFFF) << (16 * limb)
+        for byte in range(8):
+            sums[byte] += sum(spread((t >> (8 * byte)) & 0xFF) for t in terms(part, name))
+    assert all(s < 1 << 16 for s in sums)
+    return sum(even_bits(s) << (8 * byte) for byte, s in enumerate(sums))
+
+
+for name in SIGMA_PARAMS:
+    print(name, limb_offsets(name))
+    for _ in range(1000):
+        x = random.getrandbits(64)
+        sigma = small_sigma if name.startswith("small") else big_sigma
+        assert limb_sigma(x, name) == sigma(x, *SIGMA_PARAMS[name])
+
+#' small_sigma_0 [0, 1, 7]
+#' small_sigma_1 [0, 1, 2, 5, 6, 7]
+#' big_sigma_0 [3, 4, 5, 6]
+#' big_sigma_1 [0, 2, 3, 4, 5, 6, 7]
+#'
+#' A spread sum counts at most 3 set bits per position, so it fits on 16 bits and its even bits
+#' are looked up in the 2**16 rows spread sum table. Ch and Maj use the same table on the spread
+#' bytes of the working variables: Maj is the odd bits of the sum of three spread bytes, and Ch
+#' the sum of two AND, `odd(e + f)` and `odd(~e + g)`.
@@ -0,0 +1,165 @@
+//! SHA-512 and SHA-384 AIR.
+//!
+//! Each row proves the compression of one message block: the 80-round message schedule in the
+//! scheduling component, and the 80 rounds with the feed-forward in the compression component.
+//! 64-bit words are handled as four 16-bit limbs, and the sigma functions are partitioned limb by
+//! limb into lookups, see [`partitions`] and `design.py` for the exploration of the partitions.
+#![allow(non_camel_case_types)]
+#![feature(portable_simd, array_chunks, iter_array_chunks)]
+pub mod components;
+pub mod message;
+pub mod partitions;
+pub mod preprocessed;
+pub mod prover;
+pub mod public;
+pub mod relations;
+pub mod sha512;
+pub mod verifier;
+
+use stwo::{
+    core::{channel::MerkleChannel, pcs::PcsConfig, proof::StarkProof},
+    prover::backend::{simd::SimdBackend, BackendForChannel},
+};
+use utils::stwo::air::ProveError;
+
+use crate::{components::ClaimedSum, message::Block, sha512::HashFunction};
+pub use crate::{
+    prover::Sha512Prover,
+    public::HashClaim,
+    verifier::{preprocessed_root, verify_sha384, verify_sha512, verify_with, Sha512VerifierError},
+};
+
+/// Largest supported trace log size: each row does about 4224 lookups into the spread sum
+/// table, and the multiplicities must fit in M31.
+pub const MAX_LOG_SIZE: u32 = 18;
+
+/// Errors of the SHA-512 prover.
+pub type Sha512ProverError = ProveError<ClaimedSum>;
+
+/// Proves the SHA-512 compression of each message block, see [`message::gen_blocks`] to split
+/// messages into chained blocks.
+///
+/// Blocks of the same message are linked through the chain relation, so they must appear in
+/// order and with consistent chaining values. The batch is padded with standalone zero blocks,
+/// which emit no public claim: each block claims its message words and each message its digest,
+/// see [`public`]. Verify with [`verify_sha512`].
+pub fn prove_sha512<MC: MerkleChannel>(
+    blocks: &[Block],
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha512ProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    Sha512Prover::<MC>::new(log_size, config)?.prove(blocks)
+}
+
+/// Same as [`prove_sha512`] for SHA-384, which shares the compression and the preprocessed tables
+/// of SHA-512: the blocks start from the SHA-384 initial hash value, see
+/// [`message::gen_blocks_for`], and only the first 6 words of each output chaining value are
+/// claimed. Verify with [`verify_sha384`].
+pub fn prove_sha384<MC: MerkleChannel>(
+    blocks: &[Block],
+    log_size: u32,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha512ProverError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    Sha512Prover::<MC>::new(log_size, config)?.prove_with(HashFunction::Sha384, blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha384, Sha512};
+    use stwo::{
+        core::vcs::blake2_merkle::Blake2sMerkleChannel, prover::backend::simd::m31::LOG_N_LANES,
+    };
+
+    use super::*;
+    use crate::message::{gen_blocks, gen_blocks_for};
+
+    fn messages() -> Vec<Vec<u8>> {
+        vec![vec![0xab; 300], b"abc".to_vec(), vec![], vec![0x42; 112]]
+    }
+
+    #[test_log::test]
+    fn test_verify_sha512() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = messages();
+        let (proof, claimed_sum) =
+            prove_sha512::<Blake2sMerkleChannel>(&gen_blocks(&messages), log_size, config).unwrap();
+        let claims = messages
+            .iter()
+            .map(|message| HashClaim::new(message.clone()))
+            .collect::<Vec<_>>();
+        for (message, claim) in messages.iter().zip(&claims) {
+            assert_eq!(claim.digest[..], Sha512::digest(message)[..]);
+        }
+        verify_sha512::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_sha384() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = messages();
+        let blocks = gen_blocks_for(HashFunction::Sha384, &messages);
+        let (proof, claimed_sum) =
+            prove_sha384::<Blake2sMerkleChannel>(&blocks, log_size, config).unwrap();
+        let claims = messages
+            .iter()
+            .map(|message| HashClaim::with_hash_function(HashFunction::Sha384, message.clone()))
+            .collect::<Vec<_>>();
+        for (message, claim) in messages.iter().zip(&claims) {
+            assert_eq!(claim.digest[..], Sha384::digest(message)[..]);
+        }
+        verify_sha384::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims)
+            .unwrap();
+    }
+
+    #[test_log::test]
+    fn test_verify_sha512_wrong_digest() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = messages();
+        let (proof, claimed_sum) =
+            prove_sha512::<Blake2sMerkleChannel>(&gen_blocks(&messages), log_size, config).unwrap();
+        let mut claims = messages
+            .iter()
+            .map(|message| HashClaim::new(message.clone()))
+            .collect::<Vec<_>>();
+        claims[1].digest[63] ^= 1;
+        assert!(matches!(
+            verify_sha512::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims),
+            Err(Sha512VerifierError::InvalidLogupSum(_))
+        ));
+    }
+
+    #[test_log::test]
+    fn test_verify_sha384_wrong_digest_length() {
+        let log_size = 8;
+        let config = PcsConfig::default();
+        let messages = vec![b"abc".to_vec()];
+        let (proof, claimed_sum) =
+            prove_sha512::<Blake2sMerkleChannel>(&gen_blocks(&messages), log_size, config).unwrap();
+        let claims = [HashClaim::new(messages[0].clone())];
+        assert!(matches!(
+            verify_sha384::<Blake2sMerkleChannel>(proof, log_size, config, &claimed_sum, &claims),
+            Err(Sha512VerifierError::InvalidDigestLength(64))
+        ));
+    }
+
+    #[test]
+    fn test_prove_sha512_invalid_log_size() {
+        let blocks = gen_blocks(&[b"abc"]);
+        for log_size in [0, LOG_N_LANES - 1, MAX_LOG_SIZE + 1] {
+            assert!(matches!(
+                prove_sha512::<Blake2sMerkleChannel>(&blocks, log_size, PcsConfig::default()),
+                Err(Sha512ProverError::InvalidLogSize { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Message handling: FIPS 180-4 padding and splitting of messages into chained blocks.
+//!
+//! SHA-512 and SHA-384 use 1024-bit blocks of 64-bit words, and append the message length on
+//! 128 bits.
+
+use std::simd::u32x16;
+
+use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+use crate::sha512::{process_chunk, HashFunction};
+
+/// A message block, together with its position in the message and its input chaining value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    /// Index of the message this block belongs to.
+    pub message_id: u32,
+    /// Index of the block in its message.
+    pub index: u32,
+    /// Whether this is the last block of its message.
+    pub is_last: bool,
+    /// Chaining value the compression starts from.
+    pub h_in: [u64; 8],
+    /// Message words, big-endian.
+    pub words: [u64; 16],
+}
+
+impl Block {
+    pub const fn is_first(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Output chaining value, i.e. the compression of `words` from `h_in` with feed-forward.
+    pub fn h_out(&self) -> [u64; 8] {
+        process_chunk(self.words, self.h_in)
+    }
+
+    /// A standalone zero block used to pad the trace: it starts from the initial hash value of
+    /// `hash_function` and is not chained to any other block.
+    pub const fn padding(hash_function: HashFunction) -> Self {
+        Self {
+            message_id: 0,
+            index: 0,
+            is_last: true,
+            h_in: hash_function.iv(),
+            words: [0; 16],
+        }
+    }
+}
+
+impl Default for Block {
+    /// The SHA-512 padding block, see [`Block::padding`].
+    fn default() -> Self {
+        Self::padding(HashFunction::Sha512)
+    }
+}
+
+/// Pads a message according to FIPS 180-4 and splits it into 1024-bit blocks.
+pub fn pad_message(message: &[u8]) -> Vec<[u64; 16]> {
+    let mut padded = Vec::from(message);
+    padded.push(0x80);
+    while !(padded.len() + 16).is_multiple_of(128) {
+        padded.push(0x00);
+    }
+    padded.extend_from_slice(&(message.len() as u128 * 8).to_be_bytes());
+
+    padded
+        .chunks_exact(128)
+        .map(|chunk| {
+            std::array::from_fn(|i| u64::from_be_bytes(chunk[8 * i..8 * i + 8].try_into().unwrap()))
+        })
+        .collect()
+}
+
+/// Pads each message and chains its blocks, starting from the SHA-512 initial hash value.
+pub fn gen_blocks<M: AsRef<[u8]>>(messages: &[M]) -> Vec<Block> {
+    gen_blocks_for(HashFunction::Sha512, messages)
+}
+
+/// Same as [`gen_blocks`], starting from the initial hash value of `hash_function`. SHA-384 pads
+/// its messages like SHA-512.
+pub fn gen_blocks_for<M: AsRef<[u8]>>(hash_function: HashFunction, messages: &[M]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    for (message_id, message) in messages.iter().enumerate() {
+        let chunks = pad_message(message.as_ref());
+        let n_chunks = chunks.len();
+        let mut h_in = hash_function.iv();
+        for (index, words) in chunks.into_iter().enumerate() {
+            let block = Block {
+                message_id: message_id as u32,
+                index: index as u32,
+                is_last: index == n_chunks - 1,
+                h_in,
+                words,
+            };
+            h_in = block.h_out();
+            blocks.push(block);
+        }
+    }
+    blocks
+}
+
+/// Digest of each message for `hash_function`, i.e. the output chaining value of its last block
+/// truncated to the digest length.
+pub fn digests(hash_function: HashFunction, blocks: &[Block]) -> Vec<Vec<u8>> {
+    blocks
+        .iter()
+        .filter(|block| block.is_last)
+        .map(|block| hash_function.digest(block.h_out()))
+        .collect()
+}
+
+/// Splits 64-bit words into 16-bit limbs, lowest limb first, as they are laid out in the trace.
+pub fn to_limbs(words: &[u64]) -> impl Iterator<Item = u32> + '_ {
+    words
+        .iter()
+        .flat_map(|word| (0..4).map(move |i| ((word >> (16 * i)) & 0xffff) as u32))
+}
+
+/// Builds a trace column from a value of each block, padding with the [`Block::padding`] blocks of
+/// `hash_function` up to `simd_size` SIMD rows.
+pub fn block_column(
+    hash_function: HashFunction,
+    blocks: &[Block],
+    simd_size: usize,
+    f: impl Fn(&Block) -> u32,
+) -> Vec<u32x16> {
+    let padding = Block::padding(hash_function);
+    (0..simd_size)
+        .map(|simd_row| {
+            u32x16::from_array(std::array::from_fn(|lane| {
+                f(blocks
+                    .get((simd_row << LOG_N_LANES) + lane)
+                    .unwrap_or(&padding))
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha384, Sha512};
+
+    use super::*;
+    use crate::sha512::{H, H_384};
+
+    #[test]
+    fn test_pad_message() {
+        assert_eq!(pad_message(b"").len(), 1);
+        assert_eq!(pad_message(&[0; 111]).len(), 1);
+        assert_eq!(pad_message(&[0; 112]).len(), 2);
+        assert_eq!(pad_message(&[0; 128]).len(), 2);
+        assert_eq!(pad_message(&[0; 240]).len(), 3);
+
+        let chunks = pad_message(b"abc");
+        assert_eq!(chunks[0][0], 0x6162638000000000);
+        assert_eq!(chunks[0][15], 24);
+    }
+
+    #[test]
+    fn test_gen_blocks() {
+        let messages: Vec<Vec<u8>> =
+            vec![b"".to_vec(), b"hello world".to_vec(), (0..=255).collect()];
+        let blocks = gen_blocks(&messages);
+        assert_eq!(blocks.len(), 1 + 1 + 3);
+
+        for message_id in 0..messages.len() {
+            let message_blocks = blocks
+                .iter()
+                .filter(|block| block.message_id == message_id as u32)
+                .collect::<Vec<_>>();
+            assert!(message_blocks[0].is_first());
+            assert_eq!(message_blocks[0].h_in, H);
+            for (i, pair) in message_blocks.windows(2).enumerate() {
+                assert_eq!(pair[0].index, i as u32);
+                assert!(!pair[0].is_last);
+                assert_eq!(pair[0].h_out(), pair[1].h_in);
+            }
+            assert!(message_blocks.last().unwrap().is_last);
+        }
+    }
+
+    #[test]
+    fn test_digests() {
+        let messages = [b"abc".to_vec(), vec![0x42; 300]];
+        let sha512 = digests(HashFunction::Sha512, &gen_blocks(&messages));
+        let sha384 = digests(
+            HashFunction::Sha384,
+            &gen_blocks_for(HashFunction::Sha384, &messages),
+        );
+        for (i, message) in messages.iter().enumerate() {
+            assert_eq!(sha512[i][..], Sha512::digest(message)[..]);
+            assert_eq!(sha384[i][..], Sha384::digest(message)[..]);
+        }
+        assert_eq!(
+            gen_blocks_for(HashFunction::Sha384, &messages)[0].h_in,
+            H_384
+        );
+    }
+
+    #[test]
+    fn test_to_limbs() {
+        assert_eq!(
+            to_limbs(&[0x0123456789abcdef]).collect::<Vec<_>>(),
+            [0xcdef, 0x89ab, 0x4567, 0x0123]
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Limb partition of the SHA-512 sigma functions, see `design.py`.
+//!
+//! Each output bit of a sigma function is the XOR of three input bits. The input word is cut into
+//! its four 16-bit limbs, each looked up on its own: the table of a sigma function gives, for a
+//! limb and each output byte the limb reaches, the sum of the spread forms of its three terms.
+//! Summing the parts of the four limbs counts the set input bits of each output bit, which are at
+//! most 3, and the parity of the count is the output bit, see [`crate::preprocessed::spread_sum`].
+//!
+//! The spread form of a byte interleaves its bits with zeros, so that a sum of up to three spread
+//! bytes counts the set bits of each position on 2 bits: the even bits of the sum are the XOR of
+//! the bytes, and the odd bits their majority, or their AND for two bytes. Ch and Maj are looked
+//! up the same way, from the spread forms of the bytes of the working variables.
+
+use std::simd::{num::SimdUint, u32x16, u64x16};
+
+pub const SMALL_SIGMA_0: usize = 0;
+pub const SMALL_SIGMA_1: usize = 1;
+pub const BIG_SIGMA_0: usize = 2;
+pub const BIG_SIGMA_1: usize = 3;
+
+/// `x >>> r0 ^ x >>> r1 ^ x >>> r2`, the last term being a right shift for the small sigma
+/// functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sigma {
+    pub rotations: [u32; 3],
+    pub shift: bool,
+    /// Output bytes reached by a limb, relative to the first byte of the limb. The part of a limb
+    /// has one spread sum per offset.
+    pub offsets: &'static [usize],
+}
+
+pub const SIGMAS: [Sigma; 4] = [
+    Sigma {
+        rotations: [1, 8, 7],
+        shift: true,
+        offsets: &[0, 1, 7],
+    },
+    Sigma {
+        rotations: [19, 61, 6],
+        shift: true,
+        offsets: &[0, 1, 2, 5, 6, 7],
+    },
+    Sigma {
+        rotations: [28, 34, 39],
+        shift: false,
+        offsets: &[3, 4, 5, 6],
+    },
+    Sigma {
+        rotations: [14, 18, 41],
+        shift: false,
+        offsets: &[0, 2, 3, 4, 5, 6, 7],
+    },
+];
+
+impl Sigma {
+    /// Number of spread sums of a part.
+    pub const fn n_sums(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// The three terms XORed by the function.
+    pub const fn terms(&self, x: u64) -> [u64; 3] {
+        let [r0, r1, r2] = self.rotations;
+        let last = if self.shift {
+            x >> r2
+        } else {
+            x.rotate_right(r2)
+        };
+        [x.rotate_right(r0), x.rotate_right(r1), last]
+    }
+
+    pub const fn eval(&self, x: u64) -> u64 {
+        let [t0, t1, t2] = self.terms(x);
+        t0 ^ t1 ^ t2
+    }
+
+    /// Output byte of the `i`-th spread sum of the part of `limb`.
+    pub const fn output_byte(&self, limb: usize, i: usize) -> usize {
+        (2 * limb + self.offsets[i]) % 8
+    }
+
+    /// Spread sums of the part of `limb` when it is `value`, by output byte. The bytes out of
+    /// [`Self::offsets`] are zero.
+    pub fn part_u32x16(&self, limb: usize, value: u32x16) -> [u32x16; 8] {
+        let x = value.cast::<u64>() << u64x16::splat(16 * limb as u64);
+        let rotr = |r: u32| (x >> u64x16::splat(r as u64)) | (x << u64x16::splat(64 - r as u64));
+        let [r0, r1, r2] = self.rotations;
+        let last = if self.shift {
+            x >> u64x16::splat(r2 as u64)
+        } else {
+            rotr(r2)
+        };
+        let terms = [rotr(r0), rotr(r1), last];
+        std::array::from_fn(|byte| {
+            terms.iter().fold(u32x16::splat(0), |acc, term| {
+                let term_byte = (*term >> u64x16::splat(8 * byte as u64)).cast::<u32>();
+                acc + spread_u32x16(term_byte & u32x16::splat(0xff))
+            })
+        })
+    }
+}
+
+/// Interleaves the bits of `byte` with zeros.
+pub const fn spread(byte: u32) -> u32 {
+    let mut x = byte & 0xff;
+    x = (x | (x << 4)) & 0x0f0f;
+    x = (x | (x << 2)) & 0x3333;
+    (x | (x << 1)) & 0x5555
+}
+
+/// Even bits of a 16-bit sum of spread bytes, i.e. the inverse of [`spread`].
+pub const fn even_bits(sum: u32) -> u32 {
+    let mut x = sum & 0x5555;
+    x = (x | (x >> 1)) & 0x3333;
+    x = (x | (x >> 2)) & 0x0f0f;
+    (x | (x >> 4)) & 0x00ff
+}
+
+/// Odd bits of a 16-bit sum of spread bytes.
+pub const fn odd_bits(sum: u32) -> u32 {
+    even_bits(sum >> 1)
+}
+
+#[inline(always)]
+pub fn spread_u32x16(bytes: u32x16) -> u32x16 {
+    let mut x = bytes & u32x16::splat(0xff);
+    x = (x | (x << 4)) & u32x16::splat(0x0f0f);
+    x = (x | (x << 2)) & u32x16::splat(0x3333);
+    (x | (x << 1)) & u32x16::splat(0x5555)
+}
+
+#[inline(always)]
+pub fn even_bits_u32x16(sums: u32x16) -> u32x16 {
+    let mut x = sums & u32x16::splat(0x5555);
+    x = (x | (x >> 1)) & u32x16::splat(0x3333);
+    x = (x | (x >> 2)) & u32x16::splat(0x0f0f);
+    (x | (x >> 4)) & u32x16::splat(0x00ff)
+}
+
+#[inline(always)]
+pub fn odd_bits_u32x16(sums: u32x16) -> u32x16 {
+    even_bits_u32x16(sums >> 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::sha512::{big_sigma_0, big_sigma_1, small_sigma_0, small_sigma_1};
+
+    const WORDS: [u64; 4] = [
+        0x0123456789abcdef,
+        0xfedcba9876543210,
+        0xdeadbeefcafebabe,
+        u64::MAX,
+    ];
+
+    #[test]
+    fn test_sigmas() {
+        for x in WORDS {
+            assert_eq!(SIGMAS[SMALL_SIGMA_0].eval(x), small_sigma_0(x));
+            assert_eq!(SIGMAS[SMALL_SIGMA_1].eval(x), small_sigma_1(x));
+            assert_eq!(SIGMAS[BIG_SIGMA_0].eval(x), big_sigma_0(x));
+            assert_eq!(SIGMAS[BIG_SIGMA_1].eval(x), big_sigma_1(x));
+        }
+    }
+
+    #[test]
+    fn test_offsets() {
+        // The offsets are exactly the output bytes reached by a full limb.
+        for sigma in SIGMAS {
+            for limb in 0..4 {
+                let parts = sigma.part_u32x16(limb, u32x16::splat(0xffff));
+                let reached = (0..8)
+                    .filter(|byte| parts[*byte][0] != 0)
+                    .collect::<BTreeSet<_>>();
+                let expected = (0..sigma.n_sums())
+                    .map(|i| sigma.output_byte(limb, i))
+                    .collect::<BTreeSet<_>>();
+                assert_eq!(reached, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parts() {
+        // The even bits of the sums of the parts are the output bytes.
+        for sigma in SIGMAS {
+            for x in WORDS {
+                let mut sums = [u32x16::splat(0); 8];
+                for limb in 0..4 {
+                    let value = u32x16::splat(((x >> (16 * limb)) & 0xffff) as u32);
+                    let parts = sigma.part_u32x16(limb, value);
+                    for i in 0..sigma.n_sums() {
+                        let byte = sigma.output_byte(limb, i);
+                        sums[byte] += parts[byte];
+                    }
+                }
+                for (byte, sum) in sums.iter().enumerate() {
+                    assert!(sum[0] < 1 << 16);
+                    assert_eq!(
+                        even_bits(sum[0]) as u64,
+                        (sigma.eval(x) >> (8 * byte)) & 0xff
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_spread_sums() {
+        let (a, b, c) = (0b1100_1010, 0b1010_0110, 0b0111_0001);
+        let sum = spread(a) + spread(b) + spread(c);
+        assert_eq!(even_bits(sum), a ^ b ^ c);
+        assert_eq!(odd_bits(sum), (a & b) | (a & c) | (b & c));
+        assert_eq!(odd_bits(spread(a) + spread(b)), a & b);
+        assert_eq!(odd_bits(0x5555 - spread(a) + spread(b)), !a & b & 0xff);
+        assert_eq!(spread_u32x16(u32x16::splat(a))[0], spread(a));
+        assert_eq!(even_bits_u32x16(u32x16::splat(sum))[0], a ^ b ^ c);
+        assert_eq!(odd_bits_u32x16(u32x16::splat(sum))[0], odd_bits(sum));
+    }
+}
@@ -0,0 +1,68 @@
+use std::simd::u32x16;
+
+use stwo::core::channel::Channel;
+use stwo_constraint_framework::relation;
+use utils::trace_columns;
+
+// [value, carry]
+const N_COLUMNS: usize = 2;
+
+/// Log size of the table: 16-bit values and carries up to 7, which bounds the carries of the sums
+/// of up to 8 limbs.
+pub const LOG_SIZE: u32 = 19;
+
+relation!(Add8, N_COLUMNS);
+
+trace_columns!(RangeCheckAddColumns, value, carry);
+
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub add_8: Add8,
+}
+
+impl Relation {
+    pub fn dummy() -> Self {
+        Self {
+            add_8: Add8::dummy(),
+        }
+    }
+
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            add_8: Add8::draw(channel),
+        }
+    }
+}
+
+/// Row `(value << 3) + carry` is `[value, carry]`.
+pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
+    const N: usize = 1 << (LOG_SIZE - 4);
+    let mut all_columns = vec![Vec::with_capacity(N), Vec::with_capacity(N)];
+
+    for i in 0..N as u32 {
+        let index = u32x16::from_array(std::array::from_fn(|j| i * 16 + j as u32));
+        all_columns[0].push(index >> 3);
+        all_columns[1].push(index & u32x16::splat(7));
+    }
+
+    all_columns
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+    use super::*;
+
+    #[test]
+    fn test_gen_column_simd() {
+        let columns = gen_column_simd();
+        assert_eq!(columns.len(), RangeCheckAddColumns::SIZE);
+        assert_eq!(columns[0].len().ilog2(), LOG_SIZE - LOG_N_LANES);
+        assert_eq!(columns[1].len().ilog2(), LOG_SIZE - LOG_N_LANES);
+
+        let row = (0xabcd << 3) + 6;
+        assert_eq!(columns[0][row / 16][row % 16], 0xabcd);
+        assert_eq!(columns[1][row / 16][row % 16], 6);
+    }
+}
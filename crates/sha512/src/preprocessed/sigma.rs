@@ -0,0 +1,205 @@
+//! Tables of the parts of the sigma functions, one per function, see [`crate::partitions`].
+//!
+//! The table of a function has a row `[limb, value, spread sums]` for each limb index and 16-bit
+//! value, i.e. `2^18` rows, the row index being `(limb << 16) + value`.
+
+use std::simd::u32x16;
+
+use stwo::{core::channel::Channel, prover::backend::simd::qm31::PackedQM31};
+use stwo_constraint_framework::{
+    preprocessed_columns::PreProcessedColumnId, relation, EvalAtRow, Relation as _, RelationEntry,
+};
+use utils::{combine, trace_columns};
+
+use crate::partitions::{BIG_SIGMA_0, BIG_SIGMA_1, SIGMAS, SMALL_SIGMA_0, SMALL_SIGMA_1};
+
+/// Log size of the tables: 4 limbs of 16 bits.
+pub const LOG_SIZE: u32 = 18;
+
+/// `[limb, value, spread sums]`.
+pub const fn n_columns(sigma: usize) -> usize {
+    2 + SIGMAS[sigma].n_sums()
+}
+
+relation!(SmallSigma0, n_columns(SMALL_SIGMA_0));
+relation!(SmallSigma1, n_columns(SMALL_SIGMA_1));
+relation!(BigSigma0, n_columns(BIG_SIGMA_0));
+relation!(BigSigma1, n_columns(BIG_SIGMA_1));
+
+trace_columns!(SmallSigma0Columns, limb, value, sum_0, sum_1, sum_2);
+trace_columns!(
+    SmallSigma1Columns,
+    limb,
+    value,
+    sum_0,
+    sum_1,
+    sum_2,
+    sum_3,
+    sum_4,
+    sum_5
+);
+trace_columns!(BigSigma0Columns, limb, value, sum_0, sum_1, sum_2, sum_3);
+trace_columns!(
+    BigSigma1Columns,
+    limb,
+    value,
+    sum_0,
+    sum_1,
+    sum_2,
+    sum_3,
+    sum_4,
+    sum_5,
+    sum_6
+);
+
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub small_sigma_0: SmallSigma0,
+    pub small_sigma_1: SmallSigma1,
+    pub big_sigma_0: BigSigma0,
+    pub big_sigma_1: BigSigma1,
+}
+
+impl Relation {
+    pub fn dummy() -> Self {
+        Self {
+            small_sigma_0: SmallSigma0::dummy(),
+            small_sigma_1: SmallSigma1::dummy(),
+            big_sigma_0: BigSigma0::dummy(),
+            big_sigma_1: BigSigma1::dummy(),
+        }
+    }
+
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            small_sigma_0: SmallSigma0::draw(channel),
+            small_sigma_1: SmallSigma1::draw(channel),
+            big_sigma_0: BigSigma0::draw(channel),
+            big_sigma_1: BigSigma1::draw(channel),
+        }
+    }
+
+    /// Adds `[limb, value, spread sums]` to the relation of the table of `sigma`.
+    pub fn add_to_eval<E: EvalAtRow>(
+        &self,
+        eval: &mut E,
+        sigma: usize,
+        multiplicity: E::EF,
+        values: &[E::F],
+    ) {
+        debug_assert_eq!(values.len(), n_columns(sigma));
+        match sigma {
+            SMALL_SIGMA_0 => eval.add_to_relation(RelationEntry::new(
+                &self.small_sigma_0,
+                multiplicity,
+                values,
+            )),
+            SMALL_SIGMA_1 => eval.add_to_relation(RelationEntry::new(
+                &self.small_sigma_1,
+                multiplicity,
+                values,
+            )),
+            BIG_SIGMA_0 => {
+                eval.add_to_relation(RelationEntry::new(&self.big_sigma_0, multiplicity, values))
+            }
+            BIG_SIGMA_1 => {
+                eval.add_to_relation(RelationEntry::new(&self.big_sigma_1, multiplicity, values))
+            }
+            _ => panic!("No sigma function {sigma}"),
+        }
+    }
+
+    /// Combines the columns `[limb, value, spread sums]` with the relation of the table of
+    /// `sigma`.
+    pub fn combine(&self, sigma: usize, columns: &[&Vec<u32x16>]) -> Vec<PackedQM31> {
+        debug_assert_eq!(columns.len(), n_columns(sigma));
+        match sigma {
+            SMALL_SIGMA_0 => combine!(self.small_sigma_0, columns),
+            SMALL_SIGMA_1 => combine!(self.small_sigma_1, columns),
+            BIG_SIGMA_0 => combine!(self.big_sigma_0, columns),
+            BIG_SIGMA_1 => combine!(self.big_sigma_1, columns),
+            _ => panic!("No sigma function {sigma}"),
+        }
+    }
+}
+
+/// Ids of the columns of chunk `chunk` of the table of `sigma`.
+pub fn column_ids(sigma: usize, chunk: u32) -> Vec<PreProcessedColumnId> {
+    match sigma {
+        SMALL_SIGMA_0 => SmallSigma0Columns::to_ids(Some(chunk)),
+        SMALL_SIGMA_1 => SmallSigma1Columns::to_ids(Some(chunk)),
+        BIG_SIGMA_0 => BigSigma0Columns::to_ids(Some(chunk)),
+        BIG_SIGMA_1 => BigSigma1Columns::to_ids(Some(chunk)),
+        _ => panic!("No sigma function {sigma}"),
+    }
+}
+
+/// Columns `[limb, value, spread sums]` of the table of `sigma`.
+pub fn gen_table_columns(sigma: usize) -> Vec<Vec<u32x16>> {
+    let function = SIGMAS[sigma];
+    let mut all_columns = vec![vec![]; n_columns(sigma)];
+    for limb in 0..4 {
+        for i in (0..1 << 16).step_by(u32x16::LEN) {
+            let value = u32x16::from_array(std::array::from_fn(|j| i + j as u32));
+            let part = function.part_u32x16(limb, value);
+            all_columns[0].push(u32x16::splat(limb as u32));
+            all_columns[1].push(value);
+            for (j, column) in all_columns[2..].iter_mut().enumerate() {
+                column.push(part[function.output_byte(limb, j)]);
+            }
+        }
+    }
+    all_columns
+}
+
+/// Columns of all the tables, by function.
+pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
+    (0..SIGMAS.len()).flat_map(gen_table_columns).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+    use super::*;
+    use crate::partitions::even_bits;
+
+    #[test]
+    fn test_column_ids() {
+        assert_eq!(column_ids(BIG_SIGMA_0, 3).len(), BigSigma0Columns::SIZE);
+        assert_eq!(
+            column_ids(SMALL_SIGMA_1, 3)[1].id,
+            "SmallSigma1Columns_value_3"
+        );
+    }
+
+    #[test]
+    fn test_gen_column_simd() {
+        let columns = gen_column_simd();
+        assert_eq!(
+            columns.len(),
+            (0..SIGMAS.len()).map(n_columns).sum::<usize>()
+        );
+        assert!(columns
+            .iter()
+            .all(|column| column.len().ilog2() == LOG_SIZE - LOG_N_LANES));
+
+        // The parts of the limbs of a word add up to the spread form of its image.
+        let x: u64 = 0x0123456789abcdef;
+        for (sigma, function) in SIGMAS.iter().enumerate() {
+            let table = gen_table_columns(sigma);
+            let mut sums = [0; 8];
+            for limb in 0..4 {
+                let row = (limb << 16) + ((x >> (16 * limb)) & 0xffff) as usize;
+                assert_eq!(table[0][row / 16][row % 16], limb as u32);
+                for (i, column) in table[2..].iter().enumerate() {
+                    sums[function.output_byte(limb, i)] += column[row / 16][row % 16];
+                }
+            }
+            let y = function.eval(x);
+            for (byte, sum) in sums.into_iter().enumerate() {
+                assert_eq!(even_bits(sum) as u64, (y >> (8 * byte)) & 0xff);
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Spread forms of the bytes, see [`crate::partitions::spread`].
+
+use std::simd::u32x16;
+
+use stwo::core::channel::Channel;
+use stwo_constraint_framework::relation;
+use utils::trace_columns;
+
+use crate::partitions::spread_u32x16;
+
+// [byte, spread]
+const N_COLUMNS: usize = 2;
+
+pub const LOG_SIZE: u32 = 8;
+
+relation!(Spread, N_COLUMNS);
+
+trace_columns!(SpreadColumns, byte, spread);
+
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub spread: Spread,
+}
+
+impl Relation {
+    pub fn dummy() -> Self {
+        Self {
+            spread: Spread::dummy(),
+        }
+    }
+
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            spread: Spread::draw(channel),
+        }
+    }
+}
+
+/// Row `byte` is `[byte, spread(byte)]`.
+pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
+    let bytes = (0..1 << LOG_SIZE)
+        .step_by(u32x16::LEN)
+        .map(|i| u32x16::from_array(std::array::from_fn(|j| i + j as u32)))
+        .collect::<Vec<_>>();
+    let spreads = bytes.iter().map(|byte| spread_u32x16(*byte)).collect();
+    vec![bytes, spreads]
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+    use super::*;
+
+    #[test]
+    fn test_gen_column_simd() {
+        let columns = gen_column_simd();
+        assert_eq!(columns.len(), SpreadColumns::SIZE);
+        assert_eq!(columns[0].len().ilog2(), LOG_SIZE - LOG_N_LANES);
+
+        let row = 0b1011_0001;
+        assert_eq!(columns[0][row / 16][row % 16], 0b1011_0001);
+        assert_eq!(columns[1][row / 16][row % 16], 0b01_00_01_01_00_00_00_01);
+    }
+}
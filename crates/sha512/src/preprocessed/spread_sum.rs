@@ -0,0 +1,76 @@
+//! Even and odd bits of the sums of up to three spread bytes, see [`crate::partitions`].
+//!
+//! Row `sum` is `[sum, even bits, odd bits]` for each 16-bit `sum`: any 16-bit value is a sum of
+//! 2-bit counts. The even bits are the XOR of the summed bytes and the odd bits their majority, or
+//! their AND for two bytes.
+
+use std::simd::u32x16;
+
+use stwo::core::channel::Channel;
+use stwo_constraint_framework::relation;
+use utils::trace_columns;
+
+use crate::partitions::{even_bits_u32x16, odd_bits_u32x16};
+
+// [sum, bits]
+const N_COLUMNS: usize = 2;
+
+pub const LOG_SIZE: u32 = 16;
+
+relation!(Even, N_COLUMNS);
+relation!(Odd, N_COLUMNS);
+
+trace_columns!(SpreadSumColumns, sum, even, odd);
+
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub even: Even,
+    pub odd: Odd,
+}
+
+impl Relation {
+    pub fn dummy() -> Self {
+        Self {
+            even: Even::dummy(),
+            odd: Odd::dummy(),
+        }
+    }
+
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            even: Even::draw(channel),
+            odd: Odd::draw(channel),
+        }
+    }
+}
+
+pub fn gen_column_simd() -> Vec<Vec<u32x16>> {
+    let sums = (0..1 << LOG_SIZE)
+        .step_by(u32x16::LEN)
+        .map(|i| u32x16::from_array(std::array::from_fn(|j| i + j as u32)))
+        .collect::<Vec<_>>();
+    let even = sums.iter().map(|sum| even_bits_u32x16(*sum)).collect();
+    let odd = sums.iter().map(|sum| odd_bits_u32x16(*sum)).collect();
+    vec![sums, even, odd]
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo::prover::backend::simd::m31::LOG_N_LANES;
+
+    use super::*;
+    use crate::partitions::spread;
+
+    #[test]
+    fn test_gen_column_simd() {
+        let columns = gen_column_simd();
+        assert_eq!(columns.len(), SpreadSumColumns::SIZE);
+        assert_eq!(columns[0].len().ilog2(), LOG_SIZE - LOG_N_LANES);
+
+        let (a, b, c) = (0x3c, 0xa5, 0x0f);
+        let row = (spread(a) + spread(b) + spread(c)) as usize;
+        assert_eq!(columns[0][row / 16][row % 16], row as u32);
+        assert_eq!(columns[1][row / 16][row % 16], a ^ b ^ c);
+        assert_eq!(columns[2][row / 16][row % 16], (a & b) | (a & c) | (b & c));
+    }
+}
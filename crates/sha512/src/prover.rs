@@ -0,0 +1,80 @@
+//! Prover side of the SHA-512 AIR.
+//!
+//! The preprocessed trace, the twiddles and the commitment of the preprocessed tree only depend
+//! on the log size and the commitment scheme config. A [`Sha512Prover`] computes them once and
+//! shares them across all the proofs it generates.
+
+use stwo::{
+    core::{channel::MerkleChannel, pcs::PcsConfig, proof::StarkProof, vcs::MerkleHasher},
+    prover::backend::{
+        simd::{m31::LOG_N_LANES, SimdBackend},
+        BackendForChannel,
+    },
+};
+use utils::stwo::air::Prover;
+
+use crate::{
+    components::{ClaimedSum, Sha512Air},
+    message::Block,
+    preprocessed::PreProcessedTrace,
+    sha512::HashFunction,
+    Sha512ProverError, MAX_LOG_SIZE,
+};
+
+/// Proves batches of SHA-512 blocks for a fixed log size and config.
+pub struct Sha512Prover<MC: MerkleChannel>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    prover: Prover<MC>,
+}
+
+impl<MC: MerkleChannel> Sha512Prover<MC>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    pub fn new(log_size: u32, config: PcsConfig) -> Result<Self, Sha512ProverError> {
+        Sha512ProverError::check_params(log_size, LOG_N_LANES, MAX_LOG_SIZE, &config)?;
+        let preprocessed_trace = PreProcessedTrace::new(log_size);
+        Ok(Self {
+            prover: Prover::new(
+                log_size,
+                config,
+                preprocessed_trace.trace,
+                preprocessed_trace.ids,
+            ),
+        })
+    }
+
+    pub const fn log_size(&self) -> u32 {
+        self.prover.log_size()
+    }
+
+    pub const fn config(&self) -> PcsConfig {
+        self.prover.config()
+    }
+
+    /// Merkle root of the preprocessed trace, i.e. the first commitment of every proof.
+    pub fn preprocessed_root(&self) -> <MC::H as MerkleHasher>::Hash {
+        self.prover.preprocessed_root()
+    }
+
+    /// See [`crate::prove_sha512`].
+    pub fn prove(
+        &self,
+        blocks: &[Block],
+    ) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha512ProverError> {
+        self.prove_with(HashFunction::Sha512, blocks)
+    }
+
+    /// Same as [`Self::prove`] for any hash function built on the SHA-512 compression, see
+    /// [`crate::prove_sha384`]. The blocks must start from its initial hash value.
+    pub fn prove_with(
+        &self,
+        hash_function: HashFunction,
+        blocks: &[Block],
+    ) -> Result<(StarkProof<MC::H>, ClaimedSum), Sha512ProverError> {
+        Sha512ProverError::check_n_blocks(blocks.len(), self.log_size())?;
+        self.prover.prove(&Sha512Air { hash_function }, blocks)
+    }
+}
@@ -0,0 +1,196 @@
+//! Public inputs and outputs of the proof.
+//!
+//! The compression component emits two kinds of claims, consumed by the verifier:
+//! - an input claim for each block, made of its message words,
+//! - an output claim for each message, made of its digest.
+//!
+//! The verifier rebuilds the same claims from the public messages and digests, so the logup sum
+//! only balances if the proof covers exactly these messages and digests. Intermediate chaining
+//! values and the message schedules are linked inside the AIR and never claimed.
+//!
+//! Only the digest words of the output chaining value are claimed, e.g. 6 words for SHA-384.
+
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use stwo::core::{
+    channel::Channel,
+    fields::{m31::M31, qm31::SecureField, FieldExpOps},
+};
+use stwo_constraint_framework::Relation;
+
+use crate::{
+    components::STATE_SIZE,
+    message::{gen_blocks_for, pad_message, to_limbs, Block},
+    relations::Relations,
+    sha512::{HashFunction, CHUNK_SIZE},
+};
+
+/// [message_id, block_index, message words], as 16-bit limbs.
+pub const INPUT_CLAIM_SIZE: usize = 2 + 4 * CHUNK_SIZE;
+
+/// [message_id, digest], as 16-bit limbs. The limbs past the digest are zero.
+pub const OUTPUT_CLAIM_SIZE: usize = 1 + STATE_SIZE;
+
+/// Claims of a proof, see the module documentation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Claims {
+    pub inputs: Vec<[u32; INPUT_CLAIM_SIZE]>,
+    pub outputs: Vec<[u32; OUTPUT_CLAIM_SIZE]>,
+}
+
+/// A message together with its claimed digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashClaim {
+    pub message: Vec<u8>,
+    pub digest: Vec<u8>,
+}
+
+impl HashClaim {
+    /// Claims the actual SHA-512 digest of `message`.
+    pub fn new(message: impl Into<Vec<u8>>) -> Self {
+        Self::with_hash_function(HashFunction::Sha512, message)
+    }
+
+    /// Claims the actual digest of `message` for `hash_function`.
+    pub fn with_hash_function(hash_function: HashFunction, message: impl Into<Vec<u8>>) -> Self {
+        let message = message.into();
+        let blocks = gen_blocks_for(hash_function, &[&message]);
+        let digest = hash_function.digest(blocks.last().unwrap().h_out());
+        Self { message, digest }
+    }
+}
+
+fn input_claim(message_id: u32, index: u32, words: &[u64; 16]) -> [u32; INPUT_CLAIM_SIZE] {
+    [message_id, index]
+        .into_iter()
+        .chain(to_limbs(words))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+fn output_claim(message_id: u32, digest: &[u64]) -> [u32; OUTPUT_CLAIM_SIZE] {
+    [message_id]
+        .into_iter()
+        .chain(to_limbs(digest))
+        .chain(std::iter::repeat(0))
+        .take(OUTPUT_CLAIM_SIZE)
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+/// Claims emitted by the compression component for the given blocks.
+pub fn block_claims(hash_function: HashFunction, blocks: &[Block]) -> Claims {
+    let mut claims = Claims::default();
+    for block in blocks {
+        claims
+            .inputs
+            .push(input_claim(block.message_id, block.index, &block.words));
+        if block.is_last {
+            claims.outputs.push(output_claim(
+                block.message_id,
+                &block.h_out()[..hash_function.digest_words()],
+            ));
+        }
+    }
+    claims
+}
+
+/// Claims expected by the verifier, which only pads the messages. The claimed digest must be
+/// `8 * hash_function.digest_words()` bytes long. Message ids follow the order of the claims, see
+/// [`crate::message::gen_blocks_for`].
+pub fn hash_claims(hash_function: HashFunction, claims: &[HashClaim]) -> Claims {
+    let mut result = Claims::default();
+    for (message_id, claim) in claims.iter().enumerate() {
+        assert_eq!(
+            claim.digest.len(),
+            8 * hash_function.digest_words(),
+            "Invalid digest length"
+        );
+        for (index, words) in pad_message(&claim.message).iter().enumerate() {
+            result
+                .inputs
+                .push(input_claim(message_id as u32, index as u32, words));
+        }
+        let digest = claim
+            .digest
+            .chunks_exact(8)
+            .map(|word| u64::from_be_bytes(word.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        result
+            .outputs
+            .push(output_claim(message_id as u32, &digest));
+    }
+    result
+}
+
+pub fn mix_into(claims: &Claims, channel: &mut impl Channel) {
+    channel.mix_u32s(&[claims.inputs.len() as u32]);
+    for claim in &claims.inputs {
+        channel.mix_u32s(claim);
+    }
+    channel.mix_u32s(&[claims.outputs.len() as u32]);
+    for claim in &claims.outputs {
+        channel.mix_u32s(claim);
+    }
+}
+
+/// Logup sum of the verifier consuming the claims, to be added to the components claimed sum.
+pub fn logup_sum(claims: &Claims, relations: &Relations) -> SecureField {
+    let inputs = claims.inputs.iter().map(|claim| {
+        let denom: SecureField = relations.input_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    let outputs = claims.outputs.iter().map(|claim| {
+        let denom: SecureField = relations.output_claim.combine(&claim.map(M31::from));
+        denom.inverse()
+    });
+    inputs
+        .chain(outputs)
+        .fold(SecureField::zero(), |acc, frac| acc - frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha384};
+
+    use super::*;
+
+    #[test]
+    fn test_hash_claims_match_block_claims() {
+        let messages = [b"abc".to_vec(), vec![0x42; 200], vec![], vec![0x43; 112]];
+        for hash_function in [HashFunction::Sha512, HashFunction::Sha384] {
+            let claims = messages
+                .iter()
+                .map(|message| HashClaim::with_hash_function(hash_function, message.clone()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                hash_claims(hash_function, &claims),
+                block_claims(hash_function, &gen_blocks_for(hash_function, &messages))
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_claims_wrong_digest() {
+        let mut claim = HashClaim::new(b"abc".to_vec());
+        claim.digest[0] ^= 1;
+        assert_ne!(
+            hash_claims(HashFunction::Sha512, &[claim]),
+            block_claims(
+                HashFunction::Sha512,
+                &gen_blocks_for(HashFunction::Sha512, &[b"abc"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_sha384_claim() {
+        let claim = HashClaim::with_hash_function(HashFunction::Sha384, b"abc".to_vec());
+        assert_eq!(claim.digest[..], Sha384::digest(b"abc")[..]);
+        // The truncated words are claimed as zero.
+        let claims = hash_claims(HashFunction::Sha384, &[claim]);
+        assert!(claims.outputs[0][1 + 24..].iter().all(|limb| *limb == 0));
+    }
+}
@@ -0,0 +1,75 @@
+use stwo::core::channel::Channel;
+
+use crate::preprocessed::{range_check_add, sigma, spread, spread_sum};
+
+mod w {
+    use stwo_constraint_framework::relation;
+
+    use crate::components::W_SIZE;
+    // [message schedule]
+    relation!(Relation, W_SIZE);
+}
+
+mod chain {
+    use stwo_constraint_framework::relation;
+
+    use crate::components::STATE_SIZE;
+    // [message_id, block_index, state]
+    relation!(Relation, 2 + STATE_SIZE);
+}
+
+mod input_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::INPUT_CLAIM_SIZE;
+    // [message_id, block_index, message words]
+    relation!(Relation, INPUT_CLAIM_SIZE);
+}
+
+mod output_claim {
+    use stwo_constraint_framework::relation;
+
+    use crate::public::OUTPUT_CLAIM_SIZE;
+    // [message_id, digest]
+    relation!(Relation, OUTPUT_CLAIM_SIZE);
+}
+
+#[derive(Clone)]
+pub struct Relations {
+    pub sigma: sigma::Relation,
+    pub spread: spread::Relation,
+    pub spread_sum: spread_sum::Relation,
+    pub range_check_add: range_check_add::Relation,
+    pub w: w::Relation,
+    pub chain: chain::Relation,
+    pub input_claim: input_claim::Relation,
+    pub output_claim: output_claim::Relation,
+}
+
+impl Relations {
+    pub fn draw(channel: &mut impl Channel) -> Self {
+        Self {
+            sigma: sigma::Relation::draw(channel),
+            spread: spread::Relation::draw(channel),
+            spread_sum: spread_sum::Relation::draw(channel),
+            range_check_add: range_check_add::Relation::draw(channel),
+            w: w::Relation::draw(channel),
+            chain: chain::Relation::draw(channel),
+            input_claim: input_claim::Relation::draw(channel),
+            output_claim: output_claim::Relation::draw(channel),
+        }
+    }
+
+    pub fn dummy() -> Self {
+        Self {
+            sigma: sigma::Relation::dummy(),
+            spread: spread::Relation::dummy(),
+            spread_sum: spread_sum::Relation::dummy(),
+            range_check_add: range_check_add::Relation::dummy(),
+            w: w::Relation::dummy(),
+            chain: chain::Relation::dummy(),
+            input_claim: input_claim::Relation::dummy(),
+            output_claim: output_claim::Relation::dummy(),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! SHA-512 functions
+
+pub const K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// Initial hash value of SHA-512.
+pub const H: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// Initial hash value of SHA-384.
+pub const H_384: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+pub const CHUNK_SIZE: usize = 16; // 16 u64
+pub const N_SCHEDULING_ROUNDS: usize = 64; // 16..80
+pub const N_COMPRESSION_ROUNDS: usize = 80;
+
+/// Hash functions built on the SHA-512 compression, which only differ by their initial hash value
+/// and the length of their digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HashFunction {
+    #[default]
+    Sha512,
+    Sha384,
+}
+
+impl HashFunction {
+    pub const fn iv(self) -> [u64; 8] {
+        match self {
+            Self::Sha512 => H,
+            Self::Sha384 => H_384,
+        }
+    }
+
+    /// Number of 64-bit words of the digest, a prefix of the final chaining value.
+    pub const fn digest_words(self) -> usize {
+        match self {
+            Self::Sha512 => 8,
+            Self::Sha384 => 6,
+        }
+    }
+
+    /// Big-endian digest of a final chaining value.
+    pub fn digest(self, hash: [u64; 8]) -> Vec<u8> {
+        hash[..self.digest_words()]
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect()
+    }
+}
+
+#[inline(always)]
+pub const fn small_sigma_0(x: u64) -> u64 {
+    x.rotate_right(1) ^ x.rotate_right(8) ^ (x >> 7)
+}
+
+#[inline(always)]
+pub const fn small_sigma_1(x: u64) -> u64 {
+    x.rotate_right(19) ^ x.rotate_right(61) ^ (x >> 6)
+}
+
+#[inline(always)]
+pub const fn big_sigma_0(x: u64) -> u64 {
+    x.rotate_right(28) ^ x.rotate_right(34) ^ x.rotate_right(39)
+}
+
+#[inline(always)]
+pub const fn big_sigma_1(x: u64) -> u64 {
+    x.rotate_right(14) ^ x.rotate_right(18) ^ x.rotate_right(41)
+}
+
+/// Left part of Ch, see `ch_right`: the two parts have disjoint bits so Ch is their sum.
+#[inline(always)]
+pub const fn ch_left(e: u64, f: u64) -> u64 {
+    e & f
+}
+
+#[inline(always)]
+pub const fn ch_right(e: u64, g: u64) -> u64 {
+    (!e) & g
+}
+
+#[inline(always)]
+pub const fn maj(a: u64, b: u64, c: u64) -> u64 {
+    (a & b) ^ (a & c) ^ (b & c)
+}
+
+/// Message schedule of a block.
+pub fn schedule(chunk: [u64; 16]) -> [u64; 80] {
+    let mut w = [0; 80];
+    w[..16].copy_from_slice(&chunk);
+    for t in 16..80 {
+        w[t] = w[t - 16]
+            .wrapping_add(small_sigma_0(w[t - 15]))
+            .wrapping_add(w[t - 7])
+            .wrapping_add(small_sigma_1(w[t - 2]));
+    }
+    w
+}
+
+/// Compresses one block from the chaining value `hash`, with feed-forward.
+pub fn process_chunk(chunk: [u64; 16], mut hash: [u64; 8]) -> [u64; 8] {
+    let w = schedule(chunk);
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = hash;
+    for t in 0..N_COMPRESSION_ROUNDS {
+        let temp1 = h
+            .wrapping_add(big_sigma_1(e))
+            .wrapping_add(ch_left(e, f))
+            .wrapping_add(ch_right(e, g))
+            .wrapping_add(K[t])
+            .wrapping_add(w[t]);
+        let temp2 = big_sigma_0(a).wrapping_add(maj(a, b, c));
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+    for (hash, value) in hash.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *hash = hash.wrapping_add(value);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_implementation() {
+        use sha2::{Digest, Sha512};
+
+        // Single block "abc", padded with its 128-bit length
+        let mut chunk = [0u64; 16];
+        chunk[0] = u64::from_be_bytes(*b"abc\x80\0\0\0\0");
+        chunk[15] = 24;
+
+        let hash = process_chunk(chunk, H);
+        let result = hash
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(result[..], Sha512::digest(b"abc")[..]);
+    }
+
+    #[test]
+    fn test_ch_parts_disjoint() {
+        let (e, f, g) = (0x0123456789abcdef, 0xfedcba9876543210, 0xdeadbeefcafebabe);
+        assert_eq!(ch_left(e, f) & ch_right(e, g), 0);
+        assert_eq!(ch_left(e, f) + ch_right(e, g), (e & f) ^ (!e & g));
+    }
+}
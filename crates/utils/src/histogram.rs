@@ -0,0 +1,157 @@
+//! Parallel multiplicity histograms.
+//!
+//! Lookup witnesses count how many times each entry of a preprocessed table is used. Indices are
+//! produced 16 lanes at a time and scattered into dense counters. Work is split into independent
+//! tasks (e.g. one per round). With the `parallel` feature, tasks are grouped into one contiguous
+//! range per thread, each range accumulates into its own counters and the partial histograms are
+//! summed at the end.
+
+use std::simd::u32x16;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{aligned_vec, simd::into_simd};
+
+const N_LANES: usize = u32x16::LEN;
+
+/// Dense counters over `[0, 2^log_len)`, 64-byte aligned.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Histogram {
+    counts: Vec<u32>,
+}
+
+impl Histogram {
+    pub fn new(log_len: u32) -> Self {
+        Self {
+            counts: aligned_vec![0u32; (1usize << log_len).max(N_LANES)],
+        }
+    }
+
+    /// Counts each lane of `indices` once.
+    #[inline(always)]
+    pub fn add(&mut self, indices: u32x16) {
+        for index in indices.to_array() {
+            self.counts[index as usize] += 1;
+        }
+    }
+
+    /// Counts each lane of each vector of `indices` once.
+    #[inline(always)]
+    pub fn add_all<'a>(&mut self, indices: impl IntoIterator<Item = &'a u32x16>) {
+        indices.into_iter().for_each(|indices| self.add(*indices));
+    }
+
+    /// Adds the counts of `other`, which must have the same length.
+    pub fn merge(mut self, other: &Self) -> Self {
+        assert_eq!(self.counts.len(), other.counts.len());
+        self.counts
+            .iter_mut()
+            .zip(&other.counts)
+            .for_each(|(count, other)| *count += other);
+        self
+    }
+
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    /// Splits the counts into packed columns of `2^log_size` rows.
+    pub fn into_columns(self, log_size: u32) -> Vec<Vec<u32x16>> {
+        into_simd(&self.counts)
+            .chunks((1 << log_size) / N_LANES)
+            .map(|chunk| chunk.to_vec())
+            .collect()
+    }
+}
+
+/// Builds `N` histograms of lengths `2^log_lens` by running `f` on each task in `0..n_tasks`.
+///
+/// With the `parallel` feature, tasks are split into at most one range per thread, so that at most
+/// that many sets of histograms are alive at once. They are merged once all the tasks are done.
+pub fn par_histograms<const N: usize, F>(log_lens: [u32; N], n_tasks: usize, f: F) -> [Histogram; N]
+where
+    F: Fn(usize, &mut [Histogram; N]) + Sync,
+{
+    let init = || log_lens.map(Histogram::new);
+
+    #[cfg(feature = "parallel")]
+    {
+        let n_splits = rayon::current_num_threads().clamp(1, n_tasks.max(1));
+        let split_size = n_tasks.div_ceil(n_splits);
+        (0..n_splits)
+            .into_par_iter()
+            .map(|split| {
+                let mut histograms = init();
+                let end = n_tasks.min((split + 1) * split_size);
+                (split * split_size..end).for_each(|task| f(task, &mut histograms));
+                histograms
+            })
+            .reduce_with(|lhs, rhs| {
+                let mut rhs = rhs.into_iter();
+                lhs.map(|histogram| histogram.merge(&rhs.next().unwrap()))
+            })
+            .unwrap_or_else(init)
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut histograms = init();
+        (0..n_tasks).for_each(|task| f(task, &mut histograms));
+        histograms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_add() {
+        let mut histogram = Histogram::new(5);
+        histogram.add(u32x16::from_array(core::array::from_fn(|i| i as u32)));
+        histogram.add(u32x16::splat(3));
+        assert_eq!(histogram.counts().len(), 32);
+        assert_eq!(histogram.counts()[3], 17);
+        assert_eq!(histogram.counts()[15], 1);
+        assert_eq!(histogram.counts()[16], 0);
+    }
+
+    #[test]
+    fn test_par_histograms_matches_sequential() {
+        let log_len = 10;
+        let indices = (0..64)
+            .map(|task| {
+                (0..32)
+                    .map(|row| {
+                        u32x16::from_array(core::array::from_fn(|lane| {
+                            ((task * 7919 + row * 31 + lane * 17) % (1 << log_len)) as u32
+                        }))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut expected = [Histogram::new(log_len), Histogram::new(4)];
+        indices.iter().for_each(|task| {
+            expected[0].add_all(task);
+            expected[1].add(task[0] & u32x16::splat(15));
+        });
+
+        let histograms = par_histograms([log_len, 4], indices.len(), |task, [h0, h1]| {
+            h0.add_all(&indices[task]);
+            h1.add(indices[task][0] & u32x16::splat(15));
+        });
+        assert_eq!(histograms, expected);
+    }
+
+    #[test]
+    fn test_into_columns() {
+        let mut histogram = Histogram::new(6);
+        histogram.add(u32x16::splat(17));
+        let columns = histogram.into_columns(5);
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0].len(), 2);
+        assert_eq!(columns[0][1][1], 1);
+    }
+}
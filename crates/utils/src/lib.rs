@@ -1,4 +1,5 @@
 #![feature(portable_simd, array_chunks, iter_array_chunks)]
 
+pub mod histogram;
 pub mod simd;
 pub mod stwo;
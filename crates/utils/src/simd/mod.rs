@@ -3,6 +3,9 @@ use std::{
     ptr::write,
 };
 
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
 pub mod macros;
 
 /// Creates a Vec<T> with 64-byte alignment, filled with clones of `value`.
@@ -146,6 +149,59 @@ pub fn into_simd(column: &[u32]) -> &[u32x16] {
     unsafe { std::slice::from_raw_parts(column.as_ptr() as *const u32x16, column.len() / 16) }
 }
 
+/// Split columns into chunks of `chunk_size` rows, grouping the chunks of all the columns that
+/// cover the same rows, so that each group can be filled independently.
+///
+/// # Arguments
+///
+/// * `columns` - A slice of columns of the same length.
+/// * `chunk_size` - The number of rows of each chunk.
+///
+/// # Returns
+///
+/// A vector with, for each chunk of rows, the mutable chunks of all the columns.
+pub fn row_chunks_mut(columns: &mut [Vec<u32x16>], chunk_size: usize) -> Vec<Vec<&mut [u32x16]>> {
+    let n_chunks = columns
+        .first()
+        .map_or(0, |column| column.len().div_ceil(chunk_size));
+    let mut chunks: Vec<Vec<&mut [u32x16]>> = (0..n_chunks)
+        .map(|_| Vec::with_capacity(columns.len()))
+        .collect();
+
+    for column in columns.iter_mut() {
+        for (chunk, rows) in chunks.iter_mut().zip(column.chunks_mut(chunk_size)) {
+            chunk.push(rows);
+        }
+    }
+
+    chunks
+}
+
+/// Fills the trace and lookup data columns by chunks of SIMD rows, in parallel with the
+/// `parallel` feature.
+///
+/// `f` is given the chunks of all the columns covering the same rows, so that each chunk can
+/// keep the state of its rows in registers and write directly into the preallocated columns.
+pub fn for_each_row_chunk<F>(evals: &mut [Vec<u32x16>], lookup_data: &mut [Vec<u32x16>], f: F)
+where
+    F: Fn(&mut [&mut [u32x16]], &mut [&mut [u32x16]]) + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    let n_chunks = rayon::current_num_threads();
+    #[cfg(not(feature = "parallel"))]
+    let n_chunks = 1;
+    let chunk_size = evals[0].len().div_ceil(n_chunks).max(1);
+
+    let evals = row_chunks_mut(evals, chunk_size);
+    let lookup_data = row_chunks_mut(lookup_data, chunk_size);
+
+    #[cfg(feature = "parallel")]
+    let chunks = evals.into_par_iter().zip(lookup_data);
+    #[cfg(not(feature = "parallel"))]
+    let chunks = evals.into_iter().zip(lookup_data);
+    chunks.for_each(|(mut evals, mut lookup_data)| f(&mut evals, &mut lookup_data));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -230,4 +286,23 @@ mod tests {
         let flattened = flatten_simd(chunked);
         assert_eq!(flattened, &column);
     }
+
+    #[test]
+    fn test_row_chunks_mut() {
+        let mut columns = vec![
+            (0..5).map(u32x16::splat).collect::<Vec<_>>(),
+            (5..10).map(u32x16::splat).collect::<Vec<_>>(),
+        ];
+        let chunks = row_chunks_mut(&mut columns, 2);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1][0], [u32x16::splat(2), u32x16::splat(3)]);
+        assert_eq!(chunks[1][1], [u32x16::splat(7), u32x16::splat(8)]);
+        assert_eq!(chunks[2][1], [u32x16::splat(9)]);
+
+        for mut chunk in chunks {
+            chunk[0][0] = u32x16::splat(42);
+        }
+        assert_eq!(columns[0][4], u32x16::splat(42));
+        assert_eq!(columns[1][4], u32x16::splat(9));
+    }
 }
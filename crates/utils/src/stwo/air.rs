@@ -0,0 +1,509 @@
+//! Proving and verifying AIRs over the SIMD backend.
+//!
+//! Every proof commits to three trees: the preprocessed trace, the trace and the interaction
+//! trace. An [`Air`] tells how to generate the traces of a batch and build its components, and how
+//! its public claims enter the channel and the logup sum. A [`Prover`] and a [`Verifier`] run the
+//! protocol for any of them, for a fixed log size and config, and share the preprocessed trace and
+//! its commitment across proofs.
+
+use std::fmt::Debug;
+
+use num_traits::Zero;
+use stwo::{
+    core::{
+        air::Component,
+        channel::{Channel, MerkleChannel},
+        fields::{m31::BaseField, qm31::SecureField},
+        pcs::{CommitmentSchemeVerifier, PcsConfig, TreeVec},
+        poly::circle::{CanonicCoset, MAX_CIRCLE_DOMAIN_LOG_SIZE},
+        proof::StarkProof,
+        vcs::MerkleHasher,
+        verifier::{verify, VerificationError},
+        ColumnVec,
+    },
+    prover::{
+        backend::{simd::SimdBackend, BackendForChannel},
+        pcs::CommitmentTreeProver,
+        poly::{
+            circle::{CircleEvaluation, PolyOps},
+            twiddles::TwiddleTree,
+            BitReversedOrder,
+        },
+        prove,
+        vcs::prover::MerkleProver,
+        CommitmentSchemeProver, ComponentProver, ProvingError,
+    },
+};
+use stwo_constraint_framework::{
+    preprocessed_columns::PreProcessedColumnId, relation_tracker::RelationTrackerEntry,
+    TraceLocationAllocator,
+};
+use thiserror::Error;
+use tracing::{debug, span, Level};
+
+/// Extra log size of the twiddles precomputed by the prover, on top of the evaluation domain.
+pub const TWIDDLES_EXTRA_LOG_SIZE: u32 = 2;
+
+/// Number of trees committed by the prover: preprocessed, trace and interaction.
+pub const N_TREES: usize = 3;
+
+pub type Trace = ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>;
+
+/// Whether the twiddles precomputed by the prover for `log_size` fit in the circle domain.
+pub const fn is_supported(log_size: u32, config: &PcsConfig) -> bool {
+    log_size + config.fri_config.log_blowup_factor + TWIDDLES_EXTRA_LOG_SIZE
+        <= MAX_CIRCLE_DOMAIN_LOG_SIZE
+}
+
+/// Claimed sums of the logup relations of all the components, sent along with the proof.
+pub trait LogupSum: Debug {
+    fn sum(&self) -> SecureField;
+
+    fn mix_into(&self, channel: &mut impl Channel);
+}
+
+/// The components of an AIR, in trace order.
+pub trait AirComponents {
+    fn provers(&self) -> Vec<&dyn ComponentProver<SimdBackend>>;
+
+    fn components(&self) -> Vec<&dyn Component>;
+
+    fn trace_log_degree_bounds(&self) -> Vec<TreeVec<ColumnVec<u32>>>;
+
+    /// Relation entries of all the components, for debugging unbalanced lookups.
+    fn relation_entries(&self, trace: &TreeVec<Vec<&Vec<BaseField>>>) -> Vec<RelationTrackerEntry>;
+
+    /// Log sizes of the columns of each tree, in commitment order.
+    fn log_sizes(&self) -> TreeVec<ColumnVec<u32>> {
+        TreeVec::concat_cols(self.trace_log_degree_bounds().into_iter())
+    }
+}
+
+/// Trace generation and components of an AIR, see [`Prover`] and [`Verifier`].
+pub trait Air {
+    /// What a proof attests, e.g. a batch of blocks.
+    type Input: ?Sized;
+    /// Public claims of a batch, whose logup sum balances the claimed sums of the components.
+    type Claims;
+    type Relations;
+    type LookupData;
+    type ClaimedSum: LogupSum;
+    type Components: AirComponents;
+
+    fn claims(&self, input: &Self::Input) -> Self::Claims;
+
+    fn mix_claims(&self, claims: &Self::Claims, channel: &mut impl Channel);
+
+    fn draw_relations(&self, channel: &mut impl Channel) -> Self::Relations;
+
+    /// Relations to build the components before they are drawn, e.g. to get the column sizes.
+    fn dummy_relations(&self) -> Self::Relations;
+
+    fn logup_sum(&self, claims: &Self::Claims, relations: &Self::Relations) -> SecureField;
+
+    fn gen_trace(&self, log_size: u32, input: &Self::Input) -> (Trace, Self::LookupData);
+
+    fn gen_interaction_trace(
+        &self,
+        lookup_data: Self::LookupData,
+        relations: &Self::Relations,
+    ) -> (Trace, Self::ClaimedSum);
+
+    fn components(
+        &self,
+        log_size: u32,
+        location_allocator: &mut TraceLocationAllocator,
+        relations: &Self::Relations,
+        claimed_sum: &Self::ClaimedSum,
+    ) -> Self::Components;
+}
+
+/// Errors of the provers of all the AIRs, `S` being their claimed sums.
+#[derive(Debug, Error)]
+pub enum ProveError<S: Debug> {
+    #[error("Invalid log size {log_size}: supported log sizes are {min}..={max}")]
+    InvalidLogSize { log_size: u32, min: u32, max: u32 },
+    #[error("Unsupported config for log size {log_size}: {config:?}")]
+    UnsupportedConfig { log_size: u32, config: PcsConfig },
+    #[error("Too many blocks: {n_blocks} do not fit in 2^{log_size} rows")]
+    TooManyBlocks { n_blocks: usize, log_size: u32 },
+    #[error("Relation summary is not zero: {sum}")]
+    UnbalancedLogup { sum: SecureField, claimed_sum: S },
+    #[error(transparent)]
+    Stwo(#[from] ProvingError),
+}
+
+impl<S: Debug> ProveError<S> {
+    /// Checks that `log_size` is in `min..=max`, the log sizes supported by an AIR, and that
+    /// `config` is supported for it, see [`is_supported`].
+    pub fn check_params(log_size: u32, min: u32, max: u32, config: &PcsConfig) -> Result<(), Self> {
+        if !(min..=max).contains(&log_size) {
+            return Err(Self::InvalidLogSize { log_size, min, max });
+        }
+        if !is_supported(log_size, config) {
+            return Err(Self::UnsupportedConfig {
+                log_size,
+                config: *config,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that a batch of `n_blocks` blocks fits in the `2^log_size` rows of the trace, one
+    /// block per row.
+    pub fn check_n_blocks(n_blocks: usize, log_size: u32) -> Result<(), Self> {
+        if n_blocks > 1 << log_size {
+            return Err(Self::TooManyBlocks { n_blocks, log_size });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum VerifyError {
+    #[error("Invalid proof: expected {N_TREES} commitments, got {0}")]
+    InvalidCommitmentsCount(usize),
+    #[error("Invalid proof: the preprocessed commitment does not match the tables")]
+    InvalidPreprocessedRoot,
+    #[error("Relation summary is not zero: {0}")]
+    InvalidLogupSum(SecureField),
+    #[error(transparent)]
+    Stwo(#[from] VerificationError),
+}
+
+/// Commits to the preprocessed trace in a tree of its own, whose root only depends on the
+/// columns, the Merkle hasher and the blowup factor of `config`.
+pub fn commit_preprocessed<MC: MerkleChannel>(
+    trace: Trace,
+    config: PcsConfig,
+    twiddles: &TwiddleTree<SimdBackend>,
+) -> CommitmentTreeProver<SimdBackend, MC>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    let mut commitment_scheme = CommitmentSchemeProver::<SimdBackend, MC>::new(config, twiddles);
+    let mut tree_builder = commitment_scheme.tree_builder();
+    tree_builder.extend_evals(trace);
+    tree_builder.commit(&mut MC::C::default());
+    commitment_scheme.trees.pop().unwrap()
+}
+
+/// Merkle root of a preprocessed trace of columns of at most `2^log_size` rows, which must be
+/// the first commitment of every proof.
+pub fn preprocessed_root<MC: MerkleChannel>(
+    trace: Trace,
+    log_size: u32,
+    config: PcsConfig,
+) -> <MC::H as MerkleHasher>::Hash
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    // Columns are at most 2^log_size rows, so the twiddles only need to cover their extension.
+    let twiddles = SimdBackend::precompute_twiddles(
+        CanonicCoset::new(log_size + config.fri_config.log_blowup_factor)
+            .circle_domain()
+            .half_coset,
+    );
+    commit_preprocessed::<MC>(trace, config, &twiddles)
+        .commitment
+        .root()
+}
+
+/// Proves batches of an [`Air`] for a fixed log size and config.
+pub struct Prover<MC: MerkleChannel>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    log_size: u32,
+    config: PcsConfig,
+    twiddles: TwiddleTree<SimdBackend>,
+    preprocessed_ids: Vec<PreProcessedColumnId>,
+    preprocessed_tree: CommitmentTreeProver<SimdBackend, MC>,
+}
+
+impl<MC: MerkleChannel> Prover<MC>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    /// Precomputes the twiddles and commits to the preprocessed trace. `log_size` and `config`
+    /// must be supported, see [`is_supported`].
+    pub fn new(
+        log_size: u32,
+        config: PcsConfig,
+        preprocessed_trace: Trace,
+        preprocessed_ids: Vec<PreProcessedColumnId>,
+    ) -> Self {
+        // Precompute twiddles.
+        let span = span!(Level::INFO, "Precompute twiddles").entered();
+        let twiddles = SimdBackend::precompute_twiddles(
+            CanonicCoset::new(
+                log_size + config.fri_config.log_blowup_factor + TWIDDLES_EXTRA_LOG_SIZE,
+            )
+            .circle_domain()
+            .half_coset,
+        );
+        span.exit();
+
+        // Commit to the preprocessed trace once, the channel only receives the root.
+        let span = span!(Level::INFO, "Constant").entered();
+        let preprocessed_tree = commit_preprocessed::<MC>(preprocessed_trace, config, &twiddles);
+        span.exit();
+
+        Self {
+            log_size,
+            config,
+            twiddles,
+            preprocessed_ids,
+            preprocessed_tree,
+        }
+    }
+
+    pub const fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    pub const fn config(&self) -> PcsConfig {
+        self.config
+    }
+
+    /// Merkle root of the preprocessed trace, i.e. the first commitment of every proof.
+    pub fn preprocessed_root(&self) -> <MC::H as MerkleHasher>::Hash {
+        self.preprocessed_tree.commitment.root()
+    }
+
+    /// Proves `input`, which must fit in the trace. Returns the proof together with the claimed
+    /// sums of the logup relations, which the verifier needs to rebuild the components.
+    pub fn prove<A: Air>(
+        &self,
+        air: &A,
+        input: &A::Input,
+    ) -> Result<(StarkProof<MC::H>, A::ClaimedSum), ProveError<A::ClaimedSum>> {
+        let log_size = self.log_size;
+
+        // Setup protocol.
+        let channel = &mut MC::C::default();
+        self.config.mix_into(channel);
+        let claims = air.claims(input);
+        air.mix_claims(&claims, channel);
+        let mut commitment_scheme =
+            CommitmentSchemeProver::<SimdBackend, MC>::new(self.config, &self.twiddles);
+
+        // Preprocessed trace.
+        commitment_scheme
+            .trees
+            .push(clone_tree(&self.preprocessed_tree));
+        MC::mix_root(channel, self.preprocessed_root());
+
+        // Trace.
+        let span = span!(Level::INFO, "Trace").entered();
+        let (trace, lookup_data) = air.gen_trace(log_size, input);
+        let span_1 = span!(Level::INFO, "Extend evals").entered();
+        let mut tree_builder = commitment_scheme.tree_builder();
+        tree_builder.extend_evals(trace);
+        tree_builder.commit(channel);
+        span_1.exit();
+        span.exit();
+
+        // Draw lookup elements.
+        let relations = air.draw_relations(channel);
+
+        // Interaction trace.
+        let span = span!(Level::INFO, "Interaction").entered();
+        let (trace, claimed_sum) = air.gen_interaction_trace(lookup_data, &relations);
+        claimed_sum.mix_into(channel);
+        let span_1 = span!(Level::INFO, "Extend evals").entered();
+        let mut tree_builder = commitment_scheme.tree_builder();
+        tree_builder.extend_evals(trace);
+        tree_builder.commit(channel);
+        span_1.exit();
+        span.exit();
+
+        debug!(
+            "Columns count: {:?}",
+            commitment_scheme
+                .trees
+                .as_ref()
+                .map(|tree| tree.evaluations.len())
+        );
+
+        // Prove constraints.
+        let span = span!(Level::INFO, "Prove").entered();
+        let trace_allocator =
+            &mut TraceLocationAllocator::new_with_preprocessed_columns(&self.preprocessed_ids);
+        let components = air.components(log_size, trace_allocator, &relations, &claimed_sum);
+
+        #[cfg(feature = "track-relations")]
+        println!(
+            "Trace log degree bounds: {:?}",
+            components.trace_log_degree_bounds()
+        );
+
+        let total_sum = claimed_sum.sum() + air.logup_sum(&claims, &relations);
+        if total_sum != SecureField::zero() {
+            #[cfg(feature = "track-relations")]
+            println!(
+                "Relation summary: {:?}",
+                track_relations(&components, &commitment_scheme)
+            );
+            return Err(ProveError::UnbalancedLogup {
+                sum: total_sum,
+                claimed_sum,
+            });
+        }
+
+        let proof = prove(&components.provers(), channel, commitment_scheme)?;
+        span.exit();
+
+        Ok((proof, claimed_sum))
+    }
+}
+
+/// Copies a committed tree, so that each proof gets its own while the prover keeps the original.
+fn clone_tree<MC: MerkleChannel>(
+    tree: &CommitmentTreeProver<SimdBackend, MC>,
+) -> CommitmentTreeProver<SimdBackend, MC>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    CommitmentTreeProver {
+        polynomials: tree.polynomials.clone(),
+        evaluations: tree.evaluations.clone(),
+        commitment: MerkleProver {
+            layers: tree.commitment.layers.clone(),
+        },
+    }
+}
+
+/// Sums the relation entries of all the components over the committed traces.
+#[cfg(feature = "track-relations")]
+pub fn track_relations<MC: MerkleChannel>(
+    components: &impl AirComponents,
+    commitment_scheme: &CommitmentSchemeProver<'_, SimdBackend, MC>,
+) -> stwo_constraint_framework::relation_tracker::RelationSummary
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    use stwo::prover::backend::Column;
+
+    let evals = commitment_scheme.trace().polys.map(|tree| {
+        tree.iter()
+            .map(|poly| {
+                poly.evaluate(CanonicCoset::new(poly.log_size()).circle_domain())
+                    .values
+                    .to_cpu()
+            })
+            .collect()
+    });
+    let evals = &evals.as_ref();
+    let trace = &evals.into();
+
+    stwo_constraint_framework::relation_tracker::RelationSummary::summarize_relations(
+        &components.relation_entries(trace),
+    )
+    .cleaned()
+}
+
+/// Verifies proofs of an [`Air`] for a fixed log size and config.
+pub struct Verifier<MC: MerkleChannel> {
+    log_size: u32,
+    config: PcsConfig,
+    preprocessed_ids: Vec<PreProcessedColumnId>,
+    preprocessed_log_sizes: Vec<u32>,
+    preprocessed_root: <MC::H as MerkleHasher>::Hash,
+}
+
+impl<MC: MerkleChannel> Verifier<MC> {
+    /// `preprocessed_root` is the canonical root of the preprocessed trace, see
+    /// [`preprocessed_root`].
+    pub fn new(
+        log_size: u32,
+        config: PcsConfig,
+        preprocessed_ids: Vec<PreProcessedColumnId>,
+        preprocessed_log_sizes: Vec<u32>,
+        preprocessed_root: <MC::H as MerkleHasher>::Hash,
+    ) -> Self {
+        Self {
+            log_size,
+            config,
+            preprocessed_ids,
+            preprocessed_log_sizes,
+            preprocessed_root,
+        }
+    }
+
+    pub const fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    pub const fn config(&self) -> PcsConfig {
+        self.config
+    }
+
+    /// Merkle root of the preprocessed trace, i.e. the first commitment of every proof.
+    pub fn preprocessed_root(&self) -> <MC::H as MerkleHasher>::Hash {
+        self.preprocessed_root
+    }
+
+    /// Verifies that `proof` balances the given public claims.
+    ///
+    /// Replays the prover's transcript: commitments are mixed into the channel in the same order,
+    /// the relations are redrawn and the components are rebuilt before calling stwo's `verify`.
+    pub fn verify<A: Air>(
+        &self,
+        air: &A,
+        proof: StarkProof<MC::H>,
+        claimed_sum: &A::ClaimedSum,
+        claims: &A::Claims,
+    ) -> Result<(), VerifyError> {
+        let log_size = self.log_size;
+        if proof.commitments.len() != N_TREES {
+            return Err(VerifyError::InvalidCommitmentsCount(
+                proof.commitments.len(),
+            ));
+        }
+        if proof.commitments[0] != self.preprocessed_root {
+            return Err(VerifyError::InvalidPreprocessedRoot);
+        }
+
+        // Setup protocol.
+        let channel = &mut MC::C::default();
+        self.config.mix_into(channel);
+        air.mix_claims(claims, channel);
+        let commitment_scheme = &mut CommitmentSchemeVerifier::<MC>::new(self.config);
+
+        // Column sizes do not depend on the drawn relations.
+        let log_sizes = air
+            .components(
+                log_size,
+                &mut TraceLocationAllocator::new_with_preprocessed_columns(&self.preprocessed_ids),
+                &air.dummy_relations(),
+                claimed_sum,
+            )
+            .log_sizes();
+
+        // Preprocessed trace.
+        commitment_scheme.commit(proof.commitments[0], &self.preprocessed_log_sizes, channel);
+
+        // Trace.
+        commitment_scheme.commit(proof.commitments[1], &log_sizes[1], channel);
+
+        // Draw lookup elements.
+        let relations = air.draw_relations(channel);
+        let total_sum = claimed_sum.sum() + air.logup_sum(claims, &relations);
+        if total_sum != SecureField::zero() {
+            return Err(VerifyError::InvalidLogupSum(total_sum));
+        }
+
+        // Interaction trace.
+        claimed_sum.mix_into(channel);
+        commitment_scheme.commit(proof.commitments[2], &log_sizes[2], channel);
+
+        // Verify constraints.
+        let trace_allocator =
+            &mut TraceLocationAllocator::new_with_preprocessed_columns(&self.preprocessed_ids);
+        let components = air.components(log_size, trace_allocator, &relations, claimed_sum);
+
+        verify(&components.components(), channel, commitment_scheme, proof)?;
+        Ok(())
+    }
+}
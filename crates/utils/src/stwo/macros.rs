@@ -45,6 +45,7 @@ macro_rules! trace_columns {
         #[allow(dead_code)]
         impl $name<'static, ()> {
             pub const SIZE: usize = <[()]>::len(&[$(trace_columns!(@unit $column)),*]);
+            pub const NAMES: &'static [&'static str] = &[$(stringify!($column)),*];
 
             pub fn to_ids(suffix: Option<u32>) -> Vec<
                 stwo_constraint_framework::preprocessed_columns::PreProcessedColumnId
@@ -320,3 +321,194 @@ macro_rules! simd_vec {
         ]
     };
 }
+
+/// Declares the components of the `module::name` submodules, each with `witness::gen_trace`,
+/// `witness::gen_interaction_trace`, `air::Eval` and `air::Component`, along with their traces
+/// and claimed sums.
+///
+/// `lookup_data` names and types the lookup data arguments of the `gen_trace` functions, after the
+/// log size.
+#[macro_export]
+macro_rules! components {
+    (
+        relations: $relations:ty,
+        lookup_data: ($( $arg:ident: $arg_ty:ty ),+ $(,)?),
+        $( $module:ident :: $name:ident ),+ $(,)?
+    ) => {
+        $crate::components!(
+            @impl $relations;
+            log_size;
+            [$( $arg: $arg_ty ),+];
+            (log_size, $( $arg ),+);
+            $( ($module, $name) ),+
+        );
+    };
+    // internal implementation, the arguments of `gen_trace` are forwarded as a single token tree
+    // so that they can be repeated for each component
+    (
+        @impl $relations:ty;
+        $log_size:ident;
+        [$( $arg:ident: $arg_ty:ty ),+];
+        $gen_trace_args:tt;
+        $( ($module:ident, $name:ident) ),+
+    ) => {
+        type Relations = $relations;
+        use $crate::{
+            circle_evaluation_u32x16,
+            stwo::air::{AirComponents, LogupSum},
+        };
+
+        use stwo::core::fields::qm31::SecureField;
+        use std::simd::u32x16;
+        use num_traits::Zero;
+        use stwo::{
+            core::{fields::{m31::BaseField}, ColumnVec},
+            prover::{backend::simd::{SimdBackend}, poly::{circle::CircleEvaluation, BitReversedOrder}, ComponentProver},
+        };
+        use stwo::core::pcs::TreeVec;
+        use stwo::core::air::Component;
+        use stwo::core::channel::Channel;
+        use serde::{Deserialize, Serialize};
+        use stwo_constraint_framework::{
+            relation_tracker::{add_to_relation_entries, RelationTrackerEntry},
+            TraceLocationAllocator,
+        };
+
+        paste::paste! {
+            #[derive(Clone)]
+            pub struct Traces {
+                $( pub [<$module _ $name>]: Vec<Vec<u32x16>>, )+
+            }
+
+            #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+            pub struct ClaimedSum {
+                $( pub [<$module _ $name>]: SecureField, )+
+            }
+
+            impl ClaimedSum {
+                pub fn zero() -> Self {
+                    Self {
+                        $( [<$module _ $name>]: SecureField::zero(), )+
+                    }
+                }
+            }
+
+            impl LogupSum for ClaimedSum {
+                fn sum(&self) -> SecureField {
+                    SecureField::zero() $( + self.[<$module _ $name>] )+
+                }
+
+                fn mix_into(&self, channel: &mut impl Channel) {
+                    channel.mix_felts(&[ $( self.[<$module _ $name>], )+ ]);
+                }
+            }
+
+            pub fn gen_trace($log_size: u32, $( $arg: $arg_ty ),+) -> Traces {
+                Traces {
+                    $(
+                        [<$module _ $name>]: $module::$name::witness::gen_trace $gen_trace_args,
+                    )+
+                }
+            }
+
+            pub fn gen_interaction_trace(
+                traces: &Traces,
+                relations: &Relations,
+            ) -> (
+                ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+                ClaimedSum,
+            ) {
+                let mut interaction_trace = vec![];
+
+                $(
+                    let (trace, [<$module _ $name _ claimed_sum>]) =
+                        $module::$name::witness::gen_interaction_trace(&traces.[<$module _ $name>], relations);
+                    interaction_trace.extend(trace);
+                )+
+                (
+                    interaction_trace,
+                    ClaimedSum {
+                        $( [<$module _ $name>]: [<$module _ $name _ claimed_sum>], )+
+                    },
+                )
+            }
+
+            impl Traces {
+                pub fn len(&self) -> usize {
+                    0 $( + self.[<$module _ $name>].iter().map(|v| v.len()).sum::<usize>() )+
+                }
+
+                pub fn is_empty(&self) -> bool {
+                    self.len() == 0
+                }
+            }
+
+            impl IntoIterator for Traces {
+                type Item = CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>;
+                type IntoIter = std::vec::IntoIter<Self::Item>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    let cols = vec![
+                        $( self.[<$module _ $name>] ),+
+                    ];
+
+                    cols
+                        .into_iter()
+                        .flatten()
+                        .map(|col| circle_evaluation_u32x16!(col))
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                }
+            }
+
+            pub struct Components {
+                $( pub [<$module _ $name>]: $module::$name::air::Component, )+
+            }
+
+            impl Components {
+                pub fn new(
+                    log_size: u32,
+                    location_allocator: &mut TraceLocationAllocator,
+                    relations: Relations,
+                    claimed_sum: &ClaimedSum,
+                ) -> Self {
+                    Self {
+                        $(
+                            [<$module _ $name>]: $module::$name::air::Component::new(
+                                location_allocator,
+                                $module::$name::air::Eval { log_size, relations: relations.clone() },
+                                claimed_sum.[<$module _ $name>],
+                            ),
+                        )+
+                    }
+                }
+            }
+
+            impl AirComponents for Components {
+                fn provers(&self) -> Vec<&dyn ComponentProver<SimdBackend>> {
+                    vec![ $(&self.[<$module _ $name>],)+ ]
+                }
+
+                fn components(&self) -> Vec<&dyn Component> {
+                    vec![ $(&self.[<$module _ $name>],)+ ]
+                }
+
+                fn trace_log_degree_bounds(&self) -> Vec<TreeVec<ColumnVec<u32>>> {
+                    vec![
+                        $( self.[<$module _ $name>].trace_log_degree_bounds(), )+
+                    ]
+                }
+
+                fn relation_entries(
+                    &self,
+                    trace: &TreeVec<Vec<&Vec<BaseField>>>,
+                ) -> Vec<RelationTrackerEntry> {
+                    itertools::chain!(
+                        $( add_to_relation_entries(&self.[<$module _ $name>], trace) ),+
+                    )
+                    .collect()
+                }
+            }
+        }
+    };
+}
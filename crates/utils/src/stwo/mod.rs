@@ -1 +1,2 @@
+pub mod air;
 pub mod macros;